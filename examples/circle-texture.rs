@@ -157,9 +157,14 @@ fn main() {
     "##;
 
     // Compile our vertex and fragment shader, panicking if there was a
-    // compilation error
-    let vertex_shader = gl.build_vertex_shader(vertex_source).unwrap();
-    let fragment_shader = gl.build_fragment_shader(fragment_source).unwrap();
+    // compilation error. Our shaders are written in GLSL ES 1.00, so they
+    // get translated for the OpenGL 4.1 core context first
+    let vertex_shader = gl.build_vertex_shader(vertex_source)
+                          .translate_for_context()
+                          .unwrap();
+    let fragment_shader = gl.build_fragment_shader(fragment_source)
+                            .translate_for_context()
+                            .unwrap();
 
     // Combine our shaders into a program, panicking if there was a
     // linking error
@@ -0,0 +1,832 @@
+// A decoder for the ASTC LDR block format. Blocks that use features that
+// aren't part of the LDR profile (such as HDR endpoints), and blocks with
+// invalid encodings, are decoded as the error color (opaque magenta)
+
+use std::cmp;
+use image_data::Pixel;
+
+// The number of values that each quantization range can represent, for
+// color endpoints and weights
+const QUANT_LEVELS: [u32; 21] = [
+    2, 3, 4, 5, 6, 8, 10, 12, 16, 20, 24, 32, 40, 48, 64, 80, 96, 128, 160,
+    192, 256
+];
+
+const ERROR_COLOR: [u8; 4] = [0xFF, 0, 0xFF, 0xFF];
+
+// The layout of a block that isn't a void-extent block
+struct BlockMode {
+    grid_width: usize,
+    grid_height: usize,
+    dual_plane: bool,
+    weight_levels: u32
+}
+
+// Decode an ASTC block with the given block dimensions. For sRGB
+// formats, the decoded texels are the sRGB-encoded values
+pub fn decode(block: &[u8],
+              block_width: usize,
+              block_height: usize,
+              srgb: bool,
+              texels: &mut [Pixel])
+{
+    let colors = match _decode_block(block, block_width, block_height, srgb) {
+        Some(colors) => colors,
+        None => vec![ERROR_COLOR; block_width * block_height]
+    };
+    for (texel, color) in texels.iter_mut().zip(colors.iter()) {
+        *texel = Pixel::r_g_b_a(color[0], color[1], color[2], color[3]);
+    }
+}
+
+// Decode the color of each texel of a block, or return `None` if the
+// block should be decoded as the error color
+fn _decode_block(block: &[u8],
+                 block_width: usize,
+                 block_height: usize,
+                 srgb: bool)
+    -> Option<Vec<[u8; 4]>>
+{
+    let texel_count = block_width * block_height;
+
+    // Void-extent blocks have a single color for the whole block
+    if _bits(block, 0, 9) == 0x1FC {
+        if _bits(block, 9, 1) == 1 {
+            // HDR void-extent blocks aren't supported in the LDR profile
+            return None;
+        }
+        let mut color = [0; 4];
+        for (i, component) in color.iter_mut().enumerate() {
+            *component = (_bits(block, 64 + i * 16, 16) >> 8) as u8;
+        }
+        return Some(vec![color; texel_count]);
+    }
+
+    let mode = match _block_mode(_bits(block, 0, 11)) {
+        Some(mode) => mode,
+        None => { return None; }
+    };
+    if mode.grid_width > block_width || mode.grid_height > block_height {
+        return None;
+    }
+
+    let planes = if mode.dual_plane { 2 } else { 1 };
+    let weight_count = mode.grid_width * mode.grid_height * planes;
+    let weight_bits = _ise_bit_count(weight_count, mode.weight_levels);
+    if weight_count > 64 || weight_bits < 24 || weight_bits > 96 {
+        return None;
+    }
+
+    let partitions = _bits(block, 11, 2) as usize + 1;
+    if partitions == 4 && mode.dual_plane {
+        return None;
+    }
+
+    // Read the color endpoint mode of each partition. With multiple
+    // partitions, some of the mode bits can be stored below the weights
+    let mut endpoint_modes = [0; 4];
+    let mut extra_bits = 0;
+    let color_start;
+    if partitions == 1 {
+        endpoint_modes[0] = _bits(block, 13, 4);
+        color_start = 17;
+    }
+    else {
+        let selector = _bits(block, 23, 2);
+        if selector == 0 {
+            let mode = _bits(block, 25, 4);
+            for endpoint_mode in endpoint_modes.iter_mut() {
+                *endpoint_mode = mode;
+            }
+        }
+        else {
+            extra_bits = 3 * partitions - 4;
+            let extra = _bits(block, 128 - weight_bits - extra_bits, extra_bits);
+            let modes = extra << 4 | _bits(block, 25, 4);
+            for (i, endpoint_mode) in endpoint_modes.iter_mut()
+                                                    .take(partitions)
+                                                    .enumerate()
+            {
+                let class = selector - 1 + ((modes >> i) & 1);
+                let low = (modes >> (partitions + i * 2)) & 3;
+                *endpoint_mode = class << 2 | low;
+            }
+        }
+        color_start = 29;
+    }
+
+    let plane_bits = if mode.dual_plane { 2 } else { 0 };
+    let color_end = 128 - weight_bits - extra_bits - plane_bits;
+    let plane_component = _bits(block, color_end, plane_bits) as usize;
+
+    // Use the largest quantization range that fits the color data
+    let value_count = endpoint_modes.iter().take(partitions).fold(0, |count, &mode| {
+        count + ((mode >> 2) as usize + 1) * 2
+    });
+    if value_count > 18 || color_end < color_start {
+        return None;
+    }
+    let color_levels = QUANT_LEVELS.iter().rev().cloned().find(|&levels| {
+        _ise_bit_count(value_count, levels) <= color_end - color_start
+    });
+    let color_levels = match color_levels {
+        Some(levels) if levels >= 6 => levels,
+        _ => { return None; }
+    };
+
+    let values = _decode_ise(block, color_start, color_end, color_levels, value_count);
+    let mut values = values.iter().map(|&value| {
+        _unquantize_color(value, color_levels)
+    });
+    let mut endpoints = [[[0; 4]; 2]; 4];
+    for (endpoint, &mode) in endpoints.iter_mut()
+                                      .zip(endpoint_modes.iter())
+                                      .take(partitions)
+    {
+        let count = ((mode >> 2) as usize + 1) * 2;
+        let mode_values: Vec<i32> = values.by_ref().take(count).collect();
+        *endpoint = match _decode_endpoints(mode, &mode_values) {
+            Some(endpoint) => endpoint,
+            None => { return None; }
+        };
+    }
+
+    // The weights are stored in reverse, starting from the last bit of
+    // the block
+    let mut reversed = [0; 16];
+    for (i, byte) in reversed.iter_mut().enumerate() {
+        *byte = _reverse_byte(block[15 - i]);
+    }
+    let weights: Vec<u32> = _decode_ise(&reversed,
+                                        0,
+                                        weight_bits,
+                                        mode.weight_levels,
+                                        weight_count)
+        .iter()
+        .map(|&weight| _unquantize_weight(weight, mode.weight_levels))
+        .collect();
+
+    let seed = _bits(block, 13, 10);
+    let mut colors = Vec::with_capacity(texel_count);
+    for y in 0..block_height {
+        for x in 0..block_width {
+            let partition = if partitions > 1 {
+                _select_partition(seed, x, y, partitions, texel_count < 31)
+            }
+            else {
+                0
+            };
+            let endpoint = endpoints[partition];
+
+            let mut plane_weights = [0; 2];
+            for (plane, weight) in plane_weights.iter_mut().take(planes).enumerate() {
+                *weight = _infill_weight(&weights, &mode, planes, plane,
+                                         block_width, block_height, x, y);
+            }
+
+            let mut color = [0; 4];
+            for (i, component) in color.iter_mut().enumerate() {
+                let plane = if mode.dual_plane && i == plane_component { 1 } else { 0 };
+                *component = _interpolate(endpoint[0][i],
+                                          endpoint[1][i],
+                                          plane_weights[plane],
+                                          srgb && i < 3);
+            }
+            colors.push(color);
+        }
+    }
+
+    Some(colors)
+}
+
+// Decode the 11-bit block mode of a block
+fn _block_mode(mode: u32) -> Option<BlockMode> {
+    // The reserved block modes
+    if mode & 0xF == 0 || mode & 0x1C3 == 0x1C0 {
+        return None;
+    }
+
+    let a = ((mode >> 5) & 3) as usize;
+    let (width, height, range, high_precision, dual_plane);
+    if mode & 3 != 0 {
+        let b = ((mode >> 7) & 3) as usize;
+        range = (mode >> 4) & 1 | (mode & 3) << 1;
+        high_precision = (mode >> 9) & 1 == 1;
+        dual_plane = (mode >> 10) & 1 == 1;
+
+        let (w, h) = match (mode >> 2) & 3 {
+            0 => (b + 4, a + 2),
+            1 => (b + 8, a + 2),
+            2 => (a + 2, b + 8),
+            _ => {
+                if (mode >> 8) & 1 == 0 {
+                    (a + 2, (b & 1) + 6)
+                }
+                else {
+                    ((b & 1) + 2, a + 2)
+                }
+            }
+        };
+        width = w;
+        height = h;
+    }
+    else {
+        range = (mode >> 4) & 1 | ((mode >> 2) & 3) << 1;
+        high_precision = (mode >> 9) & 1 == 1;
+        dual_plane = (mode >> 10) & 1 == 1;
+
+        let (w, h) = match (mode >> 7) & 3 {
+            0 => (12, a + 2),
+            1 => (a + 2, 12),
+            3 => {
+                if (mode >> 5) & 1 == 0 { (6, 10) } else { (10, 6) }
+            },
+            _ => {
+                // This layout uses the dual plane and precision bits for
+                // its height instead
+                let b = ((mode >> 9) & 3) as usize;
+                return _range_levels(range, false).map(|levels| {
+                    BlockMode {
+                        grid_width: a + 6,
+                        grid_height: b + 6,
+                        dual_plane: false,
+                        weight_levels: levels
+                    }
+                });
+            }
+        };
+        width = w;
+        height = h;
+    }
+
+    _range_levels(range, high_precision).map(|levels| {
+        BlockMode {
+            grid_width: width,
+            grid_height: height,
+            dual_plane: dual_plane,
+            weight_levels: levels
+        }
+    })
+}
+
+// Get the number of levels in a weight quantization range
+fn _range_levels(range: u32, high_precision: bool) -> Option<u32> {
+    const LOW: [u32; 8] = [0, 0, 2, 3, 4, 5, 6, 8];
+    const HIGH: [u32; 8] = [0, 0, 10, 12, 16, 20, 24, 32];
+    let levels = if high_precision { HIGH } else { LOW }[range as usize];
+    if levels > 0 { Some(levels) } else { None }
+}
+
+// Get the number of trits, quints, and bits used to encode each value
+// with the given number of levels
+fn _ise_encoding(levels: u32) -> (u32, u32, u32) {
+    match levels {
+        3 => (1, 0, 0),
+        5 => (0, 1, 0),
+        6 => (1, 0, 1),
+        10 => (0, 1, 1),
+        12 => (1, 0, 2),
+        20 => (0, 1, 2),
+        24 => (1, 0, 3),
+        40 => (0, 1, 3),
+        48 => (1, 0, 4),
+        80 => (0, 1, 4),
+        96 => (1, 0, 5),
+        160 => (0, 1, 5),
+        192 => (1, 0, 6),
+        _ => (0, 0, 31 - levels.leading_zeros())
+    }
+}
+
+// Get the number of bits used to store `count` values with the integer
+// sequence encoding
+fn _ise_bit_count(count: usize, levels: u32) -> usize {
+    let (trits, quints, bits) = _ise_encoding(levels);
+    let mut total = count * bits as usize;
+    if trits > 0 {
+        total += (8 * count + 4) / 5;
+    }
+    if quints > 0 {
+        total += (7 * count + 2) / 3;
+    }
+    total
+}
+
+// Decode `count` values stored with the integer sequence encoding between
+// the bits `start` and `end` of a block. Bits past `end` are read as 0
+fn _decode_ise(block: &[u8], start: usize, end: usize, levels: u32, count: usize)
+    -> Vec<u32>
+{
+    let (trits, quints, bits) = _ise_encoding(levels);
+    let read = |pos: &mut usize, count: usize| -> u32 {
+        let available = if *pos < end { end - *pos } else { 0 };
+        let value = _bits(block, *pos, cmp::min(count, available));
+        *pos += count;
+        value
+    };
+
+    let mut values = Vec::with_capacity(count + 5);
+    let mut pos = start;
+    while values.len() < count {
+        if trits > 0 {
+            // Blocks of 5 values share 8 bits of trits
+            let mut low = [0; 5];
+            let mut t = 0;
+            for (i, &(shift, length)) in [(0, 2), (2, 2), (4, 1), (5, 2), (7, 1)].iter().enumerate() {
+                low[i] = read(&mut pos, bits as usize);
+                t |= read(&mut pos, length) << shift;
+            }
+            let digits = _decode_trits(t);
+            for i in 0..5 {
+                values.push(digits[i] << bits | low[i]);
+            }
+        }
+        else if quints > 0 {
+            // Blocks of 3 values share 7 bits of quints
+            let mut low = [0; 3];
+            let mut q = 0;
+            for (i, &(shift, length)) in [(0, 3), (3, 2), (5, 2)].iter().enumerate() {
+                low[i] = read(&mut pos, bits as usize);
+                q |= read(&mut pos, length) << shift;
+            }
+            let digits = _decode_quints(q);
+            for i in 0..3 {
+                values.push(digits[i] << bits | low[i]);
+            }
+        }
+        else {
+            values.push(read(&mut pos, bits as usize));
+        }
+    }
+    values.truncate(count);
+    values
+}
+
+fn _decode_trits(t: u32) -> [u32; 5] {
+    let bit = |n: u32| (t >> n) & 1;
+    let (c, t3, t4);
+    if (t >> 2) & 7 == 7 {
+        c = (t >> 5) << 2 | (t & 3);
+        t4 = 2;
+        t3 = 2;
+    }
+    else {
+        c = t & 0x1F;
+        if (t >> 5) & 3 == 3 {
+            t4 = 2;
+            t3 = bit(7);
+        }
+        else {
+            t4 = bit(7);
+            t3 = (t >> 5) & 3;
+        }
+    }
+
+    let cbit = |n: u32| (c >> n) & 1;
+    let (t0, t1, t2);
+    if c & 3 == 3 {
+        t2 = 2;
+        t1 = cbit(4);
+        t0 = cbit(3) << 1 | (cbit(2) & !cbit(3) & 1);
+    }
+    else if (c >> 2) & 3 == 3 {
+        t2 = 2;
+        t1 = 2;
+        t0 = c & 3;
+    }
+    else {
+        t2 = cbit(4);
+        t1 = (c >> 2) & 3;
+        t0 = cbit(1) << 1 | (cbit(0) & !cbit(1) & 1);
+    }
+
+    [t0, t1, t2, t3, t4]
+}
+
+fn _decode_quints(q: u32) -> [u32; 3] {
+    let bit = |n: u32| (q >> n) & 1;
+    if (q >> 1) & 3 == 3 && (q >> 5) & 3 == 0 {
+        let q2 = bit(0) << 2 | (bit(4) & !bit(0) & 1) << 1 | (bit(3) & !bit(0) & 1);
+        return [4, 4, q2];
+    }
+
+    let (c, q2);
+    if (q >> 1) & 3 == 3 {
+        q2 = 4;
+        c = ((q >> 3) & 3) << 3 | (!(q >> 5) & 3) << 1 | bit(0);
+    }
+    else {
+        q2 = (q >> 5) & 3;
+        c = q & 0x1F;
+    }
+
+    if c & 7 == 5 {
+        [c >> 3, 4, q2]
+    }
+    else {
+        [c & 7, c >> 3, q2]
+    }
+}
+
+// Unquantize a color endpoint value to the range 0-255
+fn _unquantize_color(value: u32, levels: u32) -> i32 {
+    let (trits, quints, bits) = _ise_encoding(levels);
+    if trits == 0 && quints == 0 {
+        return _replicate(value, bits, 8) as i32;
+    }
+
+    let digit = value >> bits;
+    let low = value & ((1 << bits) - 1);
+    let a = if low & 1 == 1 { 0x1FF } else { 0 };
+    let m = low >> 1;
+    let (b, c) = if trits > 0 {
+        match bits {
+            1 => (0, 204),
+            2 => (m << 8 | m << 4 | m << 2 | m << 1, 93),
+            3 => (m << 7 | m << 2 | m, 44),
+            4 => (m << 6 | m, 22),
+            5 => (m << 5 | m >> 2, 11),
+            _ => (m << 4 | m >> 4, 5)
+        }
+    }
+    else {
+        match bits {
+            1 => (0, 113),
+            2 => (m << 8 | m << 3 | m << 2, 54),
+            3 => (m << 7 | m << 1 | m >> 1, 26),
+            4 => (m << 6 | m >> 1, 13),
+            _ => (m << 5 | m >> 3, 6)
+        }
+    };
+
+    let t = (digit * c + b) ^ a;
+    ((a & 0x80) | t >> 2) as i32
+}
+
+// Unquantize a weight value to the range 0-64
+fn _unquantize_weight(value: u32, levels: u32) -> u32 {
+    let (trits, quints, bits) = _ise_encoding(levels);
+    let weight = if trits == 0 && quints == 0 {
+        _replicate(value, bits, 6)
+    }
+    else if bits == 0 {
+        return if trits > 0 { value * 32 } else { value * 16 };
+    }
+    else {
+        let digit = value >> bits;
+        let low = value & ((1 << bits) - 1);
+        let a = if low & 1 == 1 { 0x7F } else { 0 };
+        let m = low >> 1;
+        let (b, c) = match (trits > 0, bits) {
+            (true, 1) => (0, 50),
+            (true, 2) => (m << 6 | m << 2 | m, 23),
+            (true, _) => (m << 5 | m, 11),
+            (false, 1) => (0, 28),
+            (false, _) => (m << 6 | m << 1, 13)
+        };
+        let t = (digit * c + b) ^ a;
+        (a & 0x20) | t >> 2
+    };
+
+    if weight > 32 { weight + 1 } else { weight }
+}
+
+// Repeat the bits of a value to fill a larger number of bits
+fn _replicate(value: u32, bits: u32, target: u32) -> u32 {
+    let mut result = 0;
+    let mut filled = 0;
+    while filled < target {
+        result = result << bits | value;
+        filled += bits;
+    }
+    result >> (filled - target)
+}
+
+// Decode the two endpoint colors of a partition from its color values
+fn _decode_endpoints(mode: u32, v: &[i32]) -> Option<[[i32; 4]; 2]> {
+    let endpoints = match mode {
+        // Luminance, direct
+        0 => [[v[0], v[0], v[0], 0xFF], [v[1], v[1], v[1], 0xFF]],
+
+        // Luminance, base + offset
+        1 => {
+            let l0 = (v[0] >> 2) | (v[1] & 0xC0);
+            let l1 = l0 + (v[1] & 0x3F);
+            let l1 = if l1 > 0xFF { 0xFF } else { l1 };
+            [[l0, l0, l0, 0xFF], [l1, l1, l1, 0xFF]]
+        },
+
+        // Luminance and alpha, direct
+        4 => [[v[0], v[0], v[0], v[2]], [v[1], v[1], v[1], v[3]]],
+
+        // Luminance and alpha, base + offset
+        5 => {
+            let (l1, l0) = _bit_transfer_signed(v[1], v[0]);
+            let (a1, a0) = _bit_transfer_signed(v[3], v[2]);
+            let l1 = _clamp(l0 + l1);
+            [[l0, l0, l0, a0], [l1, l1, l1, _clamp(a0 + a1)]]
+        },
+
+        // RGB, base + scale
+        6 => [[(v[0] * v[3]) >> 8, (v[1] * v[3]) >> 8, (v[2] * v[3]) >> 8, 0xFF],
+              [v[0], v[1], v[2], 0xFF]],
+
+        // RGB, direct
+        8 => _rgba_direct([v[0], v[1], v[2], v[3], v[4], v[5], 0xFF, 0xFF]),
+
+        // RGB, base + offset
+        9 => _rgba_base_offset([v[0], v[1], v[2], v[3], v[4], v[5], 0xFF, 0]),
+
+        // RGB, base + scale, with two alpha values
+        10 => [[(v[0] * v[3]) >> 8, (v[1] * v[3]) >> 8, (v[2] * v[3]) >> 8, v[4]],
+               [v[0], v[1], v[2], v[5]]],
+
+        // RGBA, direct
+        12 => _rgba_direct([v[0], v[1], v[2], v[3], v[4], v[5], v[6], v[7]]),
+
+        // RGBA, base + offset
+        13 => _rgba_base_offset([v[0], v[1], v[2], v[3], v[4], v[5], v[6], v[7]]),
+
+        // The remaining modes are HDR modes
+        _ => {
+            return None;
+        }
+    };
+    Some(endpoints)
+}
+
+fn _rgba_direct(v: [i32; 8]) -> [[i32; 4]; 2] {
+    if v[1] + v[3] + v[5] >= v[0] + v[2] + v[4] {
+        [[v[0], v[2], v[4], v[6]], [v[1], v[3], v[5], v[7]]]
+    }
+    else {
+        [_blue_contract([v[1], v[3], v[5], v[7]]),
+         _blue_contract([v[0], v[2], v[4], v[6]])]
+    }
+}
+
+// Decode RGB or RGBA endpoints stored as a base and an offset. For RGB
+// endpoints, the alpha values are given as 0xFF and 0
+fn _rgba_base_offset(v: [i32; 8]) -> [[i32; 4]; 2] {
+    let mut base = [0; 4];
+    let mut offset = [0; 4];
+    for i in 0..4 {
+        let (o, b) = _bit_transfer_signed(v[i * 2 + 1], v[i * 2]);
+        base[i] = b;
+        offset[i] = o;
+    }
+    if v[7] == 0 && v[6] == 0xFF {
+        base[3] = 0xFF;
+        offset[3] = 0;
+    }
+
+    let sum = [
+        _clamp(base[0] + offset[0]),
+        _clamp(base[1] + offset[1]),
+        _clamp(base[2] + offset[2]),
+        _clamp(base[3] + offset[3])
+    ];
+    if offset[0] + offset[1] + offset[2] >= 0 {
+        [base, sum]
+    }
+    else {
+        [_blue_contract(sum), _blue_contract(base)]
+    }
+}
+
+fn _bit_transfer_signed(a: i32, b: i32) -> (i32, i32) {
+    let b = (b >> 1) | (a & 0x80);
+    let a = (a >> 1) & 0x3F;
+    let a = if a & 0x20 != 0 { a - 0x40 } else { a };
+    (a, b)
+}
+
+fn _blue_contract(color: [i32; 4]) -> [i32; 4] {
+    [(color[0] + color[2]) >> 1, (color[1] + color[2]) >> 1, color[2], color[3]]
+}
+
+// Interpolate between two endpoint components with a weight in the
+// range 0-64
+fn _interpolate(e0: i32, e1: i32, weight: u32, srgb: bool) -> u8 {
+    let expand = |e: i32| -> u32 {
+        let e = e as u32;
+        if srgb { e << 8 | 0x80 } else { e << 8 | e }
+    };
+    let (c0, c1) = (expand(e0), expand(e1));
+    let c = (c0 * (64 - weight) + c1 * weight + 32) >> 6;
+    (c >> 8) as u8
+}
+
+// Get the weight of a texel by bilinearly interpolating the weight grid
+fn _infill_weight(weights: &[u32],
+                  mode: &BlockMode,
+                  planes: usize,
+                  plane: usize,
+                  block_width: usize,
+                  block_height: usize,
+                  x: usize,
+                  y: usize)
+    -> u32
+{
+    let ds = (1024 + block_width / 2) / (block_width - 1);
+    let dt = (1024 + block_height / 2) / (block_height - 1);
+    let gs = (ds * x * (mode.grid_width - 1) + 32) >> 6;
+    let gt = (dt * y * (mode.grid_height - 1) + 32) >> 6;
+    let (js, fs) = (gs >> 4, (gs & 0xF) as u32);
+    let (jt, ft) = (gt >> 4, (gt & 0xF) as u32);
+
+    let weight = |s: usize, t: usize| -> u32 {
+        if s < mode.grid_width && t < mode.grid_height {
+            weights[(t * mode.grid_width + s) * planes + plane]
+        }
+        else {
+            0
+        }
+    };
+
+    let w11 = (fs * ft + 8) >> 4;
+    let w10 = ft - w11;
+    let w01 = fs - w11;
+    let w00 = 16 + w11 - fs - ft;
+    (weight(js, jt) * w00 + weight(js + 1, jt) * w01
+     + weight(js, jt + 1) * w10 + weight(js + 1, jt + 1) * w11 + 8) >> 4
+}
+
+// Choose the partition of a texel in a block with multiple partitions
+fn _select_partition(seed: u32, x: usize, y: usize, partitions: usize,
+                     small_block: bool)
+    -> usize
+{
+    let (x, y) = if small_block {
+        ((x as u32) << 1, (y as u32) << 1)
+    }
+    else {
+        (x as u32, y as u32)
+    };
+    let seed = seed + (partitions as u32 - 1) * 1024;
+    let rnum = _hash52(seed);
+
+    let mut seeds = [0u32; 8];
+    for (i, s) in seeds.iter_mut().enumerate() {
+        let value = (rnum >> (i * 4)) & 0xF;
+        *s = value * value;
+    }
+
+    let (sh1, sh2) = if seed & 1 == 1 {
+        (if seed & 2 == 2 { 4 } else { 5 }, if partitions == 3 { 6 } else { 5 })
+    }
+    else {
+        (if partitions == 3 { 6 } else { 5 }, if seed & 2 == 2 { 4 } else { 5 })
+    };
+    for (i, s) in seeds.iter_mut().enumerate() {
+        *s >>= if i % 2 == 0 { sh1 } else { sh2 };
+    }
+
+    let a = (seeds[0].wrapping_mul(x).wrapping_add(seeds[1].wrapping_mul(y))
+             .wrapping_add(rnum >> 14)) & 0x3F;
+    let b = (seeds[2].wrapping_mul(x).wrapping_add(seeds[3].wrapping_mul(y))
+             .wrapping_add(rnum >> 10)) & 0x3F;
+    let c = if partitions < 3 {
+        0
+    }
+    else {
+        (seeds[4].wrapping_mul(x).wrapping_add(seeds[5].wrapping_mul(y))
+         .wrapping_add(rnum >> 6)) & 0x3F
+    };
+    let d = if partitions < 4 {
+        0
+    }
+    else {
+        (seeds[6].wrapping_mul(x).wrapping_add(seeds[7].wrapping_mul(y))
+         .wrapping_add(rnum >> 2)) & 0x3F
+    };
+
+    if a >= b && a >= c && a >= d {
+        0
+    }
+    else if b >= c && b >= d {
+        1
+    }
+    else if c >= d {
+        2
+    }
+    else {
+        3
+    }
+}
+
+fn _hash52(p: u32) -> u32 {
+    let mut p = p;
+    p ^= p >> 15;
+    p = p.wrapping_sub(p << 17);
+    p = p.wrapping_add(p << 7);
+    p = p.wrapping_add(p << 4);
+    p ^= p >> 5;
+    p = p.wrapping_add(p << 16);
+    p ^= p >> 7;
+    p ^= p >> 3;
+    p ^= p << 6;
+    p ^= p >> 17;
+    p
+}
+
+fn _clamp(value: i32) -> i32 {
+    if value < 0 { 0 } else if value > 0xFF { 0xFF } else { value }
+}
+
+fn _reverse_byte(byte: u8) -> u8 {
+    let mut reversed = 0;
+    for i in 0..8 {
+        reversed |= ((byte >> i) & 1) << (7 - i);
+    }
+    reversed
+}
+
+// Read `count` bits (at most 32) starting at bit `start` of a block,
+// where bit 0 is the lowest bit of the first byte
+fn _bits(block: &[u8], start: usize, count: usize) -> u32 {
+    let mut value = 0;
+    for i in 0..count {
+        let bit = start + i;
+        if bit < 128 {
+            value |= (((block[bit / 8] >> (bit % 8)) & 1) as u32) << i;
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use image_data::Pixel;
+    use super::*;
+
+    // A single-partition block with a 4x4 grid of 2-bit weights and
+    // direct luminance endpoints of 0 and 255. Each row of weights
+    // counts from 0 to 3
+    const LUMINANCE_BLOCK: [u8; 16] = [
+        0x42, 0x00, 0x00, 0xFE, 0x01, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x27, 0x27, 0x27, 0x27
+    ];
+
+    fn _decode(block: &[u8], block_width: usize, block_height: usize)
+        -> Vec<(u8, u8, u8, u8)>
+    {
+        let mut texels = vec![Pixel::default(); block_width * block_height];
+        decode(block, block_width, block_height, false, &mut texels);
+        texels.iter().map(|texel| (texel.r, texel.g, texel.b, texel.a)).collect()
+    }
+
+    fn _assert_error_color(block: &[u8]) {
+        for texel in _decode(block, 4, 4) {
+            assert_eq!(texel, (0xFF, 0, 0xFF, 0xFF));
+        }
+    }
+
+    #[test]
+    fn test_void_extent() {
+        // The top 8 bits of each 16-bit component are used
+        let block = [
+            0xFC, 0xFD, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+            0xCD, 0x12, 0x00, 0x34, 0xFF, 0x56, 0x00, 0x78
+        ];
+        let texels = _decode(&block, 6, 6);
+        assert_eq!(texels.len(), 36);
+        for texel in texels {
+            assert_eq!(texel, (0x12, 0x34, 0x56, 0x78));
+        }
+    }
+
+    #[test]
+    fn test_luminance_block() {
+        // The weights 0-3 unquantize to 0, 21, 43, and 64
+        let texels = _decode(&LUMINANCE_BLOCK, 4, 4);
+        for row in texels.chunks(4) {
+            assert_eq!(row, &[(0, 0, 0, 0xFF),
+                              (84, 84, 84, 0xFF),
+                              (171, 171, 171, 0xFF),
+                              (0xFF, 0xFF, 0xFF, 0xFF)]);
+        }
+    }
+
+    #[test]
+    fn test_hdr_void_extent_is_error_color() {
+        let block = [
+            0xFC, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00
+        ];
+        _assert_error_color(&block);
+    }
+
+    #[test]
+    fn test_reserved_block_mode_is_error_color() {
+        _assert_error_color(&[0; 16]);
+    }
+
+    #[test]
+    fn test_hdr_endpoints_are_error_color() {
+        // The luminance block, with endpoint mode 2 (HDR luminance)
+        let mut block = LUMINANCE_BLOCK;
+        block[1] = 0x40;
+        _assert_error_color(&block);
+    }
+}
@@ -0,0 +1,246 @@
+// Decoders for the S3TC (BC1-BC3) and RGTC (BC4 and BC5) block formats
+
+use image_data::Pixel;
+
+// Decode a BC1 (DXT1) block. If `alpha` is true, the block's 3-color
+// mode uses transparent black for its fourth color
+pub fn decode_bc1(block: &[u8], texels: &mut [Pixel], alpha: bool) {
+    _decode_color(&block[0..8], texels, true, alpha);
+}
+
+// Decode a BC2 (DXT3) block, which has explicit 4-bit alpha values
+pub fn decode_bc2(block: &[u8], texels: &mut [Pixel]) {
+    _decode_color(&block[8..16], texels, false, false);
+    for (i, texel) in texels.iter_mut().enumerate() {
+        let alpha = (block[i / 2] >> ((i % 2) * 4)) & 0xF;
+        texel.a = alpha * 17;
+    }
+}
+
+// Decode a BC3 (DXT5) block, which has interpolated alpha values
+pub fn decode_bc3(block: &[u8], texels: &mut [Pixel]) {
+    _decode_color(&block[8..16], texels, false, false);
+    let alphas = _decode_channel(&block[0..8]);
+    for (texel, &alpha) in texels.iter_mut().zip(alphas.iter()) {
+        texel.a = alpha;
+    }
+}
+
+// Decode a BC4 (RGTC1) block, which stores the red component
+pub fn decode_bc4(block: &[u8], texels: &mut [Pixel]) {
+    let reds = _decode_channel(&block[0..8]);
+    for (texel, &red) in texels.iter_mut().zip(reds.iter()) {
+        *texel = Pixel::r_g_b_a(red, 0, 0, 0xFF);
+    }
+}
+
+// Decode a BC5 (RGTC2) block, which stores the red and green components
+pub fn decode_bc5(block: &[u8], texels: &mut [Pixel]) {
+    let reds = _decode_channel(&block[0..8]);
+    let greens = _decode_channel(&block[8..16]);
+    for (i, texel) in texels.iter_mut().enumerate() {
+        *texel = Pixel::r_g_b_a(reds[i], greens[i], 0, 0xFF);
+    }
+}
+
+// Decode the 8-byte color part of a BC1-BC3 block. The 3-color mode
+// (with a transparent or black fourth color) is only used by BC1
+fn _decode_color(block: &[u8],
+                 texels: &mut [Pixel],
+                 three_color_mode: bool,
+                 alpha: bool)
+{
+    let color0 = block[0] as u16 | (block[1] as u16) << 8;
+    let color1 = block[2] as u16 | (block[3] as u16) << 8;
+    let (c0, c1) = (_rgb565(color0), _rgb565(color1));
+
+    let mut colors = [c0, c1, [0; 4], [0; 4]];
+    if color0 > color1 || !three_color_mode {
+        for i in 0..3 {
+            let (a, b) = (c0[i] as u32, c1[i] as u32);
+            colors[2][i] = ((2 * a + b) / 3) as u8;
+            colors[3][i] = ((a + 2 * b) / 3) as u8;
+        }
+        colors[2][3] = 0xFF;
+        colors[3][3] = 0xFF;
+    }
+    else {
+        for i in 0..3 {
+            colors[2][i] = ((c0[i] as u32 + c1[i] as u32) / 2) as u8;
+        }
+        colors[2][3] = 0xFF;
+        colors[3] = [0, 0, 0, if alpha { 0 } else { 0xFF }];
+    }
+
+    for (i, texel) in texels.iter_mut().enumerate() {
+        let index = (block[4 + i / 4] >> ((i % 4) * 2)) & 0x3;
+        let color = colors[index as usize];
+        *texel = Pixel::r_g_b_a(color[0], color[1], color[2], color[3]);
+    }
+}
+
+// Decode the 8-byte block used for BC3 alpha values and BC4/BC5
+// components, returning the value of each texel
+fn _decode_channel(block: &[u8]) -> [u8; 16] {
+    let (v0, v1) = (block[0] as u32, block[1] as u32);
+    let mut values = [0; 8];
+    values[0] = v0;
+    values[1] = v1;
+    if v0 > v1 {
+        for i in 1..7 {
+            values[i + 1] = ((7 - i as u32) * v0 + i as u32 * v1) / 7;
+        }
+    }
+    else {
+        for i in 1..5 {
+            values[i + 1] = ((5 - i as u32) * v0 + i as u32 * v1) / 5;
+        }
+        values[6] = 0;
+        values[7] = 0xFF;
+    }
+
+    let indices = block[2..8].iter().rev().fold(0u64, |bits, &byte| {
+        bits << 8 | byte as u64
+    });
+    let mut texels = [0; 16];
+    for (i, texel) in texels.iter_mut().enumerate() {
+        *texel = values[((indices >> (i * 3)) & 0x7) as usize] as u8;
+    }
+    texels
+}
+
+fn _rgb565(color: u16) -> [u8; 4] {
+    let r = ((color >> 11) & 0x1F) as u8;
+    let g = ((color >> 5) & 0x3F) as u8;
+    let b = (color & 0x1F) as u8;
+    [r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2, 0xFF]
+}
+
+#[cfg(test)]
+mod tests {
+    use image_data::Pixel;
+    use super::*;
+
+    // The endpoints in these blocks are chosen so that the interpolated
+    // values are exact, so the expected values don't depend on rounding
+
+    // A 2-bit index for each texel, counting 0, 1, 2, 3 along each row
+    const COUNTING_INDICES: [u8; 4] = [0xE4, 0xE4, 0xE4, 0xE4];
+
+    // A 3-bit index for each texel, counting from 0 to 7 twice
+    const COUNTING_CHANNEL_INDICES: [u8; 6] = [0x88, 0xC6, 0xFA, 0x88, 0xC6, 0xFA];
+
+    fn _rgba(texel: Pixel) -> (u8, u8, u8, u8) {
+        (texel.r, texel.g, texel.b, texel.a)
+    }
+
+    fn _color_block(color0: u16, color1: u16) -> Vec<u8> {
+        let mut block = vec![color0 as u8, (color0 >> 8) as u8,
+                             color1 as u8, (color1 >> 8) as u8];
+        block.extend_from_slice(&COUNTING_INDICES);
+        block
+    }
+
+    fn _channel_block(v0: u8, v1: u8) -> Vec<u8> {
+        let mut block = vec![v0, v1];
+        block.extend_from_slice(&COUNTING_CHANNEL_INDICES);
+        block
+    }
+
+    fn _decode<F>(block: &[u8], decode: F) -> Vec<Pixel>
+        where F: Fn(&[u8], &mut [Pixel])
+    {
+        let mut texels = vec![Pixel::default(); 16];
+        decode(block, &mut texels);
+        texels
+    }
+
+    #[test]
+    fn test_bc1_four_color_mode() {
+        // White and black endpoints, interpolated to 2/3 and 1/3 gray
+        let block = _color_block(0xFFFF, 0x0000);
+        let texels = _decode(&block, |block, texels| decode_bc1(block, texels, true));
+
+        assert_eq!(_rgba(texels[0]), (0xFF, 0xFF, 0xFF, 0xFF));
+        assert_eq!(_rgba(texels[1]), (0, 0, 0, 0xFF));
+        assert_eq!(_rgba(texels[2]), (170, 170, 170, 0xFF));
+        assert_eq!(_rgba(texels[3]), (85, 85, 85, 0xFF));
+        assert_eq!(_rgba(texels[15]), (85, 85, 85, 0xFF));
+    }
+
+    #[test]
+    fn test_bc1_three_color_mode() {
+        // A red component of 16 expands to 132, which averages to 66
+        // with black. The fourth color is transparent black with alpha
+        let block = _color_block(0x0000, 0x8000);
+        let texels = _decode(&block, |block, texels| decode_bc1(block, texels, true));
+
+        assert_eq!(_rgba(texels[0]), (0, 0, 0, 0xFF));
+        assert_eq!(_rgba(texels[1]), (132, 0, 0, 0xFF));
+        assert_eq!(_rgba(texels[2]), (66, 0, 0, 0xFF));
+        assert_eq!(_rgba(texels[3]), (0, 0, 0, 0));
+
+        let texels = _decode(&block, |block, texels| decode_bc1(block, texels, false));
+        assert_eq!(_rgba(texels[3]), (0, 0, 0, 0xFF));
+    }
+
+    #[test]
+    fn test_bc2() {
+        // Each texel's 4-bit alpha is its index, and the color block
+        // always uses the 4-color mode, even though color0 <= color1
+        let mut block = vec![0x10, 0x32, 0x54, 0x76, 0x98, 0xBA, 0xDC, 0xFE];
+        block.extend(_color_block(0x0000, 0xFFFF));
+        let texels = _decode(&block, decode_bc2);
+
+        assert_eq!(_rgba(texels[0]), (0, 0, 0, 0));
+        assert_eq!(_rgba(texels[1]), (0xFF, 0xFF, 0xFF, 17));
+        assert_eq!(_rgba(texels[2]), (85, 85, 85, 34));
+        assert_eq!(_rgba(texels[3]), (170, 170, 170, 51));
+        assert_eq!(_rgba(texels[15]), (170, 170, 170, 0xFF));
+    }
+
+    #[test]
+    fn test_bc3_eight_value_alpha() {
+        let mut block = _channel_block(70, 0);
+        block.extend(_color_block(0xFFFF, 0xFFFF));
+        let texels = _decode(&block, decode_bc3);
+
+        let alphas: Vec<u8> = texels.iter().map(|texel| texel.a).collect();
+        assert_eq!(alphas, [70, 0, 60, 50, 40, 30, 20, 10,
+                            70, 0, 60, 50, 40, 30, 20, 10]);
+        assert_eq!(_rgba(texels[5]), (0xFF, 0xFF, 0xFF, 30));
+    }
+
+    #[test]
+    fn test_bc3_six_value_alpha() {
+        // The last two values are always 0 and 255 in the 6-value mode
+        let mut block = _channel_block(0, 50);
+        block.extend(_color_block(0xFFFF, 0xFFFF));
+        let texels = _decode(&block, decode_bc3);
+
+        let alphas: Vec<u8> = texels.iter().map(|texel| texel.a).collect();
+        assert_eq!(alphas, [0, 50, 10, 20, 30, 40, 0, 0xFF,
+                            0, 50, 10, 20, 30, 40, 0, 0xFF]);
+    }
+
+    #[test]
+    fn test_bc4() {
+        let block = _channel_block(70, 0);
+        let texels = _decode(&block, decode_bc4);
+
+        assert_eq!(_rgba(texels[0]), (70, 0, 0, 0xFF));
+        assert_eq!(_rgba(texels[2]), (60, 0, 0, 0xFF));
+        assert_eq!(_rgba(texels[15]), (10, 0, 0, 0xFF));
+    }
+
+    #[test]
+    fn test_bc5() {
+        let mut block = _channel_block(70, 0);
+        block.extend(_channel_block(0, 50));
+        let texels = _decode(&block, decode_bc5);
+
+        assert_eq!(_rgba(texels[0]), (70, 0, 0, 0xFF));
+        assert_eq!(_rgba(texels[2]), (60, 10, 0, 0xFF));
+        assert_eq!(_rgba(texels[7]), (10, 0xFF, 0, 0xFF));
+    }
+}
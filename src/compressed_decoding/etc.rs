@@ -0,0 +1,504 @@
+// Decoders for the ETC1, ETC2, and EAC block formats
+
+use image_data::Pixel;
+
+const ETC1_MODIFIERS: [[i32; 2]; 8] = [
+    [2, 8], [5, 17], [9, 29], [13, 42], [18, 60], [24, 80], [33, 106],
+    [47, 183]
+];
+
+const ETC2_DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8]
+];
+
+// Decode an ETC2 RGB block, which is also how ETC1 blocks are decoded.
+// If `punchthrough` is true, the block is decoded as an
+// RGB8_PUNCHTHROUGH_ALPHA1_ETC2 block
+pub fn decode_rgb(block: &[u8], texels: &mut [Pixel], punchthrough: bool) {
+    let bits = _read_u64(block);
+
+    // Punchthrough blocks replace the differential bit with an opaque bit,
+    // and are always in differential mode
+    let (differential, opaque) = if punchthrough {
+        (true, _bits(bits, 33, 1) == 1)
+    }
+    else {
+        (_bits(bits, 33, 1) == 1, true)
+    };
+
+    if !differential {
+        let base = [
+            [_bits(bits, 60, 4) * 17, _bits(bits, 52, 4) * 17, _bits(bits, 44, 4) * 17],
+            [_bits(bits, 56, 4) * 17, _bits(bits, 48, 4) * 17, _bits(bits, 40, 4) * 17]
+        ];
+        _decode_subblocks(bits, base, true, texels);
+        return;
+    }
+
+    let r = _bits(bits, 59, 5) as i32;
+    let g = _bits(bits, 51, 5) as i32;
+    let b = _bits(bits, 43, 5) as i32;
+    let r2 = r + _signed3(_bits(bits, 56, 3));
+    let g2 = g + _signed3(_bits(bits, 48, 3));
+    let b2 = b + _signed3(_bits(bits, 40, 3));
+
+    if r2 < 0 || r2 > 31 {
+        _decode_t_mode(bits, opaque, texels);
+    }
+    else if g2 < 0 || g2 > 31 {
+        _decode_h_mode(bits, opaque, texels);
+    }
+    else if b2 < 0 || b2 > 31 {
+        _decode_planar_mode(bits, texels);
+    }
+    else {
+        let base = [
+            [_extend(r as u32, 5), _extend(g as u32, 5), _extend(b as u32, 5)],
+            [_extend(r2 as u32, 5), _extend(g2 as u32, 5), _extend(b2 as u32, 5)]
+        ];
+        _decode_subblocks(bits, base, opaque, texels);
+    }
+}
+
+// Decode an RGBA8_ETC2_EAC block, which is an EAC alpha block followed by
+// an ETC2 RGB block
+pub fn decode_rgba(block: &[u8], texels: &mut [Pixel]) {
+    decode_rgb(&block[8..16], texels, false);
+    let alphas = _decode_eac(&block[0..8]);
+    for (texel, &alpha) in texels.iter_mut().zip(alphas.iter()) {
+        texel.a = alpha;
+    }
+}
+
+// Decode an R11_EAC block, which stores the red component
+pub fn decode_r11(block: &[u8], texels: &mut [Pixel]) {
+    let reds = _decode_r11(block);
+    for (texel, &red) in texels.iter_mut().zip(reds.iter()) {
+        *texel = Pixel::r_g_b_a(red, 0, 0, 0xFF);
+    }
+}
+
+// Decode an RG11_EAC block, which stores the red and green components
+pub fn decode_rg11(block: &[u8], texels: &mut [Pixel]) {
+    let reds = _decode_r11(&block[0..8]);
+    let greens = _decode_r11(&block[8..16]);
+    for (i, texel) in texels.iter_mut().enumerate() {
+        *texel = Pixel::r_g_b_a(reds[i], greens[i], 0, 0xFF);
+    }
+}
+
+// Decode the two subblocks of an individual or differential mode block,
+// given the base color of each subblock
+fn _decode_subblocks(bits: u64,
+                     base: [[u32; 3]; 2],
+                     opaque: bool,
+                     texels: &mut [Pixel])
+{
+    let flip = _bits(bits, 32, 1) == 1;
+    let tables = [_bits(bits, 37, 3) as usize, _bits(bits, 34, 3) as usize];
+
+    for y in 0..4 {
+        for x in 0..4 {
+            let subblock = if flip { y / 2 } else { x / 2 };
+            let index = _texel_index(bits, x, y);
+
+            // Non-opaque punchthrough blocks use the base color instead of
+            // the small modifiers, and index 2 is transparent
+            let modifiers = ETC1_MODIFIERS[tables[subblock]];
+            let modifier = match (index, opaque) {
+                (2, false) => {
+                    texels[y * 4 + x] = Pixel::r_g_b_a(0, 0, 0, 0);
+                    continue;
+                },
+                (0, false) => 0,
+                (0, true) => modifiers[0],
+                (1, _) => modifiers[1],
+                (2, true) => -modifiers[0],
+                _ => -modifiers[1]
+            };
+
+            let color = base[subblock];
+            texels[y * 4 + x] = Pixel::r_g_b_a(_clamp(color[0] as i32 + modifier),
+                                               _clamp(color[1] as i32 + modifier),
+                                               _clamp(color[2] as i32 + modifier),
+                                               0xFF);
+        }
+    }
+}
+
+fn _decode_t_mode(bits: u64, opaque: bool, texels: &mut [Pixel]) {
+    let color1 = [
+        (_bits(bits, 59, 2) << 2 | _bits(bits, 56, 2)) * 17,
+        _bits(bits, 52, 4) * 17,
+        _bits(bits, 48, 4) * 17
+    ];
+    let color2 = [
+        _bits(bits, 44, 4) * 17,
+        _bits(bits, 40, 4) * 17,
+        _bits(bits, 36, 4) * 17
+    ];
+    let distance = ETC2_DISTANCES[(_bits(bits, 34, 2) << 1 | _bits(bits, 32, 1)) as usize];
+
+    let paint = [
+        _offset(color1, 0),
+        _offset(color2, distance),
+        _offset(color2, 0),
+        _offset(color2, -distance)
+    ];
+    _decode_paint_colors(bits, paint, opaque, texels);
+}
+
+fn _decode_h_mode(bits: u64, opaque: bool, texels: &mut [Pixel]) {
+    let color1 = [
+        _bits(bits, 59, 4),
+        _bits(bits, 56, 3) << 1 | _bits(bits, 52, 1),
+        _bits(bits, 51, 1) << 3 | _bits(bits, 47, 3)
+    ];
+    let color2 = [
+        _bits(bits, 43, 4),
+        _bits(bits, 39, 4),
+        _bits(bits, 35, 4)
+    ];
+
+    // The lowest bit of the distance index is implied by the order of
+    // the two colors
+    let value1 = color1[0] << 8 | color1[1] << 4 | color1[2];
+    let value2 = color2[0] << 8 | color2[1] << 4 | color2[2];
+    let index = _bits(bits, 34, 1) << 2 | _bits(bits, 32, 1) << 1
+        | if value1 >= value2 { 1 } else { 0 };
+    let distance = ETC2_DISTANCES[index as usize];
+
+    let color1 = [color1[0] * 17, color1[1] * 17, color1[2] * 17];
+    let color2 = [color2[0] * 17, color2[1] * 17, color2[2] * 17];
+    let paint = [
+        _offset(color1, distance),
+        _offset(color1, -distance),
+        _offset(color2, distance),
+        _offset(color2, -distance)
+    ];
+    _decode_paint_colors(bits, paint, opaque, texels);
+}
+
+fn _decode_planar_mode(bits: u64, texels: &mut [Pixel]) {
+    let origin = [
+        _extend(_bits(bits, 57, 6), 6),
+        _extend(_bits(bits, 56, 1) << 6 | _bits(bits, 49, 6), 7),
+        _extend(_bits(bits, 48, 1) << 5 | _bits(bits, 43, 2) << 3
+                | _bits(bits, 39, 3), 6)
+    ];
+    let horizontal = [
+        _extend(_bits(bits, 34, 5) << 1 | _bits(bits, 32, 1), 6),
+        _extend(_bits(bits, 25, 7), 7),
+        _extend(_bits(bits, 19, 6), 6)
+    ];
+    let vertical = [
+        _extend(_bits(bits, 13, 6), 6),
+        _extend(_bits(bits, 6, 7), 7),
+        _extend(_bits(bits, 0, 6), 6)
+    ];
+
+    for y in 0..4 {
+        for x in 0..4 {
+            let mut color = [0; 3];
+            for i in 0..3 {
+                let (o, h, v) = (origin[i] as i32,
+                                 horizontal[i] as i32,
+                                 vertical[i] as i32);
+                let value = (x as i32 * (h - o) + y as i32 * (v - o)
+                             + 4 * o + 2) >> 2;
+                color[i] = _clamp(value);
+            }
+            texels[y * 4 + x] = Pixel::r_g_b_a(color[0], color[1], color[2],
+                                               0xFF);
+        }
+    }
+}
+
+// Decode a T or H mode block, where each texel's index selects one of
+// four paint colors
+fn _decode_paint_colors(bits: u64,
+                        paint: [[u8; 3]; 4],
+                        opaque: bool,
+                        texels: &mut [Pixel])
+{
+    for y in 0..4 {
+        for x in 0..4 {
+            let index = _texel_index(bits, x, y);
+            texels[y * 4 + x] = if index == 2 && !opaque {
+                Pixel::r_g_b_a(0, 0, 0, 0)
+            }
+            else {
+                let color = paint[index];
+                Pixel::r_g_b_a(color[0], color[1], color[2], 0xFF)
+            };
+        }
+    }
+}
+
+// Decode an EAC block with 8-bit values, as used for ETC2 alpha
+fn _decode_eac(block: &[u8]) -> [u8; 16] {
+    let bits = _read_u64(block);
+    let base = _bits(bits, 56, 8) as i32;
+    let multiplier = _bits(bits, 52, 4) as i32;
+    let modifiers = EAC_MODIFIERS[_bits(bits, 48, 4) as usize];
+
+    let mut values = [0; 16];
+    for y in 0..4 {
+        for x in 0..4 {
+            let index = _bits(bits, 45 - (x * 4 + y) as u32 * 3, 3) as usize;
+            values[y * 4 + x] = _clamp(base + modifiers[index] * multiplier);
+        }
+    }
+    values
+}
+
+// Decode an EAC block with 11-bit values, converted to 8 bits
+fn _decode_r11(block: &[u8]) -> [u8; 16] {
+    let bits = _read_u64(block);
+    let base = _bits(bits, 56, 8) as i32 * 8 + 4;
+    let multiplier = _bits(bits, 52, 4) as i32 * 8;
+    let modifiers = EAC_MODIFIERS[_bits(bits, 48, 4) as usize];
+
+    let mut values = [0; 16];
+    for y in 0..4 {
+        for x in 0..4 {
+            let index = _bits(bits, 45 - (x * 4 + y) as u32 * 3, 3) as usize;
+
+            // A multiplier of 0 uses the modifiers directly
+            let modifier = if multiplier == 0 {
+                modifiers[index]
+            }
+            else {
+                modifiers[index] * multiplier
+            };
+            let value = base + modifier;
+            let value = if value < 0 { 0 } else if value > 2047 { 2047 } else { value };
+            values[y * 4 + x] = ((value * 255 + 1023) / 2047) as u8;
+        }
+    }
+    values
+}
+
+// Get the 2-bit index of a texel in an ETC block. Texels are stored in
+// column-major order, with the high bits of each index stored separately
+// from the low bits
+fn _texel_index(bits: u64, x: usize, y: usize) -> usize {
+    let i = (x * 4 + y) as u32;
+    (_bits(bits, 16 + i, 1) << 1 | _bits(bits, i, 1)) as usize
+}
+
+fn _offset(color: [u32; 3], offset: i32) -> [u8; 3] {
+    [
+        _clamp(color[0] as i32 + offset),
+        _clamp(color[1] as i32 + offset),
+        _clamp(color[2] as i32 + offset)
+    ]
+}
+
+fn _signed3(value: u32) -> i32 {
+    if value >= 4 { value as i32 - 8 } else { value as i32 }
+}
+
+fn _extend(value: u32, bits: u32) -> u32 {
+    value << (8 - bits) | value >> (2 * bits - 8)
+}
+
+fn _clamp(value: i32) -> u8 {
+    if value < 0 { 0 } else if value > 255 { 255 } else { value as u8 }
+}
+
+fn _bits(bits: u64, start: u32, count: u32) -> u32 {
+    ((bits >> start) & ((1 << count) - 1)) as u32
+}
+
+fn _read_u64(block: &[u8]) -> u64 {
+    block[0..8].iter().fold(0, |bits, &byte| bits << 8 | byte as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use image_data::Pixel;
+    use super::*;
+
+    // The texel indices of the T and H mode blocks below, where the
+    // texels in the first column have the indices 0, 1, 2, and 3, and
+    // every other texel has the index 0
+    const FIRST_COLUMN_INDICES: [u8; 4] = [0x00, 0x0C, 0x00, 0x0A];
+
+    // The texel indices of an EAC block with the multiplier and modifier
+    // table bytes 0x80 and 0x00, where the texels count from 0 to 7 in
+    // column-major order
+    const EAC_INDICES: [u8; 6] = [0x05, 0x39, 0x77, 0x05, 0x39, 0x77];
+
+    fn _rgba(texel: Pixel) -> (u8, u8, u8, u8) {
+        (texel.r, texel.g, texel.b, texel.a)
+    }
+
+    fn _block(color: [u8; 4], indices: [u8; 4]) -> Vec<u8> {
+        let mut block = color.to_vec();
+        block.extend_from_slice(&indices);
+        block
+    }
+
+    fn _eac_block(base: u8, multiplier: u8) -> Vec<u8> {
+        let mut block = vec![base, multiplier << 4];
+        block.extend_from_slice(&EAC_INDICES);
+        block
+    }
+
+    fn _decode_rgb(block: &[u8], punchthrough: bool) -> Vec<Pixel> {
+        let mut texels = vec![Pixel::default(); 16];
+        decode_rgb(block, &mut texels, punchthrough);
+        texels
+    }
+
+    #[test]
+    fn test_etc1_individual_mode() {
+        // Gray base colors of 0x88 and 0x44, using the modifier tables 0
+        // and 7. The texel at (0, 0) has the index 3 and the texel at
+        // (3, 3) has the index 1
+        let block = _block([0x84, 0x84, 0x84, 0x1C], [0x00, 0x01, 0x80, 0x01]);
+        let texels = _decode_rgb(&block, false);
+
+        assert_eq!(_rgba(texels[0]), (128, 128, 128, 0xFF));
+        assert_eq!(_rgba(texels[1]), (138, 138, 138, 0xFF));
+        assert_eq!(_rgba(texels[2]), (115, 115, 115, 0xFF));
+        assert_eq!(_rgba(texels[4]), (138, 138, 138, 0xFF));
+        assert_eq!(_rgba(texels[15]), (251, 251, 251, 0xFF));
+    }
+
+    #[test]
+    fn test_etc1_differential_flipped_mode() {
+        // A 5-bit base color of 16 (expanded to 132) and a difference
+        // of -1 (expanded to 123). The subblocks are the top and bottom
+        // halves of the block
+        let block = _block([0x87, 0x87, 0x87, 0x03], [0; 4]);
+        let texels = _decode_rgb(&block, false);
+
+        assert_eq!(_rgba(texels[0]), (134, 134, 134, 0xFF));
+        assert_eq!(_rgba(texels[7]), (134, 134, 134, 0xFF));
+        assert_eq!(_rgba(texels[8]), (125, 125, 125, 0xFF));
+        assert_eq!(_rgba(texels[15]), (125, 125, 125, 0xFF));
+    }
+
+    #[test]
+    fn test_etc2_t_mode() {
+        // The red component overflows. The paint colors are red, and
+        // the 0x008800 green plus 6, plus 0, and minus 6
+        let block = _block([0xFB, 0x00, 0x08, 0x03], FIRST_COLUMN_INDICES);
+        let texels = _decode_rgb(&block, false);
+
+        assert_eq!(_rgba(texels[0]), (0xFF, 0, 0, 0xFF));
+        assert_eq!(_rgba(texels[4]), (6, 142, 6, 0xFF));
+        assert_eq!(_rgba(texels[8]), (0, 136, 0, 0xFF));
+        assert_eq!(_rgba(texels[12]), (0, 130, 0, 0xFF));
+        assert_eq!(_rgba(texels[15]), (0xFF, 0, 0, 0xFF));
+    }
+
+    #[test]
+    fn test_etc2_h_mode() {
+        // The green component overflows. The colors are red and blue,
+        // and red > blue sets the low bit of the distance index
+        let block = _block([0x78, 0x04, 0x00, 0x7A], FIRST_COLUMN_INDICES);
+        let texels = _decode_rgb(&block, false);
+
+        assert_eq!(_rgba(texels[0]), (0xFF, 6, 6, 0xFF));
+        assert_eq!(_rgba(texels[4]), (249, 0, 0, 0xFF));
+        assert_eq!(_rgba(texels[8]), (6, 6, 0xFF, 0xFF));
+        assert_eq!(_rgba(texels[12]), (0, 0, 249, 0xFF));
+    }
+
+    #[test]
+    fn test_etc2_planar_mode() {
+        // The blue component overflows. The origin is red, the
+        // horizontal color is green, and the vertical color is blue
+        let block = [0x7E, 0x00, 0x04, 0x02, 0xFE, 0x00, 0x00, 0x3F];
+        let texels = _decode_rgb(&block, false);
+
+        assert_eq!(_rgba(texels[0]), (0xFF, 0, 0, 0xFF));
+        assert_eq!(_rgba(texels[1]), (191, 64, 0, 0xFF));
+        assert_eq!(_rgba(texels[3]), (64, 191, 0, 0xFF));
+        assert_eq!(_rgba(texels[5]), (128, 64, 64, 0xFF));
+        assert_eq!(_rgba(texels[12]), (64, 0, 191, 0xFF));
+        assert_eq!(_rgba(texels[15]), (0, 191, 191, 0xFF));
+    }
+
+    #[test]
+    fn test_etc2_punchthrough() {
+        // With the opaque bit set, the T mode block decodes normally
+        let block = _block([0xFB, 0x00, 0x08, 0x03], FIRST_COLUMN_INDICES);
+        let texels = _decode_rgb(&block, true);
+        assert_eq!(_rgba(texels[8]), (0, 136, 0, 0xFF));
+
+        // Without it, index 2 is transparent black
+        let block = _block([0xFB, 0x00, 0x08, 0x01], FIRST_COLUMN_INDICES);
+        let texels = _decode_rgb(&block, true);
+        assert_eq!(_rgba(texels[0]), (0xFF, 0, 0, 0xFF));
+        assert_eq!(_rgba(texels[4]), (6, 142, 6, 0xFF));
+        assert_eq!(_rgba(texels[8]), (0, 0, 0, 0));
+
+        // In differential mode, index 0 uses the base color and index 2
+        // is transparent black
+        let block = _block([0x87, 0x87, 0x87, 0x01], [0x00, 0x02, 0x00, 0x00]);
+        let texels = _decode_rgb(&block, true);
+        assert_eq!(_rgba(texels[0]), (132, 132, 132, 0xFF));
+        assert_eq!(_rgba(texels[4]), (0, 0, 0, 0));
+    }
+
+    #[test]
+    fn test_eac_alpha() {
+        // A base of 128 with a multiplier of 2, using modifier table 0
+        let mut block = _eac_block(128, 2);
+        block.extend(_block([0x87, 0x87, 0x87, 0x02], [0; 4]));
+        let mut texels = vec![Pixel::default(); 16];
+        decode_rgba(&block, &mut texels);
+
+        assert_eq!(_rgba(texels[0]), (134, 134, 134, 122));
+        assert_eq!(_rgba(texels[4]), (134, 134, 134, 116));
+        assert_eq!(_rgba(texels[1]), (134, 134, 134, 132));
+        assert_eq!(_rgba(texels[15]), (125, 125, 125, 156));
+    }
+
+    #[test]
+    fn test_r11_eac() {
+        // A base of 128 * 8 + 4 with a multiplier of 8, converted from
+        // 11 bits to 8 bits with rounding
+        let block = _eac_block(128, 1);
+        let mut texels = vec![Pixel::default(); 16];
+        decode_r11(&block, &mut texels);
+
+        let reds: Vec<u8> = (0..4).map(|y| texels[y * 4].r).collect();
+        assert_eq!(reds, [125, 122, 119, 113]);
+        assert_eq!(_rgba(texels[1]), (130, 0, 0, 0xFF));
+        assert_eq!(_rgba(texels[15]), (142, 0, 0, 0xFF));
+    }
+
+    #[test]
+    fn test_rg11_eac() {
+        // A multiplier of 0 adds the modifiers without scaling them
+        let mut block = _eac_block(128, 1);
+        block.extend(_eac_block(128, 0));
+        let mut texels = vec![Pixel::default(); 16];
+        decode_rg11(&block, &mut texels);
+
+        assert_eq!(_rgba(texels[0]), (125, 128, 0, 0xFF));
+        assert_eq!(_rgba(texels[15]), (142, 130, 0, 0xFF));
+    }
+}
@@ -0,0 +1,297 @@
+//! Contains software decoders for compressed texture formats, which are
+//! used to upload compressed data to a context that doesn't support its
+//! format.
+//!
+//! The ETC1, ETC2/EAC, S3TC, RGTC, and ASTC (LDR profile) formats can be
+//! decoded with [`CompressedTexelFormat::decode`]
+//! (../enum.CompressedTexelFormat.html#method.decode), which expands the
+//! compressed blocks into [`Pixels`](../struct.Pixels.html). Decoding
+//! doesn't need an OpenGL context, so the decoders can be tested against
+//! reference images on their own. Compressed images whose format was
+//! created with [`CompressedFormat::decoded`]
+//! (../struct.CompressedFormat.html#method.decoded) (such as the
+//! formats returned by [`gl.compressed_format_or_decoded`]
+//! (../context/texture_context/trait.ContextTextureExt.html#method.compressed_format_or_decoded),
+//! which [`TextureFile`](../texture_file/struct.TextureFile.html) uses)
+//! are decoded automatically when they are uploaded.
+//!
+//! Decoded texels are always stored as 8-bit RGBA values. Formats with
+//! sRGB-encoded color are decoded to sRGB-encoded texels, and formats with
+//! fewer than 4 components are decoded the same way OpenGL would sample
+//! them (missing color components are 0, and missing alpha is opaque).
+//! The signed ETC2/EAC and RGTC formats, and the PVRTC formats, can't be
+//! decoded.
+//!
+//! # Example
+//!
+//! ```
+//! use glitter::{CompressedTexelFormat, ASTC_RGBA_4X4};
+//!
+//! // A "void-extent" ASTC block, where every texel has the same color
+//! let block = [
+//!     0xFC, 0xFD, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+//!     0xFF, 0xFF, 0x00, 0x80, 0x00, 0x00, 0xFF, 0xFF
+//! ];
+//!
+//! let format = CompressedTexelFormat::Astc(ASTC_RGBA_4X4);
+//! let pixels = format.decode(4, 4, &block).unwrap();
+//! let pixel = pixels[3][2];
+//! assert_eq!((pixel.r, pixel.g, pixel.b, pixel.a), (0xFF, 0x80, 0x00, 0xFF));
+//! ```
+
+use std::fmt;
+use std::error;
+use image_data::{Pixel, Pixels};
+use compressed_image::{CompressedTexelFormat, Etc2Format, S3tcFormat,
+                       RgtcFormat};
+
+mod bc;
+mod etc;
+mod astc;
+
+/// An error that occurred while decoding compressed texel data.
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The format can't be decoded on the CPU.
+    UnsupportedFormat(CompressedTexelFormat),
+
+    /// The length of the compressed data doesn't match the image's
+    /// dimensions.
+    WrongSize {
+        /// The number of bytes for an image with the given dimensions.
+        expected: usize,
+
+        /// The number of bytes that were provided.
+        actual: usize
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DecodeError::UnsupportedFormat(format) => {
+                write!(f, "can't decode texels with format {:?}", format)
+            },
+            DecodeError::WrongSize { expected, actual } => {
+                write!(f, "expected {} bytes of compressed data, but got {}",
+                       expected, actual)
+            }
+        }
+    }
+}
+
+impl error::Error for DecodeError {
+    fn description(&self) -> &str {
+        match *self {
+            DecodeError::UnsupportedFormat(_) => "unsupported compressed format",
+            DecodeError::WrongSize { .. } => "wrong compressed data size"
+        }
+    }
+}
+
+impl CompressedTexelFormat {
+    /// Returns `true` if data with this format can be decoded with
+    /// [`decode`](#method.decode).
+    pub fn can_decode(&self) -> bool {
+        match *self {
+            CompressedTexelFormat::Etc2(Etc2Format::SignedR11Eac) |
+            CompressedTexelFormat::Etc2(Etc2Format::SignedRg11Eac) |
+            CompressedTexelFormat::Rgtc(RgtcFormat::SignedRedRgtc1) |
+            CompressedTexelFormat::Rgtc(RgtcFormat::SignedRgRgtc2) |
+            CompressedTexelFormat::Pvrtc(_) => false,
+            _ => true
+        }
+    }
+
+    /// Decode a `width` by `height` image of compressed data into
+    /// uncompressed pixels. Returns an error if the format can't be
+    /// decoded, or if `bytes` isn't exactly [`image_size`]
+    /// (#method.image_size) bytes long.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use glitter::{CompressedTexelFormat, DXT1_RGB};
+    ///
+    /// // A DXT1 block where every texel is pure red
+    /// let block = [0x00, 0xF8, 0x00, 0xF8, 0x00, 0x00, 0x00, 0x00];
+    ///
+    /// let format = CompressedTexelFormat::S3tc(DXT1_RGB);
+    /// let pixels = format.decode(2, 2, &block).unwrap();
+    /// let pixel = pixels[1][1];
+    /// assert_eq!((pixel.r, pixel.g, pixel.b, pixel.a), (0xFF, 0x00, 0x00, 0xFF));
+    /// ```
+    pub fn decode(&self, width: usize, height: usize, bytes: &[u8])
+        -> Result<Pixels, DecodeError>
+    {
+        if !self.can_decode() {
+            return Err(DecodeError::UnsupportedFormat(*self));
+        }
+
+        let expected = self.image_size(width, height);
+        if bytes.len() != expected {
+            return Err(DecodeError::WrongSize {
+                expected: expected,
+                actual: bytes.len()
+            });
+        }
+
+        let (block_width, block_height) = self.block_dimensions();
+        let block_size = self.block_size();
+        let blocks_x = (width + block_width - 1) / block_width;
+
+        let mut pixels = Pixels::new(width, height);
+        let mut texels = vec![Pixel::default(); block_width * block_height];
+        for (i, block) in bytes.chunks(block_size).enumerate() {
+            self._decode_block(block, &mut texels);
+
+            // Copy the block's texels, skipping any texels past the edges
+            // of the image
+            let x = (i % blocks_x) * block_width;
+            let y = (i / blocks_x) * block_height;
+            for by in 0..block_height {
+                if y + by >= height {
+                    break;
+                }
+                for bx in 0..block_width {
+                    if x + bx >= width {
+                        break;
+                    }
+                    pixels[y + by][x + bx] = texels[by * block_width + bx];
+                }
+            }
+        }
+
+        Ok(pixels)
+    }
+
+    fn _decode_block(&self, block: &[u8], texels: &mut [Pixel]) {
+        match *self {
+            CompressedTexelFormat::Etc1(_) => {
+                etc::decode_rgb(block, texels, false);
+            },
+            CompressedTexelFormat::Etc2(format) => {
+                match format {
+                    Etc2Format::Rgb8Etc2 | Etc2Format::Srgb8Etc2 => {
+                        etc::decode_rgb(block, texels, false);
+                    },
+                    Etc2Format::Rgb8PunchthroughAlpha1Etc2 |
+                    Etc2Format::Srgb8PunchthroughAlpha1Etc2 => {
+                        etc::decode_rgb(block, texels, true);
+                    },
+                    Etc2Format::Rgba8Etc2Eac |
+                    Etc2Format::Srgb8Alpha8Etc2Eac => {
+                        etc::decode_rgba(block, texels);
+                    },
+                    Etc2Format::R11Eac => {
+                        etc::decode_r11(block, texels);
+                    },
+                    Etc2Format::Rg11Eac => {
+                        etc::decode_rg11(block, texels);
+                    },
+                    Etc2Format::SignedR11Eac |
+                    Etc2Format::SignedRg11Eac => {
+                        unreachable!();
+                    }
+                }
+            },
+            CompressedTexelFormat::S3tc(format) => {
+                match format {
+                    S3tcFormat::Dxt1Rgb => bc::decode_bc1(block, texels, false),
+                    S3tcFormat::Dxt1Rgba => bc::decode_bc1(block, texels, true),
+                    S3tcFormat::Dxt3Rgba => bc::decode_bc2(block, texels),
+                    S3tcFormat::Dxt5Rgba => bc::decode_bc3(block, texels)
+                }
+            },
+            CompressedTexelFormat::Rgtc(format) => {
+                match format {
+                    RgtcFormat::RedRgtc1 => bc::decode_bc4(block, texels),
+                    RgtcFormat::RgRgtc2 => bc::decode_bc5(block, texels),
+                    RgtcFormat::SignedRedRgtc1 |
+                    RgtcFormat::SignedRgRgtc2 => unreachable!()
+                }
+            },
+            CompressedTexelFormat::Astc(format) => {
+                let (block_width, block_height) = self.block_dimensions();
+                let srgb = format.gl_enum() >= 0x93D0;
+                astc::decode(block, block_width, block_height, srgb, texels);
+            },
+            CompressedTexelFormat::Pvrtc(_) => unreachable!()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image_data::Image2d;
+    use compressed_image::{CompressedTexelFormat, Etc2Format, S3tcFormat,
+                           AstcFormat};
+    use super::*;
+
+    // A DXT1 block where every texel has the given RGB565 color
+    fn _solid_dxt1(color: u16) -> Vec<u8> {
+        vec![color as u8, (color >> 8) as u8, color as u8, (color >> 8) as u8,
+             0, 0, 0, 0]
+    }
+
+    fn _rgba(pixels: &Pixels, x: usize, y: usize) -> (u8, u8, u8, u8) {
+        let pixel = pixels[y][x];
+        (pixel.r, pixel.g, pixel.b, pixel.a)
+    }
+
+    #[test]
+    fn test_partial_edge_blocks() {
+        // A 5x5 image is stored as 2x2 blocks, and only the first column
+        // and row of the right and bottom blocks are used
+        let format = CompressedTexelFormat::S3tc(S3tcFormat::Dxt1Rgb);
+        assert_eq!(format.image_size(5, 5), 32);
+
+        let mut bytes = _solid_dxt1(0xF800);
+        bytes.extend(_solid_dxt1(0x07E0));
+        bytes.extend(_solid_dxt1(0x001F));
+        bytes.extend(_solid_dxt1(0xFFFF));
+        let pixels = format.decode(5, 5, &bytes).unwrap();
+
+        assert_eq!((pixels.width(), pixels.height()), (5, 5));
+        assert_eq!(_rgba(&pixels, 3, 3), (0xFF, 0, 0, 0xFF));
+        assert_eq!(_rgba(&pixels, 4, 0), (0, 0xFF, 0, 0xFF));
+        assert_eq!(_rgba(&pixels, 0, 4), (0, 0, 0xFF, 0xFF));
+        assert_eq!(_rgba(&pixels, 4, 4), (0xFF, 0xFF, 0xFF, 0xFF));
+    }
+
+    #[test]
+    fn test_partial_astc_block() {
+        // A 5x3 image with 4x4 blocks is stored as 2 blocks
+        let block = [
+            0xFC, 0xFD, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+            0x00, 0x80, 0x00, 0x40, 0x00, 0x20, 0xFF, 0xFF
+        ];
+        let format = CompressedTexelFormat::Astc(AstcFormat::Rgba4x4);
+        let mut bytes = vec![0; 16];
+        bytes.extend_from_slice(&block);
+        let pixels = format.decode(5, 3, &bytes).unwrap();
+
+        assert_eq!((pixels.width(), pixels.height()), (5, 3));
+        assert_eq!(_rgba(&pixels, 0, 0), (0xFF, 0, 0xFF, 0xFF));
+        assert_eq!(_rgba(&pixels, 4, 2), (0x80, 0x40, 0x20, 0xFF));
+    }
+
+    #[test]
+    fn test_wrong_size() {
+        let format = CompressedTexelFormat::S3tc(S3tcFormat::Dxt1Rgb);
+        match format.decode(5, 5, &[0; 24]) {
+            Err(DecodeError::WrongSize { expected: 32, actual: 24 }) => {},
+            result => panic!("Unexpected result: {:?}", result.map(|_| ()))
+        }
+    }
+
+    #[test]
+    fn test_unsupported_format() {
+        let format = CompressedTexelFormat::Etc2(Etc2Format::SignedR11Eac);
+        assert!(!format.can_decode());
+        match format.decode(4, 4, &[0; 8]) {
+            Err(DecodeError::UnsupportedFormat(_)) => {},
+            result => panic!("Unexpected result: {:?}", result.map(|_| ()))
+        }
+    }
+}
@@ -0,0 +1,614 @@
+//! Contains types for compressed image data, which can be uploaded to a
+//! texture with [`gl.compressed_tex_image_2d`]
+//! (../context/texture_context/trait.ContextTextureExt.html#method.compressed_tex_image_2d)
+//! and stays compressed in GPU memory.
+//!
+//! Each family of compressed formats is only supported when the context
+//! supports the matching extension. The formats are described by the
+//! enums [`Etc1Format`](enum.Etc1Format.html), [`Etc2Format`]
+//! (enum.Etc2Format.html), [`S3tcFormat`](enum.S3tcFormat.html),
+//! [`RgtcFormat`](enum.RgtcFormat.html), [`PvrtcFormat`]
+//! (enum.PvrtcFormat.html), and [`AstcFormat`](enum.AstcFormat.html), but a [`CompressedFormat`]
+//! (struct.CompressedFormat.html) (which is needed to create a compressed
+//! image) can only be created from one of them with a proof that the
+//! extension is supported, such as [`S3tcSupport`]
+//! (struct.S3tcSupport.html). Alternatively, a format that the context
+//! doesn't support can be decoded on the CPU when it's uploaded (see
+//! [`CompressedFormat::decoded`]
+//! (struct.CompressedFormat.html#method.decoded)).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use glitter::prelude::*;
+//! use glitter::{S3tcSupport, CompressedImage, DXT1_RGB};
+//! # let mut gl = unsafe { glitter::Context::current_context() };
+//! # let data = vec![0; 8];
+//!
+//! let s3tc = S3tcSupport::query(&gl).expect("S3TC is not supported");
+//! let image = CompressedImage::new(4, 4, s3tc.format(DXT1_RGB), data)
+//!     .expect("Wrong number of bytes for a 4x4 DXT1 image");
+//!
+//! let texture = gl.build_texture_2d()
+//!     .compressed_image_2d(&image)
+//!     .min_filter(glitter::LINEAR)
+//!     .unwrap();
+//! ```
+
+use gl::types::*;
+use context::{BaseContext, ContextExt};
+
+gl_enum! {
+    /// The ETC1 compressed texture format, from the
+    /// `GL_OES_compressed_ETC1_RGB8_texture` extension.
+    pub gl_enum Etc1Format {
+        /// 4x4 blocks of RGB texels, stored in 8 bytes.
+        pub const Etc1Rgb8 as ETC1_RGB8 = 0x8D64
+    }
+}
+
+gl_enum! {
+    /// The ETC2 and EAC compressed texture formats, which are part of
+    /// OpenGL ES 3.0.
+    pub gl_enum Etc2Format {
+        /// 4x4 blocks with a single unsigned component, stored in 8 bytes.
+        pub const R11Eac as R11_EAC = 0x9270,
+
+        /// 4x4 blocks with a single signed component, stored in 8 bytes.
+        pub const SignedR11Eac as SIGNED_R11_EAC = 0x9271,
+
+        /// 4x4 blocks with two unsigned components, stored in 16 bytes.
+        pub const Rg11Eac as RG11_EAC = 0x9272,
+
+        /// 4x4 blocks with two signed components, stored in 16 bytes.
+        pub const SignedRg11Eac as SIGNED_RG11_EAC = 0x9273,
+
+        /// 4x4 blocks of RGB texels, stored in 8 bytes.
+        pub const Rgb8Etc2 as RGB8_ETC2 = 0x9274,
+
+        /// 4x4 blocks of sRGB texels, stored in 8 bytes.
+        pub const Srgb8Etc2 as SRGB8_ETC2 = 0x9275,
+
+        /// 4x4 blocks of RGB texels with 1-bit alpha, stored in 8 bytes.
+        pub const Rgb8PunchthroughAlpha1Etc2 as RGB8_PUNCHTHROUGH_ALPHA1_ETC2 =
+            0x9276,
+
+        /// 4x4 blocks of sRGB texels with 1-bit alpha, stored in 8 bytes.
+        pub const Srgb8PunchthroughAlpha1Etc2 as SRGB8_PUNCHTHROUGH_ALPHA1_ETC2 =
+            0x9277,
+
+        /// 4x4 blocks of RGBA texels, stored in 16 bytes.
+        pub const Rgba8Etc2Eac as RGBA8_ETC2_EAC = 0x9278,
+
+        /// 4x4 blocks of sRGB texels with alpha, stored in 16 bytes.
+        pub const Srgb8Alpha8Etc2Eac as SRGB8_ALPHA8_ETC2_EAC = 0x9279
+    }
+}
+
+gl_enum! {
+    /// The S3TC (also known as DXT or BC1-BC3) compressed texture formats,
+    /// from the `GL_EXT_texture_compression_s3tc` extension.
+    pub gl_enum S3tcFormat {
+        /// 4x4 blocks of RGB texels, stored in 8 bytes.
+        pub const Dxt1Rgb as DXT1_RGB = 0x83F0,
+
+        /// 4x4 blocks of RGB texels with 1-bit alpha, stored in 8 bytes.
+        pub const Dxt1Rgba as DXT1_RGBA = 0x83F1,
+
+        /// 4x4 blocks of RGB texels with explicit 4-bit alpha, stored
+        /// in 16 bytes.
+        pub const Dxt3Rgba as DXT3_RGBA = 0x83F2,
+
+        /// 4x4 blocks of RGB texels with interpolated alpha, stored
+        /// in 16 bytes.
+        pub const Dxt5Rgba as DXT5_RGBA = 0x83F3
+    }
+}
+
+gl_enum! {
+    /// The RGTC (also known as BC4 and BC5) compressed texture formats,
+    /// from the `GL_EXT_texture_compression_rgtc` extension.
+    pub gl_enum RgtcFormat {
+        /// 4x4 blocks with a single unsigned component, stored in 8 bytes.
+        pub const RedRgtc1 as RED_RGTC1 = 0x8DBB,
+
+        /// 4x4 blocks with a single signed component, stored in 8 bytes.
+        pub const SignedRedRgtc1 as SIGNED_RED_RGTC1 = 0x8DBC,
+
+        /// 4x4 blocks with two unsigned components, stored in 16 bytes.
+        pub const RgRgtc2 as RG_RGTC2 = 0x8DBD,
+
+        /// 4x4 blocks with two signed components, stored in 16 bytes.
+        pub const SignedRgRgtc2 as SIGNED_RG_RGTC2 = 0x8DBE
+    }
+}
+
+gl_enum! {
+    /// The PVRTC compressed texture formats, from the
+    /// `GL_IMG_texture_compression_pvrtc` extension.
+    pub gl_enum PvrtcFormat {
+        /// RGB texels, using 4 bits per texel.
+        pub const Rgb4Bpp as PVRTC_RGB_4BPP = 0x8C00,
+
+        /// RGB texels, using 2 bits per texel.
+        pub const Rgb2Bpp as PVRTC_RGB_2BPP = 0x8C01,
+
+        /// RGBA texels, using 4 bits per texel.
+        pub const Rgba4Bpp as PVRTC_RGBA_4BPP = 0x8C02,
+
+        /// RGBA texels, using 2 bits per texel.
+        pub const Rgba2Bpp as PVRTC_RGBA_2BPP = 0x8C03
+    }
+}
+
+gl_enum! {
+    /// The ASTC compressed texture formats, from the
+    /// `GL_KHR_texture_compression_astc_ldr` extension. Every block is
+    /// stored in 16 bytes, and the variant names give the size of a block
+    /// in texels.
+    pub gl_enum AstcFormat {
+        /// 4x4 blocks of RGBA texels.
+        pub const Rgba4x4 as ASTC_RGBA_4X4 = 0x93B0,
+        /// 5x4 blocks of RGBA texels.
+        pub const Rgba5x4 as ASTC_RGBA_5X4 = 0x93B1,
+        /// 5x5 blocks of RGBA texels.
+        pub const Rgba5x5 as ASTC_RGBA_5X5 = 0x93B2,
+        /// 6x5 blocks of RGBA texels.
+        pub const Rgba6x5 as ASTC_RGBA_6X5 = 0x93B3,
+        /// 6x6 blocks of RGBA texels.
+        pub const Rgba6x6 as ASTC_RGBA_6X6 = 0x93B4,
+        /// 8x5 blocks of RGBA texels.
+        pub const Rgba8x5 as ASTC_RGBA_8X5 = 0x93B5,
+        /// 8x6 blocks of RGBA texels.
+        pub const Rgba8x6 as ASTC_RGBA_8X6 = 0x93B6,
+        /// 8x8 blocks of RGBA texels.
+        pub const Rgba8x8 as ASTC_RGBA_8X8 = 0x93B7,
+        /// 10x5 blocks of RGBA texels.
+        pub const Rgba10x5 as ASTC_RGBA_10X5 = 0x93B8,
+        /// 10x6 blocks of RGBA texels.
+        pub const Rgba10x6 as ASTC_RGBA_10X6 = 0x93B9,
+        /// 10x8 blocks of RGBA texels.
+        pub const Rgba10x8 as ASTC_RGBA_10X8 = 0x93BA,
+        /// 10x10 blocks of RGBA texels.
+        pub const Rgba10x10 as ASTC_RGBA_10X10 = 0x93BB,
+        /// 12x10 blocks of RGBA texels.
+        pub const Rgba12x10 as ASTC_RGBA_12X10 = 0x93BC,
+        /// 12x12 blocks of RGBA texels.
+        pub const Rgba12x12 as ASTC_RGBA_12X12 = 0x93BD,
+
+        /// 4x4 blocks of sRGB texels with alpha.
+        pub const SrgbAlpha4x4 as ASTC_SRGB8_ALPHA8_4X4 = 0x93D0,
+        /// 5x4 blocks of sRGB texels with alpha.
+        pub const SrgbAlpha5x4 as ASTC_SRGB8_ALPHA8_5X4 = 0x93D1,
+        /// 5x5 blocks of sRGB texels with alpha.
+        pub const SrgbAlpha5x5 as ASTC_SRGB8_ALPHA8_5X5 = 0x93D2,
+        /// 6x5 blocks of sRGB texels with alpha.
+        pub const SrgbAlpha6x5 as ASTC_SRGB8_ALPHA8_6X5 = 0x93D3,
+        /// 6x6 blocks of sRGB texels with alpha.
+        pub const SrgbAlpha6x6 as ASTC_SRGB8_ALPHA8_6X6 = 0x93D4,
+        /// 8x5 blocks of sRGB texels with alpha.
+        pub const SrgbAlpha8x5 as ASTC_SRGB8_ALPHA8_8X5 = 0x93D5,
+        /// 8x6 blocks of sRGB texels with alpha.
+        pub const SrgbAlpha8x6 as ASTC_SRGB8_ALPHA8_8X6 = 0x93D6,
+        /// 8x8 blocks of sRGB texels with alpha.
+        pub const SrgbAlpha8x8 as ASTC_SRGB8_ALPHA8_8X8 = 0x93D7,
+        /// 10x5 blocks of sRGB texels with alpha.
+        pub const SrgbAlpha10x5 as ASTC_SRGB8_ALPHA8_10X5 = 0x93D8,
+        /// 10x6 blocks of sRGB texels with alpha.
+        pub const SrgbAlpha10x6 as ASTC_SRGB8_ALPHA8_10X6 = 0x93D9,
+        /// 10x8 blocks of sRGB texels with alpha.
+        pub const SrgbAlpha10x8 as ASTC_SRGB8_ALPHA8_10X8 = 0x93DA,
+        /// 10x10 blocks of sRGB texels with alpha.
+        pub const SrgbAlpha10x10 as ASTC_SRGB8_ALPHA8_10X10 = 0x93DB,
+        /// 12x10 blocks of sRGB texels with alpha.
+        pub const SrgbAlpha12x10 as ASTC_SRGB8_ALPHA8_12X10 = 0x93DC,
+        /// 12x12 blocks of sRGB texels with alpha.
+        pub const SrgbAlpha12x12 as ASTC_SRGB8_ALPHA8_12X12 = 0x93DD
+    }
+}
+
+/// Any of the compressed texture formats that glitter knows about,
+/// whether or not it is supported by the current context.
+///
+/// A `CompressedTexelFormat` can be used to describe compressed data
+/// without an OpenGL context, such as when reading a texture file.
+/// To upload the data, use [`gl.compressed_format`]
+/// (../context/texture_context/trait.ContextTextureExt.html#method.compressed_format)
+/// to check that the format is supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompressedTexelFormat {
+    /// An ETC1 format.
+    Etc1(Etc1Format),
+
+    /// An ETC2 or EAC format.
+    Etc2(Etc2Format),
+
+    /// An S3TC format.
+    S3tc(S3tcFormat),
+
+    /// An RGTC format.
+    Rgtc(RgtcFormat),
+
+    /// A PVRTC format.
+    Pvrtc(PvrtcFormat),
+
+    /// An ASTC format.
+    Astc(AstcFormat)
+}
+
+impl CompressedTexelFormat {
+    /// Convert from a raw OpenGL integer value to a compressed format.
+    /// Returns an error if the value is not a compressed format that
+    /// glitter knows about.
+    pub fn from_gl(gl_enum: GLenum) -> Result<Self, ()> {
+        Etc1Format::from_gl(gl_enum).map(CompressedTexelFormat::Etc1)
+            .or_else(|_| Etc2Format::from_gl(gl_enum).map(CompressedTexelFormat::Etc2))
+            .or_else(|_| S3tcFormat::from_gl(gl_enum).map(CompressedTexelFormat::S3tc))
+            .or_else(|_| RgtcFormat::from_gl(gl_enum).map(CompressedTexelFormat::Rgtc))
+            .or_else(|_| PvrtcFormat::from_gl(gl_enum).map(CompressedTexelFormat::Pvrtc))
+            .or_else(|_| AstcFormat::from_gl(gl_enum).map(CompressedTexelFormat::Astc))
+    }
+
+    /// Return the OpenGL integer value for the format.
+    pub fn gl_enum(&self) -> GLenum {
+        match *self {
+            CompressedTexelFormat::Etc1(format) => format.gl_enum(),
+            CompressedTexelFormat::Etc2(format) => format.gl_enum(),
+            CompressedTexelFormat::S3tc(format) => format.gl_enum(),
+            CompressedTexelFormat::Rgtc(format) => format.gl_enum(),
+            CompressedTexelFormat::Pvrtc(format) => format.gl_enum(),
+            CompressedTexelFormat::Astc(format) => format.gl_enum()
+        }
+    }
+
+    /// Get the width and height of each block of texels, in texels.
+    pub fn block_dimensions(&self) -> (usize, usize) {
+        match *self {
+            CompressedTexelFormat::Etc1(_) |
+            CompressedTexelFormat::Etc2(_) |
+            CompressedTexelFormat::S3tc(_) |
+            CompressedTexelFormat::Rgtc(_) => (4, 4),
+            CompressedTexelFormat::Pvrtc(format) => {
+                match format {
+                    PvrtcFormat::Rgb4Bpp | PvrtcFormat::Rgba4Bpp => (4, 4),
+                    PvrtcFormat::Rgb2Bpp | PvrtcFormat::Rgba2Bpp => (8, 4)
+                }
+            },
+            CompressedTexelFormat::Astc(format) => {
+                const ASTC_BLOCKS: [(usize, usize); 14] = [
+                    (4, 4), (5, 4), (5, 5), (6, 5), (6, 6), (8, 5), (8, 6),
+                    (8, 8), (10, 5), (10, 6), (10, 8), (10, 10), (12, 10),
+                    (12, 12)
+                ];
+                ASTC_BLOCKS[(format.gl_enum() & 0xF) as usize]
+            }
+        }
+    }
+
+    /// Get the number of bytes used to store each block of texels.
+    pub fn block_size(&self) -> usize {
+        match *self {
+            CompressedTexelFormat::Etc1(_) => 8,
+            CompressedTexelFormat::Etc2(format) => {
+                match format {
+                    Etc2Format::Rg11Eac |
+                    Etc2Format::SignedRg11Eac |
+                    Etc2Format::Rgba8Etc2Eac |
+                    Etc2Format::Srgb8Alpha8Etc2Eac => 16,
+                    _ => 8
+                }
+            },
+            CompressedTexelFormat::S3tc(format) => {
+                match format {
+                    S3tcFormat::Dxt1Rgb | S3tcFormat::Dxt1Rgba => 8,
+                    S3tcFormat::Dxt3Rgba | S3tcFormat::Dxt5Rgba => 16
+                }
+            },
+            CompressedTexelFormat::Rgtc(format) => {
+                match format {
+                    RgtcFormat::RedRgtc1 | RgtcFormat::SignedRedRgtc1 => 8,
+                    RgtcFormat::RgRgtc2 | RgtcFormat::SignedRgRgtc2 => 16
+                }
+            },
+            CompressedTexelFormat::Pvrtc(_) => 8,
+            CompressedTexelFormat::Astc(_) => 16
+        }
+    }
+
+    /// Get the number of bytes used to store an image with this format
+    /// and the given size. Partial blocks at the right and bottom edges
+    /// of the image take up a whole block.
+    ///
+    /// # Example
+    /// ```
+    /// use glitter::{CompressedTexelFormat, DXT5_RGBA, PVRTC_RGB_4BPP};
+    ///
+    /// let dxt5 = CompressedTexelFormat::S3tc(DXT5_RGBA);
+    /// assert_eq!(dxt5.image_size(16, 16), 256);
+    /// assert_eq!(dxt5.image_size(2, 2), 16);
+    ///
+    /// // PVRTC images always take up at least 2x2 blocks
+    /// let pvrtc = CompressedTexelFormat::Pvrtc(PVRTC_RGB_4BPP);
+    /// assert_eq!(pvrtc.image_size(1, 1), 32);
+    /// ```
+    pub fn image_size(&self, width: usize, height: usize) -> usize {
+        let (block_width, block_height) = self.block_dimensions();
+        let mut blocks_x = (width + block_width - 1) / block_width;
+        let mut blocks_y = (height + block_height - 1) / block_height;
+        if let CompressedTexelFormat::Pvrtc(_) = *self {
+            blocks_x = if blocks_x > 2 { blocks_x } else { 2 };
+            blocks_y = if blocks_y > 2 { blocks_y } else { 2 };
+        }
+        blocks_x * blocks_y * self.block_size()
+    }
+
+    /// Returns `true` if a region of an image with this format can be
+    /// updated with [`gl.compressed_tex_sub_image_2d`]
+    /// (../context/texture_context/trait.ContextTextureExt.html#method.compressed_tex_sub_image_2d).
+    /// The ETC1 and PVRTC extensions don't allow updating part of an image.
+    pub fn supports_sub_image(&self) -> bool {
+        match *self {
+            CompressedTexelFormat::Etc1(_) |
+            CompressedTexelFormat::Pvrtc(_) => false,
+            _ => true
+        }
+    }
+}
+
+impl From<Etc1Format> for CompressedTexelFormat {
+    fn from(format: Etc1Format) -> Self {
+        CompressedTexelFormat::Etc1(format)
+    }
+}
+
+impl From<Etc2Format> for CompressedTexelFormat {
+    fn from(format: Etc2Format) -> Self {
+        CompressedTexelFormat::Etc2(format)
+    }
+}
+
+impl From<S3tcFormat> for CompressedTexelFormat {
+    fn from(format: S3tcFormat) -> Self {
+        CompressedTexelFormat::S3tc(format)
+    }
+}
+
+impl From<RgtcFormat> for CompressedTexelFormat {
+    fn from(format: RgtcFormat) -> Self {
+        CompressedTexelFormat::Rgtc(format)
+    }
+}
+
+impl From<PvrtcFormat> for CompressedTexelFormat {
+    fn from(format: PvrtcFormat) -> Self {
+        CompressedTexelFormat::Pvrtc(format)
+    }
+}
+
+impl From<AstcFormat> for CompressedTexelFormat {
+    fn from(format: AstcFormat) -> Self {
+        CompressedTexelFormat::Astc(format)
+    }
+}
+
+/// A compressed texture format that is known to be uploadable to the
+/// current context. A `CompressedFormat` for a format that the context
+/// supports can only be created with one of the extension proofs (such
+/// as [`S3tcSupport`](struct.S3tcSupport.html)), or with
+/// [`gl.compressed_format`]
+/// (../context/texture_context/trait.ContextTextureExt.html#method.compressed_format).
+/// A `CompressedFormat` for any format that can be decoded on the CPU
+/// can be created with [`decoded`](#method.decoded).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompressedFormat {
+    texel_format: CompressedTexelFormat,
+    decoded: bool
+}
+
+impl CompressedFormat {
+    /// Create a `CompressedFormat` without checking that the format
+    /// is supported.
+    ///
+    /// # Safety
+    /// The format must be supported by the current context.
+    pub unsafe fn from_texel_format_unchecked<F>(format: F) -> Self
+        where F: Into<CompressedTexelFormat>
+    {
+        CompressedFormat { texel_format: format.into(), decoded: false }
+    }
+
+    /// Create a `CompressedFormat` that doesn't need to be supported by
+    /// the current context. Images with this format are decoded on the
+    /// CPU (see [`CompressedTexelFormat::decode`]
+    /// (enum.CompressedTexelFormat.html#method.decode)) and uploaded as
+    /// uncompressed RGBA data. Returns `None` if the format can't
+    /// be decoded.
+    pub fn decoded<F>(format: F) -> Option<Self>
+        where F: Into<CompressedTexelFormat>
+    {
+        let format = format.into();
+        if format.can_decode() {
+            Some(CompressedFormat { texel_format: format, decoded: true })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Get the underlying compressed texel format.
+    pub fn texel_format(&self) -> CompressedTexelFormat {
+        self.texel_format
+    }
+
+    /// Returns `true` if images with this format are decoded on the CPU
+    /// when they are uploaded, instead of being uploaded as compressed
+    /// data.
+    pub fn is_decoded(&self) -> bool {
+        self.decoded
+    }
+}
+
+macro_rules! extension_proof {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident($format:ident);
+        fn supported<$c:ident>($gl:ident) $supported:block
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name {
+            _private: ()
+        }
+
+        impl $name {
+            /// Check whether the current context supports this family of
+            /// formats, and return a proof if it does.
+            pub fn query<$c: BaseContext>($gl: &$c) -> Option<Self> {
+                if $supported {
+                    Some($name { _private: () })
+                }
+                else {
+                    None
+                }
+            }
+
+            /// Get a [`CompressedFormat`](struct.CompressedFormat.html)
+            /// for one of the formats in this family, which can be used to
+            /// create compressed images.
+            pub fn format(&self, format: $format) -> CompressedFormat {
+                CompressedFormat {
+                    texel_format: format.into(),
+                    decoded: false
+                }
+            }
+        }
+    }
+}
+
+extension_proof! {
+    /// A proof that the current context supports the [`Etc1Format`]
+    /// (enum.Etc1Format.html) format.
+    pub struct Etc1Support(Etc1Format);
+    fn supported<C>(gl) {
+        gl.has_extension("GL_OES_compressed_ETC1_RGB8_texture")
+    }
+}
+
+extension_proof! {
+    /// A proof that the current context supports the [`Etc2Format`]
+    /// (enum.Etc2Format.html) formats (see [`gl.supports_etc2_textures`]
+    /// (../context/trait.ContextExt.html#method.supports_etc2_textures)).
+    pub struct Etc2Support(Etc2Format);
+    fn supported<C>(gl) {
+        gl.supports_etc2_textures()
+    }
+}
+
+extension_proof! {
+    /// A proof that the current context supports the [`S3tcFormat`]
+    /// (enum.S3tcFormat.html) formats.
+    pub struct S3tcSupport(S3tcFormat);
+    fn supported<C>(gl) {
+        gl.has_extension("GL_EXT_texture_compression_s3tc")
+    }
+}
+
+extension_proof! {
+    /// A proof that the current context supports the [`RgtcFormat`]
+    /// (enum.RgtcFormat.html) formats.
+    pub struct RgtcSupport(RgtcFormat);
+    fn supported<C>(gl) {
+        gl.has_extension("GL_EXT_texture_compression_rgtc")
+    }
+}
+
+extension_proof! {
+    /// A proof that the current context supports the [`PvrtcFormat`]
+    /// (enum.PvrtcFormat.html) formats.
+    pub struct PvrtcSupport(PvrtcFormat);
+    fn supported<C>(gl) {
+        gl.has_extension("GL_IMG_texture_compression_pvrtc")
+    }
+}
+
+extension_proof! {
+    /// A proof that the current context supports the [`AstcFormat`]
+    /// (enum.AstcFormat.html) formats.
+    pub struct AstcSupport(AstcFormat);
+    fn supported<C>(gl) {
+        gl.has_extension("GL_KHR_texture_compression_astc_ldr")
+    }
+}
+
+/// A 2-dimensional image of compressed texel data, which can be uploaded
+/// to a texture with [`gl.compressed_tex_image_2d`]
+/// (../context/texture_context/trait.ContextTextureExt.html#method.compressed_tex_image_2d).
+pub trait CompressedImage2d {
+    /// The width of the image, in texels.
+    fn width(&self) -> usize;
+
+    /// The height of the image, in texels.
+    fn height(&self) -> usize;
+
+    /// The compressed format of the image's data.
+    fn format(&self) -> CompressedFormat;
+
+    /// The image's compressed data, which must be exactly [`image_size`]
+    /// (enum.CompressedTexelFormat.html#method.image_size) bytes long.
+    fn compressed_bytes(&self) -> &[u8];
+}
+
+/// A (heap-allocated) compressed 2D image.
+#[derive(Debug, Clone)]
+pub struct CompressedImage {
+    width: usize,
+    height: usize,
+    format: CompressedFormat,
+    bytes: Vec<u8>
+}
+
+impl CompressedImage {
+    /// Create a compressed image from its compressed data. Returns `None`
+    /// if the length of `bytes` does not match the size of a `width` by
+    /// `height` image with the given format.
+    pub fn new(width: usize,
+               height: usize,
+               format: CompressedFormat,
+               bytes: Vec<u8>)
+        -> Option<Self>
+    {
+        if bytes.len() != format.texel_format().image_size(width, height) {
+            return None;
+        }
+
+        Some(CompressedImage {
+            width: width,
+            height: height,
+            format: format,
+            bytes: bytes
+        })
+    }
+
+    /// Return the image's compressed data.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl CompressedImage2d for CompressedImage {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn format(&self) -> CompressedFormat {
+        self.format
+    }
+
+    fn compressed_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
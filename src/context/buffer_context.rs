@@ -7,6 +7,7 @@ use std::borrow::BorrowMut;
 use gl;
 use gl::types::*;
 use context::{ContextOf, BaseContext, AContext};
+use context::program_context::_validate_before_draw;
 use buffer::{Buffer, BufferDataUsage, BufferBindingTarget};
 use program::{ProgramAttrib};
 use index_data::{IndexData, IndexDatum, IndexDatumType};
@@ -149,6 +150,12 @@ pub trait ContextBufferExt: BaseContext {
     /// - `first`: The index of the first vertex to read.
     /// - `count`: The number of vertices to read.
     ///
+    /// When debug assertions are enabled and [`set_validate_before_draw`]
+    /// (../program_context/fn.set_validate_before_draw.html) is enabled,
+    /// the current program is validated before drawing, and any failure is
+    /// recorded for [`take_validation_error`]
+    /// (../program_context/fn.take_validation_error.html).
+    ///
     /// # Safety
     /// The vertex attributes for the need to be set up before calling this
     /// method by using the [`gl.vertex_attrib_pointer`]
@@ -162,6 +169,7 @@ pub trait ContextBufferExt: BaseContext {
                                 first: u32,
                                 count: usize)
     {
+        _validate_before_draw();
         gl::DrawArrays(mode.gl_enum(), first as GLint, count as GLsizei);
         dbg_gl_sanity_check! {
             GLError::InvalidEnum => "`mode` is not an accepted value",
@@ -182,6 +190,12 @@ pub trait ContextBufferExt: BaseContext {
     /// - `index_type`: Specifies the data type of the index (whether it is
     ///                 a byte or short, signed unsigned, etc).
     ///
+    /// When debug assertions are enabled and [`set_validate_before_draw`]
+    /// (../program_context/fn.set_validate_before_draw.html) is enabled,
+    /// the current program is validated before drawing, and any failure is
+    /// recorded for [`take_validation_error`]
+    /// (../program_context/fn.take_validation_error.html).
+    ///
     /// # See also
     /// [`glDrawElements`](http://docs.gl/es2/glDrawElements) OpenGL docs
     unsafe fn draw_n_elements_buffered(&self,
@@ -191,6 +205,7 @@ pub trait ContextBufferExt: BaseContext {
                                        count: usize,
                                        index_type: IndexDatumType)
     {
+        _validate_before_draw();
         _draw_elements(mode, count, index_type, ptr::null());
     }
 
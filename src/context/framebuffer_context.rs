@@ -7,6 +7,7 @@ use gl;
 use gl::types::*;
 use context::{AContext, BaseContext, ContextOf};
 use framebuffer::Framebuffer;
+use image_data::{Pixel, Pixels};
 use renderbuffer::{Renderbuffer, RenderbufferTarget};
 use texture::{Texture, TextureType, ImageTargetType,
               Texture2d, Tx2dImageTarget};
@@ -160,6 +161,50 @@ pub trait ContextFramebufferExt: BaseContext {
             }
         }
     }
+
+    /// Read a rectangle of pixels from the currently-bound framebuffer,
+    /// starting at the window coordinates `x` and `y`. The pixels are read
+    /// as 8-bit RGBA values.
+    ///
+    /// Note that OpenGL window coordinates start from the bottom-left corner,
+    /// so row 0 of the returned image is the *bottom* row of the rectangle.
+    ///
+    /// # See also
+    /// [`glReadPixels`](http://docs.gl/es2/glReadPixels) OpenGL docs
+    fn read_pixels(&self, x: u32, y: u32, width: usize, height: usize)
+        -> Pixels
+    {
+        let row_size = width * 4;
+        let mut bytes: Vec<u8> = vec![0; row_size * height];
+        if !bytes.is_empty() {
+            unsafe {
+                gl::ReadPixels(x as GLint,
+                               y as GLint,
+                               width as GLsizei,
+                               height as GLsizei,
+                               gl::RGBA,
+                               gl::UNSIGNED_BYTE,
+                               bytes.as_mut_ptr() as *mut GLvoid);
+                dbg_gl_error! {
+                    GLError::InvalidValue => "`width` or `height` is negative",
+                    GLError::InvalidOperation => "The currently-bound framebuffer cannot be read from",
+                    GLError::InvalidFramebufferOperation => "The currently-bound framebuffer is not framebuffer-complete",
+                    _ => "Unknown error"
+                }
+            }
+        }
+
+        let mut pixels = Pixels::new(width, height);
+        for (row_index, row) in bytes.chunks(row_size).enumerate() {
+            for (pixel, texel) in pixels[row_index].iter_mut()
+                                                   .zip(row.chunks(4))
+            {
+                *pixel = Pixel::r_g_b_a(texel[0], texel[1], texel[2], texel[3]);
+            }
+        }
+
+        pixels
+    }
 }
 
 impl<C: BaseContext> ContextFramebufferExt for C {
@@ -4,9 +4,11 @@
 
 use std::borrow::{Borrow, BorrowMut};
 use std::marker::PhantomData;
+use std::cell::{Cell, RefCell};
+use std::ffi::CStr;
 use gl;
 use gl::types::*;
-use types::{Color, Viewport, Capability, GLError};
+use types::{Color, Viewport, Capability, StringName, GLError};
 use program::ProgramAttrib;
 use shader::ContextShaderExt;
 use to_ref::{ToRef, ToMut};
@@ -123,6 +125,21 @@ pub struct ContextOf<B, F, P, R, T> {
     _phantom: PhantomData<*mut ()>
 }
 
+// Results of queries that are too expensive to repeat for every shader or
+// program. These are cached for the current thread, and are reset by
+// `ContextOf::current_context`, since a different context may have
+// been made current.
+thread_local! {
+    static PROGRAM_BINARY_FORMATS: RefCell<Option<Vec<GLenum>>> =
+        RefCell::new(None);
+    static PARALLEL_SHADER_COMPILE: Cell<Option<bool>> = Cell::new(None);
+}
+
+fn _reset_cached_queries() {
+    PROGRAM_BINARY_FORMATS.with(|formats| *formats.borrow_mut() = None);
+    PARALLEL_SHADER_COMPILE.with(|parallel| parallel.set(None));
+}
+
 impl<B, F, P, R, T> ContextOf<B, F, P, R, T> {
     /// Use a function to load OpenGL function pointers. This function must
     /// be called before calling [`ContextOf::current_context`]
@@ -131,10 +148,21 @@ impl<B, F, P, R, T> ContextOf<B, F, P, R, T> {
     /// # Safety
     /// `load_fn` takes an OpenGL function name, and must return a function
     /// pointer that can be used as this OpenGL function.
-    pub unsafe fn load_with<L>(load_fn: L)
+    pub unsafe fn load_with<L>(mut load_fn: L)
         where L: FnMut(&str) -> *const GLvoid
     {
-        gl::load_with(load_fn);
+        gl::load_with(|name| load_fn(name));
+
+        // OpenGL ES 2.0 only has the `OES_get_program_binary` entry points,
+        // which have the same signatures as the core functions
+        if !gl::GetProgramBinary::is_loaded() {
+            gl::GetProgramBinary::load_with(|_| {
+                load_fn("glGetProgramBinaryOES")
+            });
+        }
+        if !gl::ProgramBinary::is_loaded() {
+            gl::ProgramBinary::load_with(|_| load_fn("glProgramBinaryOES"));
+        }
     }
 
     /// Get the current OpenGL context.
@@ -183,6 +211,8 @@ impl<B, F, P, R, T> ContextOf<B, F, P, R, T> {
     /// // buffer_2: [4, 5, 6]
     /// ```
     pub unsafe fn current_context() -> Context {
+        _reset_cached_queries();
+
         ContextOf {
             buffers: BufferBinder::current(),
             framebuffer: FramebufferBinder::current(),
@@ -574,9 +604,284 @@ pub trait ContextExt: BaseContext {
             }
         }
     }
+
+    /// Get the current OpenGL viewport dimensions.
+    ///
+    /// # See also
+    /// [`glGet`](http://docs.gl/es2/glGet) OpenGL docs
+    fn get_viewport(&self) -> Viewport {
+        let mut values: [GLint; 4] = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::VIEWPORT, values.as_mut_ptr());
+            dbg_gl_sanity_check! {
+                GLError::InvalidEnum => "`pname` is not an accepted value",
+                _ => "Unknown error"
+            }
+        }
+
+        Viewport {
+            x: values[0] as u32,
+            y: values[1] as u32,
+            width: values[2] as u32,
+            height: values[3] as u32
+        }
+    }
+
+    /// Get a string describing the current OpenGL implementation, or `None`
+    /// if the string is not available.
+    ///
+    /// # Example
+    /// ```no_run
+    /// #[macro_use] extern crate glitter;
+    /// use glitter::prelude::*;
+    ///
+    /// # fn main() {
+    /// let gl = unsafe { glitter::Context::current_context() };
+    /// if let Some(renderer) = gl.get_string(glitter::RENDERER) {
+    ///     println!("Rendering with {}", renderer);
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// # See also
+    /// [`glGetString`](http://docs.gl/es2/glGetString) OpenGL docs
+    fn get_string(&self, name: StringName) -> Option<String> {
+        unsafe {
+            let ptr = gl::GetString(name.gl_enum());
+            dbg_gl_error! {
+                GLError::InvalidEnum => "`name` is not an accepted value",
+                _ => "Unknown error"
+            }
+
+            if ptr.is_null() {
+                None
+            }
+            else {
+                let c_str = CStr::from_ptr(ptr as *const _);
+                Some(c_str.to_string_lossy().into_owned())
+            }
+        }
+    }
+
+    /// Get the names of all of the OpenGL extensions supported by the
+    /// current context.
+    ///
+    /// # Note
+    /// With OpenGL ES or with a legacy desktop context, the extensions
+    /// are read from `GL_EXTENSIONS` using `glGetString`. Otherwise (for
+    /// OpenGL 3.0 or later), the extensions are read one at a time
+    /// using `glGetStringi`.
+    ///
+    /// # See also
+    /// [`glGetString`](http://docs.gl/es2/glGetString) OpenGL docs
+    ///
+    /// [`glGetStringi`](http://docs.gl/gl4/glGetString) OpenGL docs
+    fn extensions(&self) -> Vec<String> {
+        if _uses_indexed_extensions(self.get_string(StringName::Version)) {
+            unsafe {
+                let mut count: GLint = 0;
+                gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count as *mut GLint);
+                dbg_gl_sanity_check! {
+                    GLError::InvalidEnum => "`pname` is not an accepted value",
+                    _ => "Unknown error"
+                }
+
+                (0..count).filter_map(|i| {
+                    let ptr = gl::GetStringi(gl::EXTENSIONS, i as GLuint);
+                    dbg_gl_sanity_check! {
+                        GLError::InvalidValue => "`index` is outside the valid range",
+                        _ => "Unknown error"
+                    }
+
+                    if ptr.is_null() {
+                        None
+                    }
+                    else {
+                        let c_str = CStr::from_ptr(ptr as *const _);
+                        Some(c_str.to_string_lossy().into_owned())
+                    }
+                }).collect()
+            }
+        }
+        else {
+            match self.get_string(StringName::Extensions) {
+                Some(extensions) => {
+                    extensions.split_whitespace()
+                              .map(|ext| ext.to_owned())
+                              .collect()
+                },
+                None => { vec![] }
+            }
+        }
+    }
+
+    /// Returns `true` if the current context supports the OpenGL extension
+    /// with the provided name (such as `"GL_OES_texture_npot"`).
+    ///
+    /// # Note
+    /// This method queries the full extension list every time it is
+    /// called. Consider caching the result if it needs to be checked often.
+    fn has_extension(&self, name: &str) -> bool {
+        self.extensions().iter().any(|ext| ext == name)
+    }
+
+    /// Returns `true` if the current context supports compiling shaders
+    /// and linking programs in parallel (with the
+    /// `GL_KHR_parallel_shader_compile` or `GL_ARB_parallel_shader_compile`
+    /// extension), which allows the completion of a compile or link to be
+    /// polled without blocking.
+    ///
+    /// # Note
+    /// The extension list is only checked the first time this method is
+    /// called for the current thread, and is checked again after
+    /// [`ContextOf::current_context`]
+    /// (struct.ContextOf.html#method.current_context) is called.
+    ///
+    /// # See also
+    /// [`ShaderBuilder::submit`](../shader/struct.ShaderBuilder.html#method.submit)
+    /// and [`ProgramBuilder::submit`]
+    /// (program_context/struct.ProgramBuilder.html#method.submit):
+    /// Compile a shader or build a program in parallel.
+    fn supports_parallel_shader_compile(&self) -> bool {
+        if let Some(parallel) = PARALLEL_SHADER_COMPILE.with(|p| p.get()) {
+            return parallel;
+        }
+
+        let parallel = self.extensions().iter().any(|ext| {
+            ext == "GL_KHR_parallel_shader_compile" ||
+            ext == "GL_ARB_parallel_shader_compile"
+        });
+        PARALLEL_SHADER_COMPILE.with(|p| p.set(Some(parallel)));
+        parallel
+    }
+
+    /// Returns `true` if the current context fully supports textures whose
+    /// width or height is not a power of two, including wrap modes other
+    /// than `CLAMP_TO_EDGE` and mipmaps. This is the case for desktop
+    /// OpenGL, for OpenGL ES 3.0 or later, and for OpenGL ES 2.0 with
+    /// the `GL_OES_texture_npot` extension.
+    fn supports_npot_textures(&self) -> bool {
+        match _gl_es_major_version(self.get_string(StringName::Version)) {
+            Some(major) if major < 3 => {
+                self.has_extension("GL_OES_texture_npot")
+            },
+            _ => { true }
+        }
+    }
+
+    /// Returns `true` if the current context supports textures compressed
+    /// with ETC2 or EAC. These formats are part of OpenGL ES 3.0, and are
+    /// supported by desktop OpenGL with the `GL_ARB_ES3_compatibility`
+    /// extension.
+    fn supports_etc2_textures(&self) -> bool {
+        match _gl_es_major_version(self.get_string(StringName::Version)) {
+            Some(major) if major >= 3 => { true },
+            _ => { self.has_extension("GL_ARB_ES3_compatibility") }
+        }
+    }
+
+    /// Returns `true` if the current context supports textures with
+    /// 32-bit float texels (see [`TexelType::Float`]
+    /// (../image_data/enum.TexelType.html)). This is the case for desktop
+    /// OpenGL, and for OpenGL ES with the `GL_OES_texture_float` extension.
+    fn supports_float_textures(&self) -> bool {
+        match _gl_es_major_version(self.get_string(StringName::Version)) {
+            Some(_) => { self.has_extension("GL_OES_texture_float") },
+            None => { true }
+        }
+    }
+
+    /// Returns `true` if float textures can use linear filtering (including
+    /// linear filtering between mipmap levels). This is the case for
+    /// desktop OpenGL, and for OpenGL ES with the
+    /// `GL_OES_texture_float_linear` extension.
+    fn supports_float_linear_filtering(&self) -> bool {
+        match _gl_es_major_version(self.get_string(StringName::Version)) {
+            Some(_) => { self.has_extension("GL_OES_texture_float_linear") },
+            None => { true }
+        }
+    }
+
+    /// Returns `true` if the current context supports textures with
+    /// 16-bit float texels (see [`TexelType::HalfFloatOes`]
+    /// (../image_data/enum.TexelType.html)). This is the case for desktop
+    /// OpenGL 3.0 or later, for earlier desktop versions with the
+    /// `GL_ARB_half_float_pixel` extension, and for OpenGL ES with the
+    /// `GL_OES_texture_half_float` extension.
+    fn supports_half_float_textures(&self) -> bool {
+        let version = self.get_string(StringName::Version);
+        match _gl_es_major_version(version.clone()) {
+            Some(_) => { self.has_extension("GL_OES_texture_half_float") },
+            None => {
+                match _desktop_gl_major_version(version) {
+                    Some(major) if major >= 3 => { true },
+                    _ => { self.has_extension("GL_ARB_half_float_pixel") }
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if half-float textures can use linear filtering
+    /// (including linear filtering between mipmap levels). This is the
+    /// case for desktop OpenGL, and for OpenGL ES with the
+    /// `GL_OES_texture_half_float_linear` extension.
+    fn supports_half_float_linear_filtering(&self) -> bool {
+        match _gl_es_major_version(self.get_string(StringName::Version)) {
+            Some(_) => { self.has_extension("GL_OES_texture_half_float_linear") },
+            None => { true }
+        }
+    }
+}
+
+// Returns the major version of an OpenGL ES context, based on the
+// `GL_VERSION` string (such as "OpenGL ES 2.0 ..."), or `None` for a
+// desktop OpenGL context.
+fn _gl_es_major_version(version: Option<String>) -> Option<u32> {
+    match version {
+        Some(version) => {
+            if version.starts_with("OpenGL ES") {
+                let rest = version["OpenGL ES".len()..].trim_left_matches(|c: char| !c.is_digit(10));
+                rest.split('.').next()
+                    .and_then(|major| major.trim().parse().ok())
+                    .or(Some(2))
+            }
+            else {
+                None
+            }
+        },
+        None => { None }
+    }
+}
+
+// Returns true if the extensions should be queried with `glGetStringi`,
+// based on the `GL_VERSION` string. OpenGL ES version strings start with
+// "OpenGL ES", while desktop version strings start with the version number.
+fn _uses_indexed_extensions(version: Option<String>) -> bool {
+    match _desktop_gl_major_version(version) {
+        Some(major) => { major >= 3 },
+        None => { false }
+    }
+}
+
+// Returns the major version of a desktop OpenGL context, based on the
+// `GL_VERSION` string (such as "4.5.0 ..."), or `None` for an OpenGL ES
+// context.
+fn _desktop_gl_major_version(version: Option<String>) -> Option<u32> {
+    match version {
+        Some(version) => {
+            if version.starts_with("OpenGL ES") {
+                None
+            }
+            else {
+                version.split('.').next()
+                       .and_then(|major| major.trim().parse::<u32>().ok())
+            }
+        },
+        None => { None }
+    }
 }
 
-impl<C: BaseContext> ContextExt for C {
+impl<C: BaseContext + ?Sized> ContextExt for C {
 
 }
 
@@ -3,15 +3,22 @@
 use std::ptr;
 use std::error;
 use std::fmt;
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
 use std::borrow::BorrowMut;
 use std::marker::PhantomData;
 use std::ffi::CString;
 use gl;
 use gl::types::*;
-use types::{GLObject, GLError};
-use context::{AContext, BaseContext, ContextOf};
-use program::{Program, ProgramAttrib, ProgramUniform};
-use shader::Shader;
+use types::{GLObject, GLError, StringName};
+use context::{AContext, BaseContext, ContextExt, ContextOf,
+              PROGRAM_BINARY_FORMATS};
+use program::{Program, ProgramAttrib, ProgramUniform, ProgramBinary,
+              ProgramValidation};
+use program_binary_cache::ProgramBinaryCache;
+use shader::{Shader, ShaderType, AttachedShader, PendingShader,
+             ContextShaderBuilderExt, COMPLETION_STATUS_KHR,
+             _get_shader_source_by_id};
 use uniform_data::{UniformData, UniformDatumType, UniformPrimitiveType};
 
 unsafe fn _get_program_iv(program: &Program,
@@ -27,14 +34,163 @@ unsafe fn _get_program_iv(program: &Program,
     }
 }
 
+unsafe fn _program_info_log(program_id: GLuint) -> Option<String> {
+    let mut info_length : GLint = 0;
+    gl::GetProgramiv(program_id,
+                     gl::INFO_LOG_LENGTH,
+                     &mut info_length as *mut GLint);
+    dbg_gl_sanity_check! {
+        GLError::InvalidValue => "`program` is not a value generated by OpenGL",
+        GLError::InvalidOperation => "`program` does not refer to a program object",
+        _ => "Unknown error"
+    }
+
+    if info_length > 0 {
+        let mut bytes = Vec::<u8>::with_capacity(info_length as usize);
+
+        gl::GetProgramInfoLog(program_id,
+                              info_length,
+                              ptr::null_mut(),
+                              bytes.as_mut_ptr() as *mut GLchar);
+        dbg_gl_sanity_check! {
+            GLError::InvalidValue => "`program` is not a value generated by OpenGL, or `maxLength` < 0",
+            GLError::InvalidOperation => "`program` is not a program object",
+            _ => "Unknown error"
+        }
+        bytes.set_len((info_length - 1) as usize);
+
+        String::from_utf8(bytes).ok()
+    }
+    else {
+        None
+    }
+}
+
+fn _validate_program(program_id: GLuint) -> ProgramValidation {
+    unsafe {
+        gl::ValidateProgram(program_id);
+        dbg_gl_error! {
+            GLError::InvalidValue => "`program` is not a value generated by OpenGL",
+            GLError::InvalidOperation => "`program` is not a program object",
+            _ => "Unknown error"
+        }
+
+        let mut validate_status : GLint = 0;
+        gl::GetProgramiv(program_id,
+                         gl::VALIDATE_STATUS,
+                         &mut validate_status as *mut GLint);
+        dbg_gl_sanity_check! {
+            GLError::InvalidValue => "`program` is not a value generated by OpenGL",
+            GLError::InvalidOperation => "`program` does not refer to a program object",
+            _ => "Unknown error"
+        }
+
+        ProgramValidation {
+            valid: validate_status == gl::TRUE as GLint,
+            info_log: _program_info_log(program_id)
+        }
+    }
+}
+
+thread_local! {
+    static VALIDATE_BEFORE_DRAW: Cell<bool> = Cell::new(false);
+    static VALIDATED_PROGRAMS: RefCell<HashSet<GLuint>> =
+        RefCell::new(HashSet::new());
+    static VALIDATION_ERROR: RefCell<Option<GLError>> = RefCell::new(None);
+}
+
+// Forget that a program was validated, because it was (re)linked or
+// deleted (in which case its id may be reused by a new program).
+pub(crate) fn _forget_validated_program(program_id: GLuint) {
+    if cfg!(debug_assertions) {
+        VALIDATED_PROGRAMS.with(|validated| {
+            validated.borrow_mut().remove(&program_id);
+        });
+    }
+}
+
+/// Enable or disable validating each program before it is first used for
+/// drawing with [`gl.draw_arrays_range`]
+/// (../buffer_context/trait.ContextBufferExt.html#method.draw_arrays_range)
+/// or [`gl.draw_n_elements_buffered`]
+/// (../buffer_context/trait.ContextBufferExt.html#method.draw_n_elements_buffered),
+/// using [`gl.validate_program`]
+/// (trait.ContextProgramExt.html#method.validate_program). If validation
+/// fails, an error containing the program's info log is recorded, which
+/// can be retrieved with [`take_validation_error`]
+/// (fn.take_validation_error.html). Each program is validated again after
+/// it is relinked.
+///
+/// Validation is disabled by default, and is only performed when debug
+/// assertions are enabled. This setting applies to the current thread.
+pub fn set_validate_before_draw(enabled: bool) {
+    VALIDATE_BEFORE_DRAW.with(|validate| validate.set(enabled));
+}
+
+/// Take the error recorded when a program failed validation before
+/// drawing (see [`set_validate_before_draw`]
+/// (fn.set_validate_before_draw.html)), or `None` if no program has
+/// failed validation. Like `glGetError`, only the first failure is kept
+/// until it is taken, and the recorded error is cleared after it has
+/// been taken.
+pub fn take_validation_error() -> Option<GLError> {
+    VALIDATION_ERROR.with(|error| error.borrow_mut().take())
+}
+
+// Validate the current program, if validation before drawing is enabled
+// and the program has not been validated since it was last linked.
+pub(crate) fn _validate_before_draw() {
+    if !cfg!(debug_assertions) ||
+       !VALIDATE_BEFORE_DRAW.with(|validate| validate.get())
+    {
+        return;
+    }
+
+    let mut program_id: GLint = 0;
+    unsafe {
+        gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut program_id as *mut GLint);
+    }
+    let program_id = program_id as GLuint;
+    if program_id == 0 {
+        return;
+    }
+
+    let is_new = VALIDATED_PROGRAMS.with(|validated| {
+        validated.borrow_mut().insert(program_id)
+    });
+    if is_new {
+        let validation = _validate_program(program_id);
+        if !validation.valid {
+            let msg = format!("Program {} failed validation before drawing: {}",
+                              program_id,
+                              validation.info_log.unwrap_or_else(String::new));
+            VALIDATION_ERROR.with(|error| {
+                let mut error = error.borrow_mut();
+                if error.is_none() {
+                    *error = Some(GLError::Message(msg));
+                }
+            });
+        }
+    }
+}
+
 /// Provides a safe interface for creating program objects. A
 /// `ProgramBuilder` can be created using the [`gl.build_program`]
 /// (trait.ContextProgramBuilderExt.html#method.build_program) method.
+///
+/// A program can be built from shaders that have already been compiled,
+/// from shader sources (which are compiled when the program is built),
+/// or from a mix of both. Programs that are built purely from shader
+/// sources can additionally use a [`ProgramBinaryCache`]
+/// (../../program_binary_cache/struct.ProgramBinaryCache.html) to skip
+/// compilation entirely.
 pub struct ProgramBuilder<'a, C>
     where C: AContext + 'a
 {
     gl: &'a C,
-    shaders: &'a [Shader]
+    shaders: Vec<&'a Shader>,
+    sources: Vec<(ShaderType, &'a str)>,
+    binary_cache: Option<&'a ProgramBinaryCache>
 }
 
 impl<'a, C> ProgramBuilder<'a, C>
@@ -44,35 +200,118 @@ impl<'a, C> ProgramBuilder<'a, C>
     pub fn new(gl: &'a C, shaders: &'a [Shader])
         -> Self
     {
-        ProgramBuilder { gl: gl, shaders: shaders }
+        ProgramBuilder {
+            gl: gl,
+            shaders: shaders.iter().collect(),
+            sources: Vec::new(),
+            binary_cache: None
+        }
+    }
+
+    /// Attach an additional shader to the program. This is useful
+    /// when the shaders that make up a program are not stored
+    /// contiguously, such as when they are shared between multiple
+    /// programs.
+    pub fn attach(mut self, shader: &'a Shader) -> Self {
+        self.shaders.push(shader);
+        self
+    }
+
+    /// Add a shader to the program by its source. The shader will be
+    /// compiled when the program is built (unless the program was
+    /// loaded from a [`binary_cache`]
+    /// (struct.ProgramBuilder.html#method.binary_cache)).
+    pub fn shader_source(mut self, ty: ShaderType, source: &'a str) -> Self {
+        self.sources.push((ty, source));
+        self
+    }
+
+    /// Use a program binary cache when building the program. Before
+    /// compiling any shaders, the builder will try to load the program
+    /// from a binary in the cache. If there is no binary in the cache,
+    /// or if the driver rejects it, then the program's shaders will
+    /// be compiled and linked from source, and the resulting binary
+    /// will be stored in the cache.
+    ///
+    /// # Note
+    /// The cache is only used for programs whose shaders were all
+    /// provided with [`shader_source`]
+    /// (struct.ProgramBuilder.html#method.shader_source), since the source
+    /// is used to identify the program in the cache. Additionally, errors
+    /// while writing to the cache are ignored, since the program will
+    /// have still been built successfully.
+    pub fn binary_cache(mut self, cache: &'a ProgramBinaryCache) -> Self {
+        self.binary_cache = Some(cache);
+        self
     }
 
     /// Create and link the program object with the provided shaders, or
     /// return an error.
     ///
     /// # Failures
-    /// An error will be returned if there was an error linking the program
-    /// object.
+    /// An error will be returned if there was an error compiling one
+    /// of the provided shader sources, or if there was an error linking
+    /// the program object.
     ///
     /// # Panics
     /// This function will panic if an OpenGL
     /// error was generated with debug assertions enabled.
     pub fn try_unwrap(self) -> Result<Program, GLError> {
-        unsafe {
-            let mut program = try! {
-                self.gl.create_program().or_else(|_| {
-                    let msg = "Error creating OpenGL program";
-                    Err(GLError::Message(msg.to_owned()))
-                })
-            };
-
-            for shader in self.shaders {
-                self.gl.attach_shader(&mut program, shader);
-            }
+        let gl = self.gl;
+        try!(self._submit(false)).finish(gl)
+    }
 
-            try!(self.gl.link_program(&mut program));
-            Ok(program)
-        }
+    /// Start building the program, without waiting for its shaders to
+    /// compile or for the program to link. Returns a [`PendingProgram`]
+    /// (struct.PendingProgram.html), which can be polled to check if
+    /// the program has finished building. This allows many programs to
+    /// be built in parallel, such as while a loading screen is animating.
+    ///
+    /// If the context does not support parallel shader compilation (see
+    /// [`gl.supports_parallel_shader_compile`]
+    /// (../trait.ContextExt.html#method.supports_parallel_shader_compile)),
+    /// then the pending program will always report that it has finished
+    /// building, and [`PendingProgram::finish`]
+    /// (struct.PendingProgram.html#method.finish) will block until it
+    /// actually has.
+    ///
+    /// # Failures
+    /// An error will be returned if a new program or shader object could
+    /// not be created. Compilation and linking errors are returned by
+    /// `PendingProgram::finish`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// #[macro_use] extern crate glitter;
+    /// use glitter::prelude::*;
+    ///
+    /// # fn main() {
+    /// # let program_sources: Vec<(&str, &str)> = vec![];
+    /// let gl = unsafe { glitter::Context::current_context() };
+    /// let mut pending: Vec<_> = program_sources.iter().map(|&(vs, fs)| {
+    ///     gl.build_program(&[])
+    ///       .shader_source(glitter::VERTEX_SHADER, vs)
+    ///       .shader_source(glitter::FRAGMENT_SHADER, fs)
+    ///       .submit()
+    ///       .unwrap()
+    /// }).collect();
+    ///
+    /// let mut programs = vec![];
+    /// while !pending.is_empty() {
+    ///     let (complete, incomplete): (Vec<_>, Vec<_>) =
+    ///         pending.into_iter().partition(|p| p.is_complete());
+    ///     for program in complete {
+    ///         programs.push(program.finish(&gl).unwrap());
+    ///     }
+    ///     pending = incomplete;
+    ///
+    ///     // ... draw a frame of the loading screen ...
+    /// }
+    /// # }
+    /// ```
+    pub fn submit(self) -> Result<PendingProgram<'a>, GLError> {
+        let parallel = self.gl.supports_parallel_shader_compile();
+        self._submit(parallel)
     }
 
     /// Create and link the program object with the provided shaders,
@@ -85,6 +324,245 @@ impl<'a, C> ProgramBuilder<'a, C>
     pub fn unwrap(self) -> Program {
         self.try_unwrap().unwrap()
     }
+
+    // Start building the program. `parallel` should only be `true` if the
+    // context supports parallel shader compilation, and the pending
+    // program will be polled.
+    fn _submit(self, parallel: bool) -> Result<PendingProgram<'a>, GLError> {
+        let binary_cache = if self.shaders.is_empty() {
+            self.binary_cache
+        }
+        else {
+            None
+        };
+
+        if let Some(cache) = binary_cache {
+            if let Some(binary) = cache.load(&self.sources) {
+                let mut program = try!(self.create_program());
+                if self.gl.program_binary(&mut program, &binary).is_ok() {
+                    return Ok(PendingProgram {
+                        program: program,
+                        shaders: vec![],
+                        parallel: false,
+                        linked: true,
+                        binary_cache: None,
+                        sources: vec![]
+                    });
+                }
+            }
+        }
+
+        let mut compiled = Vec::with_capacity(self.sources.len());
+        for &(ty, source) in &self.sources {
+            compiled.push(try!(self.gl.build_shader(ty, source)
+                                      ._submit(parallel)));
+        }
+
+        let mut program = try!(self.create_program());
+        for shader in self.shaders.iter().cloned()
+                                   .chain(compiled.iter().map(|s| s.shader()))
+        {
+            self.gl.attach_shader(&mut program, shader);
+        }
+
+        if binary_cache.is_some() {
+            _program_binary_retrievable_hint(&program);
+        }
+
+        _forget_validated_program(program.id());
+        _link_program(&program);
+
+        Ok(PendingProgram {
+            program: program,
+            shaders: compiled,
+            parallel: parallel,
+            linked: false,
+            binary_cache: binary_cache,
+            sources: self.sources
+        })
+    }
+
+    fn create_program(&self) -> Result<Program, GLError> {
+        unsafe {
+            self.gl.create_program().or_else(|_| {
+                let msg = "Error creating OpenGL program";
+                Err(GLError::Message(msg.to_owned()))
+            })
+        }
+    }
+}
+
+/// A program that is being built, which is returned by
+/// [`ProgramBuilder::submit`](struct.ProgramBuilder.html#method.submit).
+pub struct PendingProgram<'a> {
+    program: Program,
+    shaders: Vec<PendingShader>,
+    parallel: bool,
+    linked: bool,
+    binary_cache: Option<&'a ProgramBinaryCache>,
+    sources: Vec<(ShaderType, &'a str)>
+}
+
+impl<'a> PendingProgram<'a> {
+    /// Returns `true` if the program's shaders have finished compiling and
+    /// the program has finished linking, without blocking. This always
+    /// returns `true` if the context does not support parallel shader
+    /// compilation, or if the program was loaded from a binary cache.
+    pub fn is_complete(&self) -> bool {
+        if self.linked || !self.parallel {
+            return true;
+        }
+
+        unsafe {
+            let mut complete : GLint = 0;
+            _get_program_iv(&self.program,
+                            COMPLETION_STATUS_KHR,
+                            &mut complete as *mut GLint);
+            complete == gl::TRUE as GLint
+        }
+    }
+
+    /// Wait for the program to finish building, and return the linked
+    /// program.
+    ///
+    /// # Failures
+    /// An error will be returned if there was an error compiling one
+    /// of the program's shader sources, or if there was an error linking
+    /// the program object.
+    pub fn finish<C>(self, gl: &C) -> Result<Program, GLError>
+        where C: AContext
+    {
+        if self.linked {
+            return Ok(self.program);
+        }
+
+        if let Err(link_error) = _check_link_status(gl, &self.program) {
+            // Report the first compilation error, if there was one, since
+            // it is usually the cause of the linking error
+            for shader in self.shaders {
+                try!(shader.finish(gl));
+            }
+            return Err(link_error);
+        }
+
+        if let Some(cache) = self.binary_cache {
+            if let Some(binary) = gl.get_program_binary(&self.program) {
+                let _ = cache.store(&self.sources, &binary);
+            }
+        }
+
+        Ok(self.program)
+    }
+}
+
+fn _query_program_binary_formats<C>(gl: &C) -> Vec<GLenum>
+    where C: ContextExt + ?Sized
+{
+    if !gl::GetProgramBinary::is_loaded() || !gl::ProgramBinary::is_loaded() {
+        return vec![];
+    }
+
+    let version = gl.get_string(StringName::Version).unwrap_or_else(String::new);
+    let supported = if version.starts_with("OpenGL ES") {
+        _version_at_least(&version["OpenGL ES".len()..], (3, 0)) ||
+        gl.has_extension("GL_OES_get_program_binary")
+    }
+    else {
+        _version_at_least(&version, (4, 1)) ||
+        gl.has_extension("GL_ARB_get_program_binary")
+    };
+    if !supported {
+        return vec![];
+    }
+
+    unsafe {
+        let mut count: GLint = 0;
+        gl::GetIntegerv(gl::NUM_PROGRAM_BINARY_FORMATS,
+                        &mut count as *mut GLint);
+        dbg_gl_sanity_check! {
+            GLError::InvalidEnum => "`pname` is not an accepted value",
+            _ => "Unknown error"
+        }
+        if count <= 0 {
+            return vec![];
+        }
+
+        let mut formats = vec![0 as GLint; count as usize];
+        gl::GetIntegerv(gl::PROGRAM_BINARY_FORMATS, formats.as_mut_ptr());
+        dbg_gl_sanity_check! {
+            GLError::InvalidEnum => "`pname` is not an accepted value",
+            _ => "Unknown error"
+        }
+
+        formats.into_iter().map(|format| format as GLenum).collect()
+    }
+}
+
+// Returns true if a version string (such as "4.5.0 NVIDIA 367.57" or
+// " 3.0 Mesa 12.0.3") is at least the provided major and minor version.
+fn _version_at_least(version: &str, (major, minor): (u32, u32)) -> bool {
+    let mut parts = version.trim().split(|c: char| !c.is_digit(10));
+    let version_major = parts.next().and_then(|n| n.parse::<u32>().ok());
+    let version_minor = parts.next().and_then(|n| n.parse::<u32>().ok());
+    match (version_major, version_minor) {
+        (Some(version_major), Some(version_minor)) => {
+            (version_major, version_minor) >= (major, minor)
+        },
+        _ => { false }
+    }
+}
+
+fn _link_program(program: &Program) {
+    unsafe {
+        gl::LinkProgram(program.id());
+        dbg_gl_error! {
+            GLError::InvalidValue => "`program` is not a value from OpenGL",
+            GLError::InvalidOperation => "`program` is not a program object",
+            _ => "Unknown error"
+        }
+    }
+}
+
+fn _check_link_status<C>(gl: &C, program: &Program) -> Result<(), GLError>
+    where C: ContextProgramExt + ?Sized
+{
+    let success = unsafe {
+        let mut link_status : GLint = 0;
+        _get_program_iv(program,
+                        gl::LINK_STATUS,
+                        &mut link_status as *mut GLint);
+
+        link_status == gl::TRUE as GLint
+    };
+
+    if success {
+        Ok(())
+    }
+    else {
+        let msg = match gl.get_program_info_log(&program) {
+            Some(s) => { s },
+            None => { String::from("[Unknown program error]") }
+        };
+        Err(GLError::Message(msg))
+    }
+}
+
+// Hint that the program's binary will be retrieved after linking. The hint
+// is only available with `ARB_get_program_binary` (or OpenGL 4.1); with
+// `OES_get_program_binary`, binaries are always retrievable.
+fn _program_binary_retrievable_hint(program: &Program) {
+    if gl::ProgramParameteri::is_loaded() {
+        unsafe {
+            gl::ProgramParameteri(program.id(),
+                                  gl::PROGRAM_BINARY_RETRIEVABLE_HINT,
+                                  gl::TRUE as GLint);
+            dbg_gl_sanity_check! {
+                GLError::InvalidValue => "`program` is not a value generated by OpenGL",
+                GLError::InvalidOperation => "`program` is not a program object",
+                _ => "Unknown error"
+            }
+        }
+    }
 }
 
 /// The extension trait that adds the `build_program` method.
@@ -150,6 +628,72 @@ pub trait ContextProgramExt: BaseContext {
         }
     }
 
+    /// Get the shaders that are attached to a program object, along with
+    /// each shader's type and source. This can be used to find out what
+    /// was actually compiled for a program, such as to dump the sources
+    /// of a program for debugging.
+    ///
+    /// # Example
+    /// ```no_run
+    /// #[macro_use] extern crate glitter;
+    /// use glitter::prelude::*;
+    ///
+    /// # fn main() {
+    /// # let vertex_source = "";
+    /// # let fragment_source = "";
+    /// let gl = unsafe { glitter::Context::current_context() };
+    /// let program = gl.build_program(&[])
+    ///                 .shader_source(glitter::VERTEX_SHADER, vertex_source)
+    ///                 .shader_source(glitter::FRAGMENT_SHADER, fragment_source)
+    ///                 .unwrap();
+    ///
+    /// for shader in gl.get_attached_shaders(&program) {
+    ///     println!("{:?} shader:", shader.ty);
+    ///     println!("{}", shader.source.unwrap_or_else(String::new));
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// # See also
+    /// [`glGetAttachedShaders`](http://docs.gl/es2/glGetAttachedShaders)
+    /// OpenGL docs
+    fn get_attached_shaders(&self, program: &Program) -> Vec<AttachedShader> {
+        unsafe {
+            let mut count : GLint = 0;
+            _get_program_iv(program,
+                            gl::ATTACHED_SHADERS,
+                            &mut count as *mut GLint);
+            if count <= 0 {
+                return vec![];
+            }
+
+            let mut ids = vec![0 as GLuint; count as usize];
+            let mut returned : GLsizei = 0;
+            gl::GetAttachedShaders(program.id(),
+                                   count,
+                                   &mut returned as *mut GLsizei,
+                                   ids.as_mut_ptr());
+            dbg_gl_sanity_check! {
+                GLError::InvalidValue => "`program` is not a value generated by OpenGL, or `maxCount` < 0",
+                GLError::InvalidOperation => "`program` is not a program object",
+                _ => "Unknown error"
+            }
+            ids.truncate(returned as usize);
+
+            ids.into_iter().filter_map(|id| {
+                let mut ty : GLint = 0;
+                gl::GetShaderiv(id, gl::SHADER_TYPE, &mut ty as *mut GLint);
+                ShaderType::from_gl(ty as GLenum).ok().map(|ty| {
+                    AttachedShader {
+                        id: id,
+                        ty: ty,
+                        source: _get_shader_source_by_id(id)
+                    }
+                })
+            }).collect()
+        }
+    }
+
     /// Link the program object, so that it can be used for rendering. Returns
     /// an error if the program could not be linked.
     ///
@@ -167,11 +711,131 @@ pub trait ContextProgramExt: BaseContext {
     /// # See also
     /// [`glLinkProgram`](http://docs.gl/es2/glLinkProgram) OpenGL docs
     fn link_program(&self, program: &mut Program) -> Result<(), GLError> {
+        _forget_validated_program(program.id());
+        _link_program(program);
+        _check_link_status(self, program)
+    }
+
+    /// Return the information log for the program object, if any is
+    /// available.
+    ///
+    /// # Note
+    /// If the info log returned by the OpenGL driver contained an invalid
+    /// UTF-8 sequence, `None` will be returned.
+    ///
+    /// # See also
+    /// [`glGetProgramInfoLog`](http://docs.gl/es2/glGetProgramInfoLog) OpenGL docs
+    fn get_program_info_log(&self, program: &Program) -> Option<String> {
+        unsafe { _program_info_log(program.id()) }
+    }
+
+    /// Get the list of program binary formats supported by the current
+    /// context. An empty list means that program binaries are unsupported,
+    /// which will be the case unless the context supports OpenGL 4.1,
+    /// OpenGL ES 3.0, `ARB_get_program_binary`, or `OES_get_program_binary`.
+    ///
+    /// # Note
+    /// The formats are only queried the first time this method is called
+    /// for the current thread, and are queried again after
+    /// [`ContextOf::current_context`]
+    /// (../struct.ContextOf.html#method.current_context) is called.
+    ///
+    /// # See also
+    /// [`glGet`](http://docs.gl/es3/glGet) OpenGL docs
+    fn program_binary_formats(&self) -> Vec<GLenum> {
+        let cached = PROGRAM_BINARY_FORMATS.with(|formats| {
+            formats.borrow().clone()
+        });
+        match cached {
+            Some(formats) => { formats },
+            None => {
+                let formats = _query_program_binary_formats(self);
+                PROGRAM_BINARY_FORMATS.with(|cached| {
+                    *cached.borrow_mut() = Some(formats.clone());
+                });
+                formats
+            }
+        }
+    }
+
+    /// Get the binary representation of a linked program, or `None` if
+    /// program binaries are not supported or if the driver did not
+    /// return a binary for the program.
+    ///
+    /// # Note
+    /// With `ARB_get_program_binary`, the program should have had the
+    /// `GL_PROGRAM_BINARY_RETRIEVABLE_HINT` set before it was linked.
+    /// [`ProgramBuilder`](struct.ProgramBuilder.html) sets this hint
+    /// automatically when a [`binary_cache`]
+    /// (struct.ProgramBuilder.html#method.binary_cache) is used.
+    ///
+    /// # See also
+    /// [`glGetProgramBinary`](http://docs.gl/es3/glGetProgramBinary) OpenGL docs
+    fn get_program_binary(&self, program: &Program) -> Option<ProgramBinary> {
+        if self.program_binary_formats().is_empty() {
+            return None;
+        }
+
+        unsafe {
+            let mut length: GLint = 0;
+            _get_program_iv(program,
+                            gl::PROGRAM_BINARY_LENGTH,
+                            &mut length as *mut GLint);
+            if length <= 0 {
+                return None;
+            }
+
+            let mut data = vec![0u8; length as usize];
+            let mut written: GLsizei = 0;
+            let mut format: GLenum = 0;
+            gl::GetProgramBinary(program.id(),
+                                 length as GLsizei,
+                                 &mut written as *mut GLsizei,
+                                 &mut format as *mut GLenum,
+                                 data.as_mut_ptr() as *mut GLvoid);
+            dbg_gl_error! {
+                GLError::InvalidOperation => "`bufSize` is less than the size of the program binary, or `program` was not linked successfully",
+                _ => "Unknown error"
+            }
+
+            data.truncate(written as usize);
+            if data.is_empty() {
+                None
+            }
+            else {
+                Some(ProgramBinary { format: format, data: data })
+            }
+        }
+    }
+
+    /// Load a program object from a program binary, replacing the
+    /// program's current state. If the binary is accepted, the program
+    /// will be linked, and no shaders need to be attached.
+    ///
+    /// # Failures
+    /// An error will be returned if the binary's format is not supported
+    /// by the current context, or if the driver rejected the binary (which
+    /// can happen at any time, such as after a driver update). In that
+    /// case, the program will need to be built from source.
+    ///
+    /// # See also
+    /// [`glProgramBinary`](http://docs.gl/es3/glProgramBinary) OpenGL docs
+    fn program_binary(&self, program: &mut Program, binary: &ProgramBinary)
+        -> Result<(), GLError>
+    {
+        if !self.program_binary_formats().contains(&binary.format) {
+            let msg = "Error loading program binary: unsupported binary format";
+            return Err(GLError::Message(msg.to_owned()));
+        }
+
+        _forget_validated_program(program.id());
         let success = unsafe {
-            gl::LinkProgram(program.id());
+            gl::ProgramBinary(program.id(),
+                              binary.format,
+                              binary.data.as_ptr() as *const GLvoid,
+                              binary.data.len() as GLsizei);
             dbg_gl_error! {
-                GLError::InvalidValue => "`program` is not a value from OpenGL",
-                GLError::InvalidOperation => "`program` is not a program object",
+                GLError::InvalidOperation => "`program` is not the name of an existing program object",
                 _ => "Unknown error"
             }
 
@@ -189,50 +853,12 @@ pub trait ContextProgramExt: BaseContext {
         else {
             let msg = match self.get_program_info_log(&program) {
                 Some(s) => { s },
-                None => { String::from("[Unknown program error]") }
+                None => { String::from("[Program binary was rejected]") }
             };
             Err(GLError::Message(msg))
         }
     }
 
-    /// Return the information log for the program object, if any is
-    /// available.
-    ///
-    /// # Note
-    /// If the info log returned by the OpenGL driver contained an invalid
-    /// UTF-8 sequence, `None` will be returned.
-    ///
-    /// # See also
-    /// [`glGetProgramInfoLog`](http://docs.gl/es2/glGetProgramInfoLog) OpenGL docs
-    fn get_program_info_log(&self, program: &Program) -> Option<String> {
-        unsafe {
-            let mut info_length : GLint = 0;
-            _get_program_iv(program,
-                            gl::INFO_LOG_LENGTH,
-                            &mut info_length as *mut GLint);
-
-            if info_length > 0 {
-                let mut bytes = Vec::<u8>::with_capacity(info_length as usize);
-
-                gl::GetProgramInfoLog(program.id(),
-                                      info_length,
-                                      ptr::null_mut(),
-                                      bytes.as_mut_ptr() as *mut GLchar);
-                dbg_gl_sanity_check! {
-                    GLError::InvalidValue => "`program` is not a value generated by OpenGL, or `maxLength` < 0",
-                    GLError::InvalidOperation => "`program` is not a program object",
-                    _ => "Unknown error"
-                }
-                bytes.set_len((info_length - 1) as usize);
-
-                String::from_utf8(bytes).ok()
-            }
-            else {
-                None
-            }
-        }
-    }
-
     /// Retrieve a program attribute's index by name, or return an error
     /// if the attribute was not found in the program.
     ///
@@ -404,6 +1030,46 @@ pub trait ContextProgramExt: BaseContext {
             }
         }
     }
+
+    /// Check whether the bound program can be executed with the current
+    /// OpenGL state, such as its texture bindings and uniform values. For
+    /// example, validation will fail if two sampler uniforms of different
+    /// types refer to the same texture unit. Validation is slow, so it
+    /// should only be used while debugging.
+    ///
+    /// # Example
+    /// ```no_run
+    /// #[macro_use] extern crate glitter;
+    /// use glitter::prelude::*;
+    ///
+    /// # fn main() {
+    /// # let vertex_source = "";
+    /// # let fragment_source = "";
+    /// let gl = unsafe { glitter::Context::current_context() };
+    /// let mut program = gl.build_program(&[])
+    ///                     .shader_source(glitter::VERTEX_SHADER, vertex_source)
+    ///                     .shader_source(glitter::FRAGMENT_SHADER, fragment_source)
+    ///                     .unwrap();
+    ///
+    /// let (gl_program, gl) = gl.use_program(&mut program);
+    /// let validation = gl.validate_program(&gl_program);
+    /// if !validation.valid {
+    ///     for message in validation.messages() {
+    ///         println!("{}", message);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// # See also
+    /// [`set_validate_before_draw`](fn.set_validate_before_draw.html):
+    /// Automatically validate each program before it is first used for
+    /// drawing.
+    ///
+    /// [`glValidateProgram`](http://docs.gl/es2/glValidateProgram) OpenGL docs
+    fn validate_program(&self, program: &ProgramBinding) -> ProgramValidation {
+        _validate_program(program.gl_id)
+    }
 }
 
 impl<C: BaseContext> ContextProgramExt for C {
@@ -461,6 +1127,7 @@ impl<'a, B, F, P, R, T> ProgramContext for &'a mut ContextOf<B, F, P, R, T>
 
 /// Represents a program that has been bound to the context.
 pub struct ProgramBinding<'a> {
+    gl_id: GLuint,
     _phantom_ref: PhantomData<&'a mut Program>,
     _phantom_ptr: PhantomData<*mut ()>
 }
@@ -482,6 +1149,7 @@ impl ProgramBinder {
     pub fn bind<'a>(&mut self, program: &'a mut Program) -> ProgramBinding<'a>
     {
         let binding = ProgramBinding {
+            gl_id: program.id(),
             _phantom_ref: PhantomData,
             _phantom_ptr: PhantomData
         };
@@ -1,16 +1,37 @@
 //! Contains all of the OpenGL state types related to texture bindings.
 
+use std::borrow::Cow;
+use std::cmp;
+use std::fmt;
 use std::ptr;
 use std::marker::PhantomData;
 use gl;
 use gl::types::*;
-use context::{BaseContext, TextureUnit0Context, TextureUnitBinding2d};
+use context::{BaseContext, ContextExt, TextureUnit0Context, TextureUnitBinding2d,
+              TextureUnitBindingCubeMap};
+use context::_gl_es_major_version;
 use texture::{TextureMipmapFilter, TextureFilter, TextureWrapMode,
+              NEAREST_MIPMAP_LINEAR, LINEAR, REPEAT, CLAMP_TO_EDGE,
               Texture, Texture2d, TextureCubeMap,
               Tx2d, TxCubeMap, TextureType, Tx2dImageTarget,
-              ImageTargetType, TextureBindingTarget};
-use image_data::{Image2d, TexelFormat, ImageFormat};
-use types::{GLObject, GLError};
+              TxCubeMapImageTarget, ImageTargetType, TextureBindingTarget};
+use image_data::{Image2d, Pixels, TexelType, TexelFormat, ImageFormat};
+use compressed_image::{CompressedImage2d, CompressedFormat, CompressedTexelFormat,
+                       Etc1Support, Etc2Support, S3tcSupport, RgtcSupport,
+                       PvrtcSupport, AstcSupport};
+use types::{GLObject, GLError, StringName};
+
+const GL_HALF_FLOAT: GLenum = 0x140B;
+const GL_RGBA32F: GLenum = 0x8814;
+const GL_RGB32F: GLenum = 0x8815;
+const GL_ALPHA32F_ARB: GLenum = 0x8816;
+const GL_LUMINANCE32F_ARB: GLenum = 0x8818;
+const GL_LUMINANCE_ALPHA32F_ARB: GLenum = 0x8819;
+const GL_RGBA16F: GLenum = 0x881A;
+const GL_RGB16F: GLenum = 0x881B;
+const GL_ALPHA16F_ARB: GLenum = 0x881C;
+const GL_LUMINANCE16F_ARB: GLenum = 0x881E;
+const GL_LUMINANCE_ALPHA16F_ARB: GLenum = 0x881F;
 
 /// Provide a safe interface for building a 2D texture
 /// object that is checked to be complete. A `Texture2dBuilder`
@@ -27,6 +48,9 @@ pub struct Texture2dBuilder<'a, C>
     wrap_t: Option<TextureWrapMode>,
     gen_mipmap: bool,
     image: Option<&'a Image2d>,
+    mipmaps: Vec<&'a Image2d>,
+    compressed_image: Option<&'a CompressedImage2d>,
+    compressed_mipmaps: Vec<&'a CompressedImage2d>,
     empty_params: Option<(ImageFormat, u32, u32)>
 }
 
@@ -42,6 +66,9 @@ impl<'a, C> Texture2dBuilder<'a, C>
             wrap_t: None,
             gen_mipmap: false,
             image: None,
+            mipmaps: vec![],
+            compressed_image: None,
+            compressed_mipmaps: vec![],
             empty_params: None
         }
     }
@@ -52,6 +79,61 @@ impl<'a, C> Texture2dBuilder<'a, C>
         self
     }
 
+    /// Add the image data for the next mipmap level of the texture,
+    /// starting with level 1. The image set with [`image_2d`]
+    /// (struct.Texture2dBuilder.html#method.image_2d) is used as
+    /// level 0.
+    pub fn mipmap_level(mut self, image: &'a Image2d) -> Self {
+        self.mipmaps.push(image);
+        self
+    }
+
+    /// Add the image data for each of the next mipmap levels of the
+    /// texture, such as the levels returned by [`Pixels::generate_mipmaps`]
+    /// (../../image_data/struct.Pixels.html#method.generate_mipmaps).
+    pub fn mipmaps<I>(mut self, levels: &'a [I]) -> Self
+        where I: 'a + Image2d
+    {
+        for level in levels {
+            self.mipmaps.push(level);
+        }
+        self
+    }
+
+    /// Set compressed 2D image data to fill the texture with, instead
+    /// of uncompressed image data. Images with a [decoded]
+    /// (../../compressed_image/struct.CompressedFormat.html#method.is_decoded)
+    /// format are decoded on the CPU and uploaded uncompressed.
+    pub fn compressed_image_2d(mut self, image: &'a CompressedImage2d)
+        -> Self
+    {
+        self.compressed_image = Some(image);
+        self
+    }
+
+    /// Add the compressed image data for the next mipmap level of the
+    /// texture, starting with level 1. The image set with
+    /// [`compressed_image_2d`]
+    /// (struct.Texture2dBuilder.html#method.compressed_image_2d) is used
+    /// as level 0.
+    pub fn compressed_mipmap_level(mut self, image: &'a CompressedImage2d)
+        -> Self
+    {
+        self.compressed_mipmaps.push(image);
+        self
+    }
+
+    /// Add the compressed image data for each of the next mipmap levels
+    /// of the texture.
+    pub fn compressed_mipmaps<I>(mut self, levels: &'a [I]) -> Self
+        where I: 'a + CompressedImage2d
+    {
+        for level in levels {
+            self.compressed_mipmaps.push(level);
+        }
+        self
+    }
+
     /// Set the parameters for creating an empty texture.
     pub fn empty(mut self, format: ImageFormat, width: u32, height: u32)
         -> Self
@@ -96,23 +178,171 @@ impl<'a, C> Texture2dBuilder<'a, C>
     /// or return an error.
     ///
     /// # Failures
-    /// If any of the following conditions are met, an error
-    /// will be returned:
+    /// The texture is checked against the OpenGL ES 2.0 rules for
+    /// texture completeness, so that a texture that would sample as
+    /// black is never returned. If any of the following conditions are
+    /// met, an error will be returned, describing which rule was violated:
     ///
-    /// - The texture is not complete.
-    /// - The texture was set to be empty, but either the width
-    ///   or height were 0.
     /// - The texture was neither set to be empty with [`empty`]
     ///   (struct.Texture2dBuilder.html#method.empty), nor had
     ///   any image data supplied with [`image_2d`]
-    ///   (struct.Texture2dBuilder.html#method.image_2d).
-    /// - The texture had a mipmap filter set for the [`min_filter`]
-    ///   (struct.Texture2dBuilder.html#method.min_filter), but
-    ///   mimaps were not generated using [`generate_mipmaps`]
-    ///   (struct.Texture2dBuilder.html#method.generate_mipmap).
+    ///   (struct.Texture2dBuilder.html#method.image_2d) or
+    ///   [`compressed_image_2d`]
+    ///   (struct.Texture2dBuilder.html#method.compressed_image_2d).
+    /// - The texture had both uncompressed and compressed image data.
+    /// - The texture had compressed image data, and was also set to
+    ///   generate mipmaps (OpenGL can't generate mipmaps for compressed
+    ///   textures).
+    /// - The texture had PVRTC-compressed image data whose width or height
+    ///   is not a power of two.
+    /// - The length of the compressed data for the image or a mipmap level
+    ///   does not match its size and format.
+    /// - The texture's width or height is 0.
+    /// - The texture's width or height is greater than
+    ///   `GL_MAX_TEXTURE_SIZE`.
+    /// - Mipmap levels were supplied, but the texture had no image data,
+    ///   the texture was also set to generate mipmaps, or a mipmap level
+    ///   did not have the expected size or the same format as the image.
+    ///   Uncompressed mipmap levels must be used with an uncompressed
+    ///   image, and compressed mipmap levels with a compressed image.
+    /// - The texture's width or height is not a power of two, the context
+    ///   does not support non-power-of-two textures (see
+    ///   [`gl.supports_npot_textures`]
+    ///   (../trait.ContextExt.html#method.supports_npot_textures)), and
+    ///   either the wrap mode for either coordinate is not
+    ///   `CLAMP_TO_EDGE`, the [`min_filter`]
+    ///   (struct.Texture2dBuilder.html#method.min_filter) is a mipmap
+    ///   filter, or the texture has mipmaps.
+    /// - The texture uses a mipmap filter for its minifying filter, but
+    ///   mipmaps were neither generated using [`generate_mipmap`]
+    ///   (struct.Texture2dBuilder.html#method.generate_mipmap) nor
+    ///   supplied all the way down to a 1x1 level with [`mipmaps`]
+    ///   (struct.Texture2dBuilder.html#method.mipmaps).
+    /// - The texture has float or half-float texels, and the context
+    ///   doesn't support them (see [`gl.supports_float_textures`]
+    ///   (../trait.ContextExt.html#method.supports_float_textures) and
+    ///   [`gl.supports_half_float_textures`]
+    ///   (../trait.ContextExt.html#method.supports_half_float_textures)),
+    ///   or either filter is linear (including the default filters) and
+    ///   the context doesn't support linear filtering for them.
+    ///
+    /// # Note
+    /// The default wrap mode is `REPEAT` and the default minifying filter
+    /// is `NEAREST_MIPMAP_LINEAR` (which is a mipmap filter), matching
+    /// the defaults of OpenGL.
     pub fn try_unwrap(self) -> Result<Texture2d, GLError> {
         use TextureMipmapFilter::MipmapFilter;
 
+        if self.image.is_some() && self.compressed_image.is_some() {
+            let msg = "Error building texture: both an image and a compressed image were provided";
+            return Err(GLError::Message(msg.to_owned()));
+        }
+
+        let (width, height) = if let Some(image) = self.image {
+            (image.width() as u32, image.height() as u32)
+        }
+        else if let Some(image) = self.compressed_image {
+            (image.width() as u32, image.height() as u32)
+        }
+        else if let Some((_, width, height)) = self.empty_params {
+            (width, height)
+        }
+        else {
+            let msg = "Error building texture: neither an image nor a format were provided";
+            return Err(GLError::Message(msg.to_owned()));
+        };
+
+        if !(width > 0 && height > 0) {
+            let msg = format!("Error building texture: texture must have positive dimensions, but is {}x{}",
+                              width, height);
+            return Err(GLError::Message(msg));
+        }
+
+        let max_size = self.gl.max_texture_size();
+        if width > max_size || height > max_size {
+            let msg = format!("Error building texture: texture is {}x{}, but GL_MAX_TEXTURE_SIZE is {}",
+                              width, height, max_size);
+            return Err(GLError::Message(msg));
+        }
+
+        if !self.mipmaps.is_empty() {
+            let image = match self.image {
+                Some(image) => image,
+                None => {
+                    let msg = "Error building texture: mipmap levels were provided without an image";
+                    return Err(GLError::Message(msg.to_owned()));
+                }
+            };
+            if self.gen_mipmap {
+                let msg = "Error building texture: mipmap levels were provided, but the texture was also set to generate mipmaps";
+                return Err(GLError::Message(msg.to_owned()));
+            }
+            try!(_check_mip_chain(&image, &self.mipmaps));
+        }
+
+        if let Some(image) = self.compressed_image {
+            if self.gen_mipmap {
+                let msg = "Error building texture: the texture was set to generate mipmaps, but mipmaps can't be generated for a compressed image";
+                return Err(GLError::Message(msg.to_owned()));
+            }
+            if let CompressedTexelFormat::Pvrtc(_) = image.format().texel_format() {
+                if !width.is_power_of_two() || !height.is_power_of_two() {
+                    let msg = format!("Error building texture: PVRTC textures must have power-of-two dimensions, but the texture is {}x{}",
+                                      width, height);
+                    return Err(GLError::Message(msg));
+                }
+            }
+            try!(_check_mip_chain(&image, &self.compressed_mipmaps));
+            try!(_check_compressed_size(image, "building texture"));
+            for mipmap in &self.compressed_mipmaps {
+                try!(_check_compressed_size(*mipmap, "building texture"));
+            }
+        }
+        else if !self.compressed_mipmaps.is_empty() {
+            let msg = "Error building texture: compressed mipmap levels were provided without a compressed image";
+            return Err(GLError::Message(msg.to_owned()));
+        }
+
+        let mip_count = self.mipmaps.len() + self.compressed_mipmaps.len();
+        let min_filter = self.min_filter.unwrap_or(NEAREST_MIPMAP_LINEAR);
+        let uses_mipmap_filter = match min_filter {
+            MipmapFilter {..} => true,
+            _ => false
+        };
+
+        try!(_check_npot(&self.gl,
+                         "texture",
+                         width,
+                         height,
+                         self.wrap_s.unwrap_or(REPEAT),
+                         self.wrap_t.unwrap_or(REPEAT),
+                         min_filter,
+                         self.gen_mipmap || mip_count > 0));
+
+        if uses_mipmap_filter && !self.gen_mipmap {
+            let levels = _full_mip_levels(width, height);
+            if mip_count == 0 {
+                let msg = format!("Error building texture: the min filter is {:?}, which uses mipmaps, but the texture has no mipmaps",
+                                  min_filter);
+                return Err(GLError::Message(msg));
+            }
+            else if mip_count + 1 != levels {
+                let msg = format!("Error building texture: the min filter is {:?}, which uses mipmaps, but the mipmap chain is incomplete (it has {} levels, but a {}x{} texture needs {})",
+                                  min_filter, mip_count + 1,
+                                  width, height, levels);
+                return Err(GLError::Message(msg));
+            }
+        }
+
+        let format = self.image.map(|image| image.format())
+            .or(self.empty_params.map(|(format, _, _)| format));
+        if let Some(format) = format {
+            try!(_check_texel_type(&self.gl,
+                                   format,
+                                   min_filter,
+                                   self.mag_filter.unwrap_or(LINEAR)));
+        }
+
         let gl = self.gl;
         let mut texture = unsafe { gl.gen_texture() };
 
@@ -133,13 +363,31 @@ impl<'a, C> Texture2dBuilder<'a, C>
                 gl.set_wrap_t(&mut gl_tex, wrap_t);
             }
 
-            // TODO: Find out what conditions lead to a non-complete texture
-            //       (e.g. if either width or height are 0)
             if let Some(image) = self.image {
                 gl.tex_image_2d(&mut gl_tex,
                                 Tx2dImageTarget::Texture2d,
                                 0,
                                 image);
+
+                for (level, mipmap) in self.mipmaps.iter().enumerate() {
+                    gl.tex_image_2d(&mut gl_tex,
+                                    Tx2dImageTarget::Texture2d,
+                                    level as u32 + 1,
+                                    *mipmap);
+                }
+            }
+            else if let Some(image) = self.compressed_image {
+                gl.compressed_tex_image_2d(&mut gl_tex,
+                                           Tx2dImageTarget::Texture2d,
+                                           0,
+                                           image);
+
+                for (level, mipmap) in self.compressed_mipmaps.iter().enumerate() {
+                    gl.compressed_tex_image_2d(&mut gl_tex,
+                                               Tx2dImageTarget::Texture2d,
+                                               level as u32 + 1,
+                                               *mipmap);
+                }
             }
             else if let Some((format, width, height)) = self.empty_params {
                 gl.tex_image_2d_empty(&mut gl_tex,
@@ -148,24 +396,11 @@ impl<'a, C> Texture2dBuilder<'a, C>
                                       format,
                                       width,
                                       height);
-
-                if !(width > 0 && height > 0) {
-                    let msg = "Error building texture: texture must have positive dimensions";
-                    return Err(GLError::Message(msg.to_owned()))
-                }
-            }
-            else {
-                let msg = "Error building texture: neither an image nor a format were provided";
-                return Err(GLError::Message(msg.to_owned()))
             }
 
             if self.gen_mipmap {
                 gl.generate_mipmap(&mut gl_tex);
             }
-            else if let Some(MipmapFilter {..}) = self.min_filter {
-                    let msg = "Error building texture: texture uses a mipmap filter but does not have a mipmap";
-                    return Err(GLError::Message(msg.to_owned()));
-            }
         }
 
         Ok(texture)
@@ -182,10 +417,395 @@ impl<'a, C> Texture2dBuilder<'a, C>
     }
 }
 
+/// Provide a safe interface for building a cubemap texture
+/// object that is checked to be complete. A `TextureCubeMapBuilder`
+/// can be created using the [`gl.build_texture_cube_map`]
+/// (trait.ContextTextureBuilderExt.html#method.build_texture_cube_map)
+/// method.
+pub struct TextureCubeMapBuilder<'a, C>
+    where C: 'a + TextureUnit0Context
+{
+    gl: C,
+    min_filter: Option<TextureMipmapFilter>,
+    mag_filter: Option<TextureFilter>,
+    wrap_s: Option<TextureWrapMode>,
+    wrap_t: Option<TextureWrapMode>,
+    gen_mipmap: bool,
+    faces: [Option<&'a Image2d>; 6],
+    mipmaps: [Vec<&'a Image2d>; 6],
+    compressed_faces: [Option<&'a CompressedImage2d>; 6],
+    compressed_mipmaps: [Vec<&'a CompressedImage2d>; 6],
+    empty_params: Option<(ImageFormat, u32)>
+}
+
+impl<'a, C> TextureCubeMapBuilder<'a, C>
+    where C: TextureUnit0Context
+{
+    fn new(gl: C) -> Self {
+        TextureCubeMapBuilder {
+            gl: gl,
+            min_filter: None,
+            mag_filter: None,
+            wrap_s: None,
+            wrap_t: None,
+            gen_mipmap: false,
+            faces: [None; 6],
+            mipmaps: [vec![], vec![], vec![], vec![], vec![], vec![]],
+            compressed_faces: [None; 6],
+            compressed_mipmaps: [vec![], vec![], vec![],
+                                 vec![], vec![], vec![]],
+            empty_params: None
+        }
+    }
+
+    /// Set the 2D image data to fill a single face of the cubemap with.
+    pub fn face(mut self, target: TxCubeMapImageTarget, image: &'a Image2d)
+        -> Self
+    {
+        self.faces[target.face_index()] = Some(image);
+        self
+    }
+
+    /// Set the 2D image data for all six faces of the cubemap, in the
+    /// order returned by [`TxCubeMapImageTarget::faces`]
+    /// (../../texture/enum.TxCubeMapImageTarget.html#method.faces)
+    /// (+X, -X, +Y, -Y, +Z, -Z).
+    pub fn faces(mut self, images: [&'a Image2d; 6]) -> Self {
+        for (face, image) in self.faces.iter_mut().zip(images.iter()) {
+            *face = Some(*image);
+        }
+        self
+    }
+
+    /// Add the image data for each of the next mipmap levels of a single
+    /// face of the cubemap, starting with level 1. The image set with
+    /// [`face`](struct.TextureCubeMapBuilder.html#method.face) or
+    /// [`faces`](struct.TextureCubeMapBuilder.html#method.faces) is used
+    /// as level 0.
+    pub fn face_mipmaps<I>(mut self,
+                           target: TxCubeMapImageTarget,
+                           levels: &'a [I])
+        -> Self
+        where I: 'a + Image2d
+    {
+        for level in levels {
+            self.mipmaps[target.face_index()].push(level);
+        }
+        self
+    }
+
+    /// Set compressed 2D image data to fill a single face of the cubemap
+    /// with, instead of uncompressed image data.
+    pub fn compressed_face(mut self,
+                           target: TxCubeMapImageTarget,
+                           image: &'a CompressedImage2d)
+        -> Self
+    {
+        self.compressed_faces[target.face_index()] = Some(image);
+        self
+    }
+
+    /// Set compressed 2D image data for all six faces of the cubemap, in
+    /// the same order as [`faces`]
+    /// (struct.TextureCubeMapBuilder.html#method.faces).
+    pub fn compressed_faces(mut self, images: [&'a CompressedImage2d; 6])
+        -> Self
+    {
+        for (face, image) in self.compressed_faces.iter_mut()
+                                                  .zip(images.iter())
+        {
+            *face = Some(*image);
+        }
+        self
+    }
+
+    /// Add the compressed image data for each of the next mipmap levels of
+    /// a single face of the cubemap, starting with level 1. The image set
+    /// with [`compressed_face`]
+    /// (struct.TextureCubeMapBuilder.html#method.compressed_face) or
+    /// [`compressed_faces`]
+    /// (struct.TextureCubeMapBuilder.html#method.compressed_faces) is used
+    /// as level 0.
+    pub fn compressed_face_mipmaps<I>(mut self,
+                                      target: TxCubeMapImageTarget,
+                                      levels: &'a [I])
+        -> Self
+        where I: 'a + CompressedImage2d
+    {
+        for level in levels {
+            self.compressed_mipmaps[target.face_index()].push(level);
+        }
+        self
+    }
+
+    /// Set the parameters for creating an empty cubemap, where
+    /// each face is `size` by `size` texels.
+    pub fn empty(mut self, format: ImageFormat, size: u32) -> Self {
+        self.empty_params = Some((format, size));
+        self
+    }
+
+    /// Automatically generate mipmaps for the texture.
+    pub fn generate_mipmap(mut self) -> Self {
+        self.gen_mipmap = true;
+        self
+    }
+
+    /// Set the texture's minifying filter.
+    pub fn min_filter<I>(mut self, filter: I) -> Self
+        where I: Into<TextureMipmapFilter>
+    {
+        self.min_filter = Some(filter.into());
+        self
+    }
+
+    /// Set the texture's magnifying filter.
+    pub fn mag_filter(mut self, filter: TextureFilter) -> Self {
+        self.mag_filter = Some(filter);
+        self
+    }
+
+    /// Set the texture's wrap mode for the s-coordinate.
+    pub fn wrap_s(mut self, wrap: TextureWrapMode) -> Self {
+        self.wrap_s = Some(wrap);
+        self
+    }
+
+    /// Set the texture's wrap mode for the t-coordinate.
+    pub fn wrap_t(mut self, wrap: TextureWrapMode) -> Self {
+        self.wrap_t = Some(wrap);
+        self
+    }
+
+    /// Create and return a cubemap texture with the specified options,
+    /// or return an error.
+    ///
+    /// # Failures
+    /// If any of the following conditions are met, an error
+    /// will be returned:
+    ///
+    /// - Image data was supplied for some faces, but not all six.
+    /// - Both uncompressed and compressed image data were supplied.
+    /// - Compressed image data was supplied, and the texture was also
+    ///   set to generate mipmaps.
+    /// - The length of the compressed data for a face or a mipmap level
+    ///   does not match its size and format.
+    /// - Any face is not square, or the faces are not all the same size.
+    /// - The faces do not all have the same image format.
+    /// - Mipmap levels were supplied, but not for every face, the
+    ///   texture was also set to generate mipmaps, or a mipmap level
+    ///   did not have the expected size or the same format as its face.
+    /// - The texture was set to be empty, but the size was 0.
+    /// - The faces' size is greater than `GL_MAX_CUBE_MAP_TEXTURE_SIZE`.
+    /// - The faces' size is not a power of two, the context does not
+    ///   support non-power-of-two textures (see
+    ///   [`gl.supports_npot_textures`]
+    ///   (../trait.ContextExt.html#method.supports_npot_textures)), and
+    ///   either the wrap mode for either coordinate is not
+    ///   `CLAMP_TO_EDGE`, the [`min_filter`]
+    ///   (struct.TextureCubeMapBuilder.html#method.min_filter) is a mipmap
+    ///   filter, or the texture has mipmaps.
+    /// - The texture was neither set to be empty with [`empty`]
+    ///   (struct.TextureCubeMapBuilder.html#method.empty), nor had
+    ///   any image data supplied with [`face`]
+    ///   (struct.TextureCubeMapBuilder.html#method.face), [`faces`]
+    ///   (struct.TextureCubeMapBuilder.html#method.faces), or their
+    ///   compressed equivalents.
+    /// - The texture uses a mipmap filter for its minifying filter, but
+    ///   mipmaps were neither generated using [`generate_mipmap`]
+    ///   (struct.TextureCubeMapBuilder.html#method.generate_mipmap) nor
+    ///   supplied all the way down to a 1x1 level for every face.
+    /// - The texture has float or half-float texels that the context
+    ///   doesn't support, or that the context can't filter linearly while
+    ///   either filter is linear.
+    ///
+    /// # Note
+    /// The default minifying filter is `NEAREST_MIPMAP_LINEAR` (which is
+    /// a mipmap filter), matching the defaults of OpenGL.
+    pub fn try_unwrap(self) -> Result<TextureCubeMap, GLError> {
+        use TextureMipmapFilter::MipmapFilter;
+
+        let has_faces = self.faces.iter().any(|face| face.is_some());
+        let has_mipmaps = self.mipmaps.iter().any(|levels| !levels.is_empty());
+        let has_compressed_faces = self.compressed_faces.iter()
+            .any(|face| face.is_some());
+        let has_compressed_mipmaps = self.compressed_mipmaps.iter()
+            .any(|levels| !levels.is_empty());
+        if has_faces && has_compressed_faces {
+            let msg = "Error building cubemap: both images and compressed images were provided";
+            return Err(GLError::Message(msg.to_owned()));
+        }
+        if (has_mipmaps && !has_faces)
+            || (has_compressed_mipmaps && !has_compressed_faces)
+        {
+            let msg = "Error building cubemap: mipmap levels were provided without any images";
+            return Err(GLError::Message(msg.to_owned()));
+        }
+        if has_mipmaps && self.gen_mipmap {
+            let msg = "Error building cubemap: mipmap levels were provided, but the texture was also set to generate mipmaps";
+            return Err(GLError::Message(msg.to_owned()));
+        }
+        if has_compressed_faces && self.gen_mipmap {
+            let msg = "Error building cubemap: the texture was set to generate mipmaps, but mipmaps can't be generated for compressed images";
+            return Err(GLError::Message(msg.to_owned()));
+        }
+
+        let size = if has_faces {
+            try!(_check_cube_faces(&self.faces, &self.mipmaps))
+        }
+        else if has_compressed_faces {
+            let size = try!(_check_cube_faces(&self.compressed_faces,
+                                              &self.compressed_mipmaps));
+            let faces = self.compressed_faces.iter().filter_map(|face| *face);
+            let mipmaps = self.compressed_mipmaps.iter().flat_map(|levels| {
+                levels.iter().cloned()
+            });
+            for image in faces.chain(mipmaps) {
+                try!(_check_compressed_size(image, "building cubemap"));
+            }
+            size
+        }
+        else if let Some((_, size)) = self.empty_params {
+            if size == 0 {
+                let msg = "Error building cubemap: texture must have positive dimensions";
+                return Err(GLError::Message(msg.to_owned()));
+            }
+            size
+        }
+        else {
+            let msg = "Error building cubemap: neither images nor a format were provided";
+            return Err(GLError::Message(msg.to_owned()));
+        };
+
+        let max_size = self.gl.max_cube_map_texture_size();
+        if size > max_size {
+            let msg = format!("Error building cubemap: faces are {}x{}, but GL_MAX_CUBE_MAP_TEXTURE_SIZE is {}",
+                              size, size, max_size);
+            return Err(GLError::Message(msg));
+        }
+
+        // `_check_cube_faces` ensures every face has the same number of
+        // mipmap levels, so only the first face needs to be counted
+        let mip_count = self.mipmaps[0].len()
+            + self.compressed_mipmaps[0].len();
+        let min_filter = self.min_filter.unwrap_or(NEAREST_MIPMAP_LINEAR);
+        let uses_mipmap_filter = match min_filter {
+            MipmapFilter {..} => true,
+            _ => false
+        };
+
+        try!(_check_npot(&self.gl,
+                         "cubemap",
+                         size,
+                         size,
+                         self.wrap_s.unwrap_or(REPEAT),
+                         self.wrap_t.unwrap_or(REPEAT),
+                         min_filter,
+                         self.gen_mipmap || mip_count > 0));
+
+        if uses_mipmap_filter && !self.gen_mipmap {
+            let levels = _full_mip_levels(size, size);
+            if mip_count == 0 {
+                let msg = format!("Error building cubemap: the min filter is {:?}, which uses mipmaps, but the texture has no mipmaps",
+                                  min_filter);
+                return Err(GLError::Message(msg));
+            }
+            else if mip_count + 1 != levels {
+                let msg = format!("Error building cubemap: the min filter is {:?}, which uses mipmaps, but the mipmap chain is incomplete (it has {} levels, but {}x{} faces need {})",
+                                  min_filter, mip_count + 1,
+                                  size, size, levels);
+                return Err(GLError::Message(msg));
+            }
+        }
+
+        let format = self.faces[0].map(|image| image.format())
+            .or(self.empty_params.map(|(format, _)| format));
+        if let Some(format) = format {
+            try!(_check_texel_type(&self.gl,
+                                   format,
+                                   min_filter,
+                                   self.mag_filter.unwrap_or(LINEAR)));
+        }
+
+        let gl = self.gl;
+        let mut texture = unsafe { gl.gen_texture() };
+
+        {
+            let (gl_tex_unit, gl) = gl.active_texture_0();
+            let (mut gl_tex, _) =
+                gl_tex_unit.bind_texture_cube_map(&mut texture);
+
+            if let Some(min_filter) = self.min_filter {
+                gl.set_min_filter(&mut gl_tex, min_filter);
+            }
+            if let Some(mag_filter) = self.mag_filter {
+                gl.set_mag_filter(&mut gl_tex, mag_filter);
+            }
+            if let Some(wrap_s) = self.wrap_s {
+                gl.set_wrap_s(&mut gl_tex, wrap_s);
+            }
+            if let Some(wrap_t) = self.wrap_t {
+                gl.set_wrap_t(&mut gl_tex, wrap_t);
+            }
+
+            for target in TxCubeMapImageTarget::faces().iter() {
+                if let Some(image) = self.faces[target.face_index()] {
+                    gl.tex_image_2d(&mut gl_tex, *target, 0, image);
+
+                    let mipmaps = &self.mipmaps[target.face_index()];
+                    for (level, mipmap) in mipmaps.iter().enumerate() {
+                        gl.tex_image_2d(&mut gl_tex,
+                                        *target,
+                                        level as u32 + 1,
+                                        *mipmap);
+                    }
+                }
+                else if let Some(image) = self.compressed_faces[target.face_index()] {
+                    gl.compressed_tex_image_2d(&mut gl_tex, *target, 0, image);
+
+                    let mipmaps = &self.compressed_mipmaps[target.face_index()];
+                    for (level, mipmap) in mipmaps.iter().enumerate() {
+                        gl.compressed_tex_image_2d(&mut gl_tex,
+                                                   *target,
+                                                   level as u32 + 1,
+                                                   *mipmap);
+                    }
+                }
+                else if let Some((format, size)) = self.empty_params {
+                    gl.tex_image_2d_empty(&mut gl_tex,
+                                          *target,
+                                          0,
+                                          format,
+                                          size,
+                                          size);
+                }
+            }
+
+            if self.gen_mipmap {
+                gl.generate_mipmap(&mut gl_tex);
+            }
+        }
+
+        Ok(texture)
+    }
+
+    /// Create a cubemap texture with the specified options, or panic.
+    ///
+    /// # Panic
+    /// See the [`try_unwrap`]
+    /// (struct.TextureCubeMapBuilder.html#method.try_unwrap)
+    /// method docs for all of the possible failure cases when building
+    /// a cubemap texture.
+    pub fn unwrap(self) -> TextureCubeMap {
+        self.try_unwrap().unwrap()
+    }
+}
+
 // NOTE: There is currently no way to express "a context with
 //       one free texure unit"; this design should be explored for
 //       cases like this (where the actual unit number doesn't matter)
-/// The extension trait for contexts that adds the `build_texture_2d` method.
+/// The extension trait for contexts that adds the `build_texture_2d` and
+/// `build_texture_cube_map` methods.
 ///
 /// # Note
 /// Currently, this trait is only implemented for contexts where the
@@ -197,6 +817,14 @@ pub trait ContextTextureBuilderExt: TextureUnit0Context + Sized {
     fn build_texture_2d<'a>(self) -> Texture2dBuilder<'a, Self> {
         Texture2dBuilder::new(self)
     }
+
+    /// Create a new cubemap texture builder, providing a safe interface
+    /// for constructing a cubemap texture object. See the
+    /// [`TextureCubeMapBuilder`](struct.TextureCubeMapBuilder.html)
+    /// docs for more details.
+    fn build_texture_cube_map<'a>(self) -> TextureCubeMapBuilder<'a, Self> {
+        TextureCubeMapBuilder::new(self)
+    }
 }
 
 impl<'a, C: 'a> ContextTextureBuilderExt for &'a mut C
@@ -220,6 +848,9 @@ pub trait ContextTextureExt: BaseContext {
     /// [`gl.build_texture_2d`](trait.ContextTextureBuilderExt.html#method.build_texture_2d):
     /// A safe wrapper for building 2D textures.
     ///
+    /// [`gl.build_texture_cube_map`](trait.ContextTextureBuilderExt.html#method.build_texture_cube_map):
+    /// A safe wrapper for building cubemap textures.
+    ///
     /// [`glGenTextures`](http://docs.gl/es2/glGenTextures) OpenGL docs
     unsafe fn gen_texture<TX: TextureType>(&self) -> Texture<TX> {
         let mut id : GLuint =  0;
@@ -233,14 +864,44 @@ pub trait ContextTextureExt: BaseContext {
         Texture::from_raw(id)
     }
 
+    /// Get the largest width or height that a 2D texture can have
+    /// (`GL_MAX_TEXTURE_SIZE`).
+    ///
+    /// # See also
+    /// [`glGet`](http://docs.gl/es2/glGet) OpenGL docs
+    fn max_texture_size(&self) -> u32 {
+        unsafe { _get_integer(gl::MAX_TEXTURE_SIZE) as u32 }
+    }
+
+    /// Get the largest width or height that each face of a cubemap
+    /// texture can have (`GL_MAX_CUBE_MAP_TEXTURE_SIZE`).
+    ///
+    /// # See also
+    /// [`glGet`](http://docs.gl/es2/glGet) OpenGL docs
+    fn max_cube_map_texture_size(&self) -> u32 {
+        unsafe { _get_integer(gl::MAX_CUBE_MAP_TEXTURE_SIZE) as u32 }
+    }
+
     /// Set a texture's minifying filter.
     ///
+    /// Unlike the texture builders, this doesn't check that the context
+    /// can filter the texture's image linearly. Float and half-float
+    /// images sampled with a linear filter need
+    /// [`supports_float_linear_filtering`]
+    /// (../trait.ContextExt.html#method.supports_float_linear_filtering) or
+    /// [`supports_half_float_linear_filtering`]
+    /// (../trait.ContextExt.html#method.supports_half_float_linear_filtering),
+    /// and are incomplete (sampling as black) otherwise.
+    ///
     /// # See also
     /// [`glTexParameter`](http://docs.gl/es2/glTexParameter) OpenGL docs
     fn set_min_filter<T, F>(&self, gl_texture: &mut T, filter: F)
         where T: TextureBinding, F: Into<TextureMipmapFilter>
     {
-        let gl_int = filter.into().gl_enum() as GLint;
+        let filter = filter.into();
+        gl_texture._texture_mut()._sampler_params_mut().min_filter = filter;
+
+        let gl_int = filter.gl_enum() as GLint;
         unsafe {
             _tex_parameter_iv(gl_texture.target(),
                               gl::TEXTURE_MIN_FILTER,
@@ -250,11 +911,22 @@ pub trait ContextTextureExt: BaseContext {
 
     /// Set a texture's magnifying filter.
     ///
+    /// Unlike the texture builders, this doesn't check that the context
+    /// can filter the texture's image linearly. Float and half-float
+    /// images sampled with a linear filter need
+    /// [`supports_float_linear_filtering`]
+    /// (../trait.ContextExt.html#method.supports_float_linear_filtering) or
+    /// [`supports_half_float_linear_filtering`]
+    /// (../trait.ContextExt.html#method.supports_half_float_linear_filtering),
+    /// and are incomplete (sampling as black) otherwise.
+    ///
     /// # See also
     /// [`glTexParameter`](http://docs.gl/es2/glTexParameter) OpenGL docs
     fn set_mag_filter<T>(&self, gl_texture: &mut T, filter: TextureFilter)
         where T: TextureBinding
     {
+        gl_texture._texture_mut()._sampler_params_mut().mag_filter = filter;
+
         let gl_int = filter.gl_enum() as GLint;
         unsafe {
             _tex_parameter_iv(gl_texture.target(),
@@ -270,6 +942,8 @@ pub trait ContextTextureExt: BaseContext {
     fn set_wrap_s<T>(&self, gl_texture: &mut T, wrap_mode: TextureWrapMode)
         where T: TextureBinding
     {
+        gl_texture._texture_mut()._sampler_params_mut().wrap_s = wrap_mode;
+
         let gl_int = wrap_mode.gl_enum() as GLint;
         unsafe {
             _tex_parameter_iv(gl_texture.target(),
@@ -285,6 +959,8 @@ pub trait ContextTextureExt: BaseContext {
     fn set_wrap_t<T>(&self, gl_texture: &mut T, wrap_mode: TextureWrapMode)
         where T: TextureBinding
     {
+        gl_texture._texture_mut()._sampler_params_mut().wrap_t = wrap_mode;
+
         let gl_int = wrap_mode.gl_enum() as GLint;
         unsafe {
             _tex_parameter_iv(gl_texture.target(),
@@ -300,6 +976,8 @@ pub trait ContextTextureExt: BaseContext {
     fn generate_mipmap<T>(&self, gl_texture: &mut T)
         where T: TextureBinding
     {
+        gl_texture._texture_mut()._set_mipmap_generated();
+
         unsafe {
             gl::GenerateMipmap(gl_texture.target().gl_enum())
         }
@@ -307,12 +985,28 @@ pub trait ContextTextureExt: BaseContext {
 
     /// Upload 2D image data to a texture object's image target.
     ///
-    /// - `_gl_texture`: The binding of the texture object.
+    /// - `gl_texture`: The binding of the texture object.
     /// - `target`: The texture's 2D image target to upload the image data to.
     /// - `level`: The mipmap level to upload the image data to.
     /// - `img`: The image data to upload.
+    ///
+    /// # Note
+    /// `GL_UNPACK_ALIGNMENT` is set to match the image's [`row_stride`]
+    /// (../../image_data/trait.Image2d.html#method.row_stride) before
+    /// uploading. If no alignment matches the row stride (which OpenGL ES
+    /// 2.0 can't describe otherwise), the rows are first copied into
+    /// a tightly packed buffer.
+    ///
+    /// On desktop OpenGL, float and half-float images are stored with
+    /// a sized floating-point internal format (such as `GL_RGBA32F`).
+    /// Unlike the texture builders, this doesn't check that the context
+    /// supports the image's texel type, or that the texture's filters can
+    /// sample it; see [`supports_float_textures`]
+    /// (../trait.ContextExt.html#method.supports_float_textures) and
+    /// [`supports_half_float_textures`]
+    /// (../trait.ContextExt.html#method.supports_half_float_textures).
     fn tex_image_2d<T, U, I: ?Sized>(&self,
-                                     _gl_texture: &mut T,
+                                     gl_texture: &mut T,
                                      target: U,
                                      level: u32,
                                      img: &I)
@@ -320,30 +1014,43 @@ pub trait ContextTextureExt: BaseContext {
               U: Into<<T::TextureType as TextureType>::ImageTargetType>,
               I: Image2d
     {
+        gl_texture._texture_mut()._set_image(level,
+                                             img.format(),
+                                             img.width() as u32,
+                                             img.height() as u32);
+
+        let internal_format = _gl_internal_format(self, img.format());
+        let gl_type = _gl_texel_type(self, img.format().texel_type);
+        let (alignment, bytes) = _unpack_rows(img);
         unsafe {
+            _pixel_store_unpack_alignment(alignment);
             _tex_image_2d(target.into(),
                           level,
-                          img.format().texel_format,
+                          internal_format,
                           img.width() as u32,
                           img.height() as u32,
                           0,
                           img.format(),
-                          img.texel_bytes().as_ptr());
+                          gl_type,
+                          bytes.as_ptr());
         }
     }
 
     /// Set a texture object's image target to an empty image
     /// with the specified parameters.
     ///
-    /// - `_gl_texture`: The binding of the texture object.
+    /// - `gl_texture`: The binding of the texture object.
     /// - `target`: The texture's 2D image target to set.
     /// - `level`: The mipmap level to set.
     /// - `format`: The image format to use to use for the
     ///             texture's data store.
     /// - `width`: The width to set for the texture's data store.
     /// - `height`: The height to set for the texture's data store.
+    ///
+    /// The texel type is handled the same way as in [`tex_image_2d`]
+    /// (trait.ContextTextureExt.html#method.tex_image_2d).
     fn tex_image_2d_empty<T, I>(&self,
-                                _gl_texture: &mut T,
+                                gl_texture: &mut T,
                                 target: I,
                                 level: u32,
                                 format: ImageFormat,
@@ -351,17 +1058,329 @@ pub trait ContextTextureExt: BaseContext {
                                 height: u32)
         where T: TextureBinding, I: ImageTargetType
     {
+        gl_texture._texture_mut()._set_image(level, format, width, height);
+        let internal_format = _gl_internal_format(self, format);
+        let gl_type = _gl_texel_type(self, format.texel_type);
         unsafe {
             _tex_image_2d(target,
                           level,
-                          format.texel_format,
+                          internal_format,
                           width,
                           height,
                           0,
                           format,
+                          gl_type,
                           ptr::null());
         }
     }
+
+    /// Replace a rectangular region of a texture object's image target
+    /// with new image data, without reallocating the texture's storage.
+    ///
+    /// - `gl_texture`: The binding of the texture object.
+    /// - `target`: The texture's 2D image target to update.
+    /// - `level`: The mipmap level to update.
+    /// - `x`, `y`: The texel offset of the region to update.
+    /// - `img`: The image data to upload. The region that is updated is
+    ///          the same size as the image. To upload only part of a
+    ///          larger image, use an [`ImageView`]
+    ///          (../../image_data/struct.ImageView.html).
+    ///
+    /// Rows are unpacked the same way as in [`tex_image_2d`]
+    /// (trait.ContextTextureExt.html#method.tex_image_2d).
+    ///
+    /// # Failures
+    /// An error will be returned if the image's format does not match
+    /// the format that the texture's image data was specified with (or
+    /// the texture's image data is compressed), if the mipmap level has
+    /// not been specified, or if the region does not fit within the mipmap
+    /// level. These checks are skipped if the texture's image data was
+    /// not specified through glitter.
+    ///
+    /// # See also
+    /// [`glTexSubImage2D`](http://docs.gl/es2/glTexSubImage2D) OpenGL docs
+    fn tex_sub_image_2d<T, U, I: ?Sized>(&self,
+                                         gl_texture: &mut T,
+                                         target: U,
+                                         level: u32,
+                                         x: u32,
+                                         y: u32,
+                                         img: &I)
+        -> Result<(), GLError>
+        where T: TextureBinding,
+              U: Into<<T::TextureType as TextureType>::ImageTargetType>,
+              I: Image2d
+    {
+        if let Some(format) = gl_texture.texture().format() {
+            if format != img.format() {
+                let msg = format!("Error updating texture: image has format {:?}, but the texture was created with format {:?}",
+                                  img.format(), format);
+                return Err(GLError::Message(msg));
+            }
+        }
+        if let Some(tex_format) = gl_texture.texture().compressed_format() {
+            let msg = format!("Error updating texture: image has uncompressed format {:?}, but the texture was created with compressed format {:?}",
+                              img.format(), tex_format.texel_format());
+            return Err(GLError::Message(msg));
+        }
+
+        try!(_check_sub_region(gl_texture.texture(),
+                               level,
+                               x,
+                               y,
+                               img.width() as u32,
+                               img.height() as u32));
+
+        let gl_type = _gl_texel_type(self, img.format().texel_type);
+        let (alignment, bytes) = _unpack_rows(img);
+        unsafe {
+            _pixel_store_unpack_alignment(alignment);
+            _tex_sub_image_2d(target.into(),
+                              level,
+                              x,
+                              y,
+                              img.width() as u32,
+                              img.height() as u32,
+                              img.format(),
+                              gl_type,
+                              bytes.as_ptr());
+        }
+
+        Ok(())
+    }
+
+    /// Check whether the current context supports a compressed texture
+    /// format, and return it as a [`CompressedFormat`]
+    /// (../../compressed_image/struct.CompressedFormat.html) if it does.
+    fn compressed_format(&self, format: CompressedTexelFormat)
+        -> Option<CompressedFormat>
+        where Self: Sized
+    {
+        match format {
+            CompressedTexelFormat::Etc1(format) => {
+                Etc1Support::query(self).map(|proof| proof.format(format))
+            },
+            CompressedTexelFormat::Etc2(format) => {
+                Etc2Support::query(self).map(|proof| proof.format(format))
+            },
+            CompressedTexelFormat::S3tc(format) => {
+                S3tcSupport::query(self).map(|proof| proof.format(format))
+            },
+            CompressedTexelFormat::Rgtc(format) => {
+                RgtcSupport::query(self).map(|proof| proof.format(format))
+            },
+            CompressedTexelFormat::Pvrtc(format) => {
+                PvrtcSupport::query(self).map(|proof| proof.format(format))
+            },
+            CompressedTexelFormat::Astc(format) => {
+                AstcSupport::query(self).map(|proof| proof.format(format))
+            }
+        }
+    }
+
+    /// Get a [`CompressedFormat`]
+    /// (../../compressed_image/struct.CompressedFormat.html) that can be
+    /// used to upload data with a compressed texture format. If the
+    /// current context doesn't support the format, a format that is
+    /// decoded on the CPU when it's uploaded is returned instead (see
+    /// [`CompressedFormat::decoded`]
+    /// (../../compressed_image/struct.CompressedFormat.html#method.decoded)).
+    /// Returns `None` if the format is neither supported nor decodable.
+    fn compressed_format_or_decoded(&self, format: CompressedTexelFormat)
+        -> Option<CompressedFormat>
+        where Self: Sized
+    {
+        self.compressed_format(format)
+            .or_else(|| CompressedFormat::decoded(format))
+    }
+
+    /// Upload compressed 2D image data to a texture object's image target.
+    ///
+    /// - `gl_texture`: The binding of the texture object.
+    /// - `target`: The texture's 2D image target to upload the image data to.
+    /// - `level`: The mipmap level to upload the image data to.
+    /// - `img`: The compressed image data to upload.
+    ///
+    /// If the image's format is [decoded]
+    /// (../../compressed_image/struct.CompressedFormat.html#method.is_decoded),
+    /// the image is decoded on the CPU and uploaded with
+    /// [`tex_image_2d`](#method.tex_image_2d) as uncompressed RGBA data
+    /// instead.
+    ///
+    /// # Panics
+    /// This function will panic if the length of the image's compressed
+    /// data does not match its size and format (see
+    /// [`CompressedTexelFormat::image_size`]
+    /// (../../compressed_image/enum.CompressedTexelFormat.html#method.image_size)).
+    /// [`CompressedImage::new`]
+    /// (../../compressed_image/struct.CompressedImage.html#method.new)
+    /// checks this when an image is created, and the texture builders
+    /// return an error instead.
+    ///
+    /// # See also
+    /// [`glCompressedTexImage2D`](http://docs.gl/es2/glCompressedTexImage2D) OpenGL docs
+    fn compressed_tex_image_2d<T, U, I: ?Sized>(&self,
+                                                gl_texture: &mut T,
+                                                target: U,
+                                                level: u32,
+                                                img: &I)
+        where T: TextureBinding,
+              U: Into<<T::TextureType as TextureType>::ImageTargetType>,
+              I: CompressedImage2d
+    {
+        if let Err(e) = _check_compressed_size(img, "uploading texture") {
+            panic!("{}", e);
+        }
+
+        if img.format().is_decoded() {
+            match _decode_image(img) {
+                Ok(pixels) => {
+                    self.tex_image_2d(gl_texture, target, level, &pixels);
+                },
+                Err(e) => { panic!("{}", e); }
+            }
+            return;
+        }
+
+        gl_texture._texture_mut()._set_compressed_image(level,
+                                                        img.format(),
+                                                        img.width() as u32,
+                                                        img.height() as u32);
+
+        unsafe {
+            _compressed_tex_image_2d(target.into(),
+                                     level,
+                                     img.format(),
+                                     img.width() as u32,
+                                     img.height() as u32,
+                                     img.compressed_bytes());
+        }
+    }
+
+    /// Replace a rectangular region of a texture object's image target
+    /// with new compressed image data, without reallocating the texture's
+    /// storage.
+    ///
+    /// - `gl_texture`: The binding of the texture object.
+    /// - `target`: The texture's 2D image target to update.
+    /// - `level`: The mipmap level to update.
+    /// - `x`, `y`: The texel offset of the region to update.
+    /// - `img`: The compressed image data to upload. The region that is
+    ///          updated is the same size as the image.
+    ///
+    /// # Failures
+    /// An error will be returned if any of the following conditions are met:
+    ///
+    /// - The image's format does not match the compressed format that the
+    ///   texture's image data was specified with.
+    /// - The format does not support updating part of an image (see
+    ///   [`CompressedTexelFormat::supports_sub_image`]
+    ///   (../../compressed_image/enum.CompressedTexelFormat.html#method.supports_sub_image)).
+    /// - The region does not start on a block boundary, or does not
+    ///   cover whole blocks (except at the right and bottom edges of
+    ///   the mipmap level).
+    /// - The mipmap level has not been specified, or the region does not
+    ///   fit within the mipmap level.
+    ///
+    /// The texture's format and size are only checked if its image data
+    /// was specified through glitter. If the image's format is [decoded]
+    /// (../../compressed_image/struct.CompressedFormat.html#method.is_decoded),
+    /// the image is decoded on the CPU and uploaded with
+    /// [`tex_sub_image_2d`](#method.tex_sub_image_2d) instead, so the
+    /// texture must have been created with a decoded image of the
+    /// same format.
+    ///
+    /// # See also
+    /// [`glCompressedTexSubImage2D`](http://docs.gl/es2/glCompressedTexSubImage2D) OpenGL docs
+    fn compressed_tex_sub_image_2d<T, U, I: ?Sized>(&self,
+                                                    gl_texture: &mut T,
+                                                    target: U,
+                                                    level: u32,
+                                                    x: u32,
+                                                    y: u32,
+                                                    img: &I)
+        -> Result<(), GLError>
+        where T: TextureBinding,
+              U: Into<<T::TextureType as TextureType>::ImageTargetType>,
+              I: CompressedImage2d
+    {
+        try!(_check_compressed_size(img, "updating texture"));
+
+        if img.format().is_decoded() {
+            let pixels = try!(_decode_image(img));
+            return self.tex_sub_image_2d(gl_texture,
+                                         target,
+                                         level,
+                                         x,
+                                         y,
+                                         &pixels);
+        }
+
+        let format = img.format().texel_format();
+        if let Some(tex_format) = gl_texture.texture().format() {
+            let msg = format!("Error updating texture: image has compressed format {:?}, but the texture was created with uncompressed format {:?}",
+                              format, tex_format);
+            return Err(GLError::Message(msg));
+        }
+        if let Some(tex_format) = gl_texture.texture().compressed_format() {
+            if tex_format != img.format() {
+                let msg = format!("Error updating texture: image has format {:?}, but the texture was created with format {:?}",
+                                  format, tex_format.texel_format());
+                return Err(GLError::Message(msg));
+            }
+        }
+
+        if !format.supports_sub_image() {
+            let msg = format!("Error updating texture: format {:?} does not support updating part of an image",
+                              format);
+            return Err(GLError::Message(msg));
+        }
+
+        let (block_width, block_height) = format.block_dimensions();
+        let (block_width, block_height) = (block_width as u32,
+                                           block_height as u32);
+        if x % block_width != 0 || y % block_height != 0 {
+            let msg = format!("Error updating texture: the region at ({}, {}) does not start on a {}x{} block boundary",
+                              x, y, block_width, block_height);
+            return Err(GLError::Message(msg));
+        }
+
+        let (img_width, img_height) = (img.width() as u32,
+                                       img.height() as u32);
+        let level_size = try!(_check_sub_region(gl_texture.texture(),
+                                                level,
+                                                x,
+                                                y,
+                                                img_width,
+                                                img_height));
+        if let Some((width, height)) = level_size {
+            // `_check_sub_region` ensures that these additions don't
+            // overflow
+            let partial_x = img_width % block_width != 0
+                && x + img_width != width;
+            let partial_y = img_height % block_height != 0
+                && y + img_height != height;
+            if partial_x || partial_y {
+                let msg = format!("Error updating texture: a {}x{} region must cover whole {}x{} blocks, unless it reaches the edge of the mipmap level",
+                                  img_width, img_height,
+                                  block_width, block_height);
+                return Err(GLError::Message(msg));
+            }
+        }
+
+        unsafe {
+            _compressed_tex_sub_image_2d(target.into(),
+                                         level,
+                                         x,
+                                         y,
+                                         img.width() as u32,
+                                         img.height() as u32,
+                                         img.format(),
+                                         img.compressed_bytes());
+        }
+
+        Ok(())
+    }
 }
 
 impl<C: BaseContext> ContextTextureExt for C {
@@ -370,6 +1389,369 @@ impl<C: BaseContext> ContextTextureExt for C {
 
 
 
+// Check that a compressed image's data is the right length for its size
+// and format. `action` describes what the image is being used for, for
+// the error message (such as "building texture")
+fn _check_compressed_size<I: ?Sized + CompressedImage2d>(img: &I, action: &str)
+    -> Result<(), GLError>
+{
+    let format = img.format().texel_format();
+    let expected = format.image_size(img.width(), img.height());
+    let actual = img.compressed_bytes().len();
+    if actual != expected {
+        let msg = format!("Error {}: a {}x{} image with format {:?} needs {} bytes of compressed data, but has {}",
+                          action, img.width(), img.height(), format,
+                          expected, actual);
+        return Err(GLError::Message(msg));
+    }
+    Ok(())
+}
+
+// Decode a compressed image whose format is decoded on the CPU
+fn _decode_image<I: ?Sized + CompressedImage2d>(img: &I)
+    -> Result<Pixels, GLError>
+{
+    img.format().texel_format()
+        .decode(img.width(), img.height(), img.compressed_bytes())
+        .map_err(|e| {
+            let msg = format!("Error decoding compressed image: {}", e);
+            GLError::Message(msg)
+        })
+}
+
+// Check that a `width`x`height` region at (`x`, `y`) fits within a mipmap
+// level of a texture, and return the level's size. Returns `None` if the
+// texture's image data wasn't specified through glitter, in which case
+// the level's size is unknown
+fn _check_sub_region<T>(texture: &Texture<T>,
+                        level: u32,
+                        x: u32,
+                        y: u32,
+                        width: u32,
+                        height: u32)
+    -> Result<Option<(u32, u32)>, GLError>
+    where T: TextureType
+{
+    let levels = texture.levels();
+    if levels == 0 {
+        return Ok(None);
+    }
+    if level >= levels {
+        let msg = format!("Error updating texture: mipmap level {} has not been specified (the texture has {} levels)",
+                          level, levels);
+        return Err(GLError::Message(msg));
+    }
+
+    let (level_width, level_height) = texture.level_size(level);
+    let fits_x = x.checked_add(width).map_or(false, |end| end <= level_width);
+    let fits_y = y.checked_add(height).map_or(false, |end| end <= level_height);
+    if !fits_x || !fits_y {
+        let msg = format!("Error updating texture: a {}x{} region at ({}, {}) does not fit within mipmap level {}, which is {}x{}",
+                          width, height, x, y,
+                          level, level_width, level_height);
+        return Err(GLError::Message(msg));
+    }
+
+    Ok(Some((level_width, level_height)))
+}
+
+// The number of mipmap levels in a complete mipmap chain for a texture
+// with the given base level size, including the base level
+fn _full_mip_levels(width: u32, height: u32) -> usize {
+    let mut size = cmp::max(width, height);
+    let mut levels = 1;
+    while size > 1 {
+        size /= 2;
+        levels += 1;
+    }
+    levels
+}
+
+// Check that a texture with non-power-of-two dimensions follows the
+// restrictions for NPOT textures in OpenGL ES 2.0, unless the context
+// supports NPOT textures fully. `what` is "texture" or "cubemap", and is
+// used for the error message
+fn _check_npot<C>(gl: &C,
+                  what: &str,
+                  width: u32,
+                  height: u32,
+                  wrap_s: TextureWrapMode,
+                  wrap_t: TextureWrapMode,
+                  min_filter: TextureMipmapFilter,
+                  has_mipmaps: bool)
+    -> Result<(), GLError>
+    where C: ContextExt
+{
+    use TextureMipmapFilter::MipmapFilter;
+
+    let is_npot = !width.is_power_of_two() || !height.is_power_of_two();
+    if !is_npot || gl.supports_npot_textures() {
+        return Ok(());
+    }
+
+    let uses_mipmap_filter = match min_filter {
+        MipmapFilter {..} => true,
+        _ => false
+    };
+    let rule = if wrap_s != CLAMP_TO_EDGE {
+        Some(format!("the s-coordinate wrap mode must be CLAMP_TO_EDGE, but is {:?}", wrap_s))
+    }
+    else if wrap_t != CLAMP_TO_EDGE {
+        Some(format!("the t-coordinate wrap mode must be CLAMP_TO_EDGE, but is {:?}", wrap_t))
+    }
+    else if uses_mipmap_filter {
+        Some(format!("the min filter must not use mipmaps, but is {:?}", min_filter))
+    }
+    else if has_mipmaps {
+        Some(format!("the {} must not have mipmaps", what))
+    }
+    else {
+        None
+    };
+
+    match rule {
+        Some(rule) => {
+            let msg = format!("Error building {}: {} is {}x{}, which is not a power of two, and GL_OES_texture_npot is not supported, so {}",
+                              what, what, width, height, rule);
+            Err(GLError::Message(msg))
+        },
+        None => { Ok(()) }
+    }
+}
+
+// Check that the context supports images with the given format, and
+// that they can be sampled with the given filters. Float and half-float
+// textures each need one extension to be used at all, and another to
+// use linear filtering
+fn _check_texel_type<C>(gl: &C,
+                        format: ImageFormat,
+                        min_filter: TextureMipmapFilter,
+                        mag_filter: TextureFilter)
+    -> Result<(), GLError>
+    where C: ContextExt
+{
+    let (supported, linear_supported, extension) = match format.texel_type {
+        TexelType::Float => {
+            (gl.supports_float_textures(),
+             gl.supports_float_linear_filtering(),
+             "GL_OES_texture_float")
+        },
+        TexelType::HalfFloatOes => {
+            (gl.supports_half_float_textures(),
+             gl.supports_half_float_linear_filtering(),
+             "GL_OES_texture_half_float")
+        },
+        _ => {
+            return Ok(());
+        }
+    };
+
+    if !supported {
+        let msg = format!("Error building texture: the image has texel type {:?}, but {} is not supported",
+                          format.texel_type, extension);
+        return Err(GLError::Message(msg));
+    }
+
+    let uses_linear = mag_filter == TextureFilter::Linear || match min_filter {
+        TextureMipmapFilter::Filter(filter) => filter == TextureFilter::Linear,
+        TextureMipmapFilter::MipmapFilter { criterion, mipmap } => {
+            criterion == TextureFilter::Linear || mipmap == TextureFilter::Linear
+        }
+    };
+    if uses_linear && !linear_supported {
+        let msg = format!("Error building texture: the image has texel type {:?}, which can't be filtered linearly because {}_linear is not supported, but the min filter is {:?} and the mag filter is {:?}",
+                          format.texel_type, extension, min_filter, mag_filter);
+        return Err(GLError::Message(msg));
+    }
+
+    Ok(())
+}
+
+// The size and format of an image that is used as a mipmap level, so
+// that mipmap chains of uncompressed and compressed images can be
+// checked the same way
+trait _MipLevel {
+    type Format: PartialEq + fmt::Debug;
+
+    fn _size(&self) -> (usize, usize);
+
+    fn _format(&self) -> Self::Format;
+}
+
+impl<'a> _MipLevel for &'a Image2d {
+    type Format = ImageFormat;
+
+    fn _size(&self) -> (usize, usize) {
+        (self.width(), self.height())
+    }
+
+    fn _format(&self) -> ImageFormat {
+        self.format()
+    }
+}
+
+impl<'a> _MipLevel for &'a CompressedImage2d {
+    type Format = CompressedTexelFormat;
+
+    fn _size(&self) -> (usize, usize) {
+        (self.width(), self.height())
+    }
+
+    fn _format(&self) -> CompressedTexelFormat {
+        self.format().texel_format()
+    }
+}
+
+// Check that each image in `mipmaps` has the right size and format to
+// be the next mipmap level after `base`
+fn _check_mip_chain<M: _MipLevel>(base: &M, mipmaps: &[M])
+    -> Result<(), GLError>
+{
+    let (mut width, mut height) = base._size();
+    for (index, mipmap) in mipmaps.iter().enumerate() {
+        let level = index + 1;
+        if width == 1 && height == 1 {
+            let msg = format!("Error building texture: mipmap level {} was provided, but level {} is already 1x1",
+                              level, level - 1);
+            return Err(GLError::Message(msg));
+        }
+
+        width = cmp::max(width / 2, 1);
+        height = cmp::max(height / 2, 1);
+        let (mip_width, mip_height) = mipmap._size();
+        if (mip_width, mip_height) != (width, height) {
+            let msg = format!("Error building texture: mipmap level {} should be {}x{}, but is {}x{}",
+                              level, width, height, mip_width, mip_height);
+            return Err(GLError::Message(msg));
+        }
+        if mipmap._format() != base._format() {
+            let msg = format!("Error building texture: mipmap level {} has format {:?}, but level 0 has format {:?}",
+                              level, mipmap._format(), base._format());
+            return Err(GLError::Message(msg));
+        }
+    }
+
+    Ok(())
+}
+
+// Check that all six faces of a cubemap were provided, that they are
+// square and have the same size and format, and that each face has a
+// valid mipmap chain with the same number of levels. Returns the width
+// and height of the faces
+fn _check_cube_faces<M>(faces: &[Option<M>; 6], mipmaps: &[Vec<M>; 6])
+    -> Result<u32, GLError>
+    where M: _MipLevel + Copy
+{
+    let first = match faces[0] {
+        Some(image) => image,
+        None => {
+            let msg = "Error building cubemap: no image was provided for face CubeMapPositiveX";
+            return Err(GLError::Message(msg.to_owned()));
+        }
+    };
+    let (size, _) = first._size();
+
+    for target in TxCubeMapImageTarget::faces().iter() {
+        let image = match faces[target.face_index()] {
+            Some(image) => image,
+            None => {
+                let msg = format!("Error building cubemap: no image was provided for face {:?}", target);
+                return Err(GLError::Message(msg));
+            }
+        };
+
+        let (width, height) = image._size();
+        if width != height {
+            let msg = format!("Error building cubemap: face {:?} is {}x{}, but cubemap faces must be square",
+                              target, width, height);
+            return Err(GLError::Message(msg));
+        }
+        if width != size {
+            let msg = format!("Error building cubemap: face {:?} is {}x{}, but face CubeMapPositiveX is {}x{}",
+                              target, width, height, size, size);
+            return Err(GLError::Message(msg));
+        }
+        if image._format() != first._format() {
+            let msg = format!("Error building cubemap: face {:?} has a different image format than face CubeMapPositiveX",
+                              target);
+            return Err(GLError::Message(msg));
+        }
+    }
+
+    if size == 0 {
+        let msg = "Error building cubemap: texture must have positive dimensions";
+        return Err(GLError::Message(msg.to_owned()));
+    }
+
+    let levels = mipmaps[0].len();
+    for target in TxCubeMapImageTarget::faces().iter() {
+        let face_mipmaps = &mipmaps[target.face_index()];
+        if face_mipmaps.len() != levels {
+            let msg = format!("Error building cubemap: face {:?} has {} mipmap levels, but face CubeMapPositiveX has {}",
+                              target, face_mipmaps.len(), levels);
+            return Err(GLError::Message(msg));
+        }
+        if let Some(ref image) = faces[target.face_index()] {
+            try!(_check_mip_chain(image, face_mipmaps));
+        }
+    }
+
+    Ok(size as u32)
+}
+
+// Returns the `GL_UNPACK_ALIGNMENT` to use to upload the image's rows,
+// along with the bytes to upload. If the image's row stride can't be
+// described with an alignment, the rows are repacked without padding.
+fn _unpack_rows<I: ?Sized + Image2d>(img: &I) -> (usize, Cow<[u8]>) {
+    let row = img.width() * img.format().texel_size();
+    let stride = if img.height() > 1 { img.row_stride() } else { row };
+
+    if let Some(alignment) = _unpack_alignment(row, stride) {
+        return (alignment, Cow::Borrowed(img.texel_bytes()));
+    }
+
+    let src = img.texel_bytes();
+    let mut bytes = Vec::with_capacity(row * img.height());
+    for y in 0..img.height() {
+        let start = y * stride;
+        bytes.extend_from_slice(&src[start..(start + row)]);
+    }
+
+    let alignment = _unpack_alignment(row, row).unwrap_or(1);
+    (alignment, Cow::Owned(bytes))
+}
+
+// Returns the largest alignment that makes OpenGL read rows of `row`
+// bytes that start `stride` bytes apart.
+fn _unpack_alignment(row: usize, stride: usize) -> Option<usize> {
+    for &alignment in [8, 4, 2, 1].iter() {
+        let padded = (row + alignment - 1) / alignment * alignment;
+        if padded == stride {
+            return Some(alignment);
+        }
+    }
+    None
+}
+
+unsafe fn _pixel_store_unpack_alignment(alignment: usize) {
+    gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment as GLint);
+    dbg_gl_sanity_check! {
+        GLError::InvalidEnum => "`pname` is not an accepted value",
+        GLError::InvalidValue => "`param` is not 1, 2, 4, or 8",
+        _ => "Unknown error"
+    }
+}
+
+unsafe fn _get_integer(pname: GLenum) -> GLint {
+    let mut value: GLint = 0;
+    gl::GetIntegerv(pname, &mut value as *mut GLint);
+    dbg_gl_sanity_check! {
+        GLError::InvalidEnum => "`pname` is not an accepted value",
+        _ => "Unknown error"
+    }
+    value
+}
+
 unsafe fn _tex_parameter_iv(target: TextureBindingTarget,
                             pname: GLenum,
                             params: *const GLint)
@@ -381,23 +1763,68 @@ unsafe fn _tex_parameter_iv(target: TextureBindingTarget,
     }
 }
 
+// Get the OpenGL enum to upload texels with the given type. Desktop OpenGL
+// uses `GL_HALF_FLOAT` for half-float texels, which has the same layout as
+// the `GL_HALF_FLOAT_OES` value that OpenGL ES uses
+fn _gl_texel_type<C: ?Sized + ContextExt>(gl: &C, texel_type: TexelType) -> GLenum {
+    match texel_type {
+        TexelType::HalfFloatOes => {
+            match _gl_es_major_version(gl.get_string(StringName::Version)) {
+                Some(_) => { texel_type.gl_enum() },
+                None => { GL_HALF_FLOAT }
+            }
+        },
+        _ => { texel_type.gl_enum() }
+    }
+}
+
+// Get the internal format to allocate a texture's storage with. OpenGL ES
+// 2.0 derives the storage from the unsized format and the texel type, but
+// desktop OpenGL allocates normalized fixed-point storage for an unsized
+// format, so floating-point textures need a sized internal format there
+fn _gl_internal_format<C: ?Sized + ContextExt>(gl: &C, format: ImageFormat) -> GLenum {
+    let texel_format = format.texel_format;
+    let is_half_float = match format.texel_type {
+        TexelType::Float => { false },
+        TexelType::HalfFloatOes => { true },
+        _ => { return texel_format.gl_enum(); }
+    };
+
+    if _gl_es_major_version(gl.get_string(StringName::Version)).is_some() {
+        return texel_format.gl_enum();
+    }
+
+    match (texel_format, is_half_float) {
+        (TexelFormat::RGBA, false) => { GL_RGBA32F },
+        (TexelFormat::RGBA, true) => { GL_RGBA16F },
+        (TexelFormat::RGB, false) => { GL_RGB32F },
+        (TexelFormat::RGB, true) => { GL_RGB16F },
+        (TexelFormat::Alpha, false) => { GL_ALPHA32F_ARB },
+        (TexelFormat::Alpha, true) => { GL_ALPHA16F_ARB },
+        (TexelFormat::Luminance, false) => { GL_LUMINANCE32F_ARB },
+        (TexelFormat::Luminance, true) => { GL_LUMINANCE16F_ARB },
+        (TexelFormat::LuminanceAlpha, false) => { GL_LUMINANCE_ALPHA32F_ARB },
+        (TexelFormat::LuminanceAlpha, true) => { GL_LUMINANCE_ALPHA16F_ARB }
+    }
+}
+
 unsafe fn _tex_image_2d<T: ImageTargetType>(target: T,
                                             level: u32,
-                                            internal_format: TexelFormat,
+                                            internal_format: GLenum,
                                             width: u32,
                                             height: u32,
                                             border: u32,
                                             format: ImageFormat,
+                                            gl_type: GLenum,
                                             image_ptr: *const u8) {
-    debug_assert!(internal_format == format.texel_format);
     gl::TexImage2D(target.gl_enum(),
                    level as GLint,
-                   internal_format.gl_enum() as GLint,
+                   internal_format as GLint,
                    width as GLint,
                    height as GLint,
                    border as GLint,
                    format.texel_format.gl_enum(),
-                   format.texel_type.gl_enum(),
+                   gl_type,
                    image_ptr as *const GLvoid);
     dbg_gl_sanity_check! {
         GLError::InvalidEnum => "`target`, `format`, or `type` is not an accepted value",
@@ -407,6 +1834,79 @@ unsafe fn _tex_image_2d<T: ImageTargetType>(target: T,
     }
 }
 
+unsafe fn _tex_sub_image_2d<T: ImageTargetType>(target: T,
+                                                level: u32,
+                                                x: u32,
+                                                y: u32,
+                                                width: u32,
+                                                height: u32,
+                                                format: ImageFormat,
+                                                gl_type: GLenum,
+                                                image_ptr: *const u8) {
+    gl::TexSubImage2D(target.gl_enum(),
+                      level as GLint,
+                      x as GLint,
+                      y as GLint,
+                      width as GLsizei,
+                      height as GLsizei,
+                      format.texel_format.gl_enum(),
+                      gl_type,
+                      image_ptr as *const GLvoid);
+    dbg_gl_error! {
+        GLError::InvalidEnum => "`target`, `format`, or `type` is not an accepted value",
+        GLError::InvalidValue => "`level` is invalid, or the region extends outside of the texture's image",
+        GLError::InvalidOperation => "The texture's image was not defined, or `format` and `type` do not match the texture's image",
+        _ => "Unknown error"
+    }
+}
+
+unsafe fn _compressed_tex_image_2d<T: ImageTargetType>(target: T,
+                                                       level: u32,
+                                                       format: CompressedFormat,
+                                                       width: u32,
+                                                       height: u32,
+                                                       data: &[u8]) {
+    gl::CompressedTexImage2D(target.gl_enum(),
+                             level as GLint,
+                             format.texel_format().gl_enum(),
+                             width as GLsizei,
+                             height as GLsizei,
+                             0,
+                             data.len() as GLsizei,
+                             data.as_ptr() as *const GLvoid);
+    dbg_gl_error! {
+        GLError::InvalidEnum => "`target` or `internalformat` is not an accepted value",
+        GLError::InvalidValue => "`level`, `width`, or `height` is invalid, or `imageSize` does not match the format and size of the image",
+        GLError::InvalidOperation => "The image's parameters are not supported by the compressed format",
+        _ => "Unknown error"
+    }
+}
+
+unsafe fn _compressed_tex_sub_image_2d<T: ImageTargetType>(target: T,
+                                                           level: u32,
+                                                           x: u32,
+                                                           y: u32,
+                                                           width: u32,
+                                                           height: u32,
+                                                           format: CompressedFormat,
+                                                           data: &[u8]) {
+    gl::CompressedTexSubImage2D(target.gl_enum(),
+                                level as GLint,
+                                x as GLint,
+                                y as GLint,
+                                width as GLsizei,
+                                height as GLsizei,
+                                format.texel_format().gl_enum(),
+                                data.len() as GLsizei,
+                                data.as_ptr() as *const GLvoid);
+    dbg_gl_error! {
+        GLError::InvalidEnum => "`target` or `format` is not an accepted value",
+        GLError::InvalidValue => "`level` is invalid, the region extends outside of the texture's image, or `imageSize` does not match the format and size of the region",
+        GLError::InvalidOperation => "`format` does not match the texture's image, or the region is not supported by the compressed format",
+        _ => "Unknown error"
+    }
+}
+
 /// Represents a texture that has been bound to a texture unit.
 pub trait TextureBinding {
     /// The type of texture that this binding represents.
@@ -414,12 +1914,18 @@ pub trait TextureBinding {
 
     /// The OpenGL texture target of this binding.
     fn target(&self) -> TextureBindingTarget;
+
+    /// The texture that is bound.
+    fn texture(&self) -> &Texture<Self::TextureType>;
+
+    #[doc(hidden)]
+    fn _texture_mut(&mut self) -> &mut Texture<Self::TextureType>;
 }
 
 /// Represents a texture that has been bound to the `GL_TEXTURE_2D` binding
 /// target of a texture unit.
 pub struct Texture2dBinding<'a> {
-    _phantom_ref: PhantomData<&'a mut Texture2d>,
+    texture: &'a mut Texture2d,
     _phantom_ptr: PhantomData<*mut ()>
 }
 
@@ -429,12 +1935,20 @@ impl<'a> TextureBinding for Texture2dBinding<'a> {
     fn target(&self) -> TextureBindingTarget {
         Tx2d::target()
     }
+
+    fn texture(&self) -> &Texture2d {
+        &*self.texture
+    }
+
+    fn _texture_mut(&mut self) -> &mut Texture2d {
+        &mut *self.texture
+    }
 }
 
 /// Represents a texture that has been bound to the `GL_TEXTURE_CUBE_MAP`
 /// binding target of a texture unit.
 pub struct TextureCubeMapBinding<'a> {
-    _phantom_ref: PhantomData<&'a mut TextureCubeMap>,
+    texture: &'a mut TextureCubeMap,
     _phantom_ptr: PhantomData<*mut ()>
 }
 
@@ -444,6 +1958,14 @@ impl<'a> TextureBinding for TextureCubeMapBinding<'a> {
     fn target(&self) -> TextureBindingTarget {
         TxCubeMap::target()
     }
+
+    fn texture(&self) -> &TextureCubeMap {
+        &*self.texture
+    }
+
+    fn _texture_mut(&mut self) -> &mut TextureCubeMap {
+        &mut *self.texture
+    }
 }
 
 
@@ -478,14 +2000,14 @@ impl Texture2dBinder {
 
     /// Bind a texture to the `GL_TEXTURE_2D` target,
     /// returning a binding.
-    pub fn bind<'a>(&mut self, texture: &mut Texture2d)
+    pub fn bind<'a>(&mut self, texture: &'a mut Texture2d)
         -> Texture2dBinding<'a>
     {
         unsafe {
             _bind_texture(texture);
         }
         Texture2dBinding {
-            _phantom_ref: PhantomData,
+            texture: texture,
             _phantom_ptr: PhantomData
         }
     }
@@ -519,7 +2041,7 @@ impl TextureCubeMapBinder {
             _bind_texture(texture);
         }
         TextureCubeMapBinding {
-            _phantom_ref: PhantomData,
+            texture: texture,
             _phantom_ptr: PhantomData
         }
     }
@@ -0,0 +1,370 @@
+//! Contains helpers for building the six faces of a cubemap from a single
+//! 2D image on the CPU.
+
+use std::f32::consts::PI;
+use std::fmt;
+use std::error;
+use image_data::{Image2d, Pixel, Pixels, ImageFormat};
+use pixel_formats::{_can_decode_texels, _decode_texel};
+use texture::TxCubeMapImageTarget;
+
+/// The ways that the six faces of a cubemap can be laid out within a
+/// single 2D image.
+///
+/// In every layout, each face is expected to be oriented the way that
+/// OpenGL samples it: the first row of a face's image data is the row
+/// with the lowest `t` texture coordinate for that face.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubeMapLayout {
+    /// A 4x3 grid of faces, laid out as a horizontal cross:
+    ///
+    /// ```text
+    ///       +Y
+    ///   -X  +Z  +X  -Z
+    ///       -Y
+    /// ```
+    HorizontalCross,
+
+    /// A 3x4 grid of faces, laid out as a vertical cross. The -Z face
+    /// is stored rotated by 180 degrees:
+    ///
+    /// ```text
+    ///       +Y
+    ///   -X  +Z  +X
+    ///       -Y
+    ///       -Z
+    /// ```
+    VerticalCross,
+
+    /// A 6x1 row of faces, in the order +X, -X, +Y, -Y, +Z, -Z.
+    HorizontalStrip,
+
+    /// A 1x6 column of faces, in the order +X, -X, +Y, -Y, +Z, -Z.
+    VerticalStrip,
+
+    /// An equirectangular (latitude/longitude) panorama with a 2:1
+    /// aspect ratio. The center of the image faces +Z, the top row
+    /// faces +Y, and +X is to the right of the center. Each face will
+    /// be a quarter of the image's width; use
+    /// [`CubeMapFaces::from_equirectangular`]
+    /// (struct.CubeMapFaces.html#method.from_equirectangular)
+    /// to pick a different face size.
+    Equirectangular
+}
+
+/// An error that occurred while splitting an image into cubemap faces.
+#[derive(Debug)]
+pub enum CubeMapLayoutError {
+    /// The image's dimensions don't match the requested layout.
+    InvalidDimensions {
+        /// The layout that was requested.
+        layout: CubeMapLayout,
+
+        /// The width of the image.
+        width: usize,
+
+        /// The height of the image.
+        height: usize
+    },
+
+    /// The image's texel data can't be read on the CPU.
+    UnsupportedFormat(ImageFormat)
+}
+
+impl fmt::Display for CubeMapLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CubeMapLayoutError::InvalidDimensions { layout, width, height } => {
+                write!(f, "a {}x{} image can't be split as a {:?} cubemap",
+                       width, height, layout)
+            },
+            CubeMapLayoutError::UnsupportedFormat(format) => {
+                write!(f, "can't read texels with format {:?}", format)
+            }
+        }
+    }
+}
+
+impl error::Error for CubeMapLayoutError {
+    fn description(&self) -> &str {
+        match *self {
+            CubeMapLayoutError::InvalidDimensions { .. } => {
+                "image dimensions don't match the cubemap layout"
+            },
+            CubeMapLayoutError::UnsupportedFormat(_) => {
+                "unsupported image format"
+            }
+        }
+    }
+}
+
+/// The six faces of a cubemap, stored as [`Pixels`](struct.Pixels.html).
+/// The faces can be uploaded using [`TextureCubeMapBuilder::faces`]
+/// (context/texture_context/struct.TextureCubeMapBuilder.html#method.faces).
+///
+/// # Example
+///
+/// ```no_run
+/// # #![allow(unused_variables)]
+/// # extern crate glitter;
+/// # use glitter::prelude::*;
+/// # use glitter::{CubeMapFaces, CubeMapLayout, Pixels};
+/// # fn main() {
+/// # let mut gl = unsafe { glitter::Context::current_context() };
+/// # let skybox = Pixels::new(512, 384);
+/// let faces = CubeMapFaces::from_layout(&skybox,
+///                                       CubeMapLayout::HorizontalCross)
+///     .unwrap();
+/// let texture = gl.build_texture_cube_map()
+///     .faces(faces.images())
+///     .generate_mipmap()
+///     .unwrap();
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct CubeMapFaces {
+    faces: Vec<Pixels>
+}
+
+impl CubeMapFaces {
+    /// Split or resample an image with the given layout into six faces.
+    ///
+    /// # Failures
+    /// An error will be returned if the image's dimensions don't evenly
+    /// fit the layout (for example, a horizontal cross must be made of
+    /// square faces, with a 4:3 aspect ratio overall), or if the
+    /// image's format can't be read on the CPU.
+    pub fn from_layout(image: &Image2d, layout: CubeMapLayout)
+        -> Result<Self, CubeMapLayoutError>
+    {
+        let (width, height) = (image.width(), image.height());
+        let invalid = CubeMapLayoutError::InvalidDimensions {
+            layout: layout,
+            width: width,
+            height: height
+        };
+
+        let (columns, rows) = match layout {
+            CubeMapLayout::HorizontalCross => (4, 3),
+            CubeMapLayout::VerticalCross => (3, 4),
+            CubeMapLayout::HorizontalStrip => (6, 1),
+            CubeMapLayout::VerticalStrip => (1, 6),
+            CubeMapLayout::Equirectangular => {
+                if width == 0 || width != height * 2 || width % 4 != 0 {
+                    return Err(invalid);
+                }
+                return CubeMapFaces::from_equirectangular(image, width / 4);
+            }
+        };
+
+        let size = width / columns;
+        if size == 0 || width % columns != 0 || height != size * rows {
+            return Err(invalid);
+        }
+
+        let reader = try!(TexelReader::new(image));
+
+        // The (column, row) of each face within the layout, in the
+        // same order as `TxCubeMapImageTarget::faces()`, along with
+        // whether the face is rotated by 180 degrees
+        let cells = match layout {
+            CubeMapLayout::HorizontalCross => [
+                (2, 1, false), (0, 1, false), (1, 0, false),
+                (1, 2, false), (1, 1, false), (3, 1, false)
+            ],
+            CubeMapLayout::VerticalCross => [
+                (2, 1, false), (0, 1, false), (1, 0, false),
+                (1, 2, false), (1, 1, false), (1, 3, true)
+            ],
+            CubeMapLayout::HorizontalStrip => [
+                (0, 0, false), (1, 0, false), (2, 0, false),
+                (3, 0, false), (4, 0, false), (5, 0, false)
+            ],
+            CubeMapLayout::VerticalStrip => [
+                (0, 0, false), (0, 1, false), (0, 2, false),
+                (0, 3, false), (0, 4, false), (0, 5, false)
+            ],
+            CubeMapLayout::Equirectangular => unreachable!()
+        };
+
+        let faces = cells.iter().map(|&(column, row, rotated)| {
+            let mut face = Pixels::new(size, size);
+            for y in 0..size {
+                for x in 0..size {
+                    let (fx, fy) = if rotated {
+                        (size - 1 - x, size - 1 - y)
+                    }
+                    else {
+                        (x, y)
+                    };
+                    face[y][x] = reader.read(column * size + fx,
+                                             row * size + fy);
+                }
+            }
+            face
+        }).collect();
+
+        Ok(CubeMapFaces { faces: faces })
+    }
+
+    /// Resample an equirectangular panorama into six faces, each
+    /// `size` by `size` pixels. See [`CubeMapLayout::Equirectangular`]
+    /// (enum.CubeMapLayout.html#variant.Equirectangular) for details
+    /// about how the panorama is oriented. Texels are sampled with
+    /// bilinear filtering.
+    ///
+    /// # Failures
+    /// An error will be returned if the image or `size` is empty, or if
+    /// the image's format can't be read on the CPU.
+    pub fn from_equirectangular(image: &Image2d, size: usize)
+        -> Result<Self, CubeMapLayoutError>
+    {
+        let (width, height) = (image.width(), image.height());
+        if width == 0 || height == 0 || size == 0 {
+            return Err(CubeMapLayoutError::InvalidDimensions {
+                layout: CubeMapLayout::Equirectangular,
+                width: width,
+                height: height
+            });
+        }
+
+        let reader = try!(TexelReader::new(image));
+
+        let faces = TxCubeMapImageTarget::faces().iter().map(|target| {
+            let mut face = Pixels::new(size, size);
+            for y in 0..size {
+                for x in 0..size {
+                    let s = 2.0 * (x as f32 + 0.5) / size as f32 - 1.0;
+                    let t = 2.0 * (y as f32 + 0.5) / size as f32 - 1.0;
+                    let (dx, dy, dz) = _face_direction(*target, s, t);
+                    let len = (dx*dx + dy*dy + dz*dz).sqrt();
+
+                    let longitude = dx.atan2(dz);
+                    let latitude = (dy / len).asin();
+                    let u = 0.5 + longitude / (2.0 * PI);
+                    let v = 0.5 - latitude / PI;
+
+                    face[y][x] = reader.sample(u * width as f32,
+                                               v * height as f32);
+                }
+            }
+            face
+        }).collect();
+
+        Ok(CubeMapFaces { faces: faces })
+    }
+
+    /// Get the image data for a single face.
+    pub fn face(&self, target: TxCubeMapImageTarget) -> &Pixels {
+        &self.faces[target.face_index()]
+    }
+
+    /// Get the image data for a single face mutably.
+    pub fn face_mut(&mut self, target: TxCubeMapImageTarget) -> &mut Pixels {
+        &mut self.faces[target.face_index()]
+    }
+
+    /// Get the size of each face, in pixels.
+    pub fn size(&self) -> usize {
+        self.faces[0].width()
+    }
+
+    /// Get all six faces as images, in the order returned by
+    /// [`TxCubeMapImageTarget::faces`]
+    /// (enum.TxCubeMapImageTarget.html#method.faces).
+    pub fn images(&self) -> [&Image2d; 6] {
+        [&self.faces[0], &self.faces[1], &self.faces[2],
+         &self.faces[3], &self.faces[4], &self.faces[5]]
+    }
+
+    /// Consume the faces, returning each face paired with its target.
+    pub fn into_faces(self) -> Vec<(TxCubeMapImageTarget, Pixels)> {
+        TxCubeMapImageTarget::faces().iter().cloned()
+            .zip(self.faces.into_iter())
+            .collect()
+    }
+}
+
+// Returns the (unnormalized) direction that OpenGL samples for the
+// given face and face coordinates, where `s` and `t` are in [-1, 1]
+// (see table 3.21 of the OpenGL ES 2.0 spec).
+fn _face_direction(target: TxCubeMapImageTarget, s: f32, t: f32)
+    -> (f32, f32, f32)
+{
+    match target {
+        TxCubeMapImageTarget::CubeMapPositiveX => (1.0, -t, -s),
+        TxCubeMapImageTarget::CubeMapNegativeX => (-1.0, -t, s),
+        TxCubeMapImageTarget::CubeMapPositiveY => (s, 1.0, t),
+        TxCubeMapImageTarget::CubeMapNegativeY => (s, -1.0, -t),
+        TxCubeMapImageTarget::CubeMapPositiveZ => (s, -t, 1.0),
+        TxCubeMapImageTarget::CubeMapNegativeZ => (-s, -t, -1.0)
+    }
+}
+
+// Reads individual texels from an `Image2d` as `Pixel`s.
+struct TexelReader<'a> {
+    bytes: &'a [u8],
+    format: ImageFormat,
+    width: usize,
+    height: usize,
+    stride: usize,
+    texel_size: usize
+}
+
+impl<'a> TexelReader<'a> {
+    fn new(image: &'a Image2d) -> Result<Self, CubeMapLayoutError> {
+        let format = image.format();
+        if !_can_decode_texels(format) {
+            return Err(CubeMapLayoutError::UnsupportedFormat(format));
+        }
+
+        Ok(TexelReader {
+            bytes: image.texel_bytes(),
+            format: format,
+            width: image.width(),
+            height: image.height(),
+            stride: image.row_stride(),
+            texel_size: format.texel_size()
+        })
+    }
+
+    fn read(&self, x: usize, y: usize) -> Pixel {
+        let offset = y * self.stride + x * self.texel_size;
+        _decode_texel(self.format, &self.bytes[offset..offset + self.texel_size])
+    }
+
+    // Bilinearly sample the image at the given texel-space coordinates,
+    // wrapping horizontally and clamping vertically.
+    fn sample(&self, x: f32, y: f32) -> Pixel {
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+
+        let width = self.width as isize;
+        let max_y = self.height as isize - 1;
+        let wrap = |x: isize| (((x % width) + width) % width) as usize;
+        let clamp = |y: isize| {
+            if y < 0 { 0 } else if y > max_y { max_y as usize } else { y as usize }
+        };
+
+        let (x0, y0) = (x0 as isize, y0 as isize);
+        let (left, right) = (wrap(x0), wrap(x0 + 1));
+        let (top, bottom) = (clamp(y0), clamp(y0 + 1));
+
+        let p00 = self.read(left, top);
+        let p10 = self.read(right, top);
+        let p01 = self.read(left, bottom);
+        let p11 = self.read(right, bottom);
+
+        let lerp = |a: u8, b: u8, c: u8, d: u8| {
+            let top = a as f32 + (b as f32 - a as f32) * fx;
+            let bottom = c as f32 + (d as f32 - c as f32) * fx;
+            (top + (bottom - top) * fy).round() as u8
+        };
+
+        Pixel::r_g_b_a(lerp(p00.r, p10.r, p01.r, p11.r),
+                       lerp(p00.g, p10.g, p01.g, p11.g),
+                       lerp(p00.b, p10.b, p01.b, p11.b),
+                       lerp(p00.a, p10.a, p01.a, p11.a))
+    }
+}
@@ -0,0 +1,807 @@
+//! Helpers for validating shaders from a Cargo build script.
+
+use std::io;
+use std::fmt;
+use std::env;
+use std::error;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use shader::ShaderType;
+use super::validate::{validate_es100, GlslError, ShaderInterface};
+
+/// An error that occurred while validating a shader source file.
+#[derive(Debug)]
+pub enum ShaderBuildError {
+    /// The shader source file could not be read, or the generated
+    /// module could not be written.
+    Io(PathBuf, io::Error),
+
+    /// The shader failed validation.
+    Invalid(PathBuf, Vec<GlslError>)
+}
+
+impl fmt::Display for ShaderBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ShaderBuildError::Io(ref path, ref e) => {
+                write!(f, "Error accessing {}: {}", path.display(), e)
+            },
+            ShaderBuildError::Invalid(ref path, ref errors) => {
+                try!(write!(f, "Invalid shader {}:", path.display()));
+                for e in errors {
+                    try!(write!(f, "\n{}:{}:{}: {}",
+                                path.display(), e.line, e.column, e.message));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl error::Error for ShaderBuildError {
+    fn description(&self) -> &str {
+        match *self {
+            ShaderBuildError::Io(_, ref e) => { error::Error::description(e) },
+            ShaderBuildError::Invalid(..) => { "invalid shader" }
+        }
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            ShaderBuildError::Io(_, ref e) => { Some(e) },
+            ShaderBuildError::Invalid(..) => { None }
+        }
+    }
+}
+
+fn _resolve(path: &Path) -> PathBuf {
+    match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(ref dir) if path.is_relative() => { Path::new(dir).join(path) },
+        _ => { path.to_owned() }
+    }
+}
+
+fn _read(path: &Path) -> Result<String, ShaderBuildError> {
+    let mut source = String::new();
+    match fs::File::open(path).and_then(|mut f| f.read_to_string(&mut source)) {
+        Ok(_) => { Ok(source) },
+        Err(e) => { Err(ShaderBuildError::Io(path.to_owned(), e)) }
+    }
+}
+
+/// Read and validate a GLSL ES 1.00 shader source file with
+/// [`validate_es100`](fn.validate_es100.html). Relative paths are
+/// resolved from the crate's root directory (`CARGO_MANIFEST_DIR`).
+///
+/// This function also tells Cargo to re-run the build script whenever
+/// the file changes, so it should only be called from a build script.
+pub fn check_shader<P>(path: P, ty: ShaderType)
+    -> Result<ShaderInterface, ShaderBuildError>
+    where P: AsRef<Path>
+{
+    let path = _resolve(path.as_ref());
+    println!("cargo:rerun-if-changed={}", path.display());
+
+    let source = try!(_read(&path));
+    validate_es100(&source, ty).map_err(|errors| {
+        ShaderBuildError::Invalid(path.clone(), errors)
+    })
+}
+
+/// Convert a GLSL identifier (such as `a_position` or `uModelView`) into
+/// the name of a Rust constant (such as `A_POSITION` or `U_MODEL_VIEW`).
+///
+/// # Example
+/// ```
+/// use glitter::glsl::constant_name;
+///
+/// assert_eq!(constant_name("a_position"), "A_POSITION");
+/// assert_eq!(constant_name("uModelView"), "U_MODEL_VIEW");
+/// assert_eq!(constant_name("tex0"), "TEX0");
+/// ```
+pub fn constant_name(ident: &str) -> String {
+    let mut name = String::with_capacity(ident.len() + 4);
+    let mut prev_lower = false;
+    for c in ident.chars() {
+        if c.is_uppercase() && prev_lower {
+            name.push('_');
+        }
+        prev_lower = c.is_lowercase() || c.is_numeric();
+        name.extend(c.to_uppercase());
+    }
+    name
+}
+
+/// Validate a GLSL ES 1.00 shader source file (like [`check_shader`]
+/// (fn.check_shader.html)), and generate a Rust module for it named
+/// `module_name` in Cargo's `OUT_DIR`. The module can then be included
+/// in the crate with the [`include_shader!`](../macro.include_shader!.html)
+/// macro.
+///
+/// The generated module contains:
+///
+/// - `SOURCE`, the shader's source code.
+/// - `attributes`, `uniforms`, and `varyings` modules, each with a
+///   constant for the name of each variable (named with
+///   [`constant_name`](fn.constant_name.html)).
+///
+/// # Failures
+/// An error will be returned if the shader could not be read, failed
+/// validation, if two variables of the same kind would have the same
+/// constant name (such as `uColor` and `u_color`), or if the module could
+/// not be written. Panicking with the error will fail the build, and print
+/// each validation error along with its line number.
+///
+/// # Panics
+/// This function will panic if the `OUT_DIR` environment variable is not
+/// set (which means it was not called from a build script).
+///
+/// # Example
+/// In `build.rs`:
+///
+/// ```no_run
+/// extern crate glitter;
+///
+/// fn main() {
+///     glitter::glsl::generate_shader_module("shaders/main.vert",
+///                                           glitter::VERTEX_SHADER,
+///                                           "main_vert")
+///         .unwrap_or_else(|e| panic!("{}", e));
+/// }
+/// ```
+///
+/// In the crate:
+///
+/// ```ignore
+/// #[macro_use] extern crate glitter;
+///
+/// include_shader!(main_vert);
+///
+/// # fn main() {
+/// let shader = gl.build_vertex_shader(main_vert::SOURCE).unwrap();
+/// let position = gl.get_attrib_location(&program,
+///                                       main_vert::attributes::A_POSITION);
+/// # }
+/// ```
+pub fn generate_shader_module<P>(path: P, ty: ShaderType, module_name: &str)
+    -> Result<ShaderInterface, ShaderBuildError>
+    where P: AsRef<Path>
+{
+    let interface = try!(check_shader(&path, ty));
+    let path = _resolve(path.as_ref());
+
+    let module = match _shader_module(module_name, &path, &interface) {
+        Ok(module) => { module },
+        Err(errors) => { return Err(ShaderBuildError::Invalid(path, errors)); }
+    };
+    try!(_write_module(module_name, &module));
+    Ok(interface)
+}
+
+// Generate the source of the module for `generate_shader_module`. Returns
+// an error if two variables of the same kind would have the same
+// constant name.
+fn _shader_module(module_name: &str,
+                  path: &Path,
+                  interface: &ShaderInterface)
+    -> Result<String, Vec<GlslError>>
+{
+    let mut module = String::new();
+    module.push_str(&format!("pub mod {} {{\n", module_name));
+    module.push_str(&format!("    pub const SOURCE: &'static str = \
+                              include_str!({:?});\n",
+                             path.to_string_lossy()));
+
+    let mut errors = vec![];
+    let groups = [
+        ("attributes", &interface.attributes),
+        ("uniforms", &interface.uniforms),
+        ("varyings", &interface.varyings)
+    ];
+    for &(group, variables) in &groups {
+        module.push_str(&format!("\n    pub mod {} {{\n", group));
+        for (idx, variable) in variables.iter().enumerate() {
+            let name = constant_name(&variable.name);
+            let other = variables[..idx].iter().find(|other| {
+                constant_name(&other.name) == name
+            });
+            if let Some(other) = other {
+                errors.push(GlslError {
+                    line: variable.line,
+                    column: variable.column,
+                    message: format!("`{}` and `{}` (from line {}) would \
+                                      both be named `{}`",
+                                     variable.name, other.name, other.line,
+                                     name)
+                });
+                continue;
+            }
+
+            module.push_str(&format!("        pub const {}: &'static str = \
+                                      {:?};\n",
+                                     name, variable.name));
+        }
+        module.push_str("    }\n");
+    }
+    module.push_str("}\n");
+
+    if errors.is_empty() {
+        Ok(module)
+    }
+    else {
+        Err(errors)
+    }
+}
+
+fn _write_module(module_name: &str, module: &str)
+    -> Result<(), ShaderBuildError>
+{
+    let out_dir = env::var_os("OUT_DIR").expect("OUT_DIR is not set");
+    let out_path = Path::new(&out_dir).join(format!("{}.rs", module_name));
+    let written = fs::File::create(&out_path)
+        .and_then(|mut file| file.write_all(module.as_bytes()));
+    written.map_err(|e| ShaderBuildError::Io(out_path, e))
+}
+
+// The Rust type used for a vertex attribute with the given GLSL type.
+fn _vertex_type(ty: &str) -> Option<&'static str> {
+    match ty {
+        "float" => { Some("f32") },
+        "vec2" => { Some("[f32; 2]") },
+        "vec3" => { Some("[f32; 3]") },
+        "vec4" => { Some("[f32; 4]") },
+        _ => { None }
+    }
+}
+
+// The Rust type used to set a uniform with the given GLSL type.
+fn _uniform_type(ty: &str) -> Option<&'static str> {
+    match ty {
+        "float" => { Some("f32") },
+        "vec2" => { Some("[f32; 2]") },
+        "vec3" => { Some("[f32; 3]") },
+        "vec4" => { Some("[f32; 4]") },
+        "int" | "bool" | "sampler2D" | "samplerCube" => { Some("i32") },
+        "ivec2" | "bvec2" => { Some("[i32; 2]") },
+        "ivec3" | "bvec3" => { Some("[i32; 3]") },
+        "ivec4" | "bvec4" => { Some("[i32; 4]") },
+        "mat2" => { Some("[[f32; 2]; 2]") },
+        "mat3" => { Some("[[f32; 3]; 3]") },
+        "mat4" => { Some("[[f32; 4]; 4]") },
+        _ => { None }
+    }
+}
+
+/// Validate a pair of GLSL ES 1.00 vertex and fragment shader source
+/// files (like [`check_shader`](fn.check_shader.html)), and generate a
+/// Rust module for the program they make up, named `module_name` in
+/// Cargo's `OUT_DIR`. The module can then be included in the crate with
+/// the [`include_shader!`](../macro.include_shader!.html) macro.
+///
+/// The generated module contains:
+///
+/// - `VERTEX_SOURCE` and `FRAGMENT_SOURCE`, the shaders' source code.
+/// - `Vertex`, a `#[repr(C)]` struct with a field for each `attribute`
+///   of the vertex shader (using the attribute's name as the field name,
+///   followed by a `_` if the name is a Rust keyword, such as `type_`),
+///   which implements [`VertexData`](../vertex_data/trait.VertexData.html).
+///   `Vertex::attrib_binder(gl, &program)` returns an [`AttribBinder`]
+///   (../vertex_buffer/struct.AttribBinder.html) for the struct.
+/// - `Uniforms`, a struct with the location of each `uniform` of both
+///   shaders (with fields named the same way as `Vertex`).
+///   `Uniforms::new(gl, &program)` looks up each location, and
+///   each uniform has a typed setter (such as `set_u_color` for a `vec4`
+///   named `u_color`, which takes a `[f32; 4]`).
+///
+/// Uniforms that were optimized out of the program have no location, and
+/// setting them does nothing. Array uniforms have a location, but no
+/// setter, and uniforms with a `struct` type are skipped.
+///
+/// # Failures
+/// An error will be returned if either shader could not be read or failed
+/// validation, if an attribute's type can't be used in a vertex struct,
+/// if the two shaders declare the same uniform or varying with different
+/// types, if the fragment shader uses a varying that the vertex shader
+/// does not declare, if two attributes or uniforms would have the same
+/// field or setter name, or if the module could not be written.
+///
+/// # Panics
+/// This function will panic if the `OUT_DIR` environment variable is not
+/// set (which means it was not called from a build script).
+///
+/// # Example
+/// In `build.rs`:
+///
+/// ```no_run
+/// extern crate glitter;
+///
+/// fn main() {
+///     glitter::glsl::generate_program_module("shaders/main.vert",
+///                                            "shaders/main.frag",
+///                                            "main_program")
+///         .unwrap_or_else(|e| panic!("{}", e));
+/// }
+/// ```
+///
+/// In the crate:
+///
+/// ```ignore
+/// #[macro_use] extern crate glitter;
+///
+/// include_shader!(main_program);
+///
+/// # fn main() {
+/// let vertices = [
+///     main_program::Vertex { position: [0.0, 1.0], color: [1.0, 0.0, 0.0] },
+///     // ...
+/// ];
+/// vertex_buffer.bind_attrib_pointers(
+///     main_program::Vertex::attrib_binder(&gl, &program).unwrap()
+/// );
+///
+/// let uniforms = main_program::Uniforms::new(&gl, &program);
+/// uniforms.set_tint(&gl, &gl_program, [1.0, 1.0, 1.0, 0.5]);
+/// # }
+/// ```
+pub fn generate_program_module<V, F>(vertex_path: V,
+                                     fragment_path: F,
+                                     module_name: &str)
+    -> Result<(), ShaderBuildError>
+    where V: AsRef<Path>, F: AsRef<Path>
+{
+    let vertex = try!(check_shader(&vertex_path, ShaderType::VertexShader));
+    let fragment = try!(check_shader(&fragment_path,
+                                     ShaderType::FragmentShader));
+    let vertex_path = _resolve(vertex_path.as_ref());
+    let fragment_path = _resolve(fragment_path.as_ref());
+
+    let module = try!(_program_module(module_name,
+                                      &vertex_path,
+                                      &vertex,
+                                      &fragment_path,
+                                      &fragment));
+    _write_module(module_name, &module)
+}
+
+// Generate the source of the module for `generate_program_module`.
+fn _program_module(module_name: &str,
+                   vertex_path: &Path,
+                   vertex: &ShaderInterface,
+                   fragment_path: &Path,
+                   fragment: &ShaderInterface)
+    -> Result<String, ShaderBuildError>
+{
+    let mut vertex_errors = vec![];
+    for (idx, attrib) in vertex.attributes.iter().enumerate() {
+        if _vertex_type(&attrib.ty).is_none() {
+            vertex_errors.push(GlslError {
+                line: attrib.line,
+                column: attrib.column,
+                message: format!("attribute `{}` has type `{}`, which can't \
+                                  be used in a generated vertex struct",
+                                 attrib.name, attrib.ty)
+            });
+        }
+
+        let field = _field_name(&attrib.name);
+        let other = vertex.attributes[..idx].iter().find(|other| {
+            _field_name(&other.name) == field
+        });
+        if let Some(other) = other {
+            vertex_errors.push(GlslError {
+                line: attrib.line,
+                column: attrib.column,
+                message: format!("`{}` and `{}` (from line {}) would both \
+                                  be named `{}`",
+                                 attrib.name, other.name, other.line, field)
+            });
+        }
+    }
+    if !vertex_errors.is_empty() {
+        return Err(ShaderBuildError::Invalid(vertex_path.to_owned(),
+                                             vertex_errors));
+    }
+
+    let mut uniforms = vertex.uniforms.clone();
+    let mut fragment_errors = vec![];
+    let fragment_start = uniforms.len();
+    for uniform in &fragment.uniforms {
+        match uniforms.iter().find(|u| u.name == uniform.name) {
+            Some(other) => {
+                if other.ty != uniform.ty ||
+                   other.array_size != uniform.array_size
+                {
+                    fragment_errors.push(GlslError {
+                        line: uniform.line,
+                        column: uniform.column,
+                        message: format!("uniform `{}` has a different type \
+                                          than in the vertex shader",
+                                         uniform.name)
+                    });
+                }
+                continue;
+            },
+            None => { }
+        }
+        uniforms.push(uniform.clone());
+    }
+    for varying in &fragment.varyings {
+        match vertex.varyings.iter().find(|v| v.name == varying.name) {
+            Some(other) => {
+                if other.ty != varying.ty ||
+                   other.array_size != varying.array_size
+                {
+                    fragment_errors.push(GlslError {
+                        line: varying.line,
+                        column: varying.column,
+                        message: format!("varying `{}` has a different type \
+                                          than in the vertex shader",
+                                         varying.name)
+                    });
+                }
+            },
+            None => {
+                fragment_errors.push(GlslError {
+                    line: varying.line,
+                    column: varying.column,
+                    message: format!("varying `{}` is not declared in the \
+                                      vertex shader",
+                                     varying.name)
+                });
+            }
+        }
+    }
+
+    // Check that each uniform has a distinct field and setter name
+    for idx in 0..uniforms.len() {
+        let uniform = &uniforms[idx];
+        if _uniform_type(&uniform.ty).is_none() {
+            continue;
+        }
+        let field = _field_name(&uniform.name);
+        let setter = _snake_case(&uniform.name);
+        let other = uniforms[..idx].iter().find(|other| {
+            _uniform_type(&other.ty).is_some() &&
+            (_field_name(&other.name) == field ||
+             _snake_case(&other.name) == setter)
+        });
+        if let Some(other) = other {
+            let error = GlslError {
+                line: uniform.line,
+                column: uniform.column,
+                message: format!("uniforms `{}` and `{}` would both be \
+                                  named `{}`",
+                                 uniform.name, other.name,
+                                 if _field_name(&other.name) == field {
+                                     field
+                                 }
+                                 else {
+                                     format!("set_{}", setter)
+                                 })
+            };
+            if idx < fragment_start {
+                vertex_errors.push(error);
+            }
+            else {
+                fragment_errors.push(error);
+            }
+        }
+    }
+    if !vertex_errors.is_empty() {
+        return Err(ShaderBuildError::Invalid(vertex_path.to_owned(),
+                                             vertex_errors));
+    }
+    if !fragment_errors.is_empty() {
+        return Err(ShaderBuildError::Invalid(fragment_path.to_owned(),
+                                             fragment_errors));
+    }
+
+    let mut module = String::new();
+    module.push_str(&format!("pub mod {} {{\n", module_name));
+    module.push_str(&format!("    pub const VERTEX_SOURCE: &'static str = \
+                              include_str!({:?});\n",
+                             vertex_path.to_string_lossy()));
+    module.push_str(&format!("    pub const FRAGMENT_SOURCE: &'static str = \
+                              include_str!({:?});\n",
+                             fragment_path.to_string_lossy()));
+
+    if !vertex.attributes.is_empty() {
+        let fields: Vec<_> = vertex.attributes.iter()
+                                              .map(|a| _field_name(&a.name))
+                                              .collect();
+
+        module.push_str("\n    #[repr(C)]\n");
+        module.push_str("    #[allow(non_snake_case)]\n");
+        module.push_str("    #[derive(Debug, Clone, Copy, PartialEq)]\n");
+        module.push_str("    pub struct Vertex {\n");
+        for (attrib, field) in vertex.attributes.iter().zip(&fields) {
+            module.push_str(&format!("        pub {}: {},\n",
+                                     field,
+                                     _vertex_type(&attrib.ty).unwrap()));
+        }
+        module.push_str("    }\n\n");
+        module.push_str(&format!("    impl_vertex_data!(Vertex, {});\n\n",
+                                 fields.join(", ")));
+
+        module.push_str("    impl Vertex {\n");
+        module.push_str("        pub fn attrib_binder<C>(gl: &C, \
+                                 program: &::glitter::Program)\n");
+        module.push_str("            -> Result<::glitter::AttribBinder, \
+                         ::glitter::UnknownProgramAttrib<'static>>\n");
+        module.push_str("            where C: ::glitter::ContextProgramExt\n");
+        module.push_str("        {\n");
+        module.push_str("            let mut binder = \
+                         ::glitter::AttribBinder::new();\n");
+        // The binder is keyed by the field names, which are used as the
+        // attribute names by `impl_vertex_data!`
+        for (attrib, field) in vertex.attributes.iter().zip(&fields) {
+            module.push_str(&format!("            binder.add({:?}, \
+                                      try!(gl.get_attrib_location(program, \
+                                      {:?}))).unwrap();\n",
+                                     field, attrib.name));
+        }
+        module.push_str("            Ok(binder)\n");
+        module.push_str("        }\n");
+        module.push_str("    }\n");
+    }
+
+    let uniforms: Vec<_> = uniforms.into_iter()
+                                   .filter(|u| _uniform_type(&u.ty).is_some())
+                                   .collect();
+
+    module.push_str("\n    #[allow(non_snake_case)]\n");
+    module.push_str("    #[derive(Debug, Clone, Copy)]\n");
+    module.push_str("    pub struct Uniforms {\n");
+    for uniform in &uniforms {
+        module.push_str(&format!("        pub {}: \
+                                  Option<::glitter::ProgramUniform>,\n",
+                                 _field_name(&uniform.name)));
+    }
+    module.push_str("    }\n\n");
+
+    module.push_str("    impl Uniforms {\n");
+    module.push_str("        pub fn new<C>(gl: &C, \
+                             program: &::glitter::Program) -> Self\n");
+    module.push_str("            where C: ::glitter::ContextProgramExt\n");
+    module.push_str("        {\n");
+    module.push_str("            Uniforms {\n");
+    for uniform in &uniforms {
+        module.push_str(&format!("                {}: \
+                                  gl.get_uniform_location(program, {:?})\
+                                  .ok(),\n",
+                                 _field_name(&uniform.name), uniform.name));
+    }
+    module.push_str("            }\n");
+    module.push_str("        }\n");
+    for uniform in uniforms.iter().filter(|u| u.array_size.is_none()) {
+        module.push_str(&format!("\n        pub fn set_{}<C>(&self, gl: &C, \
+                                  program: &::glitter::ProgramBinding, \
+                                  value: {})\n",
+                                 _snake_case(&uniform.name),
+                                 _uniform_type(&uniform.ty).unwrap()));
+        module.push_str("            where C: ::glitter::ContextProgramExt\n");
+        module.push_str("        {\n");
+        module.push_str(&format!("            if let Some(uniform) = \
+                                  self.{} {{\n",
+                                 _field_name(&uniform.name)));
+        module.push_str("                gl.set_uniform(program, uniform, \
+                         value);\n");
+        module.push_str("            }\n");
+        module.push_str("        }\n");
+    }
+    module.push_str("    }\n");
+    module.push_str("}\n");
+
+    Ok(module)
+}
+
+fn _snake_case(ident: &str) -> String {
+    constant_name(ident).to_lowercase()
+}
+
+// Rust keywords (including reserved keywords), which can't be used as the
+// name of a field.
+const RUST_KEYWORDS: &'static [&'static str] = &[
+    "abstract", "alignof", "as", "async", "await", "become", "box", "break",
+    "const", "continue", "crate", "do", "dyn", "else", "enum", "extern",
+    "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "offsetof", "override", "priv",
+    "proc", "pub", "pure", "ref", "return", "self", "Self", "sizeof",
+    "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield"
+];
+
+// The name of the field for a variable, which is the GLSL name, with a
+// trailing `_` if the name is a Rust keyword (such as `type_`).
+fn _field_name(ident: &str) -> String {
+    if RUST_KEYWORDS.iter().any(|&keyword| keyword == ident) {
+        format!("{}_", ident)
+    }
+    else {
+        ident.to_owned()
+    }
+}
+
+/// Include a shader module generated by [`generate_shader_module`]
+/// (glsl/fn.generate_shader_module.html) from a build script.
+///
+/// # Example
+/// ```ignore
+/// #[macro_use] extern crate glitter;
+///
+/// // Generated with `generate_shader_module(.., "main_frag")`
+/// include_shader!(main_frag);
+///
+/// # fn main() {
+/// println!("{}", main_frag::SOURCE);
+/// println!("{}", main_frag::uniforms::TEXTURE);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! include_shader {
+    ($module_name:ident) => {
+        include!(concat!(env!("OUT_DIR"), "/",
+                         stringify!($module_name), ".rs"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use shader::ShaderType;
+    use super::*;
+    use super::super::validate::validate_es100;
+
+    #[test]
+    fn shader_module_with_branches() {
+        let source = "#version 100
+precision mediump float;
+#ifdef TEXTURED
+uniform sampler2D u_texture;
+varying vec2 v_uv;
+#else
+uniform sampler2D u_texture;
+#endif
+void main() {
+    gl_FragColor = vec4(1.0);
+}
+";
+        let interface = validate_es100(source, ShaderType::FragmentShader)
+            .unwrap();
+        let module = _shader_module("frag", Path::new("frag.glsl"), &interface)
+            .unwrap();
+        assert_eq!(module.matches("pub const U_TEXTURE:").count(), 1);
+        assert_eq!(module.matches("pub const V_UV:").count(), 1);
+    }
+
+    fn _interfaces(vertex_source: &str, fragment_source: &str)
+        -> (ShaderInterface, ShaderInterface)
+    {
+        (validate_es100(vertex_source, ShaderType::VertexShader).unwrap(),
+         validate_es100(fragment_source, ShaderType::FragmentShader).unwrap())
+    }
+
+    #[test]
+    fn program_module_escapes_keywords() {
+        let (vertex, fragment) = _interfaces("#version 100
+attribute vec4 position;
+attribute float type;
+attribute vec2 ref;
+uniform mat4 move;
+varying float v;
+void main() {
+    v = type + ref.x;
+    gl_Position = move * position;
+}
+", "#version 100
+precision mediump float;
+uniform vec4 match;
+uniform float self;
+varying float v;
+void main() {
+    gl_FragColor = match * self * v;
+}
+");
+        let module = _program_module("program",
+                                     Path::new("vert.glsl"), &vertex,
+                                     Path::new("frag.glsl"), &fragment)
+            .unwrap();
+
+        assert!(module.contains("pub type_: f32,"));
+        assert!(module.contains("pub ref_: [f32; 2],"));
+        assert!(module.contains("impl_vertex_data!(Vertex, position, type_, \
+                                 ref_);"));
+        assert!(module.contains("binder.add(\"type_\", \
+                                 try!(gl.get_attrib_location(program, \
+                                 \"type\"))).unwrap();"));
+        for &(field, name) in &[("move_", "move"),
+                                ("match_", "match"),
+                                ("self_", "self")]
+        {
+            assert!(module.contains(&format!("pub {}: \
+                                              Option<::glitter::ProgramUniform>,",
+                                             field)));
+            assert!(module.contains(&format!("{}: gl.get_uniform_location(\
+                                              program, {:?}).ok(),",
+                                             field, name)));
+            assert!(module.contains(&format!("pub fn set_{}<C>", name)));
+            assert!(module.contains(&format!("self.{} {{", field)));
+        }
+    }
+
+    #[test]
+    fn program_module_name_collision() {
+        let (vertex, fragment) = _interfaces("#version 100
+attribute vec4 position;
+uniform vec4 type;
+uniform vec4 type_;
+void main() {
+    gl_Position = position * type * type_;
+}
+", "#version 100
+precision mediump float;
+uniform vec4 uTint;
+uniform vec4 u_tint;
+void main() {
+    gl_FragColor = uTint * u_tint;
+}
+");
+        let error = _program_module("program",
+                                    Path::new("vert.glsl"), &vertex,
+                                    Path::new("frag.glsl"), &fragment)
+            .unwrap_err();
+        match error {
+            ShaderBuildError::Invalid(path, errors) => {
+                assert_eq!(path, Path::new("vert.glsl"));
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].line, 4);
+                assert_eq!(errors[0].message,
+                           "uniforms `type_` and `type` would both be \
+                            named `type_`");
+            },
+            _ => { panic!("expected a validation error"); }
+        }
+
+        let vertex = validate_es100("#version 100
+attribute vec4 position;
+void main() {
+    gl_Position = position;
+}
+", ShaderType::VertexShader).unwrap();
+        let error = _program_module("program",
+                                    Path::new("vert.glsl"), &vertex,
+                                    Path::new("frag.glsl"), &fragment)
+            .unwrap_err();
+        match error {
+            ShaderBuildError::Invalid(path, errors) => {
+                assert_eq!(path, Path::new("frag.glsl"));
+                assert_eq!(errors[0].message,
+                           "uniforms `u_tint` and `uTint` would both be \
+                            named `set_u_tint`");
+            },
+            _ => { panic!("expected a validation error"); }
+        }
+    }
+
+    #[test]
+    fn shader_module_name_collision() {
+        let source = "#version 100
+attribute vec4 position;
+uniform vec4 uColor;
+uniform vec4 u_color;
+void main() {
+    gl_Position = position * uColor * u_color;
+}
+";
+        let interface = validate_es100(source, ShaderType::VertexShader)
+            .unwrap();
+        let errors = _shader_module("vert", Path::new("vert.glsl"), &interface)
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 4);
+        assert_eq!(errors[0].message,
+                   "`u_color` and `uColor` (from line 3) would both be \
+                    named `U_COLOR`");
+    }
+}
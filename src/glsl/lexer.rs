@@ -0,0 +1,208 @@
+//! A minimal GLSL tokenizer.
+
+/// The different kinds of GLSL tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// An identifier or keyword, such as `vec4` or `gl_Position`.
+    Identifier,
+
+    /// A numeric literal, such as `1.0` or `0x1F`.
+    Number,
+
+    /// A single punctuation or operator character, such as `;` or `#`.
+    Punctuation,
+
+    /// A single newline character.
+    Newline,
+
+    /// A run of whitespace that does not contain a newline.
+    Whitespace,
+
+    /// A line comment (`// ...`) or a block comment (`/* ... */`).
+    Comment
+}
+
+/// A single GLSL token, which borrows its text from the source it
+/// was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    /// The kind of token.
+    pub kind: TokenKind,
+
+    /// The source text of the token.
+    pub text: &'a str,
+
+    /// The byte offset of the token within the source.
+    pub offset: usize,
+
+    /// The 1-based line number that the token starts on.
+    pub line: usize,
+
+    /// The 1-based column (in characters) that the token starts on.
+    pub column: usize
+}
+
+impl<'a> Token<'a> {
+    /// Returns `true` if the token is whitespace, a newline, or a comment.
+    pub fn is_trivia(&self) -> bool {
+        match self.kind {
+            TokenKind::Whitespace |
+            TokenKind::Newline |
+            TokenKind::Comment => { true },
+            _ => { false }
+        }
+    }
+
+    /// Returns `true` if the token is an identifier with the provided text.
+    pub fn is_ident(&self, ident: &str) -> bool {
+        self.kind == TokenKind::Identifier && self.text == ident
+    }
+
+    /// Returns `true` if the token is the provided punctuation character.
+    pub fn is_punct(&self, punct: char) -> bool {
+        self.kind == TokenKind::Punctuation &&
+        self.text.chars().next() == Some(punct)
+    }
+}
+
+fn _is_ident_start(c: char) -> bool {
+    c == '_' || (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z')
+}
+
+fn _is_ident_continue(c: char) -> bool {
+    _is_ident_start(c) || (c >= '0' && c <= '9')
+}
+
+/// Split GLSL source into tokens. Every byte of the source belongs to
+/// exactly one token, so concatenating the text of each token will
+/// reproduce the original source.
+///
+/// # Example
+/// ```
+/// use glitter::glsl::{tokenize, TokenKind};
+///
+/// let tokens = tokenize("gl_FragColor = vec4(1.0); // red");
+/// let idents: Vec<_> = tokens.iter()
+///                            .filter(|t| t.kind == TokenKind::Identifier)
+///                            .map(|t| t.text)
+///                            .collect();
+/// assert_eq!(idents, vec!["gl_FragColor", "vec4"]);
+/// assert_eq!(tokens.last().unwrap().kind, TokenKind::Comment);
+/// ```
+pub fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut line = 1;
+    let mut column = 1;
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let start_line = line;
+        let kind = if c == '\n' {
+            line += 1;
+            TokenKind::Newline
+        }
+        else if c.is_whitespace() {
+            while let Some(&(_, next)) = chars.peek() {
+                if next == '\n' || !next.is_whitespace() { break; }
+                chars.next();
+            }
+            TokenKind::Whitespace
+        }
+        else if c == '/' && chars.peek().map(|&(_, n)| n) == Some('/') {
+            while let Some(&(_, next)) = chars.peek() {
+                if next == '\n' { break; }
+                chars.next();
+            }
+            TokenKind::Comment
+        }
+        else if c == '/' && chars.peek().map(|&(_, n)| n) == Some('*') {
+            chars.next();
+            let mut prev = ' ';
+            while let Some((_, next)) = chars.next() {
+                if next == '\n' { line += 1; }
+                if prev == '*' && next == '/' { break; }
+                prev = next;
+            }
+            TokenKind::Comment
+        }
+        else if _is_ident_start(c) {
+            while let Some(&(_, next)) = chars.peek() {
+                if !_is_ident_continue(next) { break; }
+                chars.next();
+            }
+            TokenKind::Identifier
+        }
+        else if (c >= '0' && c <= '9') ||
+                (c == '.' && chars.peek().map_or(false, |&(_, n)| {
+                    n >= '0' && n <= '9'
+                }))
+        {
+            let mut prev = c;
+            while let Some(&(_, next)) = chars.peek() {
+                let exponent_sign = (next == '+' || next == '-') &&
+                                    (prev == 'e' || prev == 'E');
+                if !_is_ident_continue(next) && next != '.' && !exponent_sign {
+                    break;
+                }
+                prev = next;
+                chars.next();
+            }
+            TokenKind::Number
+        }
+        else {
+            TokenKind::Punctuation
+        };
+
+        let end = chars.peek().map_or(source.len(), |&(i, _)| i);
+        let text = &source[start..end];
+        tokens.push(Token {
+            kind: kind,
+            text: text,
+            offset: start,
+            line: start_line,
+            column: column
+        });
+
+        column = match text.rfind('\n') {
+            Some(newline) => { text[newline + 1..].chars().count() + 1 },
+            None => { column + text.chars().count() }
+        };
+    }
+
+    tokens
+}
+
+/// Find each preprocessor directive (a line starting with `#`) within a
+/// list of tokens, returning the tokens that make up each directive (not
+/// including the `#` token or any trivia).
+pub fn directives<'a, 'b>(tokens: &'b [Token<'a>]) -> Vec<Vec<Token<'a>>> {
+    let mut result = vec![];
+    let mut at_line_start = true;
+    let mut current: Option<Vec<Token<'a>>> = None;
+
+    for token in tokens {
+        match token.kind {
+            TokenKind::Newline => {
+                if let Some(directive) = current.take() {
+                    result.push(directive);
+                }
+                at_line_start = true;
+            },
+            TokenKind::Whitespace | TokenKind::Comment => { },
+            _ => {
+                if current.is_some() {
+                    current.as_mut().unwrap().push(*token);
+                }
+                else if at_line_start && token.is_punct('#') {
+                    current = Some(vec![]);
+                }
+                at_line_start = false;
+            }
+        }
+    }
+    if let Some(directive) = current.take() {
+        result.push(directive);
+    }
+
+    result
+}
@@ -0,0 +1,12 @@
+//! Contains pure-Rust tools for working with GLSL source code, which
+//! don't require an OpenGL context.
+
+mod lexer;
+mod translate;
+mod validate;
+mod build;
+
+pub use self::lexer::*;
+pub use self::translate::*;
+pub use self::validate::*;
+pub use self::build::*;
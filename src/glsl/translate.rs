@@ -0,0 +1,392 @@
+//! Translation of GLSL ES 1.00 shaders into newer GLSL dialects.
+
+use context::ContextExt;
+use shader::ShaderType;
+use types::StringName;
+use super::lexer::{tokenize, Token, TokenKind};
+
+/// The GLSL dialects that GLSL ES 1.00 shaders can be translated into.
+/// See [`translate_es100`](fn.translate_es100.html) for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GlslTarget {
+    /// GLSL ES 1.00 (`#version 100`), used by OpenGL ES 2. Shaders are
+    /// left untranslated for this target.
+    Es100,
+
+    /// GLSL ES 3.00 (`#version 300 es`), used by OpenGL ES 3.
+    Es300,
+
+    /// GLSL 3.30 (`#version 330 core`), used by OpenGL 3.3 and later
+    /// core profile contexts.
+    Core330
+}
+
+impl GlslTarget {
+    /// Pick the target that matches an OpenGL `GL_VERSION` string. OpenGL
+    /// ES 3 contexts map to `Es300`, desktop contexts with OpenGL 3.3 or
+    /// later map to `Core330`, and everything else maps to `Es100`.
+    ///
+    /// # Example
+    /// ```
+    /// use glitter::glsl::GlslTarget;
+    ///
+    /// assert_eq!(GlslTarget::from_version_string("OpenGL ES 2.0 Mesa"),
+    ///            GlslTarget::Es100);
+    /// assert_eq!(GlslTarget::from_version_string("OpenGL ES 3.1 Mesa"),
+    ///            GlslTarget::Es300);
+    /// assert_eq!(GlslTarget::from_version_string("4.1 INTEL-10.2.37"),
+    ///            GlslTarget::Core330);
+    /// assert_eq!(GlslTarget::from_version_string("2.1 Mesa 11.0.0"),
+    ///            GlslTarget::Es100);
+    /// ```
+    pub fn from_version_string(version: &str) -> Self {
+        let (es, numbers) = if version.starts_with("OpenGL ES") {
+            (true, version["OpenGL ES".len()..].trim_left())
+        }
+        else {
+            (false, version.trim_left())
+        };
+
+        let mut parts = numbers.split(|c: char| c == '.' || c == ' ');
+        let major: u32 = parts.next()
+                              .and_then(|n| n.parse().ok())
+                              .unwrap_or(0);
+        let minor: u32 = parts.next()
+                              .and_then(|n| n.parse().ok())
+                              .unwrap_or(0);
+
+        if es {
+            if major >= 3 { GlslTarget::Es300 } else { GlslTarget::Es100 }
+        }
+        else if (major, minor) >= (3, 3) {
+            GlslTarget::Core330
+        }
+        else {
+            GlslTarget::Es100
+        }
+    }
+
+    /// Pick the target that matches the current context, based on its
+    /// `GL_VERSION` string.
+    pub fn for_context<C: ContextExt>(gl: &C) -> Self {
+        match gl.get_string(StringName::Version) {
+            Some(version) => { GlslTarget::from_version_string(&version) },
+            None => { GlslTarget::Es100 }
+        }
+    }
+
+    /// Get the `#version` directive for this target.
+    pub fn version_directive(&self) -> &'static str {
+        match *self {
+            GlslTarget::Es100 => { "#version 100" },
+            GlslTarget::Es300 => { "#version 300 es" },
+            GlslTarget::Core330 => { "#version 330 core" }
+        }
+    }
+}
+
+// Identifiers that are allowed as names in GLSL ES 1.00, but that are
+// keywords or built-in functions in the newer dialects.
+const RESERVED_IDENTS: &'static [&'static str] = &[
+    "texture", "textureProj", "textureLod", "textureProjLod", "textureSize",
+    "texelFetch", "flat", "smooth", "layout", "centroid", "sample",
+    "noperspective", "uint", "uvec2", "uvec3", "uvec4"
+];
+
+const FRAG_COLOR: &'static str = "glitter_FragColor";
+const FRAG_DATA: &'static str = "glitter_FragData";
+
+fn _translate_ident(ident: &str, ty: ShaderType) -> Option<&'static str> {
+    match (ident, ty) {
+        ("attribute", ShaderType::VertexShader) => { Some("in") },
+        ("varying", ShaderType::VertexShader) => { Some("out") },
+        ("varying", ShaderType::FragmentShader) => { Some("in") },
+        ("gl_FragColor", ShaderType::FragmentShader) => { Some(FRAG_COLOR) },
+        ("gl_FragData", ShaderType::FragmentShader) => { Some(FRAG_DATA) },
+        ("texture2D", _) | ("textureCube", _) => { Some("texture") },
+        ("texture2DProj", _) => { Some("textureProj") },
+        ("texture2DLod", _) | ("textureCubeLod", _) |
+        ("texture2DLodEXT", _) | ("textureCubeLodEXT", _) => {
+            Some("textureLod")
+        },
+        ("texture2DProjLod", _) | ("texture2DProjLodEXT", _) => {
+            Some("textureProjLod")
+        },
+        _ => { None }
+    }
+}
+
+// A `#version` or `#extension` directive, with the byte range of its line
+// (including the trailing newline, if any).
+struct Directive<'a> {
+    name: &'a str,
+    args: Vec<Token<'a>>,
+    start: usize,
+    end: usize
+}
+
+fn _top_level_directives<'a>(source: &'a str, tokens: &[Token<'a>])
+    -> Vec<Directive<'a>>
+{
+    let mut result = vec![];
+    let mut depth = 0;
+    let mut at_line_start = true;
+    let mut current: Option<Directive<'a>> = None;
+
+    for token in tokens {
+        match token.kind {
+            TokenKind::Newline => {
+                if let Some(mut directive) = current.take() {
+                    directive.end = token.offset + 1;
+                    match directive.name {
+                        "if" | "ifdef" | "ifndef" => { depth += 1; },
+                        "endif" => { depth -= 1; },
+                        "version" | "extension" if depth == 0 => {
+                            result.push(directive);
+                        },
+                        _ => { }
+                    }
+                }
+                at_line_start = true;
+            },
+            TokenKind::Whitespace | TokenKind::Comment => { },
+            _ => {
+                if current.is_some() {
+                    let directive = current.as_mut().unwrap();
+                    if directive.name.is_empty() {
+                        directive.name = token.text;
+                    }
+                    else {
+                        directive.args.push(*token);
+                    }
+                }
+                else if at_line_start && token.is_punct('#') {
+                    current = Some(Directive {
+                        name: "",
+                        args: vec![],
+                        start: token.offset,
+                        end: source.len()
+                    });
+                }
+                at_line_start = false;
+            }
+        }
+    }
+    if let Some(directive) = current.take() {
+        if depth == 0 &&
+           (directive.name == "version" || directive.name == "extension")
+        {
+            result.push(directive);
+        }
+    }
+
+    result
+}
+
+/// Translate a GLSL ES 1.00 shader into an equivalent shader for another
+/// GLSL dialect. Shaders that declare a `#version` other than `100` are
+/// returned unchanged, as are all shaders when the target is
+/// [`GlslTarget::Es100`](enum.GlslTarget.html).
+///
+/// The translation performs the following rewrites:
+///
+/// - The `#version` directive is replaced with the target's directive
+///   (and added if the shader had none).
+/// - `attribute` becomes `in`, and `varying` becomes `out` in vertex
+///   shaders or `in` in fragment shaders.
+/// - `gl_FragColor` and `gl_FragData` are replaced with user-declared
+///   fragment outputs, which are declared after any `#extension`
+///   directives at the top of the shader.
+/// - `texture2D`, `textureCube`, and their `Proj`/`Lod` variants are
+///   replaced with the overloaded `texture` family of functions.
+/// - Identifiers that are keywords or built-in functions in the target
+///   dialect (such as a variable named `texture`) are renamed with a
+///   `glitter_` prefix.
+///
+/// Precision qualifiers and `precision` statements are kept as-is, since
+/// both GLSL ES 3.00 and GLSL 3.30 accept them. Since `GL_ES` is not
+/// defined for desktop GLSL, precision statements guarded with
+/// `#ifdef GL_ES` are still skipped for the `Core330` target.
+///
+/// # Example
+/// ```
+/// use glitter::glsl::{translate_es100, GlslTarget};
+///
+/// let source = "#version 100
+/// precision mediump float;
+/// varying vec2 v_uv;
+/// uniform sampler2D tex;
+/// void main() {
+///     gl_FragColor = texture2D(tex, v_uv);
+/// }
+/// ";
+///
+/// let translated = translate_es100(source,
+///                                  glitter::FRAGMENT_SHADER,
+///                                  GlslTarget::Core330);
+/// assert_eq!(translated, "#version 330 core
+/// out vec4 glitter_FragColor;
+/// precision mediump float;
+/// in vec2 v_uv;
+/// uniform sampler2D tex;
+/// void main() {
+///     glitter_FragColor = texture(tex, v_uv);
+/// }
+/// ");
+/// ```
+pub fn translate_es100(source: &str, ty: ShaderType, target: GlslTarget)
+    -> String
+{
+    translate_es100_sources(&[source], ty, target).pop().unwrap()
+}
+
+/// Translate a GLSL ES 1.00 shader that is split across several source
+/// strings, which are passed to the compiler together (see
+/// [`gl.shader_sources`](../shader/trait.ContextShaderExt.html#method.shader_sources)).
+/// The `#version` directive and any fragment output declarations are
+/// written to the first source, and every other source is only rewritten
+/// token-by-token, so that its line numbers are left unchanged. See
+/// [`translate_es100`](fn.translate_es100.html) for details about
+/// the translation.
+///
+/// # Example
+/// ```
+/// use glitter::glsl::{translate_es100_sources, GlslTarget};
+///
+/// let prelude = "#version 100
+/// precision mediump float;
+/// ";
+/// let source = "varying vec2 v_uv;
+/// void main() {
+///     gl_FragColor = vec4(v_uv, 0.0, 1.0);
+/// }
+/// ";
+///
+/// let translated = translate_es100_sources(&[prelude, source],
+///                                          glitter::FRAGMENT_SHADER,
+///                                          GlslTarget::Core330);
+/// assert_eq!(translated, vec!["#version 330 core
+/// out vec4 glitter_FragColor;
+/// precision mediump float;
+/// ", "in vec2 v_uv;
+/// void main() {
+///     glitter_FragColor = vec4(v_uv, 0.0, 1.0);
+/// }
+/// "]);
+/// ```
+pub fn translate_es100_sources(sources: &[&str],
+                               ty: ShaderType,
+                               target: GlslTarget)
+    -> Vec<String>
+{
+    let unchanged = || sources.iter().map(|&s| s.to_owned()).collect();
+    let first = match sources.first() {
+        Some(first) => { *first },
+        None => { return vec![]; }
+    };
+
+    let tokens: Vec<_> = sources.iter().map(|&s| tokenize(s)).collect();
+    let directives = _top_level_directives(first, &tokens[0]);
+
+    let version = directives.iter().find(|d| d.name == "version");
+    if let Some(version) = version {
+        let is_100 = version.args.first().map_or(false, |n| n.text == "100");
+        if !is_100 {
+            return unchanged();
+        }
+    }
+    if target == GlslTarget::Es100 {
+        return unchanged();
+    }
+
+    let version_range = version.map(|v| (v.start, v.end));
+    let output_offset = directives.iter()
+                                  .map(|d| d.end)
+                                  .max()
+                                  .unwrap_or(0);
+
+    let uses_ident = |ident: &str| {
+        tokens.iter().any(|tokens| tokens.iter().any(|t| t.is_ident(ident)))
+    };
+    let mut output_decl = String::new();
+    if ty == ShaderType::FragmentShader {
+        let precision = match target {
+            GlslTarget::Es300 => { "mediump " },
+            _ => { "" }
+        };
+        if uses_ident("gl_FragColor") {
+            output_decl.push_str(&format!("out {}vec4 {};\n",
+                                          precision,
+                                          FRAG_COLOR));
+        }
+        if uses_ident("gl_FragData") {
+            output_decl.push_str(&format!("out {}vec4 {}[gl_MaxDrawBuffers];\n",
+                                          precision,
+                                          FRAG_DATA));
+        }
+    }
+
+    let mut result = String::with_capacity(first.len() + 64);
+    result.push_str(target.version_directive());
+    result.push('\n');
+    if output_offset == 0 {
+        result.push_str(&output_decl);
+    }
+    _translate_tokens(&mut result,
+                      &tokens[0],
+                      ty,
+                      version_range,
+                      output_offset,
+                      &output_decl);
+
+    let mut results = vec![result];
+    for (source, tokens) in sources[1..].iter().zip(&tokens[1..]) {
+        let mut result = String::with_capacity(source.len());
+        _translate_tokens(&mut result, tokens, ty, None, 0, "");
+        results.push(result);
+    }
+    results
+}
+
+// Append the translated tokens to `result`, skipping the tokens within
+// `skip_range`, and inserting `output_decl` after the token that ends at
+// `output_offset` (if it is non-zero).
+fn _translate_tokens(result: &mut String,
+                     tokens: &[Token],
+                     ty: ShaderType,
+                     skip_range: Option<(usize, usize)>,
+                     output_offset: usize,
+                     output_decl: &str)
+{
+    for token in tokens {
+        let skipped = skip_range.map_or(false, |(start, end)| {
+            token.offset >= start && token.offset < end
+        });
+        if !skipped {
+            let renamed = if token.kind == TokenKind::Identifier {
+                _translate_ident(token.text, ty)
+            }
+            else {
+                None
+            };
+
+            match renamed {
+                Some(renamed) => { result.push_str(renamed); },
+                None if token.kind == TokenKind::Identifier &&
+                        RESERVED_IDENTS.iter().any(|&r| r == token.text) => {
+                    result.push_str("glitter_");
+                    result.push_str(token.text);
+                },
+                None => { result.push_str(token.text); }
+            }
+        }
+
+        let token_end = token.offset + token.text.len();
+        if output_offset > 0 && token_end == output_offset {
+            if !result.ends_with('\n') {
+                result.push('\n');
+            }
+            result.push_str(output_decl);
+        }
+    }
+}
@@ -0,0 +1,868 @@
+//! Validation of GLSL ES 1.00 shaders without an OpenGL context.
+
+use std::fmt;
+use std::error;
+use std::collections::{HashMap, HashSet};
+use shader::ShaderType;
+use super::lexer::{tokenize, Token, TokenKind};
+
+/// An error found while validating a shader, along with the line and
+/// column that it was found on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlslError {
+    /// The 1-based line number of the error.
+    pub line: usize,
+
+    /// The 1-based column (in characters) of the error.
+    pub column: usize,
+
+    /// A description of the error.
+    pub message: String
+}
+
+impl fmt::Display for GlslError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl error::Error for GlslError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+/// A global `attribute`, `uniform`, or `varying` variable declared by
+/// a shader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderVariable {
+    /// The name of the variable.
+    pub name: String,
+
+    /// The name of the variable's type, such as `vec4` or `sampler2D`.
+    pub ty: String,
+
+    /// The variable's precision qualifier (`lowp`, `mediump`, or `highp`),
+    /// if it has one.
+    pub precision: Option<String>,
+
+    /// The number of elements, if the variable is an array.
+    pub array_size: Option<usize>,
+
+    /// The 1-based line number that the variable was declared on.
+    pub line: usize,
+
+    /// The 1-based column (in characters) of the variable's name.
+    pub column: usize
+}
+
+/// The global variables that make up the interface of a shader, as
+/// found by [`validate_es100`](fn.validate_es100.html).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShaderInterface {
+    /// The shader's `attribute` variables, in declaration order.
+    pub attributes: Vec<ShaderVariable>,
+
+    /// The shader's `uniform` variables, in declaration order.
+    pub uniforms: Vec<ShaderVariable>,
+
+    /// The shader's `varying` variables, in declaration order.
+    pub varyings: Vec<ShaderVariable>
+}
+
+const KEYWORDS: &'static [&'static str] = &[
+    "attribute", "const", "uniform", "varying", "break", "continue", "do",
+    "for", "while", "if", "else", "in", "out", "inout", "true", "false",
+    "lowp", "mediump", "highp", "precision", "invariant", "discard",
+    "return", "struct"
+];
+
+const TYPES: &'static [&'static str] = &[
+    "void", "bool", "int", "float", "vec2", "vec3", "vec4", "bvec2", "bvec3",
+    "bvec4", "ivec2", "ivec3", "ivec4", "mat2", "mat3", "mat4", "sampler2D",
+    "samplerCube", "samplerExternalOES"
+];
+
+const FLOAT_TYPES: &'static [&'static str] = &[
+    "float", "vec2", "vec3", "vec4", "mat2", "mat3", "mat4"
+];
+
+const PRECISIONS: &'static [&'static str] = &["lowp", "mediump", "highp"];
+
+const BUILTIN_FUNCTIONS: &'static [&'static str] = &[
+    "radians", "degrees", "sin", "cos", "tan", "asin", "acos", "atan", "pow",
+    "exp", "log", "exp2", "log2", "sqrt", "inversesqrt", "abs", "sign",
+    "floor", "ceil", "fract", "mod", "min", "max", "clamp", "mix", "step",
+    "smoothstep", "length", "distance", "dot", "cross", "normalize",
+    "faceforward", "reflect", "refract", "matrixCompMult", "lessThan",
+    "lessThanEqual", "greaterThan", "greaterThanEqual", "equal", "notEqual",
+    "any", "all", "not", "texture2D", "texture2DProj", "texture2DLod",
+    "texture2DProjLod", "textureCube", "textureCubeLod",
+    // OES_standard_derivatives
+    "dFdx", "dFdy", "fwidth",
+    // EXT_shader_texture_lod
+    "texture2DLodEXT", "texture2DProjLodEXT", "textureCubeLodEXT",
+    "texture2DGradEXT", "texture2DProjGradEXT", "textureCubeGradEXT"
+];
+
+const BUILTIN_VARIABLES: &'static [&'static str] = &[
+    "gl_MaxVertexAttribs", "gl_MaxVertexUniformVectors",
+    "gl_MaxVaryingVectors", "gl_MaxVertexTextureImageUnits",
+    "gl_MaxCombinedTextureImageUnits", "gl_MaxTextureImageUnits",
+    "gl_MaxFragmentUniformVectors", "gl_MaxDrawBuffers", "gl_DepthRange",
+    "gl_DepthRangeParameters",
+    // Predefined macros
+    "__LINE__", "__FILE__", "__VERSION__", "GL_ES"
+];
+
+const VERTEX_VARIABLES: &'static [&'static str] = &[
+    "gl_Position", "gl_PointSize"
+];
+
+const FRAGMENT_VARIABLES: &'static [&'static str] = &[
+    "gl_FragCoord", "gl_FrontFacing", "gl_FragColor", "gl_FragData",
+    "gl_PointCoord", "gl_FragDepthEXT"
+];
+
+fn _contains(list: &[&str], ident: &str) -> bool {
+    list.iter().any(|&x| x == ident)
+}
+
+fn _error(errors: &mut Vec<GlslError>, token: &Token, message: String) {
+    errors.push(GlslError {
+        line: token.line,
+        column: token.column,
+        message: message
+    });
+}
+
+// Split a shader into its preprocessor directives and the (non-trivia)
+// tokens of its code.
+fn _split<'a>(tokens: &[Token<'a>]) -> (Vec<Vec<Token<'a>>>, Vec<Token<'a>>) {
+    let mut directives = vec![];
+    let mut code = vec![];
+    let mut at_line_start = true;
+    let mut current: Option<Vec<Token<'a>>> = None;
+
+    for token in tokens {
+        match token.kind {
+            TokenKind::Newline => {
+                if let Some(directive) = current.take() {
+                    directives.push(directive);
+                }
+                at_line_start = true;
+            },
+            TokenKind::Whitespace | TokenKind::Comment => { },
+            _ => {
+                if current.is_some() {
+                    current.as_mut().unwrap().push(*token);
+                }
+                else if at_line_start && token.is_punct('#') {
+                    current = Some(vec![*token]);
+                }
+                else {
+                    code.push(*token);
+                }
+                at_line_start = false;
+            }
+        }
+    }
+    if let Some(directive) = current.take() {
+        directives.push(directive);
+    }
+
+    (directives, code)
+}
+
+fn _check_brackets(code: &[Token], errors: &mut Vec<GlslError>) {
+    let mut stack: Vec<(char, &Token)> = vec![];
+    for token in code {
+        if token.kind != TokenKind::Punctuation {
+            continue;
+        }
+        let c = token.text.chars().next().unwrap_or(' ');
+        let open = match c {
+            '(' | '[' | '{' => { stack.push((c, token)); continue; },
+            ')' => { '(' },
+            ']' => { '[' },
+            '}' => { '{' },
+            _ => { continue; }
+        };
+        match stack.pop() {
+            Some((top, _)) if top == open => { },
+            Some((top, open)) => {
+                _error(errors, token,
+                       format!("found `{}`, but `{}` from line {} is not \
+                                closed",
+                               c, top, open.line));
+                return;
+            },
+            None => {
+                _error(errors, token, format!("unmatched `{}`", c));
+                return;
+            }
+        }
+    }
+    if let Some(&(top, open)) = stack.last() {
+        _error(errors, open, format!("`{}` is never closed", top));
+    }
+}
+
+// Try to evaluate an array size, which may be an integer literal, a
+// `#define`d integer, or a `const int` with a literal initializer.
+fn _array_size(tokens: &[Token], constants: &HashMap<&str, usize>)
+    -> Option<usize>
+{
+    if tokens.len() != 1 {
+        return None;
+    }
+    match tokens[0].kind {
+        TokenKind::Number => { tokens[0].text.parse().ok() },
+        TokenKind::Identifier => { constants.get(tokens[0].text).cloned() },
+        _ => { None }
+    }
+}
+
+// The name of a variable's type, including its array size.
+fn _type_name(variable: &ShaderVariable) -> String {
+    match variable.array_size {
+        Some(size) => { format!("{}[{}]", variable.ty, size) },
+        None => { variable.ty.clone() }
+    }
+}
+
+/// Validate a GLSL ES 1.00 shader without compiling it, and return the
+/// `attribute`, `uniform`, and `varying` variables that it declares.
+///
+/// This is not a complete GLSL compiler. It finds common mistakes, such
+/// as unbalanced brackets, undeclared identifiers (which are usually
+/// typos), built-in variables used in the wrong shader stage, a missing
+/// `main` function, or a fragment shader without a default `float`
+/// precision. A shader that passes validation may still fail to compile.
+///
+/// Every branch of a preprocessor conditional (`#if`, `#ifdef`, etc.) is
+/// validated, and identifiers defined with `#define` are always treated
+/// as declared. A variable that is declared in more than one branch only
+/// appears in the interface once, and must have the same type in
+/// each branch.
+///
+/// # Failures
+/// All of the errors that were found will be returned, ordered by line
+/// and column.
+///
+/// # Example
+/// ```
+/// use glitter::glsl::validate_es100;
+///
+/// let source = "#version 100
+/// attribute vec4 position;
+/// uniform mat4 transform;
+/// void main() {
+///     gl_Position = transfrom * position;
+/// }
+/// ";
+///
+/// let errors = validate_es100(source, glitter::VERTEX_SHADER).unwrap_err();
+/// assert_eq!((errors[0].line, errors[0].column), (5, 19));
+/// assert_eq!(errors[0].message, "undeclared identifier `transfrom`");
+///
+/// let fixed = source.replace("transfrom", "transform");
+/// let interface = validate_es100(&fixed, glitter::VERTEX_SHADER).unwrap();
+/// assert_eq!(interface.attributes[0].name, "position");
+/// assert_eq!(interface.uniforms[0].ty, "mat4");
+/// ```
+pub fn validate_es100(source: &str, ty: ShaderType)
+    -> Result<ShaderInterface, Vec<GlslError>>
+{
+    let tokens = tokenize(source);
+    let (directives, code) = _split(&tokens);
+    let mut errors = vec![];
+
+    let mut macros = HashSet::new();
+    let mut constants = HashMap::new();
+    for (idx, directive) in directives.iter().enumerate() {
+        let hash = directive[0];
+        let name = directive.get(1).map_or("", |t| t.text);
+        match name {
+            "version" => {
+                let is_first = idx == 0 &&
+                               code.first().map_or(true, |t| {
+                                   t.offset > hash.offset
+                               });
+                if !is_first {
+                    _error(&mut errors, &hash,
+                           "`#version` must come before anything else"
+                           .to_owned());
+                }
+                let version = directive.get(2).map_or("", |t| t.text);
+                if version != "100" {
+                    _error(&mut errors, &hash,
+                           format!("unsupported GLSL version `{}` \
+                                    (expected `100`)",
+                                   version));
+                }
+            },
+            "define" => {
+                if let Some(macro_name) = directive.get(2) {
+                    macros.insert(macro_name.text);
+                    if directive.len() == 4 {
+                        if let Ok(value) = directive[3].text.parse() {
+                            constants.insert(macro_name.text, value);
+                        }
+                    }
+                }
+            },
+            _ => { }
+        }
+    }
+
+    _check_brackets(&code, &mut errors);
+
+    // Collect the names of all user-defined types, then all declared
+    // names (ignoring scope).
+    let mut user_types = HashSet::new();
+    for window in code.windows(2) {
+        if window[0].is_ident("struct") &&
+           window[1].kind == TokenKind::Identifier
+        {
+            user_types.insert(window[1].text);
+        }
+    }
+    let is_type = |ident: &str| {
+        _contains(TYPES, ident) || user_types.contains(ident)
+    };
+    let is_name = |token: &Token| {
+        token.kind == TokenKind::Identifier &&
+        !_contains(KEYWORDS, token.text) &&
+        !is_type(token.text)
+    };
+
+    let mut declared = HashSet::new();
+    let mut has_main = false;
+    for idx in 0..code.len() {
+        if !(code[idx].kind == TokenKind::Identifier &&
+             is_type(code[idx].text))
+        {
+            continue;
+        }
+        let name = match code.get(idx + 1) {
+            Some(token) if is_name(token) => { *token },
+            _ => { continue; }
+        };
+        declared.insert(name.text);
+
+        if code.get(idx + 2).map_or(false, |t| t.is_punct('(')) {
+            if code[idx].is_ident("void") && name.text == "main" {
+                has_main = true;
+            }
+            continue;
+        }
+
+        if code[idx].is_ident("int") &&
+           code.get(idx + 2).map_or(false, |t| t.is_punct('=')) &&
+           code.get(idx + 4).map_or(false, |t| t.is_punct(';'))
+        {
+            if let Ok(value) = code[idx + 3].text.parse() {
+                constants.insert(name.text, value);
+            }
+        }
+
+        // Find the rest of the names in a declarator list
+        // (e.g. `float a, b;`)
+        let mut depth = 0;
+        for (next_idx, token) in code.iter().enumerate().skip(idx + 2) {
+            if token.is_punct('(') || token.is_punct('[') {
+                depth += 1;
+            }
+            else if token.is_punct(')') || token.is_punct(']') {
+                if depth == 0 { break; }
+                depth -= 1;
+            }
+            else if depth == 0 &&
+                    (token.is_punct(';') ||
+                     token.is_punct('{') ||
+                     token.is_punct('}'))
+            {
+                break;
+            }
+            else if depth == 0 && token.is_punct(',') {
+                if let Some(next) = code.get(next_idx + 1) {
+                    if is_name(next) {
+                        declared.insert(next.text);
+                    }
+                }
+            }
+        }
+    }
+
+    let (stage_variables, other_variables, other_stage) = match ty {
+        ShaderType::VertexShader => {
+            (VERTEX_VARIABLES, FRAGMENT_VARIABLES, "fragment")
+        },
+        ShaderType::FragmentShader => {
+            (FRAGMENT_VARIABLES, VERTEX_VARIABLES, "vertex")
+        }
+    };
+
+    // Check that every identifier is declared
+    let mut reported = HashSet::new();
+    for (idx, token) in code.iter().enumerate() {
+        if token.kind != TokenKind::Identifier {
+            continue;
+        }
+        if idx > 0 && code[idx - 1].is_punct('.') {
+            continue;
+        }
+        let ident = token.text;
+        if _contains(KEYWORDS, ident) ||
+           is_type(ident) ||
+           _contains(BUILTIN_FUNCTIONS, ident) ||
+           _contains(BUILTIN_VARIABLES, ident) ||
+           _contains(stage_variables, ident) ||
+           declared.contains(ident) ||
+           macros.contains(ident) ||
+           reported.contains(ident)
+        {
+            continue;
+        }
+
+        reported.insert(ident);
+        if _contains(other_variables, ident) {
+            _error(&mut errors, token,
+                   format!("`{}` can only be used in {} shaders",
+                           ident, other_stage));
+        }
+        else {
+            _error(&mut errors, token,
+                   format!("undeclared identifier `{}`", ident));
+        }
+    }
+
+    if !has_main {
+        // Report the error at the end of the source
+        let (line, column) = tokens.last().map_or((1, 1), |last| {
+            if last.kind == TokenKind::Newline {
+                (last.line + 1, 1)
+            }
+            else {
+                (last.line, last.column + last.text.chars().count())
+            }
+        });
+        errors.push(GlslError {
+            line: line,
+            column: column,
+            message: "missing `void main()` function".to_owned()
+        });
+    }
+
+    // Collect the shader interface, and check the global declarations
+    let mut interface = ShaderInterface::default();
+    let mut has_float_precision = false;
+    let mut unqualified_float: Option<Token> = None;
+    let mut depth = 0;
+    for (idx, token) in code.iter().enumerate() {
+        if token.is_punct('{') {
+            depth += 1;
+        }
+        else if token.is_punct('}') {
+            depth -= 1;
+        }
+
+        if token.is_ident("precision") &&
+           code.get(idx + 2).map_or(false, |t| t.is_ident("float"))
+        {
+            has_float_precision = true;
+        }
+
+        if token.kind == TokenKind::Identifier &&
+           _contains(FLOAT_TYPES, token.text) &&
+           code.get(idx + 1).map_or(false, |t| is_name(t)) &&
+           !(idx > 0 && _contains(PRECISIONS, code[idx - 1].text)) &&
+           unqualified_float.is_none()
+        {
+            unqualified_float = Some(*token);
+        }
+
+        let qualifier = token.text;
+        if !(token.kind == TokenKind::Identifier &&
+             (qualifier == "attribute" ||
+              qualifier == "uniform" ||
+              qualifier == "varying"))
+        {
+            continue;
+        }
+
+        if depth > 0 {
+            _error(&mut errors, token,
+                   format!("`{}` variables must be declared globally",
+                           qualifier));
+            continue;
+        }
+        if qualifier == "attribute" && ty == ShaderType::FragmentShader {
+            _error(&mut errors, token,
+                   "`attribute` variables can only be declared in vertex \
+                    shaders".to_owned());
+            continue;
+        }
+
+        let mut next = idx + 1;
+        let precision = match code.get(next) {
+            Some(t) if _contains(PRECISIONS, t.text) => {
+                next += 1;
+                Some(t.text.to_owned())
+            },
+            _ => { None }
+        };
+        let var_ty = match code.get(next) {
+            Some(t) if t.kind == TokenKind::Identifier && is_type(t.text) => {
+                next += 1;
+                t.text
+            },
+            _ => {
+                _error(&mut errors, token,
+                       format!("expected a type after `{}`", qualifier));
+                continue;
+            }
+        };
+
+        // Read each name (and optional array size) in the declarator list
+        loop {
+            let name = match code.get(next) {
+                Some(t) if is_name(t) => { *t },
+                _ => {
+                    _error(&mut errors, token,
+                           format!("expected a variable name after `{}`",
+                                   var_ty));
+                    break;
+                }
+            };
+            next += 1;
+
+            let mut array_size = None;
+            if code.get(next).map_or(false, |t| t.is_punct('[')) {
+                let size_start = next + 1;
+                let size_end = code[size_start..].iter()
+                                                 .position(|t| t.is_punct(']'))
+                                                 .map_or(code.len(), |end| {
+                                                     size_start + end
+                                                 });
+                let size_tokens = &code[size_start..size_end];
+                array_size = _array_size(size_tokens, &constants);
+                if array_size.is_none() {
+                    _error(&mut errors, &name,
+                           format!("the size of array `{}` must be an \
+                                    integer literal or constant",
+                                   name.text));
+                }
+                next = size_end + 1;
+            }
+
+            if qualifier == "attribute" {
+                if array_size.is_some() {
+                    _error(&mut errors, &name,
+                           format!("attribute `{}` cannot be an array",
+                                   name.text));
+                }
+                if !_contains(FLOAT_TYPES, var_ty) {
+                    _error(&mut errors, &name,
+                           format!("attribute `{}` must have a float, \
+                                    vector, or matrix type",
+                                   name.text));
+                }
+            }
+
+            let variable = ShaderVariable {
+                name: name.text.to_owned(),
+                ty: var_ty.to_owned(),
+                precision: precision.clone(),
+                array_size: array_size,
+                line: name.line,
+                column: name.column
+            };
+            let variables = match qualifier {
+                "attribute" => { &mut interface.attributes },
+                "uniform" => { &mut interface.uniforms },
+                _ => { &mut interface.varyings }
+            };
+
+            // A variable can be declared in more than one branch of a
+            // preprocessor conditional, but only the first declaration
+            // is part of the interface
+            let existing = variables.iter().position(|v| {
+                v.name == variable.name
+            });
+            match existing {
+                Some(existing) => {
+                    let other = &variables[existing];
+                    if other.ty != variable.ty ||
+                       other.array_size != variable.array_size
+                    {
+                        _error(&mut errors, &name,
+                               format!("`{}` is declared as `{}`, but was \
+                                        declared as `{}` on line {}",
+                                       name.text,
+                                       _type_name(&variable),
+                                       _type_name(other),
+                                       other.line));
+                    }
+                },
+                None => { variables.push(variable); }
+            }
+
+            if code.get(next).map_or(false, |t| t.is_punct(',')) {
+                next += 1;
+            }
+            else {
+                break;
+            }
+        }
+    }
+
+    if ty == ShaderType::FragmentShader && !has_float_precision {
+        if let Some(float) = unqualified_float {
+            _error(&mut errors, &float,
+                   "fragment shaders must declare a default precision \
+                    for `float` (such as `precision mediump float;`)"
+                   .to_owned());
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(interface)
+    }
+    else {
+        errors.sort_by_key(|e| (e.line, e.column));
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shader::ShaderType;
+    use super::*;
+
+    #[test]
+    fn uniform_declared_in_both_branches() {
+        let source = "#version 100
+precision mediump float;
+#ifdef TINTED
+uniform highp vec4 color;
+#else
+uniform lowp vec4 color;
+#endif
+void main() {
+    gl_FragColor = color;
+}
+";
+        let interface = validate_es100(source, ShaderType::FragmentShader)
+            .unwrap();
+        assert_eq!(interface.uniforms.len(), 1);
+        assert_eq!(interface.uniforms[0].name, "color");
+        assert_eq!(interface.uniforms[0].line, 4);
+    }
+
+    #[test]
+    fn uniform_declared_with_different_types() {
+        let source = "#version 100
+precision mediump float;
+#ifdef TINTED
+uniform vec4 color;
+#else
+uniform vec3 color;
+#endif
+void main() {
+    gl_FragColor = vec4(1.0);
+}
+";
+        let errors = validate_es100(source, ShaderType::FragmentShader)
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 6);
+        assert_eq!(errors[0].message,
+                   "`color` is declared as `vec3`, but was declared as \
+                    `vec4` on line 4");
+    }
+
+    #[test]
+    fn array_declared_with_different_sizes() {
+        let source = "#version 100
+#if defined(SKINNED)
+uniform mat4 bones[32];
+#elif defined(MORPHED)
+uniform mat4 bones[16];
+#endif
+attribute vec4 position;
+void main() {
+    gl_Position = position;
+}
+";
+        let errors = validate_es100(source, ShaderType::VertexShader)
+            .unwrap_err();
+        assert_eq!(errors[0].message,
+                   "`bones` is declared as `mat4[16]`, but was declared as \
+                    `mat4[32]` on line 3");
+    }
+
+    #[test]
+    fn same_name_with_different_qualifiers() {
+        let source = "#version 100
+attribute vec4 position;
+varying vec2 uv;
+#ifdef FIXED_UV
+uniform vec2 uv;
+#endif
+void main() {
+    gl_Position = position;
+}
+";
+        let interface = validate_es100(source, ShaderType::VertexShader)
+            .unwrap();
+        assert_eq!(interface.varyings.len(), 1);
+        assert_eq!(interface.uniforms.len(), 1);
+    }
+
+    #[test]
+    fn macros_are_declared() {
+        let source = "#version 100
+#define LIGHTS 4
+uniform vec3 lights[LIGHTS];
+attribute vec4 position;
+void main() {
+    gl_Position = position + vec4(lights[LIGHTS - 1], 0.0);
+}
+";
+        let interface = validate_es100(source, ShaderType::VertexShader)
+            .unwrap();
+        assert_eq!(interface.uniforms[0].array_size, Some(4));
+    }
+
+    fn _errors(source: &str, ty: ShaderType) -> Vec<(usize, usize, String)> {
+        validate_es100(source, ty).unwrap_err()
+                                  .into_iter()
+                                  .map(|e| (e.line, e.column, e.message))
+                                  .collect()
+    }
+
+    #[test]
+    fn undeclared_identifier() {
+        let source = "#version 100
+attribute vec4 position;
+void main() {
+    gl_Position = position * scale;
+}
+";
+        assert_eq!(_errors(source, ShaderType::VertexShader),
+                   vec![(4, 30, "undeclared identifier `scale`".to_owned())]);
+    }
+
+    #[test]
+    fn builtin_from_other_stage() {
+        let source = "#version 100
+precision mediump float;
+void main() {
+    gl_Position = vec4(0.0);
+    gl_FragColor = vec4(1.0);
+}
+";
+        assert_eq!(_errors(source, ShaderType::FragmentShader),
+                   vec![(4, 5, "`gl_Position` can only be used in vertex \
+                                shaders".to_owned())]);
+
+        let source = "#version 100
+void main() {
+  gl_Position = gl_FragCoord;
+}
+";
+        assert_eq!(_errors(source, ShaderType::VertexShader),
+                   vec![(3, 17, "`gl_FragCoord` can only be used in \
+                                 fragment shaders".to_owned())]);
+    }
+
+    #[test]
+    fn missing_main() {
+        let source = "#version 100
+attribute vec4 position;
+void run() {
+    gl_Position = position;
+}
+";
+        assert_eq!(_errors(source, ShaderType::VertexShader),
+                   vec![(6, 1, "missing `void main()` function".to_owned())]);
+
+        // Without a trailing newline, the error is reported after the
+        // last token
+        let source = "#version 100\nvoid mian() { }";
+        assert_eq!(_errors(source, ShaderType::VertexShader),
+                   vec![(2, 16, "missing `void main()` function".to_owned())]);
+    }
+
+    #[test]
+    fn unbalanced_brackets() {
+        let source = "#version 100
+attribute vec4 position;
+void main() {
+    gl_Position = vec4(position.xyz, 1.0;
+}
+";
+        assert_eq!(_errors(source, ShaderType::VertexShader),
+                   vec![(5, 1, "found `}`, but `(` from line 4 is not \
+                                closed".to_owned())]);
+
+        let source = "#version 100
+attribute vec4 position;
+void main() {
+    gl_Position = position;
+}
+}
+";
+        assert_eq!(_errors(source, ShaderType::VertexShader),
+                   vec![(6, 1, "unmatched `}`".to_owned())]);
+
+        let source = "#version 100
+attribute vec4 position;
+void main() {
+    gl_Position = position;
+";
+        assert_eq!(_errors(source, ShaderType::VertexShader),
+                   vec![(3, 13, "`{` is never closed".to_owned())]);
+    }
+
+    #[test]
+    fn missing_float_precision() {
+        let source = "#version 100
+varying vec2 uv;
+void main() {
+    gl_FragColor = vec4(uv, 0.0, 1.0);
+}
+";
+        assert_eq!(_errors(source, ShaderType::FragmentShader),
+                   vec![(2, 9, "fragment shaders must declare a default \
+                                precision for `float` (such as \
+                                `precision mediump float;`)".to_owned())]);
+
+        // Qualified declarations don't need a default precision, and
+        // vertex shaders always have one
+        let qualified = source.replace("varying vec2", "varying mediump vec2");
+        assert!(validate_es100(&qualified, ShaderType::FragmentShader).is_ok());
+        let vertex = "#version 100
+varying vec2 uv;
+void main() {
+    uv = vec2(0.0);
+    gl_Position = vec4(uv, 0.0, 1.0);
+}
+";
+        assert!(validate_es100(vertex, ShaderType::VertexShader).is_ok());
+    }
+
+    #[test]
+    fn columns_count_characters() {
+        let source = "#version 100
+attribute vec4 position;
+void main() {
+    /* \u{e9}\u{e9} */ gl_Position = positon;
+}
+";
+        assert_eq!(_errors(source, ShaderType::VertexShader),
+                   vec![(4, 28, "undeclared identifier `positon`".to_owned())]);
+    }
+}
@@ -13,6 +13,12 @@ use gl;
 /// method.
 ///
 /// See [`Pixels`](struct.Pixels.html) for a simple `Image2d`.
+///
+/// When the `image` feature is enabled, `Image2d` is also implemented for
+/// `image::DynamicImage` and for `image::ImageBuffer`s of `Rgba<u8>`,
+/// `Rgb<u8>`, `Luma<u8>`, and `LumaA<u8>` pixels. These are uploaded
+/// directly from the image's buffer (without converting or copying it),
+/// using the texel format that matches the image's channels.
 pub trait Image2d {
     /// Get the width of the image, in texels.
     fn width(&self) -> usize;
@@ -25,7 +31,20 @@ pub trait Image2d {
     fn format(&self) -> ImageFormat;
 
     /// Get the raw texel data of the image data, as a `u8` slice.
+    ///
+    /// Each row of the image starts [`row_stride`]
+    /// (trait.Image2d.html#method.row_stride) bytes after the start of
+    /// the previous row, and the slice must contain at least the texels
+    /// of the last row.
     fn texel_bytes(&self) -> &[u8];
+
+    /// Get the number of bytes between the start of one row of the image
+    /// and the start of the next row in [`texel_bytes`]
+    /// (trait.Image2d.html#tymethod.texel_bytes). By default, rows are
+    /// assumed to be tightly packed, with no padding between them.
+    fn row_stride(&self) -> usize {
+        self.width() * self.format().texel_size()
+    }
 }
 
 /// A single OpenGL color value, with `u8` components laid out
@@ -259,7 +278,17 @@ gl_enum! {
 
         /// Each texel is 16 bits, with 5-bit/5-bit/5-bit/1-bit components.
         pub const UnsignedShort5551 as UNSIGNED_SHORT_5_5_5_1 =
-            gl::UNSIGNED_SHORT_5_5_5_1
+            gl::UNSIGNED_SHORT_5_5_5_1,
+
+        /// Each component is a 32-bit float. Requires the
+        /// `GL_OES_texture_float` extension on OpenGL ES 2.
+        pub const Float as FLOAT_TEXEL = gl::FLOAT,
+
+        /// Each component is a 16-bit (half-precision) float. Requires
+        /// the `GL_OES_texture_half_float` extension on OpenGL ES, or
+        /// OpenGL 3.0 (or `GL_ARB_half_float_pixel`) on desktop OpenGL,
+        /// where the texels are uploaded as `GL_HALF_FLOAT` instead.
+        pub const HalfFloatOes as HALF_FLOAT_OES = 0x8D61
     }
 }
 
@@ -273,7 +302,15 @@ gl_enum! {
         pub const RGB as RGB = gl::RGB,
 
         /// A texel contains red, green, blue, and alpha components.
-        pub const RGBA as RGBA = gl::RGBA
+        pub const RGBA as RGBA = gl::RGBA,
+
+        /// A texel contains a single luminance component, which is
+        /// used as the red, green, and blue components when sampled.
+        pub const Luminance as LUMINANCE = 0x1909,
+
+        /// A texel contains a luminance component (used as the red,
+        /// green, and blue components when sampled) and an alpha component.
+        pub const LuminanceAlpha as LUMINANCE_ALPHA = 0x190A
     }
 }
 
@@ -300,7 +337,7 @@ gl_enum! {
 }
 
 /// A type that contains the texel type and format that make up a 2D image.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ImageFormat {
     /// The texel type of the image.
     pub texel_type: TexelType,
@@ -317,4 +354,248 @@ impl ImageFormat {
             texel_format: TexelFormat::RGBA
         }
     }
+
+    /// Returns the size of a single texel with this format, in bytes.
+    ///
+    /// # Examples
+    /// ```
+    /// use glitter::{ImageFormat, TexelType, TexelFormat};
+    ///
+    /// assert_eq!(ImageFormat::rgba8().texel_size(), 4);
+    ///
+    /// let rgb565 = ImageFormat {
+    ///     texel_type: TexelType::UnsignedShort565,
+    ///     texel_format: TexelFormat::RGB
+    /// };
+    /// assert_eq!(rgb565.texel_size(), 2);
+    ///
+    /// let rgba_f32 = ImageFormat {
+    ///     texel_type: TexelType::Float,
+    ///     texel_format: TexelFormat::RGBA
+    /// };
+    /// assert_eq!(rgba_f32.texel_size(), 16);
+    /// ```
+    pub fn texel_size(&self) -> usize {
+        let components = match self.texel_format {
+            TexelFormat::Alpha | TexelFormat::Luminance => 1,
+            TexelFormat::LuminanceAlpha => 2,
+            TexelFormat::RGB => 3,
+            TexelFormat::RGBA => 4
+        };
+
+        match self.texel_type {
+            TexelType::UnsignedByte => components,
+            TexelType::Float => components * 4,
+            TexelType::HalfFloatOes => components * 2,
+            TexelType::UnsignedShort565
+            | TexelType::UnsignedShort4444
+            | TexelType::UnsignedShort5551 => 2
+        }
+    }
+}
+
+/// A rectangular region copied out of another [`Image2d`]
+/// (trait.Image2d.html), which can be used to upload part of an
+/// image with [`gl.tex_sub_image_2d`]
+/// (../context/texture_context/trait.ContextTextureExt.html#method.tex_sub_image_2d).
+///
+/// To refer to part of an image without copying it, use
+/// [`ImageView::sub_view`](struct.ImageView.html#method.sub_view) instead.
+///
+/// # Example
+///
+/// ```
+/// use glitter::{Image2d, Pixels, Pixel, SubImage2d};
+///
+/// let mut atlas = Pixels::new(64, 64);
+/// atlas[10][20] = Pixel::rgb(0xFF0000);
+///
+/// let glyph = SubImage2d::new(&atlas, 16, 8, 8, 4).unwrap();
+/// assert_eq!((glyph.width(), glyph.height()), (8, 4));
+/// assert_eq!(&glyph.texel_bytes()[(2*8 + 4)*4..][..4], &[0xFF, 0, 0, 0xFF]);
+///
+/// assert!(SubImage2d::new(&atlas, 60, 0, 8, 8).is_none());
+/// ```
+#[derive(Clone)]
+pub struct SubImage2d {
+    width: usize,
+    height: usize,
+    format: ImageFormat,
+    bytes: Vec<u8>
+}
+
+impl SubImage2d {
+    /// Copy the `width` by `height` region of `image` whose top-left
+    /// corner is at (`x`, `y`). Returns `None` if the region is not
+    /// within the bounds of the image.
+    pub fn new(image: &Image2d, x: usize, y: usize, width: usize, height: usize)
+        -> Option<Self>
+    {
+        if x + width > image.width() || y + height > image.height() {
+            return None;
+        }
+
+        let format = image.format();
+        let texel_size = format.texel_size();
+        let src_row = image.row_stride();
+        let row = width * texel_size;
+        let src = image.texel_bytes();
+
+        let mut bytes = Vec::with_capacity(row * height);
+        for src_y in y..(y + height) {
+            let start = src_y * src_row + x * texel_size;
+            bytes.extend_from_slice(&src[start..(start + row)]);
+        }
+
+        Some(SubImage2d {
+            width: width,
+            height: height,
+            format: format,
+            bytes: bytes
+        })
+    }
+}
+
+impl Image2d for SubImage2d {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn format(&self) -> ImageFormat {
+        self.format
+    }
+
+    fn texel_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// A borrowed view of 2D image data, where each row may be followed by
+/// padding. An `ImageView` can describe image data that wasn't created by
+/// glitter (such as a frame from a video decoder), or a rectangular region
+/// of a larger image, without copying it.
+///
+/// # Example
+///
+/// ```
+/// use glitter::{Image2d, ImageView, ImageFormat, TexelType, TexelFormat};
+///
+/// // A 3x2 RGB image, where each row is padded to 12 bytes
+/// let bytes = [0u8; 24];
+/// let rgb8 = ImageFormat {
+///     texel_type: TexelType::UnsignedByte,
+///     texel_format: TexelFormat::RGB
+/// };
+/// let view = ImageView::new(&bytes, 3, 2, rgb8, 12).unwrap();
+/// assert_eq!(view.row_stride(), 12);
+///
+/// // The 2x1 region in the bottom-right corner
+/// let corner = view.sub_view(1, 1, 2, 1).unwrap();
+/// assert_eq!(corner.texel_bytes().len(), 6);
+/// ```
+#[derive(Clone, Copy)]
+pub struct ImageView<'a> {
+    bytes: &'a [u8],
+    width: usize,
+    height: usize,
+    format: ImageFormat,
+    stride: usize
+}
+
+impl<'a> ImageView<'a> {
+    /// Create a view over `bytes`, which holds `height` rows of `width`
+    /// texels each, with each row starting `stride` bytes after the
+    /// previous one. Returns `None` if `stride` is smaller than a row of
+    /// texels, or if `bytes` is too short to contain every row.
+    pub fn new(bytes: &'a [u8],
+               width: usize,
+               height: usize,
+               format: ImageFormat,
+               stride: usize)
+        -> Option<Self>
+    {
+        let row = width * format.texel_size();
+        if stride < row {
+            return None;
+        }
+        if height > 0 && bytes.len() < (height - 1) * stride + row {
+            return None;
+        }
+
+        Some(ImageView {
+            bytes: bytes,
+            width: width,
+            height: height,
+            format: format,
+            stride: stride
+        })
+    }
+
+    /// Create a view over all of the image data of an `Image2d`.
+    pub fn of(image: &'a Image2d) -> Self {
+        ImageView {
+            bytes: image.texel_bytes(),
+            width: image.width(),
+            height: image.height(),
+            format: image.format(),
+            stride: image.row_stride()
+        }
+    }
+
+    /// Create a view of the `width` by `height` region of this view whose
+    /// top-left corner is at (`x`, `y`). Returns `None` if the region is
+    /// not within the bounds of this view.
+    pub fn sub_view(&self, x: usize, y: usize, width: usize, height: usize)
+        -> Option<ImageView<'a>>
+    {
+        if x + width > self.width || y + height > self.height {
+            return None;
+        }
+
+        let all_bytes: &'a [u8] = self.bytes;
+        let start = y * self.stride + x * self.format.texel_size();
+        let bytes = if width > 0 && height > 0 {
+            let end = start
+                    + (height - 1) * self.stride
+                    + width * self.format.texel_size();
+            &all_bytes[start..end]
+        }
+        else {
+            &all_bytes[0..0]
+        };
+
+        Some(ImageView {
+            bytes: bytes,
+            width: width,
+            height: height,
+            format: self.format,
+            stride: self.stride
+        })
+    }
+}
+
+impl<'a> Image2d for ImageView<'a> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn format(&self) -> ImageFormat {
+        self.format
+    }
+
+    fn texel_bytes(&self) -> &[u8] {
+        self.bytes
+    }
+
+    fn row_stride(&self) -> usize {
+        self.stride
+    }
 }
@@ -1,5 +1,9 @@
+use std::ops::Deref;
 use image;
 use image_data;
+use image_data::{Image2d, ImageFormat, TexelType, TexelFormat};
+use pixel_formats::{PixelsRgb8, PixelsRgb565, PixelsRgba4444, PixelsRgba5551,
+                    PixelsAlpha8, PixelsLuminance8, PixelsLuminanceAlpha8};
 
 impl<P: image::Pixel<Subpixel=u8>> From<P> for image_data::Pixel {
     fn from(pixel: P) -> image_data::Pixel {
@@ -17,9 +21,256 @@ impl<'a, I, P> From<I> for image_data::Pixels
         let (w, h) = img.dimensions();
         let mut pixels = image_data::Pixels::new(w as usize, h as usize);
         for (x, y, pixel) in img.pixels() {
-            pixels[x as usize][y as usize] = pixel.into();
+            pixels[y as usize][x as usize] = pixel.into();
         }
 
         pixels
     }
 }
+
+// Implement `Image2d` for an `ImageBuffer` of 8-bit pixels, which
+// uploads the buffer's data directly, without copying it.
+macro_rules! impl_image_2d_for_buffer {
+    ($pixel:ident, $texel_format:ident) => {
+        impl<C> Image2d for image::ImageBuffer<image::$pixel<u8>, C>
+            where C: Deref<Target=[u8]>
+        {
+            fn width(&self) -> usize {
+                image::ImageBuffer::width(self) as usize
+            }
+
+            fn height(&self) -> usize {
+                image::ImageBuffer::height(self) as usize
+            }
+
+            fn format(&self) -> ImageFormat {
+                ImageFormat {
+                    texel_type: TexelType::UnsignedByte,
+                    texel_format: TexelFormat::$texel_format
+                }
+            }
+
+            fn texel_bytes(&self) -> &[u8] {
+                &**self
+            }
+        }
+    }
+}
+
+impl_image_2d_for_buffer!(Rgba, RGBA);
+impl_image_2d_for_buffer!(Rgb, RGB);
+impl_image_2d_for_buffer!(Luma, Luminance);
+impl_image_2d_for_buffer!(LumaA, LuminanceAlpha);
+
+// A `DynamicImage` is uploaded with the format that matches its
+// channels, so (for example) a grayscale PNG is uploaded as a
+// `LUMINANCE` texture instead of being expanded to RGBA.
+impl Image2d for image::DynamicImage {
+    fn width(&self) -> usize {
+        match *self {
+            image::DynamicImage::ImageLuma8(ref img) => Image2d::width(img),
+            image::DynamicImage::ImageLumaA8(ref img) => Image2d::width(img),
+            image::DynamicImage::ImageRgb8(ref img) => Image2d::width(img),
+            image::DynamicImage::ImageRgba8(ref img) => Image2d::width(img)
+        }
+    }
+
+    fn height(&self) -> usize {
+        match *self {
+            image::DynamicImage::ImageLuma8(ref img) => Image2d::height(img),
+            image::DynamicImage::ImageLumaA8(ref img) => Image2d::height(img),
+            image::DynamicImage::ImageRgb8(ref img) => Image2d::height(img),
+            image::DynamicImage::ImageRgba8(ref img) => Image2d::height(img)
+        }
+    }
+
+    fn format(&self) -> ImageFormat {
+        match *self {
+            image::DynamicImage::ImageLuma8(ref img) => img.format(),
+            image::DynamicImage::ImageLumaA8(ref img) => img.format(),
+            image::DynamicImage::ImageRgb8(ref img) => img.format(),
+            image::DynamicImage::ImageRgba8(ref img) => img.format()
+        }
+    }
+
+    fn texel_bytes(&self) -> &[u8] {
+        match *self {
+            image::DynamicImage::ImageLuma8(ref img) => img.texel_bytes(),
+            image::DynamicImage::ImageLumaA8(ref img) => img.texel_bytes(),
+            image::DynamicImage::ImageRgb8(ref img) => img.texel_bytes(),
+            image::DynamicImage::ImageRgba8(ref img) => img.texel_bytes()
+        }
+    }
+}
+
+/// A trait for images that can be converted to an `image::ImageBuffer`,
+/// such as [`Pixels`](struct.Pixels.html) and the typed
+/// buffers in the [`pixel_formats`](pixel_formats/index.html) module.
+/// Only available with the `image` feature.
+///
+/// # Example
+///
+/// ```
+/// use glitter::{Pixels, Pixel, ToImageBuffer};
+///
+/// let mut pixels = Pixels::new(1, 2);
+/// pixels[0][0] = Pixel::rgb(0xFF0000);
+///
+/// let image = pixels.to_image_buffer();
+/// assert_eq!(image.get_pixel(0, 0).data, [0xFF, 0x00, 0x00, 0xFF]);
+///
+/// // OpenGL images start from the bottom row, so flip them vertically
+/// // before saving them to a file
+/// let flipped = pixels.to_image_buffer_flipped();
+/// assert_eq!(flipped.get_pixel(0, 1).data, [0xFF, 0x00, 0x00, 0xFF]);
+/// ```
+pub trait ToImageBuffer {
+    /// The pixel type of the converted `ImageBuffer`.
+    type Pixel: image::Pixel<Subpixel=u8> + 'static;
+
+    /// Convert the image to an `ImageBuffer`, keeping the order of its rows.
+    fn to_image_buffer(&self) -> image::ImageBuffer<Self::Pixel, Vec<u8>>;
+
+    /// Convert the image to an `ImageBuffer`, reversing the order of
+    /// its rows. This converts from OpenGL's bottom-left origin (such
+    /// as an image returned from [`gl.read_pixels`]
+    /// (context/framebuffer_context/trait.ContextFramebufferExt.html#method.read_pixels))
+    /// to the top-left origin that image files use.
+    fn to_image_buffer_flipped(&self)
+        -> image::ImageBuffer<Self::Pixel, Vec<u8>>;
+}
+
+// Implement `ToImageBuffer` for an image whose texels already have the
+// same layout as `$pixel`.
+macro_rules! impl_to_image_buffer {
+    ($name:ty, $pixel:ident) => {
+        impl ToImageBuffer for $name {
+            type Pixel = image::$pixel<u8>;
+
+            fn to_image_buffer(&self)
+                -> image::ImageBuffer<image::$pixel<u8>, Vec<u8>>
+            {
+                _to_image_buffer(self, false)
+            }
+
+            fn to_image_buffer_flipped(&self)
+                -> image::ImageBuffer<image::$pixel<u8>, Vec<u8>>
+            {
+                _to_image_buffer(self, true)
+            }
+        }
+    }
+}
+
+// Implement `ToImageBuffer` for a packed 16-bit image, which is
+// expanded to 8-bit RGBA first.
+macro_rules! impl_to_image_buffer_via_pixels {
+    ($name:ty) => {
+        impl ToImageBuffer for $name {
+            type Pixel = image::Rgba<u8>;
+
+            fn to_image_buffer(&self)
+                -> image::ImageBuffer<image::Rgba<u8>, Vec<u8>>
+            {
+                _to_image_buffer(&self.to_pixels(), false)
+            }
+
+            fn to_image_buffer_flipped(&self)
+                -> image::ImageBuffer<image::Rgba<u8>, Vec<u8>>
+            {
+                _to_image_buffer(&self.to_pixels(), true)
+            }
+        }
+    }
+}
+
+impl_to_image_buffer!(image_data::Pixels, Rgba);
+impl_to_image_buffer!(PixelsRgb8, Rgb);
+impl_to_image_buffer!(PixelsLuminance8, Luma);
+impl_to_image_buffer!(PixelsLuminanceAlpha8, LumaA);
+
+// An alpha-only image (such as a mask) is converted to a grayscale image,
+// since `image` has no alpha-only pixel type
+impl_to_image_buffer!(PixelsAlpha8, Luma);
+
+impl_to_image_buffer_via_pixels!(PixelsRgb565);
+impl_to_image_buffer_via_pixels!(PixelsRgba4444);
+impl_to_image_buffer_via_pixels!(PixelsRgba5551);
+
+// Copy the rows of `img` into a new `ImageBuffer`, optionally in
+// reverse order. `P` must have the same texel layout as `img`.
+fn _to_image_buffer<P>(img: &Image2d, flip: bool)
+    -> image::ImageBuffer<P, Vec<u8>>
+    where P: image::Pixel<Subpixel=u8> + 'static
+{
+    let (width, height) = (img.width(), img.height());
+    let row_size = width * img.format().texel_size();
+    let stride = img.row_stride();
+    let bytes = img.texel_bytes();
+
+    let mut data = Vec::with_capacity(row_size * height);
+    for row in 0..height {
+        let src_row = if flip { height - row - 1 } else { row };
+        let start = src_row * stride;
+        data.extend_from_slice(&bytes[start..start + row_size]);
+    }
+
+    image::ImageBuffer::from_raw(width as u32, height as u32, data)
+        .expect("Image data does not match the ImageBuffer's pixel type")
+}
+
+#[cfg(test)]
+mod tests {
+    use image;
+    use image_data::{Pixels, Pixel};
+    use pixel_formats::{PixelsRgb8, PixelsLuminance8};
+    use super::*;
+
+    // A 3x2 image where every texel has a different value in each
+    // component
+    fn _pixels() -> Pixels {
+        let mut pixels = Pixels::new(3, 2);
+        for y in 0..2 {
+            for x in 0..3 {
+                let i = (y * 3 + x) as u8;
+                pixels[y][x] = Pixel::r_g_b_a(i, 0x10 + i, 0x20 + i, 0x30 + i);
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn test_pixels_to_image_buffer() {
+        let image = _pixels().to_image_buffer();
+        assert_eq!(image.dimensions(), (3, 2));
+        assert_eq!(image.get_pixel(0, 0).data, [0x00, 0x10, 0x20, 0x30]);
+        assert_eq!(image.get_pixel(2, 0).data, [0x02, 0x12, 0x22, 0x32]);
+        assert_eq!(image.get_pixel(1, 1).data, [0x04, 0x14, 0x24, 0x34]);
+    }
+
+    #[test]
+    fn test_pixels_to_image_buffer_flipped() {
+        // The bottom row of the image is the first row of the pixels
+        let image = _pixels().to_image_buffer_flipped();
+        assert_eq!(image.dimensions(), (3, 2));
+        assert_eq!(image.get_pixel(0, 1).data, [0x00, 0x10, 0x20, 0x30]);
+        assert_eq!(image.get_pixel(2, 1).data, [0x02, 0x12, 0x22, 0x32]);
+        assert_eq!(image.get_pixel(0, 0).data, [0x03, 0x13, 0x23, 0x33]);
+        assert_eq!(image.get_pixel(2, 0).data, [0x05, 0x15, 0x25, 0x35]);
+    }
+
+    #[test]
+    fn test_typed_pixels_to_image_buffer() {
+        let rgb = PixelsRgb8::from_pixels(&_pixels());
+        let image: image::ImageBuffer<image::Rgb<u8>, _> = rgb.to_image_buffer_flipped();
+        assert_eq!(image.dimensions(), (3, 2));
+        assert_eq!(image.get_pixel(1, 0).data, [0x04, 0x14, 0x24]);
+        assert_eq!(image.get_pixel(1, 1).data, [0x01, 0x11, 0x21]);
+
+        let mut gray = PixelsLuminance8::new(3, 2);
+        gray[0][2] = 0x7F;
+        let image = gray.to_image_buffer_flipped();
+        assert_eq!(image.get_pixel(2, 1).data, [0x7F]);
+        assert_eq!(image.get_pixel(2, 0).data, [0x00]);
+    }
+}
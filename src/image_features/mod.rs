@@ -1 +1,5 @@
 mod image_conversions;
+mod screenshot;
+
+pub use self::image_conversions::*;
+pub use self::screenshot::*;
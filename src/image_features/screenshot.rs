@@ -0,0 +1,44 @@
+use std::io;
+use std::fs::File;
+use std::path::Path;
+use image;
+use context::{BaseContext, ContextExt, ContextFramebufferExt};
+use image_data::Image2d;
+use super::image_conversions::ToImageBuffer;
+
+/// An extension trait that saves the contents of the framebuffer to an
+/// image file. Only available with the `image` feature.
+pub trait ContextScreenshotExt: BaseContext + Sized {
+    /// Read the current viewport of the currently-bound framebuffer
+    /// (with [`gl.read_pixels`]
+    /// (context/framebuffer_context/trait.ContextFramebufferExt.html#method.read_pixels)),
+    /// and save it as a PNG file at `path`. The image is flipped vertically,
+    /// so the top row of the viewport is the top row of the file.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use glitter::prelude::*;
+    /// # let gl = unsafe { glitter::Context::current_context() };
+    /// gl.save_screenshot("screenshot.png").unwrap();
+    /// ```
+    fn save_screenshot<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let viewport = self.get_viewport();
+        let pixels = self.read_pixels(viewport.x,
+                                      viewport.y,
+                                      viewport.width as usize,
+                                      viewport.height as usize);
+        let flipped = pixels.to_image_buffer_flipped();
+
+        let file = try!(File::create(path));
+        let encoder = image::png::PNGEncoder::new(file);
+        encoder.encode(&flipped,
+                       pixels.width() as u32,
+                       pixels.height() as u32,
+                       image::ColorType::RGBA(8))
+    }
+}
+
+impl<C: BaseContext> ContextScreenshotExt for C {
+
+}
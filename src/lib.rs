@@ -70,11 +70,21 @@ mod to_ref;
 pub mod context;
 pub mod buffer;
 pub mod shader;
+pub mod glsl;
+pub mod shader_watcher;
 pub mod program;
+pub mod program_cache;
+pub mod program_binary_cache;
 pub mod framebuffer;
 pub mod renderbuffer;
 pub mod texture;
+pub mod cube_map;
+pub mod compressed_image;
+pub mod compressed_decoding;
+pub mod texture_file;
 pub mod image_data;
+pub mod pixel_formats;
+pub mod mipmap;
 pub mod vertex_data;
 pub mod vertex_buffer;
 pub mod index_data;
@@ -88,17 +98,28 @@ pub mod types;
 pub use context::*;
 pub use buffer::*;
 pub use shader::*;
+pub use shader_watcher::*;
 pub use program::*;
+pub use program_cache::*;
+pub use program_binary_cache::*;
 pub use framebuffer::*;
 pub use renderbuffer::*;
 pub use texture::*;
+pub use cube_map::*;
+pub use compressed_image::*;
+pub use compressed_decoding::*;
+pub use texture_file::*;
 pub use image_data::*;
+pub use pixel_formats::*;
+pub use mipmap::*;
 pub use vertex_data::*;
 pub use vertex_buffer::*;
 pub use index_data::*;
 pub use uniform_data::*;
 pub use types::*;
 
+#[cfg(feature = "image")] pub use image_features::*;
+
 /// Re-exports essential extension traits. Everything exported in this module
 /// should be used anywhere that glitter is used.
 ///
@@ -127,4 +148,5 @@ pub mod prelude {
     pub use vertex_buffer::{VertexBufferContext, IndexBufferContext,
                             ContextVertexBufferExt};
     pub use types::GLObject;
+    #[cfg(feature = "image")] pub use image_features::ContextScreenshotExt;
 }
@@ -0,0 +1,168 @@
+//! Contains functions for generating mipmaps on the CPU.
+
+use image_data::{Image2d, Pixel, Pixels};
+
+/// The filters that can be used to downsample an image when generating
+/// mipmaps on the CPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownsampleFilter {
+    /// Average the texels that make up each destination texel,
+    /// treating color components as linear values.
+    Box,
+
+    /// Average the texels that make up each destination texel, treating
+    /// color components as sRGB-encoded values. The red, green, and blue
+    /// components are converted to linear space before averaging and back
+    /// to sRGB afterwards, which keeps mipmaps of sRGB images from
+    /// darkening. The alpha component is always averaged linearly.
+    GammaCorrect
+}
+
+impl Pixels {
+    /// Downsample the image to half its width and height (rounding down,
+    /// with a minimum of 1), using the given filter.
+    ///
+    /// When a dimension is odd, each destination texel averages every
+    /// source texel that it overlaps, so no source texels are skipped.
+    ///
+    /// # Examples
+    /// ```
+    /// use glitter::{Image2d, Pixels, Pixel, DownsampleFilter};
+    ///
+    /// let mut pixels = Pixels::new(2, 2);
+    /// pixels[0][0] = Pixel::rgb(0xFFFFFF);
+    /// pixels[1][1] = Pixel::rgb(0xFFFFFF);
+    ///
+    /// let half = pixels.downsample(DownsampleFilter::Box);
+    /// assert_eq!((half.width(), half.height()), (1, 1));
+    /// assert_eq!(half[0][0].r, 0x80);
+    ///
+    /// let half = pixels.downsample(DownsampleFilter::GammaCorrect);
+    /// assert_eq!(half[0][0].r, 0xBC);
+    /// ```
+    pub fn downsample(&self, filter: DownsampleFilter) -> Pixels {
+        let (src_width, src_height) = (self.width(), self.height());
+        let width = if src_width > 1 { src_width / 2 } else { 1 };
+        let height = if src_height > 1 { src_height / 2 } else { 1 };
+
+        let to_linear = match filter {
+            DownsampleFilter::Box => None,
+            DownsampleFilter::GammaCorrect => Some(_srgb_to_linear_table())
+        };
+        let decode = |component: u8| {
+            match to_linear {
+                Some(ref table) => table[component as usize],
+                None => component as f32 / 255.0
+            }
+        };
+        let encode = |value: f32| {
+            let value = match to_linear {
+                Some(_) => _linear_to_srgb(value),
+                None => value
+            };
+            _to_u8(value)
+        };
+
+        let mut dst = Pixels::new(width, height);
+        for y in 0..height {
+            let (y0, y1) = _source_range(y, height, src_height);
+            for x in 0..width {
+                let (x0, x1) = _source_range(x, width, src_width);
+
+                let mut sum = [0.0; 4];
+                for src_y in y0..y1 {
+                    for src_x in x0..x1 {
+                        let pixel = self[src_y][src_x];
+                        sum[0] += decode(pixel.r);
+                        sum[1] += decode(pixel.g);
+                        sum[2] += decode(pixel.b);
+                        sum[3] += pixel.a as f32 / 255.0;
+                    }
+                }
+
+                let count = ((x1 - x0) * (y1 - y0)) as f32;
+                dst[y][x] = Pixel::r_g_b_a(encode(sum[0] / count),
+                                           encode(sum[1] / count),
+                                           encode(sum[2] / count),
+                                           _to_u8(sum[3] / count));
+            }
+        }
+
+        dst
+    }
+
+    /// Generate every mipmap level below this image, down to a 1x1 image,
+    /// using the given filter. The returned list starts at mipmap level 1,
+    /// and can be passed to [`Texture2dBuilder::mipmaps`]
+    /// (../context/texture_context/struct.Texture2dBuilder.html#method.mipmaps).
+    ///
+    /// # Examples
+    /// ```
+    /// use glitter::{Image2d, Pixels, DownsampleFilter};
+    ///
+    /// let pixels = Pixels::new(8, 2);
+    /// let mipmaps = pixels.generate_mipmaps(DownsampleFilter::Box);
+    ///
+    /// let sizes: Vec<_> = mipmaps.iter()
+    ///     .map(|level| (level.width(), level.height()))
+    ///     .collect();
+    /// assert_eq!(sizes, vec![(4, 1), (2, 1), (1, 1)]);
+    /// ```
+    pub fn generate_mipmaps(&self, filter: DownsampleFilter) -> Vec<Pixels> {
+        let mut levels: Vec<Pixels> = vec![];
+        loop {
+            let next = {
+                let last = levels.last().unwrap_or(self);
+                if last.width() <= 1 && last.height() <= 1 {
+                    break;
+                }
+                last.downsample(filter)
+            };
+            levels.push(next);
+        }
+        levels
+    }
+}
+
+// The range of source texels covered by the destination texel `dst`.
+fn _source_range(dst: usize, dst_size: usize, src_size: usize)
+    -> (usize, usize)
+{
+    let start = dst * src_size / dst_size;
+    let end = ((dst + 1) * src_size + dst_size - 1) / dst_size;
+    (start, end)
+}
+
+fn _srgb_to_linear_table() -> Vec<f32> {
+    (0..256).map(|component| {
+        let value = component as f32 / 255.0;
+        if value <= 0.04045 {
+            value / 12.92
+        }
+        else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    }).collect()
+}
+
+fn _linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    }
+    else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn _to_u8(value: f32) -> u8 {
+    let value = value * 255.0 + 0.5;
+    if value <= 0.0 {
+        0
+    }
+    else if value >= 255.0 {
+        255
+    }
+    else {
+        value as u8
+    }
+}
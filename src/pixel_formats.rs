@@ -0,0 +1,641 @@
+//! Contains typed 2D image buffers for each of the texel formats supported
+//! by OpenGL ES 2.0, and conversions between them.
+//!
+//! [`Pixels`](../image_data/struct.Pixels.html) stores every texel as
+//! 8-bit RGBA, which uses 4 bytes per texel. The buffers in this module
+//! store texels in smaller formats, such as [`PixelsRgb565`]
+//! (struct.PixelsRgb565.html) (2 bytes per texel) or [`PixelsAlpha8`]
+//! (struct.PixelsAlpha8.html) (1 byte per texel). Each buffer implements
+//! [`Image2d`](../image_data/trait.Image2d.html), so it can be uploaded
+//! to a texture directly.
+//!
+//! `Pixels` is used as the common format for conversions: every buffer
+//! can be created from `Pixels` with `from_pixels`, and converted back
+//! with `to_pixels`. The 16-bit buffers can also be created with
+//! `from_pixels_dithered`, which applies ordered dithering to hide the
+//! banding caused by reducing the number of bits per component.
+//!
+//! [`PixelsF32`](struct.PixelsF32.html) and [`PixelsF16`]
+//! (struct.PixelsF16.html) store RGBA texels as floats, which aren't
+//! limited to the range 0-1. They are useful for HDR images and for data
+//! textures, but can only be uploaded if the context supports float or
+//! half-float textures (see [`gl.supports_float_textures`]
+//! (../context/trait.ContextExt.html#method.supports_float_textures)).
+//! When they are converted to `Pixels`, each component is clamped to the
+//! range 0-1.
+//!
+//! # Example
+//!
+//! ```
+//! use glitter::{Image2d, Pixels, Pixel, PixelsRgb565, PixelsAlpha8};
+//!
+//! let mut pixels = Pixels::new(2, 1);
+//! pixels[0][0] = Pixel::rgb_a(0xFF8000, 0x40);
+//!
+//! let rgb565 = PixelsRgb565::from_pixels(&pixels);
+//! assert_eq!(rgb565.texel_bytes().len(), 4);
+//! assert_eq!(rgb565[0][0], 0b11111_100000_00000);
+//!
+//! let mask = PixelsAlpha8::from_pixels(&pixels);
+//! assert_eq!(mask[0][0], 0x40);
+//! ```
+
+use std::ops;
+use std::mem;
+use std::slice;
+use image_data::{Image2d, ImageFormat, TexelType, TexelFormat,
+                 Pixel, Pixels};
+
+macro_rules! typed_pixels {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident($texel:ty, $texel_type:ident, $texel_format:ident);
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone)]
+        pub struct $name {
+            width: usize,
+            height: usize,
+            texels: Vec<$texel>
+        }
+
+        impl $name {
+            /// Create a new image with the desired width and height, with
+            /// every texel initialized to zero.
+            pub fn new(width: usize, height: usize) -> Self {
+                $name {
+                    width: width,
+                    height: height,
+                    texels: vec![Default::default(); width * height]
+                }
+            }
+
+            /// Convert the image to 8-bit RGBA [`Pixels`]
+            /// (../image_data/struct.Pixels.html).
+            pub fn to_pixels(&self) -> Pixels {
+                Pixels::from_image(self)
+                    .expect("Every typed pixel format can be decoded")
+            }
+
+            // Create an image the same size as `pixels`, converting
+            // each pixel (given its x and y coordinates) with `convert`
+            fn map_pixels<F>(pixels: &Pixels, convert: F) -> Self
+                where F: Fn(Pixel, usize, usize) -> $texel
+            {
+                let (width, height) = (pixels.width(), pixels.height());
+                let mut converted = $name::new(width, height);
+                for y in 0..height {
+                    for x in 0..width {
+                        converted[y][x] = convert(pixels[y][x], x, y);
+                    }
+                }
+                converted
+            }
+        }
+
+        impl ops::Index<usize> for $name {
+            type Output = [$texel];
+
+            fn index(&self, row: usize) -> &[$texel] {
+                debug_assert!(row < self.height);
+
+                &self.texels[(row*self.width)..((row+1)*self.width)]
+            }
+        }
+
+        impl ops::IndexMut<usize> for $name {
+            fn index_mut(&mut self, row: usize) -> &mut [$texel] {
+                debug_assert!(row < self.height);
+
+                &mut self.texels[(row*self.width)..((row+1)*self.width)]
+            }
+        }
+
+        impl Image2d for $name {
+            fn width(&self) -> usize {
+                self.width
+            }
+
+            fn height(&self) -> usize {
+                self.height
+            }
+
+            fn format(&self) -> ImageFormat {
+                ImageFormat {
+                    texel_type: TexelType::$texel_type,
+                    texel_format: TexelFormat::$texel_format
+                }
+            }
+
+            fn texel_bytes(&self) -> &[u8] {
+                let len = self.texels.len() * mem::size_of::<$texel>();
+                unsafe {
+                    slice::from_raw_parts(self.texels.as_ptr() as *const u8,
+                                          len)
+                }
+            }
+        }
+    }
+}
+
+typed_pixels! {
+    /// A (heap-allocated) 2D image with 8-bit red, green, and blue
+    /// components, stored as `[r, g, b]`.
+    pub struct PixelsRgb8([u8; 3], UnsignedByte, RGB);
+}
+
+typed_pixels! {
+    /// A (heap-allocated) 2D image with 5-bit red, 6-bit green, and 5-bit
+    /// blue components, packed into a `u16` from the most significant bit.
+    pub struct PixelsRgb565(u16, UnsignedShort565, RGB);
+}
+
+typed_pixels! {
+    /// A (heap-allocated) 2D image with 4-bit red, green, blue, and alpha
+    /// components, packed into a `u16` from the most significant bit.
+    pub struct PixelsRgba4444(u16, UnsignedShort4444, RGBA);
+}
+
+typed_pixels! {
+    /// A (heap-allocated) 2D image with 5-bit red, green, and blue
+    /// components and a 1-bit alpha component, packed into a `u16`
+    /// from the most significant bit.
+    pub struct PixelsRgba5551(u16, UnsignedShort5551, RGBA);
+}
+
+typed_pixels! {
+    /// A (heap-allocated) 2D image with only an 8-bit alpha component,
+    /// such as a mask.
+    pub struct PixelsAlpha8(u8, UnsignedByte, Alpha);
+}
+
+typed_pixels! {
+    /// A (heap-allocated) 2D image with only an 8-bit luminance component,
+    /// which is used as the red, green, and blue components when sampled.
+    pub struct PixelsLuminance8(u8, UnsignedByte, Luminance);
+}
+
+typed_pixels! {
+    /// A (heap-allocated) 2D image with 8-bit luminance and alpha
+    /// components, stored as `[luminance, alpha]`.
+    pub struct PixelsLuminanceAlpha8([u8; 2], UnsignedByte, LuminanceAlpha);
+}
+
+typed_pixels! {
+    /// A (heap-allocated) 2D image with 32-bit float red, green, blue, and
+    /// alpha components, stored as `[r, g, b, a]`.
+    pub struct PixelsF32([f32; 4], Float, RGBA);
+}
+
+typed_pixels! {
+    /// A (heap-allocated) 2D image with 16-bit (half-precision) float red,
+    /// green, blue, and alpha components, stored as `[r, g, b, a]`. Each
+    /// component is the bit pattern of an IEEE 754 half-precision float,
+    /// since Rust has no 16-bit float type. Use [`from_f32`]
+    /// (#method.from_f32) and [`to_f32`](#method.to_f32) to convert
+    /// from and to 32-bit floats.
+    ///
+    /// # Example
+    /// ```
+    /// use glitter::{PixelsF16, PixelsF32};
+    ///
+    /// let mut hdr = PixelsF32::new(1, 1);
+    /// hdr[0][0] = [4.0, 0.5, -1.0, 1.0];
+    ///
+    /// let half = PixelsF16::from_f32(&hdr);
+    /// assert_eq!(half[0][0], [0x4400, 0x3800, 0xBC00, 0x3C00]);
+    /// assert_eq!(half.to_f32()[0][0], [4.0, 0.5, -1.0, 1.0]);
+    /// ```
+    pub struct PixelsF16([u16; 4], HalfFloatOes, RGBA);
+}
+
+impl PixelsRgb8 {
+    /// Convert 8-bit RGBA `Pixels`, discarding the alpha component.
+    pub fn from_pixels(pixels: &Pixels) -> Self {
+        PixelsRgb8::map_pixels(pixels, |pixel, _, _| {
+            [pixel.r, pixel.g, pixel.b]
+        })
+    }
+}
+
+impl PixelsRgb565 {
+    /// Convert 8-bit RGBA `Pixels`, discarding the alpha component and
+    /// rounding each color component to the nearest value.
+    pub fn from_pixels(pixels: &Pixels) -> Self {
+        PixelsRgb565::map_pixels(pixels, |pixel, _, _| {
+            _pack_565(pixel, None)
+        })
+    }
+
+    /// Convert 8-bit RGBA `Pixels`, discarding the alpha component and
+    /// applying ordered dithering to each color component.
+    pub fn from_pixels_dithered(pixels: &Pixels) -> Self {
+        PixelsRgb565::map_pixels(pixels, |pixel, x, y| {
+            _pack_565(pixel, Some((x, y)))
+        })
+    }
+}
+
+impl PixelsRgba4444 {
+    /// Convert 8-bit RGBA `Pixels`, rounding each component to the
+    /// nearest value.
+    pub fn from_pixels(pixels: &Pixels) -> Self {
+        PixelsRgba4444::map_pixels(pixels, |pixel, _, _| {
+            _pack_4444(pixel, None)
+        })
+    }
+
+    /// Convert 8-bit RGBA `Pixels`, applying ordered dithering to
+    /// each component.
+    pub fn from_pixels_dithered(pixels: &Pixels) -> Self {
+        PixelsRgba4444::map_pixels(pixels, |pixel, x, y| {
+            _pack_4444(pixel, Some((x, y)))
+        })
+    }
+}
+
+impl PixelsRgba5551 {
+    /// Convert 8-bit RGBA `Pixels`, rounding each color component to the
+    /// nearest value. The alpha component is set if it is at least `0x80`.
+    pub fn from_pixels(pixels: &Pixels) -> Self {
+        PixelsRgba5551::map_pixels(pixels, |pixel, _, _| {
+            _pack_5551(pixel, None)
+        })
+    }
+
+    /// Convert 8-bit RGBA `Pixels`, applying ordered dithering to each
+    /// color component. The alpha component is set if it is at
+    /// least `0x80`.
+    pub fn from_pixels_dithered(pixels: &Pixels) -> Self {
+        PixelsRgba5551::map_pixels(pixels, |pixel, x, y| {
+            _pack_5551(pixel, Some((x, y)))
+        })
+    }
+}
+
+impl PixelsAlpha8 {
+    /// Convert 8-bit RGBA `Pixels`, keeping only the alpha component.
+    pub fn from_pixels(pixels: &Pixels) -> Self {
+        PixelsAlpha8::map_pixels(pixels, |pixel, _, _| pixel.a)
+    }
+}
+
+impl PixelsLuminance8 {
+    /// Convert 8-bit RGBA `Pixels`, computing the luminance of each
+    /// texel (using the Rec. 601 weights) and discarding the alpha
+    /// component.
+    pub fn from_pixels(pixels: &Pixels) -> Self {
+        PixelsLuminance8::map_pixels(pixels, |pixel, _, _| {
+            _luminance(pixel)
+        })
+    }
+}
+
+impl PixelsLuminanceAlpha8 {
+    /// Convert 8-bit RGBA `Pixels`, computing the luminance of each
+    /// texel (using the Rec. 601 weights).
+    pub fn from_pixels(pixels: &Pixels) -> Self {
+        PixelsLuminanceAlpha8::map_pixels(pixels, |pixel, _, _| {
+            [_luminance(pixel), pixel.a]
+        })
+    }
+}
+
+impl PixelsF32 {
+    /// Convert 8-bit RGBA `Pixels`, mapping each component to the
+    /// range 0-1.
+    pub fn from_pixels(pixels: &Pixels) -> Self {
+        PixelsF32::map_pixels(pixels, |pixel, _, _| {
+            [pixel.r as f32 / 255.0,
+             pixel.g as f32 / 255.0,
+             pixel.b as f32 / 255.0,
+             pixel.a as f32 / 255.0]
+        })
+    }
+}
+
+impl PixelsF16 {
+    /// Convert 8-bit RGBA `Pixels`, mapping each component to the
+    /// range 0-1.
+    pub fn from_pixels(pixels: &Pixels) -> Self {
+        PixelsF16::map_pixels(pixels, |pixel, _, _| {
+            [_f32_to_f16(pixel.r as f32 / 255.0),
+             _f32_to_f16(pixel.g as f32 / 255.0),
+             _f32_to_f16(pixel.b as f32 / 255.0),
+             _f32_to_f16(pixel.a as f32 / 255.0)]
+        })
+    }
+
+    /// Convert an image with 32-bit float components, rounding each
+    /// component to the nearest half-precision float. Values that are
+    /// too large for a half-precision float become infinite.
+    pub fn from_f32(image: &PixelsF32) -> Self {
+        let (width, height) = (image.width(), image.height());
+        let mut converted = PixelsF16::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let texel = image[y][x];
+                converted[y][x] = [_f32_to_f16(texel[0]),
+                                   _f32_to_f16(texel[1]),
+                                   _f32_to_f16(texel[2]),
+                                   _f32_to_f16(texel[3])];
+            }
+        }
+        converted
+    }
+
+    /// Convert to an image with 32-bit float components.
+    pub fn to_f32(&self) -> PixelsF32 {
+        let (width, height) = (self.width(), self.height());
+        let mut converted = PixelsF32::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let texel = self[y][x];
+                converted[y][x] = [_f16_to_f32(texel[0]),
+                                   _f16_to_f32(texel[1]),
+                                   _f16_to_f32(texel[2]),
+                                   _f16_to_f32(texel[3])];
+            }
+        }
+        converted
+    }
+}
+
+impl Pixels {
+    /// Convert any image to 8-bit RGBA `Pixels`. Returns `None` if the
+    /// image's format can't be read on the CPU.
+    ///
+    /// # Examples
+    /// ```
+    /// use glitter::{Pixels, PixelsLuminance8};
+    ///
+    /// let mut gray = PixelsLuminance8::new(1, 1);
+    /// gray[0][0] = 0x7F;
+    ///
+    /// let pixels = Pixels::from_image(&gray).unwrap();
+    /// assert_eq!((pixels[0][0].r, pixels[0][0].a), (0x7F, 0xFF));
+    /// ```
+    pub fn from_image(image: &Image2d) -> Option<Pixels> {
+        let format = image.format();
+        if !_can_decode_texels(format) {
+            return None;
+        }
+
+        let (width, height) = (image.width(), image.height());
+        let texel_size = format.texel_size();
+        let stride = image.row_stride();
+        let bytes = image.texel_bytes();
+
+        let mut pixels = Pixels::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let offset = y * stride + x * texel_size;
+                let texel = &bytes[offset..(offset + texel_size)];
+                pixels[y][x] = _decode_texel(format, texel);
+            }
+        }
+        Some(pixels)
+    }
+}
+
+// The 4x4 Bayer matrix used for ordered dithering
+const BAYER_4X4: [[u8; 4]; 4] = [
+    [ 0,  8,  2, 10],
+    [12,  4, 14,  6],
+    [ 3, 11,  1,  9],
+    [15,  7, 13,  5]
+];
+
+// Reduce an 8-bit component to `bits` bits, either rounding to the
+// nearest value or dithering based on the texel's position.
+fn _quantize(component: u8, bits: u32, position: Option<(usize, usize)>)
+    -> u16
+{
+    let max = ((1 << bits) - 1) as f32;
+    let value = component as f32 * max / 255.0;
+    let value = match position {
+        Some((x, y)) => {
+            let threshold = (BAYER_4X4[y % 4][x % 4] as f32 + 0.5) / 16.0;
+            (value + threshold - 0.5).round()
+        },
+        None => value.round()
+    };
+
+    if value <= 0.0 {
+        0
+    }
+    else if value >= max {
+        max as u16
+    }
+    else {
+        value as u16
+    }
+}
+
+fn _pack_565(pixel: Pixel, position: Option<(usize, usize)>) -> u16 {
+    (_quantize(pixel.r, 5, position) << 11)
+        | (_quantize(pixel.g, 6, position) << 5)
+        | _quantize(pixel.b, 5, position)
+}
+
+fn _pack_4444(pixel: Pixel, position: Option<(usize, usize)>) -> u16 {
+    (_quantize(pixel.r, 4, position) << 12)
+        | (_quantize(pixel.g, 4, position) << 8)
+        | (_quantize(pixel.b, 4, position) << 4)
+        | _quantize(pixel.a, 4, position)
+}
+
+fn _pack_5551(pixel: Pixel, position: Option<(usize, usize)>) -> u16 {
+    let alpha = if pixel.a >= 0x80 { 1 } else { 0 };
+    (_quantize(pixel.r, 5, position) << 11)
+        | (_quantize(pixel.g, 5, position) << 6)
+        | (_quantize(pixel.b, 5, position) << 1)
+        | alpha
+}
+
+// Convert a float to the bit pattern of the nearest half-precision float
+fn _f32_to_f16(value: f32) -> u16 {
+    let bits: u32 = unsafe { mem::transmute(value) };
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xFF) as i32;
+    let mantissa = bits & 0x7FFFFF;
+
+    if exponent == 0xFF {
+        // Infinity or NaN
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7C00 | nan;
+    }
+
+    let exponent = exponent - 127 + 15;
+    if exponent >= 31 {
+        return sign | 0x7C00;
+    }
+
+    // Round the remaining bits to nearest, with ties to even
+    let (half, shift) = if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+        // The result is a subnormal half-precision float
+        let shift = (14 - exponent) as u32;
+        ((mantissa | 0x800000) >> shift, shift)
+    }
+    else {
+        ((exponent as u32) << 10 | mantissa >> 13, 13)
+    };
+    let remainder = (mantissa | 0x800000) & ((1 << shift) - 1);
+    let halfway = 1 << (shift - 1);
+    let rounded = if remainder > halfway
+                     || (remainder == halfway && half & 1 == 1)
+    {
+        half + 1
+    }
+    else {
+        half
+    };
+    sign | rounded as u16
+}
+
+// Convert the bit pattern of a half-precision float to a float
+fn _f16_to_f32(value: u16) -> f32 {
+    let sign = ((value & 0x8000) as u32) << 16;
+    let exponent = ((value >> 10) & 0x1F) as u32;
+    let mantissa = (value & 0x3FF) as u32;
+
+    let bits = if exponent == 0 {
+        // Zero or a subnormal half-precision float, which is
+        // mantissa * 2^-24
+        let magnitude = mantissa as f32 / (1 << 24) as f32;
+        let magnitude: u32 = unsafe { mem::transmute(magnitude) };
+        sign | magnitude
+    }
+    else if exponent == 0x1F {
+        sign | 0x7F800000 | mantissa << 13
+    }
+    else {
+        sign | (exponent + 127 - 15) << 23 | mantissa << 13
+    };
+    unsafe { mem::transmute(bits) }
+}
+
+// Read a float component with the given texel type, clamped to 0-1 and
+// converted to an 8-bit component
+fn _float_component(texel_type: TexelType, bytes: &[u8]) -> u8 {
+    let value = if texel_type == TexelType::Float {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        let value: f32 = unsafe { mem::transmute(bytes) };
+        value
+    }
+    else {
+        let bytes = [bytes[0], bytes[1]];
+        let value: u16 = unsafe { mem::transmute(bytes) };
+        _f16_to_f32(value)
+    };
+
+    if value >= 1.0 {
+        0xFF
+    }
+    else if value > 0.0 {
+        (value * 255.0).round() as u8
+    }
+    else {
+        // Negative values and NaN
+        0
+    }
+}
+
+fn _luminance(pixel: Pixel) -> u8 {
+    let luminance = 299 * pixel.r as u32
+                  + 587 * pixel.g as u32
+                  + 114 * pixel.b as u32;
+    ((luminance + 500) / 1000) as u8
+}
+
+// Returns true if texels with the given format can be decoded
+// with `_decode_texel`.
+#[doc(hidden)]
+pub fn _can_decode_texels(format: ImageFormat) -> bool {
+    match (format.texel_type, format.texel_format) {
+        (TexelType::UnsignedByte, _) => true,
+        (TexelType::UnsignedShort565, TexelFormat::RGB) => true,
+        (TexelType::UnsignedShort4444, TexelFormat::RGBA) => true,
+        (TexelType::UnsignedShort5551, TexelFormat::RGBA) => true,
+        (TexelType::Float, _) | (TexelType::HalfFloatOes, _) => true,
+        _ => false
+    }
+}
+
+// Decode a single texel with the given format (which should be checked
+// with `_can_decode_texels` first) as an 8-bit RGBA `Pixel`.
+#[doc(hidden)]
+pub fn _decode_texel(format: ImageFormat, texel: &[u8]) -> Pixel {
+    let packed = match format.texel_type {
+        TexelType::UnsignedShort565
+        | TexelType::UnsignedShort4444
+        | TexelType::UnsignedShort5551 => {
+            // Packed texels are read as native-endian 16-bit values,
+            // the same way that OpenGL reads them
+            let bytes = [texel[0], texel[1]];
+            let value: u16 = unsafe { mem::transmute(bytes) };
+            value as u32
+        },
+        _ => 0
+    };
+
+    match (format.texel_type, format.texel_format) {
+        (TexelType::UnsignedByte, TexelFormat::Alpha) => {
+            Pixel::r_g_b_a(0, 0, 0, texel[0])
+        },
+        (TexelType::UnsignedByte, TexelFormat::Luminance) => {
+            Pixel::r_g_b(texel[0], texel[0], texel[0])
+        },
+        (TexelType::UnsignedByte, TexelFormat::LuminanceAlpha) => {
+            Pixel::r_g_b_a(texel[0], texel[0], texel[0], texel[1])
+        },
+        (TexelType::UnsignedByte, TexelFormat::RGB) => {
+            Pixel::r_g_b(texel[0], texel[1], texel[2])
+        },
+        (TexelType::UnsignedByte, TexelFormat::RGBA) => {
+            Pixel::r_g_b_a(texel[0], texel[1], texel[2], texel[3])
+        },
+        (TexelType::UnsignedShort565, _) => {
+            Pixel::r_g_b(_expand(packed >> 11, 5),
+                         _expand(packed >> 5, 6),
+                         _expand(packed, 5))
+        },
+        (TexelType::UnsignedShort4444, _) => {
+            Pixel::r_g_b_a(_expand(packed >> 12, 4),
+                           _expand(packed >> 8, 4),
+                           _expand(packed >> 4, 4),
+                           _expand(packed, 4))
+        },
+        (TexelType::UnsignedShort5551, _) => {
+            Pixel::r_g_b_a(_expand(packed >> 11, 5),
+                           _expand(packed >> 6, 5),
+                           _expand(packed >> 1, 5),
+                           _expand(packed, 1))
+        },
+        (TexelType::Float, _) | (TexelType::HalfFloatOes, _) => {
+            // Convert each component to 8 bits, then decode the texel
+            // like an unsigned byte texel with the same format
+            let size = if format.texel_type == TexelType::Float { 4 } else { 2 };
+            let mut components = [0; 4];
+            for (component, bytes) in components.iter_mut()
+                                                .zip(texel.chunks(size))
+            {
+                *component = _float_component(format.texel_type, bytes);
+            }
+            let format = ImageFormat {
+                texel_type: TexelType::UnsignedByte,
+                texel_format: format.texel_format
+            };
+            _decode_texel(format, &components)
+        }
+    }
+}
+
+// Expand the lowest `bits` bits of `value` to an 8-bit component.
+fn _expand(value: u32, bits: u32) -> u8 {
+    let max = (1 << bits) - 1;
+    (((value & max) * 255 + max / 2) / max) as u8
+}
@@ -4,6 +4,7 @@ use std::marker::PhantomData;
 use gl;
 use gl::types::*;
 use types::GLObject;
+use context::program_context::_forget_validated_program;
 
 /// An OpenGL program object.
 ///
@@ -34,6 +35,7 @@ impl Drop for Program {
         unsafe {
             gl::DeleteProgram(self.gl_id);
         }
+        _forget_validated_program(self.gl_id);
     }
 }
 
@@ -52,6 +54,29 @@ impl GLObject for Program {
     }
 }
 
+/// The compiled, driver-specific binary representation of a linked program,
+/// along with the binary format that the driver reported for it.
+///
+/// A program binary can be used to skip shader compilation and linking
+/// by passing it to [`gl.program_binary`]
+/// (context/program_context/trait.ContextProgramExt.html#method.program_binary).
+/// The driver is allowed to reject binaries at any time (such as after a
+/// driver update), so a program binary should always be treated as a cache.
+///
+/// # See also
+/// [`gl.get_program_binary`](context/program_context/trait.ContextProgramExt.html#method.get_program_binary):
+/// Get the binary representation of a linked program.
+///
+/// [`ProgramBinaryCache`](program_binary_cache/struct.ProgramBinaryCache.html):
+/// Store program binaries on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramBinary {
+    /// The driver-specific format of the binary data.
+    pub format: GLenum,
+
+    /// The raw binary data.
+    pub data: Vec<u8>
+}
 
 /// An OpenGL generic vertex attribute.
 ///
@@ -86,3 +111,32 @@ pub struct ProgramUniform {
     /// The index of the the program uniform.
     pub gl_index: GLuint
 }
+
+/// The result of validating a program against the current OpenGL state,
+/// as returned by [`gl.validate_program`]
+/// (context/program_context/trait.ContextProgramExt.html#method.validate_program).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramValidation {
+    /// Whether the program can be executed with the current OpenGL state
+    /// (the program's `GL_VALIDATE_STATUS`).
+    pub valid: bool,
+
+    /// The program's info log after validation, which usually explains
+    /// why validation failed.
+    pub info_log: Option<String>
+}
+
+impl ProgramValidation {
+    /// Get each non-empty line of the info log.
+    pub fn messages(&self) -> Vec<&str> {
+        match self.info_log {
+            Some(ref info_log) => {
+                info_log.lines()
+                        .map(|line| line.trim())
+                        .filter(|line| !line.is_empty())
+                        .collect()
+            },
+            None => { vec![] }
+        }
+    }
+}
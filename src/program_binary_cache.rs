@@ -0,0 +1,363 @@
+//! Contains the [`ProgramBinaryCache`](struct.ProgramBinaryCache.html) type,
+//! which stores linked program binaries on disk.
+
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use gl::types::*;
+use context::ContextExt;
+use program::ProgramBinary;
+use shader::ShaderType;
+use types::StringName;
+
+const MAGIC: &'static [u8] = b"GLTRPBIN";
+
+// Counts the temporary files written by this process, so that threads
+// storing the same binary don't write to the same temporary file
+static TMP_FILE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// A directory of program binaries, which can be used to skip shader
+/// compilation when building a program with the same sources a second
+/// time (such as the next time an application is launched).
+///
+/// Each binary is stored along with a "driver fingerprint", built from the
+/// `GL_RENDERER` and `GL_VERSION` strings of the context that produced it.
+/// Binaries with a different fingerprint than the cache's are never
+/// loaded, since a binary produced by one driver is generally not usable
+/// by any other driver (or even by a different version of the same
+/// driver). The shader sources are also stored with each binary, so a
+/// binary is never loaded for a program built from different sources.
+///
+/// A program binary cache is used by passing it to
+/// [`ProgramBuilder::binary_cache`]
+/// (../context/program_context/struct.ProgramBuilder.html#method.binary_cache).
+///
+/// # Example
+/// ```no_run
+/// #[macro_use] extern crate glitter;
+/// use glitter::prelude::*;
+///
+/// # fn main() {
+/// let vertex_source = r##"#version 100
+///     attribute vec4 position;
+///
+///     void main() {
+///         gl_Position = position;
+///     }
+/// "##;
+/// let fragment_source = r##"#version 100
+///     void main() {
+///         gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+///     }
+/// "##;
+///
+/// let gl = unsafe { glitter::Context::current_context() };
+/// let cache = glitter::ProgramBinaryCache::new(&gl, "shader-cache");
+/// let program = gl.build_program(&[])
+///                 .shader_source(glitter::VERTEX_SHADER, vertex_source)
+///                 .shader_source(glitter::FRAGMENT_SHADER, fragment_source)
+///                 .binary_cache(&cache)
+///                 .unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProgramBinaryCache {
+    dir: PathBuf,
+    fingerprint: String
+}
+
+impl ProgramBinaryCache {
+    /// Create a program binary cache that stores binaries within the
+    /// provided directory, using the current context's driver fingerprint.
+    /// The directory will be created when the first binary is stored.
+    pub fn new<C, P>(gl: &C, dir: P) -> Self
+        where C: ContextExt, P: Into<PathBuf>
+    {
+        let renderer = gl.get_string(StringName::Renderer)
+                         .unwrap_or_else(String::new);
+        let version = gl.get_string(StringName::Version)
+                        .unwrap_or_else(String::new);
+        let fingerprint = format!("{}\n{}", renderer, version);
+        ProgramBinaryCache::with_fingerprint(dir, &fingerprint)
+    }
+
+    /// Create a program binary cache that stores binaries within the
+    /// provided directory, using an explicit driver fingerprint.
+    pub fn with_fingerprint<P>(dir: P, fingerprint: &str) -> Self
+        where P: Into<PathBuf>
+    {
+        ProgramBinaryCache {
+            dir: dir.into(),
+            fingerprint: fingerprint.to_owned()
+        }
+    }
+
+    /// Get the directory that binaries are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Get the driver fingerprint used to validate binaries.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    /// Get the path of the file used to store the binary for a program
+    /// built from the provided shader sources.
+    pub fn path(&self, sources: &[(ShaderType, &str)]) -> PathBuf {
+        // A 64-bit FNV-1a hash, which (unlike the standard library's
+        // hashers) is stable between Rust releases. Collisions only cause
+        // binaries to be missed, since the sources are compared on load.
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in _encode_sources(sources) {
+            hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+        self.dir.join(format!("{:016x}.bin", hash))
+    }
+
+    /// Load the binary for a program built from the provided shader
+    /// sources. Returns `None` if there is no binary stored for the
+    /// program, if the stored binary was produced by a driver with a
+    /// different fingerprint or from different sources, or if the stored
+    /// file is corrupt.
+    pub fn load(&self, sources: &[(ShaderType, &str)])
+        -> Option<ProgramBinary>
+    {
+        let mut bytes = vec![];
+        let read = fs::File::open(self.path(sources))
+            .and_then(|mut file| file.read_to_end(&mut bytes));
+        if read.is_err() {
+            return None;
+        }
+
+        _decode(&bytes, &self.fingerprint, &_encode_sources(sources))
+    }
+
+    /// Store the binary for a program built from the provided shader
+    /// sources, replacing any previously stored binary.
+    ///
+    /// # Failures
+    /// An error will be returned if the cache directory could not be
+    /// created, or if the binary could not be written.
+    pub fn store(&self,
+                 sources: &[(ShaderType, &str)],
+                 binary: &ProgramBinary)
+        -> io::Result<()>
+    {
+        try!(fs::create_dir_all(&self.dir));
+
+        // The binary is written to a temporary file that's unique to this
+        // process and call, then renamed, so concurrent stores of the
+        // same program never leave a partially written file behind
+        let path = self.path(sources);
+        let count = TMP_FILE_COUNT.fetch_add(1, Ordering::SeqCst);
+        let tmp_path = path.with_extension(format!("{}.{}.tmp",
+                                                   process::id(),
+                                                   count));
+        {
+            let mut file = try!(fs::File::create(&tmp_path));
+            try!(file.write_all(&_encode(binary,
+                                          &self.fingerprint,
+                                          &_encode_sources(sources))));
+        }
+        fs::rename(&tmp_path, &path)
+    }
+}
+
+// File layout (all integers are little-endian `u32`s):
+//
+// - The magic bytes "GLTRPBIN"
+// - The fingerprint length, followed by the fingerprint
+// - The sources length, followed by the sources (see `_encode_sources`)
+// - The binary format
+// - The binary length, followed by the binary data
+fn _encode(binary: &ProgramBinary, fingerprint: &str, sources: &[u8])
+    -> Vec<u8>
+{
+    let mut bytes = Vec::with_capacity(MAGIC.len() +
+                                       fingerprint.len() +
+                                       sources.len() +
+                                       binary.data.len() +
+                                       16);
+    bytes.extend_from_slice(MAGIC);
+    _push_u32(&mut bytes, fingerprint.len() as u32);
+    bytes.extend_from_slice(fingerprint.as_bytes());
+    _push_u32(&mut bytes, sources.len() as u32);
+    bytes.extend_from_slice(sources);
+    _push_u32(&mut bytes, binary.format as u32);
+    _push_u32(&mut bytes, binary.data.len() as u32);
+    bytes.extend_from_slice(&binary.data);
+    bytes
+}
+
+fn _decode(bytes: &[u8], fingerprint: &str, sources: &[u8])
+    -> Option<ProgramBinary>
+{
+    if !bytes.starts_with(MAGIC) {
+        return None;
+    }
+    let rest = &bytes[MAGIC.len()..];
+
+    let rest = match _read_bytes(rest) {
+        Some((stored, rest)) if stored == fingerprint.as_bytes() => { rest },
+        _ => { return None; }
+    };
+    let rest = match _read_bytes(rest) {
+        Some((stored, rest)) if stored == sources => { rest },
+        _ => { return None; }
+    };
+
+    let (format, rest) = match _read_u32(rest) {
+        Some(x) => { x },
+        None => { return None; }
+    };
+    match _read_bytes(rest) {
+        Some((data, rest)) if rest.is_empty() => {
+            Some(ProgramBinary {
+                format: format as GLenum,
+                data: data.to_vec()
+            })
+        },
+        _ => { None }
+    }
+}
+
+// Each source is stored as its shader type, followed by the length of
+// the source and the source itself.
+fn _encode_sources(sources: &[(ShaderType, &str)]) -> Vec<u8> {
+    let mut bytes = vec![];
+    for &(ty, source) in sources {
+        _push_u32(&mut bytes, ty.gl_enum() as u32);
+        _push_u32(&mut bytes, source.len() as u32);
+        bytes.extend_from_slice(source.as_bytes());
+    }
+    bytes
+}
+
+fn _push_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.push(value as u8);
+    bytes.push((value >> 8) as u8);
+    bytes.push((value >> 16) as u8);
+    bytes.push((value >> 24) as u8);
+}
+
+fn _read_u32(bytes: &[u8]) -> Option<(u32, &[u8])> {
+    if bytes.len() < 4 {
+        None
+    }
+    else {
+        let value = (bytes[0] as u32) |
+                    (bytes[1] as u32) << 8 |
+                    (bytes[2] as u32) << 16 |
+                    (bytes[3] as u32) << 24;
+        Some((value, &bytes[4..]))
+    }
+}
+
+// Read a length-prefixed byte string.
+fn _read_bytes(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    match _read_u32(bytes) {
+        Some((len, rest)) if rest.len() >= len as usize => {
+            Some(rest.split_at(len as usize))
+        },
+        _ => { None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+    use std::process;
+    use std::thread;
+    use program::ProgramBinary;
+    use shader::ShaderType;
+    use super::*;
+
+    const SOURCES: &'static [(ShaderType, &'static str)] = &[
+        (ShaderType::VertexShader, "void main() { gl_Position = vec4(0.0); }"),
+        (ShaderType::FragmentShader, "void main() { gl_FragColor = vec4(1.0); }")
+    ];
+
+    fn binary() -> ProgramBinary {
+        ProgramBinary { format: 0x8740, data: vec![1, 2, 3, 4, 5] }
+    }
+
+    #[test]
+    fn decode_encoded_binary() {
+        let sources = _encode_sources(SOURCES);
+        let bytes = _encode(&binary(), "renderer\nversion", &sources);
+        let decoded = _decode(&bytes, "renderer\nversion", &sources).unwrap();
+        assert_eq!(decoded.format, 0x8740);
+        assert_eq!(decoded.data, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn reject_different_fingerprint() {
+        let sources = _encode_sources(SOURCES);
+        let bytes = _encode(&binary(), "renderer\nversion", &sources);
+        assert!(_decode(&bytes, "renderer\nversion 2", &sources).is_none());
+    }
+
+    #[test]
+    fn reject_different_sources() {
+        let sources = _encode_sources(SOURCES);
+        let bytes = _encode(&binary(), "renderer\nversion", &sources);
+
+        let other = _encode_sources(&SOURCES[..1]);
+        assert!(_decode(&bytes, "renderer\nversion", &other).is_none());
+
+        let swapped = _encode_sources(&[
+            (ShaderType::FragmentShader, SOURCES[0].1),
+            (ShaderType::VertexShader, SOURCES[1].1)
+        ]);
+        assert!(_decode(&bytes, "renderer\nversion", &swapped).is_none());
+    }
+
+    #[test]
+    fn reject_truncated_file() {
+        let sources = _encode_sources(SOURCES);
+        let bytes = _encode(&binary(), "renderer\nversion", &sources);
+        for len in 0..bytes.len() {
+            assert!(_decode(&bytes[..len], "renderer\nversion", &sources).is_none());
+        }
+    }
+
+    #[test]
+    fn stable_path() {
+        let cache = ProgramBinaryCache::with_fingerprint("cache", "fingerprint");
+        assert_eq!(cache.path(&[]), Path::new("cache/cbf29ce484222325.bin"));
+        assert_eq!(cache.path(SOURCES), cache.path(SOURCES));
+        assert!(cache.path(SOURCES) != cache.path(&SOURCES[..1]));
+    }
+
+    #[test]
+    fn concurrent_stores() {
+        let dir = env::temp_dir().join(format!("glitter-binary-cache-{}",
+                                               process::id()));
+        let threads: Vec<_> = (0..8).map(|_| {
+            let dir = dir.clone();
+            thread::spawn(move || {
+                let cache = ProgramBinaryCache::with_fingerprint(dir, "fingerprint");
+                for _ in 0..16 {
+                    cache.store(SOURCES, &binary()).unwrap();
+                }
+            })
+        }).collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let cache = ProgramBinaryCache::with_fingerprint(&dir, "fingerprint");
+        assert_eq!(cache.load(SOURCES).unwrap().data, vec![1, 2, 3, 4, 5]);
+
+        // Only the stored binary is left, without any temporary files
+        let files = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(files, 1);
+    }
+}
@@ -0,0 +1,218 @@
+//! Contains the [`ProgramCache`](struct.ProgramCache.html) type, which
+//! compiles and links shader permutations only once.
+
+use std::collections::{BTreeMap, HashMap};
+use context::{AContext, ContextProgramBuilderExt};
+use program::Program;
+use shader::{Shader, ShaderType, ContextShaderBuilderExt};
+use types::GLError;
+
+/// A canonical set of preprocessor definitions, used to identify a
+/// permutation of a shader. Definitions are sorted by name, so the
+/// order they were provided in does not affect caching.
+pub type ShaderDefines = BTreeMap<String, String>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ShaderKey {
+    ty: ShaderType,
+    source: String,
+    defines: ShaderDefines
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ProgramKey {
+    vertex: ShaderKey,
+    fragment: ShaderKey
+}
+
+fn _defines(defines: &[(&str, &str)]) -> ShaderDefines {
+    defines.iter()
+        .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+        .collect()
+}
+
+/// A cache of compiled shaders and linked programs, keyed by their
+/// source and the set of preprocessor definitions used to build them.
+///
+/// Requesting the same program twice returns the program that was
+/// linked the first time, and two programs that use an identical shader
+/// (with the same source and definitions) will share a single
+/// [`Shader`](../shader/struct.Shader.html) object. This makes it cheap
+/// to request "feature permutations" of a material (such as fog or
+/// skinning being enabled or disabled) every time they are needed.
+///
+/// Shaders and programs are built with [`gl.build_shader`]
+/// (../shader/trait.ContextShaderBuilderExt.html#tymethod.build_shader)
+/// and [`gl.build_program`]
+/// (../context/program_context/trait.ContextProgramBuilderExt.html#method.build_program),
+/// so each definition is inserted after the `#version` directive as
+/// described by [`ShaderBuilder::define`]
+/// (../shader/struct.ShaderBuilder.html#method.define).
+///
+/// # Example
+/// ```no_run
+/// #[macro_use] extern crate glitter;
+/// use glitter::prelude::*;
+///
+/// # fn main() {
+/// let vertex_source = r##"#version 100
+///     attribute vec4 position;
+///
+///     void main() {
+///         gl_Position = position;
+///     }
+/// "##;
+/// let fragment_source = r##"#version 100
+///     void main() {
+///     #ifdef FOG
+///         gl_FragColor = vec4(0.5, 0.5, 0.5, 1.0);
+///     #else
+///         gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+///     #endif
+///     }
+/// "##;
+/// let outline_source = r##"#version 100
+///     void main() {
+///         gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+///     }
+/// "##;
+///
+/// let gl = unsafe { glitter::Context::current_context() };
+/// let mut cache = glitter::ProgramCache::new();
+///
+/// cache.program(&gl, vertex_source, fragment_source, &[("FOG", "1")])
+///      .unwrap();
+/// cache.program(&gl, vertex_source, fragment_source, &[]).unwrap();
+///
+/// // The definitions are used for both shaders, so each permutation
+/// // has its own vertex shader
+/// assert_eq!(cache.shader_count(), 4);
+///
+/// cache.program(&gl, vertex_source, outline_source, &[]).unwrap();
+///
+/// // The outline program shares the vertex shader without `FOG`
+/// assert_eq!(cache.shader_count(), 5);
+/// assert_eq!(cache.program_count(), 3);
+/// # }
+/// ```
+pub struct ProgramCache {
+    shaders: HashMap<ShaderKey, Shader>,
+    programs: HashMap<ProgramKey, Program>
+}
+
+impl ProgramCache {
+    /// Create a new, empty program cache.
+    pub fn new() -> Self {
+        ProgramCache {
+            shaders: HashMap::new(),
+            programs: HashMap::new()
+        }
+    }
+
+    /// Get a compiled shader with the provided type, source, and
+    /// preprocessor definitions, compiling it if it is not already
+    /// in the cache.
+    ///
+    /// # Failures
+    /// An error will be returned if the shader could not be compiled.
+    /// Shaders that fail to compile are not cached.
+    pub fn shader<C>(&mut self,
+                     gl: &C,
+                     ty: ShaderType,
+                     source: &str,
+                     defines: &[(&str, &str)])
+        -> Result<&Shader, GLError>
+        where C: AContext
+    {
+        let key = ShaderKey {
+            ty: ty,
+            source: source.to_owned(),
+            defines: _defines(defines)
+        };
+        try!(self.cache_shader(gl, &key));
+        Ok(&self.shaders[&key])
+    }
+
+    /// Get a linked program made up of a vertex shader and a fragment
+    /// shader with the provided sources, built using the same set of
+    /// preprocessor definitions for both shaders. The program and its
+    /// shaders will be built if they are not already in the cache.
+    ///
+    /// # Failures
+    /// An error will be returned if either shader could not be compiled,
+    /// or if the program could not be linked. Programs that fail to
+    /// link are not cached.
+    pub fn program<C>(&mut self,
+                      gl: &C,
+                      vertex_source: &str,
+                      fragment_source: &str,
+                      defines: &[(&str, &str)])
+        -> Result<&mut Program, GLError>
+        where C: AContext
+    {
+        let defines = _defines(defines);
+        let key = ProgramKey {
+            vertex: ShaderKey {
+                ty: ShaderType::VertexShader,
+                source: vertex_source.to_owned(),
+                defines: defines.clone()
+            },
+            fragment: ShaderKey {
+                ty: ShaderType::FragmentShader,
+                source: fragment_source.to_owned(),
+                defines: defines
+            }
+        };
+
+        if !self.programs.contains_key(&key) {
+            try!(self.cache_shader(gl, &key.vertex));
+            try!(self.cache_shader(gl, &key.fragment));
+
+            let program = {
+                let vertex = &self.shaders[&key.vertex];
+                let fragment = &self.shaders[&key.fragment];
+                try!(gl.build_program(&[])
+                       .attach(vertex)
+                       .attach(fragment)
+                       .try_unwrap())
+            };
+            self.programs.insert(key.clone(), program);
+        }
+
+        Ok(self.programs.get_mut(&key).unwrap())
+    }
+
+    /// Get the number of distinct shaders that are in the cache.
+    pub fn shader_count(&self) -> usize {
+        self.shaders.len()
+    }
+
+    /// Get the number of distinct programs that are in the cache.
+    pub fn program_count(&self) -> usize {
+        self.programs.len()
+    }
+
+    /// Remove all shaders and programs from the cache, deleting
+    /// them in the process.
+    pub fn clear(&mut self) {
+        self.programs.clear();
+        self.shaders.clear();
+    }
+
+    fn cache_shader<C>(&mut self, gl: &C, key: &ShaderKey)
+        -> Result<(), GLError>
+        where C: AContext
+    {
+        if !self.shaders.contains_key(key) {
+            let shader = {
+                let builder = key.defines.iter().fold(
+                    gl.build_shader(key.ty, &key.source),
+                    |builder, (name, value)| builder.define(name, value)
+                );
+                try!(builder.try_unwrap())
+            };
+            self.shaders.insert(key.clone(), shader);
+        }
+        Ok(())
+    }
+}
@@ -1,11 +1,13 @@
 //! Exposes the OpenGL [`Shader`](struct.Shader.html) object and related types.
 
+use std::borrow::Cow;
 use std::marker::PhantomData;
 use std::ptr;
 use gl;
 use gl::types::*;
-use context::{AContext, BaseContext};
-use types::{GLObject, GLError};
+use context::{AContext, BaseContext, ContextExt};
+use glsl::{GlslTarget, translate_es100_sources, tokenize};
+use types::{GLObject, GLError, StringName};
 
 /// An OpenGL shader object.
 ///
@@ -70,6 +72,182 @@ unsafe fn _get_shader_iv(shader: &Shader,
     }
 }
 
+// `GL_COMPLETION_STATUS_KHR`, from `KHR_parallel_shader_compile`
+#[doc(hidden)]
+pub const COMPLETION_STATUS_KHR: GLenum = 0x91B1;
+
+fn _compile_shader(shader: &Shader) {
+    unsafe {
+        gl::CompileShader(shader.id());
+        dbg_gl_error! {
+            GLError::InvalidOperation => "`shader` is not a shader object or shader compiler is not supported",
+            GLError::InvalidValue => "`shader` is not a value generated by OpenGL",
+            _ => "Unknown error"
+        }
+    }
+}
+
+fn _check_compile_status<C>(gl: &C, shader: &Shader) -> Result<(), GLError>
+    where C: ContextShaderExt + ?Sized
+{
+    let success = unsafe {
+        let mut compile_status : GLint = 0;
+        _get_shader_iv(shader,
+                       gl::COMPILE_STATUS,
+                       &mut compile_status as *mut GLint);
+
+        compile_status == gl::TRUE as GLint
+    };
+
+    if success {
+        Ok(())
+    }
+    else {
+        let msg = match gl.get_shader_info_log(&shader) {
+            Some(s) => { s },
+            None => { String::from("[Unknown shader error]") }
+        };
+        Err(GLError::Message(msg))
+    }
+}
+
+// Get the source of a shader by its raw ID, which may belong to a shader
+// that glitter doesn't own (such as one returned by
+// `gl.get_attached_shaders`).
+#[doc(hidden)]
+pub unsafe fn _get_shader_source_by_id(id: GLuint) -> Option<String> {
+    let mut source_length : GLint = 0;
+    gl::GetShaderiv(id,
+                    gl::SHADER_SOURCE_LENGTH,
+                    &mut source_length as *mut GLint);
+    dbg_gl_sanity_check! {
+        GLError::InvalidValue => "`shader` is not a value generated by OpenGL",
+        GLError::InvalidOperation => "`shader` is not a shader object",
+        _ => "Unknown error"
+    }
+
+    if source_length > 0 {
+        let mut bytes = Vec::<u8>::with_capacity(source_length as usize);
+
+        gl::GetShaderSource(id,
+                            source_length,
+                            ptr::null_mut(),
+                            bytes.as_mut_ptr() as *mut GLchar);
+        dbg_gl_sanity_check! {
+            GLError::InvalidValue => "`shader` is not a value generated by OpenGL, or `bufSize` < 0",
+            GLError::InvalidOperation => "`shader` is not a shader object",
+            _ => "Unknown error"
+        }
+
+        bytes.set_len((source_length - 1) as usize);
+
+        String::from_utf8(bytes).ok()
+    }
+    else {
+        None
+    }
+}
+
+// Returns the byte offset directly after the `#version` line of a shader's
+// source, or 0 if the source has no `#version` directive.
+fn _after_version_directive(source: &str) -> usize {
+    let mut offset = 0;
+    for line in source.split('\n') {
+        let next_offset = offset + line.len() + 1;
+        let trimmed = line.trim_left();
+        if trimmed.starts_with("#") &&
+           trimmed[1..].trim_left().starts_with("version")
+        {
+            return ::std::cmp::min(next_offset, source.len());
+        }
+        offset = next_offset;
+    }
+    0
+}
+
+fn _insert_defines(source: &str, defines: &[(&str, &str)]) -> String {
+    let split = _after_version_directive(source);
+    let (head, tail) = source.split_at(split);
+
+    let mut result = String::with_capacity(source.len() + defines.len() * 16);
+    result.push_str(head);
+    if !head.is_empty() && !head.ends_with('\n') {
+        result.push('\n');
+    }
+    for &(name, value) in defines {
+        result.push_str("#define ");
+        result.push_str(name);
+        result.push(' ');
+        result.push_str(value);
+        result.push('\n');
+    }
+    result.push_str(tail);
+    result
+}
+
+// Replaces each `highp` precision qualifier with `mediump`.
+fn _rewrite_highp(source: &str) -> String {
+    let mut result = String::with_capacity(source.len());
+    for token in tokenize(source) {
+        if token.is_ident("highp") {
+            result.push_str("mediump");
+        }
+        else {
+            result.push_str(token.text);
+        }
+    }
+    result
+}
+
+fn _uses_highp(source: &str) -> bool {
+    tokenize(source).iter().any(|token| token.is_ident("highp"))
+}
+
+/// What a [`ShaderBuilder`](struct.ShaderBuilder.html) should do with
+/// `highp` precision qualifiers in a fragment shader, when the device
+/// does not support high precision in fragment shaders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HighpFallback {
+    /// Replace each `highp` qualifier with `mediump`.
+    Mediump,
+
+    /// Fail to build the shader.
+    Reject
+}
+
+// Apply the builder's options to each of a shader's source strings. The
+// last source is the shader's main source, and the others are preludes.
+// Every rewrite except for translation and defines (which only add lines
+// to the first source) preserves the line numbers of each source.
+fn _build_sources<'a>(sources: &[&'a str],
+                      ty: ShaderType,
+                      defines: &[(&str, &str)],
+                      target: Option<GlslTarget>,
+                      highp_to_mediump: bool)
+    -> Vec<Cow<'a, str>>
+{
+    let mut sources: Vec<_> = sources.iter().map(|&source| {
+        if highp_to_mediump {
+            Cow::Owned(_rewrite_highp(source))
+        }
+        else {
+            Cow::Borrowed(source)
+        }
+    }).collect();
+    if let Some(target) = target {
+        let translated = {
+            let refs: Vec<&str> = sources.iter().map(|s| &s[..]).collect();
+            translate_es100_sources(&refs, ty, target)
+        };
+        sources = translated.into_iter().map(Cow::Owned).collect();
+    }
+    if !defines.is_empty() {
+        let first = Cow::Owned(_insert_defines(&sources[0], defines));
+        sources[0] = first;
+    }
+    sources
+}
+
 /// A safe interface for creating a shader with a source, and returning an error
 /// or panicking if there is an error.
 pub struct ShaderBuilder<'a, C: 'a>
@@ -77,7 +255,10 @@ pub struct ShaderBuilder<'a, C: 'a>
 {
     gl: &'a C,
     ty: ShaderType,
-    source: &'a str
+    sources: Vec<&'a str>,
+    defines: Vec<(&'a str, &'a str)>,
+    target: Option<GlslTarget>,
+    highp_fallback: Option<HighpFallback>
 }
 
 impl<'a, C: 'a> ShaderBuilder<'a, C>
@@ -86,25 +267,219 @@ impl<'a, C: 'a> ShaderBuilder<'a, C>
     fn new(gl: &'a C, ty: ShaderType, source: &'a str)
         -> Self
     {
-        ShaderBuilder { gl: gl, ty: ty, source: source }
+        ShaderBuilder {
+            gl: gl,
+            ty: ty,
+            sources: vec![source],
+            defines: Vec::new(),
+            target: None,
+            highp_fallback: None
+        }
+    }
+
+    /// Translate the shader from GLSL ES 1.00 into another GLSL dialect
+    /// before compiling it. See [`glsl::translate_es100`]
+    /// (../glsl/fn.translate_es100.html) for details about
+    /// the translation.
+    pub fn translate(mut self, target: GlslTarget) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// Translate the shader from GLSL ES 1.00 into the GLSL dialect
+    /// used by the current context (as determined by [`GlslTarget::for_context`]
+    /// (../glsl/enum.GlslTarget.html#method.for_context)). This allows
+    /// the same GLSL ES 1.00 shaders to be used with both OpenGL ES 2
+    /// contexts and OpenGL 3.3+ core profile contexts.
+    pub fn translate_for_context(self) -> Self {
+        let target = GlslTarget::for_context(self.gl);
+        self.translate(target)
+    }
+
+    /// Add a source string to compile before the shader's main source,
+    /// such as a `#version` directive and shared declarations. Preludes
+    /// are compiled in the order they were added, and each is passed to
+    /// the compiler as a separate source string (see
+    /// [`gl.shader_sources`](trait.ContextShaderExt.html#method.shader_sources)),
+    /// so that line numbers in compile errors refer to lines within each
+    /// string. Most drivers report the source string's index in the
+    /// error, where the main source comes after all of the preludes.
+    ///
+    /// Translation and defines only add lines to the first source string,
+    /// so line numbers in every later string are left unchanged.
+    ///
+    /// # Example
+    /// ```no_run
+    /// #[macro_use] extern crate glitter;
+    /// use glitter::prelude::*;
+    ///
+    /// # fn main() {
+    /// let prelude = "#version 100
+    ///     precision mediump float;
+    /// ";
+    /// let fragment_source = "
+    ///     void main() {
+    ///         gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+    ///     }
+    /// ";
+    ///
+    /// let gl = unsafe { glitter::Context::current_context() };
+    /// let shader = gl.build_fragment_shader(fragment_source)
+    ///                .prelude(prelude)
+    ///                .unwrap();
+    /// # }
+    /// ```
+    pub fn prelude(mut self, prelude: &'a str) -> Self {
+        let main_index = self.sources.len() - 1;
+        self.sources.insert(main_index, prelude);
+        self
+    }
+
+    /// Add a preprocessor macro definition to the shader. Each definition
+    /// is inserted as a `#define name value` line directly after the
+    /// shader's `#version` directive (or at the start of the source, if
+    /// there is no `#version` directive). If the shader has preludes,
+    /// the definitions are inserted into the first prelude.
+    pub fn define(mut self, name: &'a str, value: &'a str) -> Self {
+        self.defines.push((name, value));
+        self
+    }
+
+    /// Set what to do with `highp` precision qualifiers when building a
+    /// fragment shader for a device that does not support high precision
+    /// floats in fragment shaders (as determined by
+    /// [`gl.get_shader_precision_format`]
+    /// (trait.ContextShaderExt.html#method.get_shader_precision_format)).
+    /// This option has no effect on vertex shaders, which always support
+    /// high precision.
+    ///
+    /// # Example
+    /// ```no_run
+    /// #[macro_use] extern crate glitter;
+    /// use glitter::prelude::*;
+    /// use glitter::HighpFallback;
+    ///
+    /// # fn main() {
+    /// let fragment_source = r##"#version 100
+    ///     precision highp float;
+    ///
+    ///     void main() {
+    ///         gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+    ///     }
+    /// "##;
+    ///
+    /// let gl = unsafe { glitter::Context::current_context() };
+    /// let shader = gl.build_fragment_shader(fragment_source)
+    ///                .highp_fallback(HighpFallback::Mediump)
+    ///                .unwrap();
+    /// # }
+    /// ```
+    pub fn highp_fallback(mut self, fallback: HighpFallback) -> Self {
+        // The precision format is only queried once, and the fallback is
+        // only stored if it needs to be applied
+        self.highp_fallback = if self.ty == ShaderType::FragmentShader {
+            let format = self.gl.get_shader_precision_format(
+                ShaderType::FragmentShader,
+                PrecisionType::HighFloat
+            );
+            match format {
+                Some(ref format) if !format.is_supported() => {
+                    Some(fallback)
+                },
+                _ => { None }
+            }
+        }
+        else {
+            None
+        };
+        self
+    }
+
+    /// Get the final source strings that will be passed to the shader
+    /// compiler, after all of the builder's options have been applied.
+    /// The shader's main source is the last string, after any preludes.
+    pub fn sources(&self) -> Vec<Cow<'a, str>> {
+        _build_sources(&self.sources,
+                       self.ty,
+                       &self.defines,
+                       self.target,
+                       self.highp_fallback == Some(HighpFallback::Mediump))
+    }
+
+    /// Get the final source that will be passed to the shader compiler,
+    /// after all of the builder's options have been applied. If the
+    /// shader has preludes, all of the source strings are concatenated.
+    pub fn source(&self) -> Cow<'a, str> {
+        let mut sources = self.sources();
+        if sources.len() == 1 {
+            sources.pop().unwrap()
+        }
+        else {
+            Cow::Owned(sources.concat())
+        }
     }
 
     /// Try to compile a shader with the provided options, or `Err` if
     /// a new shader object could not be created or if there was an error
     /// compiling the provided source.
     pub fn try_unwrap(self) -> Result<Shader, GLError> {
-        unsafe {
-            let mut shader = try! {
-                self.gl.create_shader(self.ty).or_else(|_| {
-                    let msg = "Error creating OpenGL shader";
-                    Err(GLError::Message(msg.to_owned()))
-                })
-            };
-
-            self.gl.shader_source(&mut shader, self.source);
-            try!(self.gl.compile_shader(&mut shader));
-            Ok(shader)
+        let gl = self.gl;
+        try!(self._submit(false)).finish(gl)
+    }
+
+    /// Start compiling a shader with the provided options, without waiting
+    /// for compilation to finish. Returns a [`PendingShader`]
+    /// (struct.PendingShader.html), which can be polled to check if
+    /// compilation has finished.
+    ///
+    /// If the context does not support parallel shader compilation (see
+    /// [`gl.supports_parallel_shader_compile`]
+    /// (../context/trait.ContextExt.html#method.supports_parallel_shader_compile)),
+    /// then the pending shader will always report that compilation has
+    /// finished, and [`PendingShader::finish`]
+    /// (struct.PendingShader.html#method.finish) will block until it
+    /// actually has.
+    ///
+    /// # Failures
+    /// An error will be returned if a new shader object could not be
+    /// created, or if the shader uses `highp` and was rejected (see
+    /// [`highp_fallback`](struct.ShaderBuilder.html#method.highp_fallback)).
+    /// Compilation errors are returned by `PendingShader::finish`.
+    pub fn submit(self) -> Result<PendingShader, GLError> {
+        let parallel = self.gl.supports_parallel_shader_compile();
+        self._submit(parallel)
+    }
+
+    // Start compiling the shader. `parallel` should only be `true` if
+    // the context supports parallel shader compilation, and the pending
+    // shader will be polled.
+    pub(crate) fn _submit(self, parallel: bool) -> Result<PendingShader, GLError> {
+        if self.highp_fallback == Some(HighpFallback::Reject) &&
+           self.sources.iter().any(|source| _uses_highp(source))
+        {
+            let msg = "Fragment shader uses `highp`, but high precision is \
+                       not supported in fragment shaders by this device";
+            return Err(GLError::Message(msg.to_owned()));
+        }
+
+        let shader = unsafe { self.gl.create_shader(self.ty) };
+        let mut shader = try! {
+            shader.or_else(|_| {
+                let msg = "Error creating OpenGL shader";
+                Err(GLError::Message(msg.to_owned()))
+            })
+        };
+
+        {
+            let sources = self.sources();
+            let sources: Vec<&str> = sources.iter().map(|s| &s[..]).collect();
+            self.gl.shader_sources(&mut shader, &sources);
         }
+        _compile_shader(&shader);
+        Ok(PendingShader {
+            shader: shader,
+            parallel: parallel
+        })
     }
 
     /// Try to compile a shader with the provided options, panicking if
@@ -115,6 +490,50 @@ impl<'a, C: 'a> ShaderBuilder<'a, C>
     }
 }
 
+/// A shader that is being compiled, which is returned by
+/// [`ShaderBuilder::submit`](struct.ShaderBuilder.html#method.submit).
+pub struct PendingShader {
+    shader: Shader,
+    parallel: bool
+}
+
+impl PendingShader {
+    /// Get the shader that is being compiled. The shader can be attached
+    /// to a program before compilation has finished.
+    pub fn shader(&self) -> &Shader {
+        &self.shader
+    }
+
+    /// Returns `true` if compilation has finished, without blocking. This
+    /// always returns `true` if the context does not support parallel
+    /// shader compilation.
+    pub fn is_complete(&self) -> bool {
+        if !self.parallel {
+            return true;
+        }
+
+        unsafe {
+            let mut complete : GLint = 0;
+            _get_shader_iv(&self.shader,
+                           COMPLETION_STATUS_KHR,
+                           &mut complete as *mut GLint);
+            complete == gl::TRUE as GLint
+        }
+    }
+
+    /// Wait for compilation to finish, and return the compiled shader.
+    ///
+    /// # Failures
+    /// An error containing the shader's info log will be returned if
+    /// there was an error compiling the shader.
+    pub fn finish<C>(self, gl: &C) -> Result<Shader, GLError>
+        where C: ContextShaderExt
+    {
+        try!(_check_compile_status(gl, &self.shader));
+        Ok(self.shader)
+    }
+}
+
 /// An extension trait for [`ContextOf`](../context/struct.ContextOf.html) that
 /// adds functions to build shaders using the [`ShaderBuilder`]
 /// (struct.ShaderBuilder.html) interface.
@@ -282,13 +701,58 @@ pub trait ContextShaderExt: BaseContext {
     ///
     /// [`glShaderSource`](http://docs.gl/es2/glShaderSource) OpenGL docs
     fn shader_source(&self, shader: &mut Shader, source: &str) {
+        self.shader_sources(shader, &[source]);
+    }
+
+    /// Set or replace a shader object's source with multiple source strings,
+    /// which are concatenated by the shader compiler (such as a common
+    /// prelude followed by the body of the shader). Note that a `#version`
+    /// directive must still come first, so it should be part of the first
+    /// source string. The shader should be recompiled after calling this
+    /// function by using the [`gl.compile_shader`]
+    /// (trait.ContextShaderExt.html#method.compile_shader) function.
+    ///
+    /// # Example
+    /// ```no_run
+    /// #[macro_use] extern crate glitter;
+    /// use glitter::prelude::*;
+    ///
+    /// # fn main() {
+    /// let gl = unsafe { glitter::Context::current_context() };
+    /// let prelude = "#version 100
+    ///     precision mediump float;
+    /// ";
+    /// let body = "
+    ///     void main() {
+    ///         gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+    ///     }
+    /// ";
+    ///
+    /// let shader = unsafe {
+    ///     let mut shader = gl.create_shader(glitter::FRAGMENT_SHADER).unwrap();
+    ///     gl.shader_sources(&mut shader, &[prelude, body]);
+    ///     gl.compile_shader(&mut shader).unwrap();
+    ///
+    ///     shader
+    /// };
+    /// # }
+    /// ```
+    ///
+    /// # See also
+    /// [`glShaderSource`](http://docs.gl/es2/glShaderSource) OpenGL docs
+    fn shader_sources(&self, shader: &mut Shader, sources: &[&str]) {
         unsafe {
-            let source_ptr = source.as_ptr() as *const GLchar;
-            let source_len = source.len() as GLint;
+            let source_ptrs: Vec<_> = sources.iter().map(|source| {
+                source.as_ptr() as *const GLchar
+            }).collect();
+            let source_lens: Vec<_> = sources.iter().map(|source| {
+                source.len() as GLint
+            }).collect();
 
-            gl::ShaderSource(shader.id(), 1,
-                             &source_ptr as *const *const GLchar,
-                             &source_len as *const GLint);
+            gl::ShaderSource(shader.id(),
+                             sources.len() as GLsizei,
+                             source_ptrs.as_ptr(),
+                             source_lens.as_ptr());
             dbg_gl_error! {
                 GLError::InvalidOperation => "`shader` is not a shader object or shader compiler is not supported",
                 GLError::InvalidValue => "`shader` is not a value generated by OpenGL or `count` < 0",
@@ -325,32 +789,8 @@ pub trait ContextShaderExt: BaseContext {
     ///
     /// [`glCompileShader`](http://docs.gl/es2/glCompileShader) OpenGL docs
     fn compile_shader(&self, shader: &mut Shader) -> Result<(), GLError> {
-        let success = unsafe {
-            gl::CompileShader(shader.id());
-            dbg_gl_error! {
-                GLError::InvalidOperation => "`shader` is not a shader object or shader compiler is not supported",
-                GLError::InvalidValue => "`shader` is not a value generated by OpenGL",
-                _ => "Unknown error"
-            }
-
-            let mut compile_status : GLint = 0;
-            _get_shader_iv(shader,
-                           gl::COMPILE_STATUS,
-                           &mut compile_status as *mut GLint);
-
-            compile_status == gl::TRUE as GLint
-        };
-
-        if success {
-            Ok(())
-        }
-        else {
-            let msg = match self.get_shader_info_log(&shader) {
-                Some(s) => { s },
-                None => { String::from("[Unknown shader error]") }
-            };
-            Err(GLError::Message(msg))
-        }
+        _compile_shader(shader);
+        _check_compile_status(self, shader)
     }
 
     /// Get the information log associated with a shader. This is used to
@@ -413,12 +853,105 @@ pub trait ContextShaderExt: BaseContext {
             }
         }
     }
+
+    /// Get the source of a shader object, which is the concatenation of
+    /// all of the source strings that were last passed to
+    /// [`gl.shader_source`](trait.ContextShaderExt.html#method.shader_source)
+    /// or [`gl.shader_sources`]
+    /// (trait.ContextShaderExt.html#method.shader_sources). This includes
+    /// any changes made by a [`ShaderBuilder`](struct.ShaderBuilder.html),
+    /// such as translation and inserted `#define`s. Returns `None` if the
+    /// shader has no source.
+    ///
+    /// # See also
+    /// [`glGetShaderSource`](http://docs.gl/es2/glGetShaderSource) OpenGL docs
+    fn get_shader_source(&self, shader: &Shader) -> Option<String> {
+        unsafe { _get_shader_source_by_id(shader.id()) }
+    }
+
+    /// Get the range and precision of a numeric format in a type of shader.
+    /// Returns `None` if the current context does not support querying
+    /// precision formats, which requires OpenGL ES 2.0, OpenGL 4.1, or
+    /// the `GL_ARB_ES2_compatibility` extension.
+    ///
+    /// # Example
+    /// ```no_run
+    /// #[macro_use] extern crate glitter;
+    /// use glitter::prelude::*;
+    ///
+    /// # fn main() {
+    /// let gl = unsafe { glitter::Context::current_context() };
+    /// let format = gl.get_shader_precision_format(glitter::FRAGMENT_SHADER,
+    ///                                             glitter::HIGH_FLOAT);
+    /// match format {
+    ///     Some(ref format) if !format.is_supported() => {
+    ///         println!("highp is not supported in fragment shaders");
+    ///     },
+    ///     _ => { }
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// # See also
+    /// [`glGetShaderPrecisionFormat`](http://docs.gl/es2/glGetShaderPrecisionFormat)
+    /// OpenGL docs
+    fn get_shader_precision_format(&self,
+                                   shader_type: ShaderType,
+                                   precision_type: PrecisionType)
+        -> Option<ShaderPrecisionFormat>
+    {
+        if !gl::GetShaderPrecisionFormat::is_loaded() ||
+           !_supports_precision_formats(self)
+        {
+            return None;
+        }
+
+        unsafe {
+            let mut range: [GLint; 2] = [0, 0];
+            let mut precision: GLint = 0;
+            gl::GetShaderPrecisionFormat(shader_type.gl_enum(),
+                                         precision_type.gl_enum(),
+                                         range.as_mut_ptr(),
+                                         &mut precision as *mut GLint);
+            dbg_gl_sanity_check! {
+                GLError::InvalidEnum => "`shadertype` or `precisiontype` is not an accepted value",
+                _ => "Unknown error"
+            }
+
+            Some(ShaderPrecisionFormat {
+                range_min: range[0],
+                range_max: range[1],
+                precision: precision
+            })
+        }
+    }
 }
 
 impl<C: BaseContext> ContextShaderExt for C {
 
 }
 
+// Returns true if precision formats can be queried, which is the case for
+// OpenGL ES 2.0 and later, for OpenGL 4.1 and later, and for earlier
+// desktop versions with `GL_ARB_ES2_compatibility`. The version is checked
+// up front, instead of checking for an error after the query, so that
+// errors from earlier calls aren't consumed.
+fn _supports_precision_formats<C: ?Sized + ContextExt>(gl: &C) -> bool {
+    let version = gl.get_string(StringName::Version).unwrap_or_else(String::new);
+    if version.starts_with("OpenGL ES") {
+        return true;
+    }
+
+    let mut parts = version.trim_left().split(|c: char| c == '.' || c == ' ');
+    let major: u32 = parts.next()
+                          .and_then(|n| n.parse().ok())
+                          .unwrap_or(0);
+    let minor: u32 = parts.next()
+                          .and_then(|n| n.parse().ok())
+                          .unwrap_or(0);
+    (major, minor) >= (4, 1) || gl.has_extension("GL_ARB_ES2_compatibility")
+}
+
 gl_enum! {
     /// The possible types of shader objects.
     pub gl_enum ShaderType {
@@ -430,3 +963,167 @@ gl_enum! {
         pub const FragmentShader as FRAGMENT_SHADER = gl::FRAGMENT_SHADER
     }
 }
+
+/// A shader that is attached to a program, as returned by
+/// [`gl.get_attached_shaders`]
+/// (../context/program_context/trait.ContextProgramExt.html#method.get_attached_shaders).
+/// This is a snapshot of the shader's state, and does not own the shader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttachedShader {
+    /// The raw OpenGL ID of the shader.
+    pub id: GLuint,
+
+    /// The type of the shader.
+    pub ty: ShaderType,
+
+    /// The shader's source, or `None` if it has no source.
+    pub source: Option<String>
+}
+
+gl_enum! {
+    /// The numeric formats whose precision can be queried with
+    /// [`gl.get_shader_precision_format`]
+    /// (trait.ContextShaderExt.html#method.get_shader_precision_format).
+    pub gl_enum PrecisionType {
+        /// The format of `lowp float` values.
+        pub const LowFloat as LOW_FLOAT = gl::LOW_FLOAT,
+
+        /// The format of `mediump float` values.
+        pub const MediumFloat as MEDIUM_FLOAT = gl::MEDIUM_FLOAT,
+
+        /// The format of `highp float` values.
+        pub const HighFloat as HIGH_FLOAT = gl::HIGH_FLOAT,
+
+        /// The format of `lowp int` values.
+        pub const LowInt as LOW_INT = gl::LOW_INT,
+
+        /// The format of `mediump int` values.
+        pub const MediumInt as MEDIUM_INT = gl::MEDIUM_INT,
+
+        /// The format of `highp int` values.
+        pub const HighInt as HIGH_INT = gl::HIGH_INT
+    }
+}
+
+/// The range and precision of a numeric format within a shader, as
+/// returned by [`gl.get_shader_precision_format`]
+/// (trait.ContextShaderExt.html#method.get_shader_precision_format).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShaderPrecisionFormat {
+    /// The base-2 logarithm of the absolute value of the smallest
+    /// representable value.
+    pub range_min: i32,
+
+    /// The base-2 logarithm of the absolute value of the largest
+    /// representable value.
+    pub range_max: i32,
+
+    /// The base-2 logarithm of the precision of the format. This is
+    /// always 0 for integer formats.
+    pub precision: i32
+}
+
+impl ShaderPrecisionFormat {
+    /// Returns `true` if the format is supported. OpenGL ES reports a
+    /// range and precision of 0 for unsupported formats, such as `highp`
+    /// in the fragment shaders of some OpenGL ES 2 devices.
+    pub fn is_supported(&self) -> bool {
+        self.range_min != 0 || self.range_max != 0 || self.precision != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glsl::GlslTarget;
+    use super::*;
+
+    const PRELUDE: &'static str = "#version 100
+precision highp float;
+";
+
+    // Each line ends with a comment naming its line number, so that lines
+    // can be matched up after the source has been rewritten
+    const MAIN_SOURCE: &'static str = "varying highp vec2 v_uv; // line 1
+uniform sampler2D texture; // line 2
+void main() { // line 3
+    gl_FragColor = texture2D(texture, v_uv); // line 4
+} // line 5
+";
+
+    fn _line_of(source: &str, marker: &str) -> Option<usize> {
+        source.lines().position(|line| line.ends_with(marker)).map(|i| i + 1)
+    }
+
+    fn _assert_main_lines(source: &str) {
+        for line in 1..6 {
+            let marker = format!("// line {}", line);
+            assert_eq!(_line_of(source, &marker), Some(line));
+        }
+    }
+
+    #[test]
+    fn test_build_sources_unchanged() {
+        let sources = _build_sources(&[PRELUDE, MAIN_SOURCE],
+                                     ShaderType::FragmentShader,
+                                     &[],
+                                     None,
+                                     false);
+        assert_eq!(sources, vec![PRELUDE, MAIN_SOURCE]);
+    }
+
+    #[test]
+    fn test_build_sources_main_source_lines() {
+        let defines = [("FOO", "1"), ("BAR", "2")];
+        let targets = [None, Some(GlslTarget::Es300), Some(GlslTarget::Core330)];
+        for &target in &targets {
+            for &mediump in &[false, true] {
+                let sources = _build_sources(&[PRELUDE, MAIN_SOURCE],
+                                             ShaderType::FragmentShader,
+                                             &defines,
+                                             target,
+                                             mediump);
+                assert_eq!(sources.len(), 2);
+                assert!(sources[0].contains("#define FOO 1\n#define BAR 2\n"));
+                _assert_main_lines(&sources[1]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_sources_translated() {
+        let sources = _build_sources(&[PRELUDE, MAIN_SOURCE],
+                                     ShaderType::FragmentShader,
+                                     &[("FOO", "1")],
+                                     Some(GlslTarget::Core330),
+                                     true);
+        assert_eq!(sources[0], "#version 330 core
+#define FOO 1
+out vec4 glitter_FragColor;
+precision mediump float;
+");
+        assert_eq!(sources[1], "in mediump vec2 v_uv; // line 1
+uniform sampler2D glitter_texture; // line 2
+void main() { // line 3
+    glitter_FragColor = texture(glitter_texture, v_uv); // line 4
+} // line 5
+");
+    }
+
+    #[test]
+    fn test_build_sources_prelude_lines() {
+        // Lines in the first source are only shifted by the inserted
+        // defines and output declarations
+        let prelude = "#version 100
+precision mediump float; // line 2
+";
+        let sources = _build_sources(&[prelude, "", MAIN_SOURCE],
+                                     ShaderType::FragmentShader,
+                                     &[("FOO", "1")],
+                                     Some(GlslTarget::Es300),
+                                     false);
+        assert_eq!(sources.len(), 3);
+        assert_eq!(_line_of(&sources[0], "// line 2"), Some(2 + 2));
+        assert_eq!(sources[1], "");
+        _assert_main_lines(&sources[2]);
+    }
+}
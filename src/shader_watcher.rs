@@ -0,0 +1,589 @@
+//! Contains the [`ShaderWatcher`](struct.ShaderWatcher.html) type, which
+//! rebuilds programs when their shader source files change.
+
+use std::io;
+use std::fmt;
+use std::error;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use context::{AContext, ContextProgramBuilderExt};
+use glsl::GlslTarget;
+use program::Program;
+use shader::{ContextShaderBuilderExt, HighpFallback, ShaderBuilder};
+use types::GLError;
+
+/// An identifier for a program owned by a [`ShaderWatcher`]
+/// (struct.ShaderWatcher.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WatchedProgramId(usize);
+
+/// An error that occurred while building a program from its source files.
+#[derive(Debug)]
+pub enum ShaderWatchError {
+    /// A shader source file could not be read.
+    Io(PathBuf, io::Error),
+
+    /// A shader could not be compiled, or the program could not be linked.
+    Build(GLError)
+}
+
+impl fmt::Display for ShaderWatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ShaderWatchError::Io(ref path, ref e) => {
+                write!(f, "Error reading {}: {}", path.display(), e)
+            },
+            ShaderWatchError::Build(ref e) => {
+                write!(f, "Error building program: {}", e)
+            }
+        }
+    }
+}
+
+impl error::Error for ShaderWatchError {
+    fn description(&self) -> &str {
+        match *self {
+            ShaderWatchError::Io(_, ref e) => { error::Error::description(e) },
+            ShaderWatchError::Build(ref e) => { error::Error::description(e) }
+        }
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            ShaderWatchError::Io(_, ref e) => { Some(e) },
+            ShaderWatchError::Build(ref e) => { Some(e) }
+        }
+    }
+}
+
+/// The result of reloading a single program, returned from
+/// [`ShaderWatcher::poll`](struct.ShaderWatcher.html#method.poll).
+#[derive(Debug)]
+pub enum ShaderReload {
+    /// The program's sources changed, and the program was rebuilt and
+    /// replaced. Any uniform locations or attribute indices retrieved from
+    /// the old program are now stale.
+    Reloaded(WatchedProgramId),
+
+    /// The program's sources changed, but the new program could not be
+    /// built. The old program is kept in place.
+    Failed(WatchedProgramId, ShaderWatchError)
+}
+
+/// Options that are applied to each [`ShaderBuilder`]
+/// (../shader/struct.ShaderBuilder.html) when a watched program's shaders
+/// are built, which can be passed to [`ShaderWatcher::watch_with_options`]
+/// (struct.ShaderWatcher.html#method.watch_with_options).
+///
+/// # Example
+/// ```no_run
+/// #[macro_use] extern crate glitter;
+/// use glitter::prelude::*;
+/// use glitter::{ShaderWatchOptions, HighpFallback};
+///
+/// # fn main() {
+/// let gl = unsafe { glitter::Context::current_context() };
+/// let options = ShaderWatchOptions::new()
+///     .translate_for_context()
+///     .define("MAX_LIGHTS", "4")
+///     .highp_fallback(HighpFallback::Mediump);
+///
+/// let mut watcher = glitter::ShaderWatcher::new();
+/// let id = watcher.watch_with_options(&gl,
+///                                     "shaders/main.vert",
+///                                     "shaders/main.frag",
+///                                     options)
+///                 .unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ShaderWatchOptions {
+    translate_for_context: bool,
+    target: Option<GlslTarget>,
+    defines: Vec<(String, String)>,
+    highp_fallback: Option<HighpFallback>
+}
+
+impl ShaderWatchOptions {
+    /// Create a new set of options, which builds shaders as-is.
+    pub fn new() -> Self {
+        ShaderWatchOptions::default()
+    }
+
+    /// Translate the shaders into another GLSL dialect. See
+    /// [`ShaderBuilder::translate`]
+    /// (../shader/struct.ShaderBuilder.html#method.translate).
+    pub fn translate(mut self, target: GlslTarget) -> Self {
+        self.translate_for_context = false;
+        self.target = Some(target);
+        self
+    }
+
+    /// Translate the shaders into the GLSL dialect used by the context
+    /// that the programs are built with. See
+    /// [`ShaderBuilder::translate_for_context`]
+    /// (../shader/struct.ShaderBuilder.html#method.translate_for_context).
+    pub fn translate_for_context(mut self) -> Self {
+        self.translate_for_context = true;
+        self.target = None;
+        self
+    }
+
+    /// Add a preprocessor macro definition to both shaders. See
+    /// [`ShaderBuilder::define`]
+    /// (../shader/struct.ShaderBuilder.html#method.define).
+    pub fn define<N, V>(mut self, name: N, value: V) -> Self
+        where N: Into<String>, V: Into<String>
+    {
+        self.defines.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set what to do with `highp` precision qualifiers in the fragment
+    /// shader. See [`ShaderBuilder::highp_fallback`]
+    /// (../shader/struct.ShaderBuilder.html#method.highp_fallback).
+    pub fn highp_fallback(mut self, fallback: HighpFallback) -> Self {
+        self.highp_fallback = Some(fallback);
+        self
+    }
+
+    fn _apply<'a, C>(&'a self, mut builder: ShaderBuilder<'a, C>)
+        -> ShaderBuilder<'a, C>
+        where C: AContext
+    {
+        if self.translate_for_context {
+            builder = builder.translate_for_context();
+        }
+        if let Some(target) = self.target {
+            builder = builder.translate(target);
+        }
+        for &(ref name, ref value) in &self.defines {
+            builder = builder.define(name, value);
+        }
+        if let Some(fallback) = self.highp_fallback {
+            builder = builder.highp_fallback(fallback);
+        }
+        builder
+    }
+}
+
+// The source files of a watched program, along with the program itself
+// (which is generic so that reloading can be tested without a context).
+struct WatchedProgram<P> {
+    vertex_path: PathBuf,
+    fragment_path: PathBuf,
+    vertex_modified: Option<SystemTime>,
+    fragment_modified: Option<SystemTime>,
+    options: ShaderWatchOptions,
+    program: P,
+    generation: u64,
+    last_error: Option<String>
+}
+
+impl<P> WatchedProgram<P> {
+    // Start watching a program, using the current modification times of
+    // its source files.
+    fn new(vertex_path: PathBuf,
+           fragment_path: PathBuf,
+           options: ShaderWatchOptions,
+           program: P)
+        -> Self
+    {
+        WatchedProgram {
+            vertex_modified: _modified(&vertex_path),
+            fragment_modified: _modified(&fragment_path),
+            vertex_path: vertex_path,
+            fragment_path: fragment_path,
+            options: options,
+            program: program,
+            generation: 0,
+            last_error: None
+        }
+    }
+
+    // Returns `true` if either source file's modification time changed
+    // (including if the file was created or removed) since the last call.
+    fn _changed(&mut self) -> bool {
+        let vertex_modified = _modified(&self.vertex_path);
+        let fragment_modified = _modified(&self.fragment_path);
+        if vertex_modified == self.vertex_modified &&
+           fragment_modified == self.fragment_modified
+        {
+            return false;
+        }
+
+        self.vertex_modified = vertex_modified;
+        self.fragment_modified = fragment_modified;
+        true
+    }
+
+    // Rebuild the program with `build`, which is passed the program's
+    // source files and options. The program is only replaced if the
+    // rebuild succeeded.
+    fn _reload<F>(&mut self, id: WatchedProgramId, build: F) -> ShaderReload
+        where F: FnOnce(&Path, &Path, &ShaderWatchOptions)
+                        -> Result<P, ShaderWatchError>
+    {
+        match build(&self.vertex_path, &self.fragment_path, &self.options) {
+            Ok(program) => {
+                self.program = program;
+                self.generation += 1;
+                self.last_error = None;
+                ShaderReload::Reloaded(id)
+            },
+            Err(e) => {
+                self.last_error = Some(format!("{}", e));
+                ShaderReload::Failed(id, e)
+            }
+        }
+    }
+}
+
+/// Owns a set of programs built from vertex and fragment shader source
+/// files, and rebuilds each program when its source files change.
+///
+/// Changes are detected by polling the modification time of each source
+/// file with [`poll`](struct.ShaderWatcher.html#method.poll), which should
+/// usually be called once per frame. A program is only replaced once its
+/// new sources have been compiled and linked successfully. If there was
+/// an error, the old program is kept, and the error is reported.
+///
+/// # Stale locations
+/// Every time a program is replaced, its [`generation`]
+/// (struct.ShaderWatcher.html#method.generation) is incremented. Uniform
+/// locations and attribute indices retrieved from a program are only valid
+/// for the generation they were retrieved from, so callers should store the
+/// generation alongside them, and look them up again (for example, with
+/// [`AttribBinder::refresh`]
+/// (../vertex_buffer/struct.AttribBinder.html#method.refresh)) when the
+/// generation changes.
+///
+/// # Example
+/// ```no_run
+/// #[macro_use] extern crate glitter;
+/// use glitter::prelude::*;
+///
+/// # fn main() {
+/// let gl = unsafe { glitter::Context::current_context() };
+/// let mut watcher = glitter::ShaderWatcher::new();
+/// let id = watcher.watch(&gl, "shaders/main.vert", "shaders/main.frag")
+///                 .unwrap();
+///
+/// loop {
+///     for reload in watcher.poll(&gl) {
+///         match reload {
+///             glitter::ShaderReload::Reloaded(_) => {
+///                 println!("Reloaded shaders");
+///             },
+///             glitter::ShaderReload::Failed(_, e) => {
+///                 println!("{}", e);
+///             }
+///         }
+///     }
+///
+///     let program = watcher.program_mut(id);
+///     // ... draw with `program` ...
+/// #   break;
+/// }
+/// # }
+/// ```
+pub struct ShaderWatcher {
+    programs: Vec<WatchedProgram<Program>>
+}
+
+impl ShaderWatcher {
+    /// Create a new shader watcher with no programs.
+    pub fn new() -> Self {
+        ShaderWatcher {
+            programs: Vec::new()
+        }
+    }
+
+    /// Build a program from a vertex shader source file and a fragment
+    /// shader source file, and watch both files for changes.
+    ///
+    /// # Failures
+    /// An error will be returned if either file could not be read, or if
+    /// the program could not be built. In that case, the files will
+    /// not be watched.
+    pub fn watch<C, V, F>(&mut self, gl: &C, vertex_path: V, fragment_path: F)
+        -> Result<WatchedProgramId, ShaderWatchError>
+        where C: AContext, V: Into<PathBuf>, F: Into<PathBuf>
+    {
+        self.watch_with_options(gl,
+                                vertex_path,
+                                fragment_path,
+                                ShaderWatchOptions::new())
+    }
+
+    /// Build a program from a vertex shader source file and a fragment
+    /// shader source file, and watch both files for changes. The provided
+    /// options are applied to both shaders each time the program is built.
+    ///
+    /// # Failures
+    /// An error will be returned if either file could not be read, or if
+    /// the program could not be built. In that case, the files will
+    /// not be watched.
+    pub fn watch_with_options<C, V, F>(&mut self,
+                                       gl: &C,
+                                       vertex_path: V,
+                                       fragment_path: F,
+                                       options: ShaderWatchOptions)
+        -> Result<WatchedProgramId, ShaderWatchError>
+        where C: AContext, V: Into<PathBuf>, F: Into<PathBuf>
+    {
+        let vertex_path = vertex_path.into();
+        let fragment_path = fragment_path.into();
+        let vertex_modified = _modified(&vertex_path);
+        let fragment_modified = _modified(&fragment_path);
+
+        let program = try!(_build(gl, &vertex_path, &fragment_path, &options));
+
+        let mut watched = WatchedProgram::new(vertex_path,
+                                              fragment_path,
+                                              options,
+                                              program);
+        // Keep the modification times from before the build, so that
+        // changes made while building are picked up by the next poll
+        watched.vertex_modified = vertex_modified;
+        watched.fragment_modified = fragment_modified;
+        self.programs.push(watched);
+        Ok(WatchedProgramId(self.programs.len() - 1))
+    }
+
+    /// Check each watched program's source files for changes, and
+    /// rebuild the programs whose files changed. Returns the result of
+    /// each rebuild that was attempted.
+    ///
+    /// # Note
+    /// After a failed rebuild, the program will not be rebuilt again
+    /// until one of its source files changes again.
+    pub fn poll<C>(&mut self, gl: &C) -> Vec<ShaderReload>
+        where C: AContext
+    {
+        let mut reloads = vec![];
+        for (idx, watched) in self.programs.iter_mut().enumerate() {
+            if watched._changed() {
+                let id = WatchedProgramId(idx);
+                let reload = watched._reload(id, |vertex, fragment, options| {
+                    _build(gl, vertex, fragment, options)
+                });
+                reloads.push(reload);
+            }
+        }
+        reloads
+    }
+
+    /// Get a watched program.
+    ///
+    /// # Panics
+    /// This function will panic if `id` was not returned by this watcher.
+    pub fn program(&self, id: WatchedProgramId) -> &Program {
+        &self.programs[id.0].program
+    }
+
+    /// Get a watched program mutably, such as to use it for drawing with
+    /// [`gl.use_program`]
+    /// (../context/program_context/trait.ProgramContext.html#method.use_program).
+    ///
+    /// # Panics
+    /// This function will panic if `id` was not returned by this watcher.
+    pub fn program_mut(&mut self, id: WatchedProgramId) -> &mut Program {
+        &mut self.programs[id.0].program
+    }
+
+    /// Get the number of times a watched program has been replaced.
+    ///
+    /// # Panics
+    /// This function will panic if `id` was not returned by this watcher.
+    pub fn generation(&self, id: WatchedProgramId) -> u64 {
+        self.programs[id.0].generation
+    }
+
+    /// Get the error message from the most recent rebuild of a watched
+    /// program, or `None` if the most recent rebuild succeeded.
+    ///
+    /// # Panics
+    /// This function will panic if `id` was not returned by this watcher.
+    pub fn last_error(&self, id: WatchedProgramId) -> Option<&str> {
+        self.programs[id.0].last_error.as_ref().map(|e| &e[..])
+    }
+
+    /// Get the paths of a watched program's vertex shader and fragment
+    /// shader source files.
+    ///
+    /// # Panics
+    /// This function will panic if `id` was not returned by this watcher.
+    pub fn paths(&self, id: WatchedProgramId) -> (&Path, &Path) {
+        let watched = &self.programs[id.0];
+        (&watched.vertex_path, &watched.fragment_path)
+    }
+}
+
+fn _modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn _read(path: &Path) -> Result<String, ShaderWatchError> {
+    let mut source = String::new();
+    match fs::File::open(path).and_then(|mut f| f.read_to_string(&mut source)) {
+        Ok(_) => { Ok(source) },
+        Err(e) => { Err(ShaderWatchError::Io(path.to_owned(), e)) }
+    }
+}
+
+fn _build<C>(gl: &C,
+             vertex_path: &Path,
+             fragment_path: &Path,
+             options: &ShaderWatchOptions)
+    -> Result<Program, ShaderWatchError>
+    where C: AContext
+{
+    let vertex_source = try!(_read(vertex_path));
+    let fragment_source = try!(_read(fragment_path));
+
+    let shaders = [
+        try!(options._apply(gl.build_vertex_shader(&vertex_source))
+                    .try_unwrap()
+                    .map_err(ShaderWatchError::Build)),
+        try!(options._apply(gl.build_fragment_shader(&fragment_source))
+                    .try_unwrap()
+                    .map_err(ShaderWatchError::Build))
+    ];
+    gl.build_program(&shaders).try_unwrap().map_err(ShaderWatchError::Build)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use std::process;
+    use super::*;
+
+    fn _temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("glitter-shader-watcher-{}-{}",
+                                               name,
+                                               process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn _write(path: &Path, source: &str) {
+        let mut file = fs::File::create(path).unwrap();
+        file.write_all(source.as_bytes()).unwrap();
+    }
+
+    // Stands in for building a program: "compiles" the sources into their
+    // concatenation, and fails if either source contains `error`
+    fn _build_fake(vertex_path: &Path,
+                   fragment_path: &Path,
+                   _options: &ShaderWatchOptions)
+        -> Result<String, ShaderWatchError>
+    {
+        let vertex_source = try!(_read(vertex_path));
+        let fragment_source = try!(_read(fragment_path));
+        let source = vertex_source + &fragment_source;
+        if source.contains("error") {
+            let msg = "Error compiling shader: error".to_owned();
+            return Err(ShaderWatchError::Build(GLError::Message(msg)));
+        }
+        Ok(source)
+    }
+
+    fn _watch(dir: &Path) -> WatchedProgram<String> {
+        let vertex_path = dir.join("test.vert");
+        let fragment_path = dir.join("test.frag");
+        _write(&vertex_path, "vertex;");
+        _write(&fragment_path, "fragment;");
+
+        let program = _build_fake(&vertex_path,
+                                  &fragment_path,
+                                  &ShaderWatchOptions::new()).unwrap();
+        WatchedProgram::new(vertex_path,
+                            fragment_path,
+                            ShaderWatchOptions::new(),
+                            program)
+    }
+
+    #[test]
+    fn test_changed() {
+        let dir = _temp_dir("changed");
+        let mut watched = _watch(&dir);
+        assert!(!watched._changed());
+
+        // Removing a file changes its modification time to `None`
+        fs::remove_file(&watched.fragment_path).unwrap();
+        assert!(watched._changed());
+        assert!(!watched._changed());
+
+        _write(&dir.join("test.frag"), "fragment;");
+        assert!(watched._changed());
+        assert!(!watched._changed());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reload() {
+        let dir = _temp_dir("reload");
+        let mut watched = _watch(&dir);
+        assert_eq!(watched.program, "vertex;fragment;");
+
+        _write(&dir.join("test.frag"), "fragment 2;");
+        match watched._reload(WatchedProgramId(3), _build_fake) {
+            ShaderReload::Reloaded(WatchedProgramId(3)) => { },
+            reload => { panic!("Unexpected reload: {:?}", reload); }
+        }
+        assert_eq!(watched.program, "vertex;fragment 2;");
+        assert_eq!(watched.generation, 1);
+        assert_eq!(watched.last_error, None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reload_keeps_program_on_error() {
+        let dir = _temp_dir("reload-error");
+        let mut watched = _watch(&dir);
+
+        _write(&dir.join("test.frag"), "error;");
+        match watched._reload(WatchedProgramId(0), _build_fake) {
+            ShaderReload::Failed(WatchedProgramId(0),
+                                 ShaderWatchError::Build(_)) => { },
+            reload => { panic!("Unexpected reload: {:?}", reload); }
+        }
+        assert_eq!(watched.program, "vertex;fragment;");
+        assert_eq!(watched.generation, 0);
+        assert_eq!(watched.last_error.as_ref().map(|e| &e[..]),
+                   Some("Error building program: Error compiling shader: error"));
+
+        // Missing files are reported, and the old program is still kept
+        fs::remove_file(&watched.vertex_path).unwrap();
+        match watched._reload(WatchedProgramId(0), _build_fake) {
+            ShaderReload::Failed(_, ShaderWatchError::Io(ref path, _)) => {
+                assert_eq!(path, &dir.join("test.vert"));
+            },
+            reload => { panic!("Unexpected reload: {:?}", reload); }
+        }
+        assert_eq!(watched.program, "vertex;fragment;");
+        assert!(watched.last_error.is_some());
+
+        // The error is cleared once the program is rebuilt successfully
+        _write(&dir.join("test.vert"), "vertex 2;");
+        _write(&dir.join("test.frag"), "fragment 2;");
+        match watched._reload(WatchedProgramId(0), _build_fake) {
+            ShaderReload::Reloaded(_) => { },
+            reload => { panic!("Unexpected reload: {:?}", reload); }
+        }
+        assert_eq!(watched.program, "vertex 2;fragment 2;");
+        assert_eq!(watched.generation, 1);
+        assert_eq!(watched.last_error, None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,10 +1,13 @@
 //! Exposes the OpenGL [`Texture`](struct.Texture.html) family of objects and
 //! related types.
 
+use std::cmp;
 use std::marker::PhantomData;
 use gl;
 use gl::types::*;
 use types::GLObject;
+use image_data::ImageFormat;
+use compressed_image::CompressedFormat;
 
 /// A type of OpenGL texture.
 ///
@@ -34,14 +37,22 @@ use types::GLObject;
 /// [`gl.build_texture_2d`](trait.ContextTextureBuilderExt.html#method.build_texture_2d):
 /// Build a new [`Texture2d`](type.Texture2d.html).
 ///
+/// [`gl.build_texture_cube_map`](trait.ContextTextureBuilderExt.html#method.build_texture_cube_map):
+/// Build a new [`TextureCubeMap`](type.TextureCubeMap.html).
+///
 /// [`gl.gen_texture`](../context/texture_context/trait.ContextTextureExt.html#method.gen_texture):
-/// Create a new, uninitialized texture. Note that this is currently the only
-/// way to create textures that aren't 2D.
+/// Create a new, uninitialized texture.
 ///
 /// [`context::texture_units`](../context/texture_units/index.html): The module
 /// with details details about binding a texture in a context.
 pub struct Texture<T: TextureType> {
     gl_id: GLuint,
+    format: Option<ImageFormat>,
+    compressed_format: Option<CompressedFormat>,
+    width: u32,
+    height: u32,
+    levels: u32,
+    sampler_params: SamplerParams,
     phantom: PhantomData<*mut T>
 }
 
@@ -76,6 +87,12 @@ impl<T: TextureType> GLObject for Texture<T> {
     unsafe fn from_raw(id: Self::Id) -> Self {
         Texture {
             gl_id: id,
+            format: None,
+            compressed_format: None,
+            width: 0,
+            height: 0,
+            levels: 0,
+            sampler_params: SamplerParams::default(),
             phantom: PhantomData
         }
     }
@@ -85,6 +102,171 @@ impl<T: TextureType> GLObject for Texture<T> {
     }
 }
 
+impl<T: TextureType> Texture<T> {
+    /// Get the image format that the texture's image data was last
+    /// specified with, or `None` if no image data has been specified
+    /// through glitter.
+    pub fn format(&self) -> Option<ImageFormat> {
+        self.format
+    }
+
+    /// Get the compressed format that the texture's image data was last
+    /// specified with, or `None` if the texture's image data is not
+    /// compressed (or hasn't been specified through glitter).
+    pub fn compressed_format(&self) -> Option<CompressedFormat> {
+        self.compressed_format
+    }
+
+    /// Get the width of the texture's base mipmap level, in texels. For
+    /// cubemaps, this is the width of each face. Returns 0 if no image
+    /// data has been specified through glitter.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the texture's base mipmap level, in texels. For
+    /// cubemaps, this is the height of each face. Returns 0 if no image
+    /// data has been specified through glitter.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the width and height of a mipmap level of the texture,
+    /// in texels.
+    pub fn level_size(&self, level: u32) -> (u32, u32) {
+        (_mip_dimension(self.width, level), _mip_dimension(self.height, level))
+    }
+
+    /// Get the number of mipmap levels that have been specified for the
+    /// texture, counting from the base level. Returns 0 if no image data
+    /// has been specified through glitter.
+    pub fn levels(&self) -> u32 {
+        self.levels
+    }
+
+    /// Get the texture's filtering and wrapping parameters, as last set
+    /// through glitter.
+    pub fn sampler_params(&self) -> SamplerParams {
+        self.sampler_params
+    }
+
+    /// Get the approximate number of bytes used by the texture's image
+    /// data, including each of its mipmap levels and faces.
+    pub fn memory_size(&self) -> usize {
+        if self.format.is_none() && self.compressed_format.is_none() {
+            return 0;
+        }
+        let faces = match T::target() {
+            TextureBindingTarget::Texture2d => 1,
+            TextureBindingTarget::TextureCubeMap => 6
+        };
+
+        let level_bytes = (0..self.levels).map(|level| {
+            let (width, height) = self.level_size(level);
+            let (width, height) = (width as usize, height as usize);
+            match (self.format, self.compressed_format) {
+                (Some(format), _) => width * height * format.texel_size(),
+                (None, Some(format)) => {
+                    format.texel_format().image_size(width, height)
+                },
+                (None, None) => 0
+            }
+        });
+        level_bytes.fold(0, |sum, bytes| sum + bytes) * faces
+    }
+
+    #[doc(hidden)]
+    pub fn _set_image(&mut self,
+                      level: u32,
+                      format: ImageFormat,
+                      width: u32,
+                      height: u32)
+    {
+        self._set_level_size(level, width, height);
+        self.format = Some(format);
+        self.compressed_format = None;
+    }
+
+    #[doc(hidden)]
+    pub fn _set_compressed_image(&mut self,
+                                 level: u32,
+                                 format: CompressedFormat,
+                                 width: u32,
+                                 height: u32)
+    {
+        self._set_level_size(level, width, height);
+        self.format = None;
+        self.compressed_format = Some(format);
+    }
+
+    fn _set_level_size(&mut self, level: u32, width: u32, height: u32) {
+        if level == 0 {
+            if (width, height) != (self.width, self.height) {
+                self.levels = 0;
+            }
+            self.width = width;
+            self.height = height;
+        }
+        if level + 1 > self.levels {
+            self.levels = level + 1;
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn _set_mipmap_generated(&mut self) {
+        let mut levels = 1;
+        while _mip_dimension(self.width, levels - 1) > 1
+            || _mip_dimension(self.height, levels - 1) > 1
+        {
+            levels += 1;
+        }
+        self.levels = levels;
+    }
+
+    #[doc(hidden)]
+    pub fn _sampler_params_mut(&mut self) -> &mut SamplerParams {
+        &mut self.sampler_params
+    }
+}
+
+fn _mip_dimension(base: u32, level: u32) -> u32 {
+    if level >= 32 {
+        1
+    }
+    else {
+        cmp::max(base >> level, 1)
+    }
+}
+
+/// The filtering and wrapping parameters of a texture, which determine
+/// how the texture is sampled. The default values are the initial values
+/// of a new OpenGL texture object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SamplerParams {
+    /// The texture's minifying filter.
+    pub min_filter: TextureMipmapFilter,
+
+    /// The texture's magnifying filter.
+    pub mag_filter: TextureFilter,
+
+    /// The texture's wrap mode for the s-coordinate.
+    pub wrap_s: TextureWrapMode,
+
+    /// The texture's wrap mode for the t-coordinate.
+    pub wrap_t: TextureWrapMode
+}
+
+impl Default for SamplerParams {
+    fn default() -> Self {
+        SamplerParams {
+            min_filter: NEAREST_MIPMAP_LINEAR,
+            mag_filter: LINEAR,
+            wrap_s: REPEAT,
+            wrap_t: REPEAT
+        }
+    }
+}
+
 
 
 /// A trait implemented for types that are used to represent all of the
@@ -172,6 +354,33 @@ gl_enum! {
     }
 }
 
+impl TxCubeMapImageTarget {
+    /// Returns all six cubemap faces, in the order of their OpenGL
+    /// enum values (+X, -X, +Y, -Y, +Z, -Z).
+    pub fn faces() -> [TxCubeMapImageTarget; 6] {
+        [TxCubeMapImageTarget::CubeMapPositiveX,
+         TxCubeMapImageTarget::CubeMapNegativeX,
+         TxCubeMapImageTarget::CubeMapPositiveY,
+         TxCubeMapImageTarget::CubeMapNegativeY,
+         TxCubeMapImageTarget::CubeMapPositiveZ,
+         TxCubeMapImageTarget::CubeMapNegativeZ]
+    }
+
+    /// Returns the index of this face within the array returned
+    /// by [`TxCubeMapImageTarget::faces`]
+    /// (enum.TxCubeMapImageTarget.html#method.faces).
+    pub fn face_index(&self) -> usize {
+        match *self {
+            TxCubeMapImageTarget::CubeMapPositiveX => 0,
+            TxCubeMapImageTarget::CubeMapNegativeX => 1,
+            TxCubeMapImageTarget::CubeMapPositiveY => 2,
+            TxCubeMapImageTarget::CubeMapNegativeY => 3,
+            TxCubeMapImageTarget::CubeMapPositiveZ => 4,
+            TxCubeMapImageTarget::CubeMapNegativeZ => 5
+        }
+    }
+}
+
 impl ImageTargetType for TxCubeMapImageTarget {
     fn gl_enum(&self) -> GLenum {
         self.gl_enum()
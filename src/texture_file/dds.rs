@@ -0,0 +1,849 @@
+//! Contains the parser for [DDS]
+//! (https://learn.microsoft.com/en-us/windows/win32/direct3ddds/dx-graphics-dds-pguide)
+//! files, including files with the DX10 header extension.
+
+use std::fmt;
+use std::error;
+use image_data::{ImageFormat, TexelType, TexelFormat};
+use compressed_image::{CompressedTexelFormat, S3tcFormat, RgtcFormat};
+use super::{TextureFile, TextureFileFormat};
+
+const DDS_MAGIC: u32 = 0x20534444;
+const DDS_HEADER_SIZE: u32 = 124;
+const DDS_PIXEL_FORMAT_SIZE: u32 = 32;
+const DX10_HEADER_SIZE: usize = 20;
+
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_DEPTH: u32 = 0x800000;
+
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_ALPHA: u32 = 0x2;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDPF_LUMINANCE: u32 = 0x20000;
+
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_CUBEMAP_ALL_FACES: u32 = 0xFC00;
+const DDSCAPS2_VOLUME: u32 = 0x200000;
+
+const DX10_DIMENSION_TEXTURE2D: u32 = 3;
+const DX10_MISC_TEXTURECUBE: u32 = 0x4;
+
+/// An error that occurred while reading a DDS file.
+#[derive(Debug)]
+pub enum DdsError {
+    /// The data doesn't start with the DDS magic number.
+    InvalidMagic,
+
+    /// The data ended before the end of the file's header or image data.
+    UnexpectedEnd,
+
+    /// The file's header contains an invalid value.
+    InvalidHeader(&'static str),
+
+    /// The file contains a kind of texture that can't be loaded, such as
+    /// a volume texture or a texture array.
+    UnsupportedTexture(&'static str),
+
+    /// The file's pixel format uses a FourCC code for a compressed format
+    /// that glitter can't upload.
+    UnsupportedFourCc([u8; 4]),
+
+    /// The `DXGI_FORMAT` in the file's DX10 header doesn't have an
+    /// equivalent format that glitter can upload.
+    UnsupportedDxgiFormat(u32),
+
+    /// The file's pixel format describes uncompressed texels that don't
+    /// have an equivalent format that glitter can upload.
+    UnsupportedPixelFormat {
+        /// The number of bits used to store each texel.
+        bit_count: u32,
+
+        /// The bits of each texel used for the red (or luminance)
+        /// component.
+        red_mask: u32,
+
+        /// The bits of each texel used for the green component.
+        green_mask: u32,
+
+        /// The bits of each texel used for the blue component.
+        blue_mask: u32,
+
+        /// The bits of each texel used for the alpha component.
+        alpha_mask: u32
+    }
+}
+
+impl fmt::Display for DdsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DdsError::InvalidMagic => {
+                write!(f, "the data is not a DDS file")
+            },
+            DdsError::UnexpectedEnd => {
+                write!(f, "the DDS file is truncated")
+            },
+            DdsError::InvalidHeader(msg) => {
+                write!(f, "the DDS file has an invalid header: {}", msg)
+            },
+            DdsError::UnsupportedTexture(msg) => {
+                write!(f, "the DDS file contains an unsupported texture: {}",
+                       msg)
+            },
+            DdsError::UnsupportedFourCc(four_cc) => {
+                write!(f, "unsupported DDS FourCC code {:?}",
+                       String::from_utf8_lossy(&four_cc))
+            },
+            DdsError::UnsupportedDxgiFormat(dxgi_format) => {
+                write!(f, "unsupported DDS DXGI_FORMAT {}", dxgi_format)
+            },
+            DdsError::UnsupportedPixelFormat { bit_count,
+                                               red_mask,
+                                               green_mask,
+                                               blue_mask,
+                                               alpha_mask } => {
+                write!(f, "unsupported DDS pixel format ({} bits per texel, masks R 0x{:X}, G 0x{:X}, B 0x{:X}, A 0x{:X})",
+                       bit_count, red_mask, green_mask, blue_mask, alpha_mask)
+            }
+        }
+    }
+}
+
+impl error::Error for DdsError {
+    fn description(&self) -> &str {
+        match *self {
+            DdsError::InvalidMagic => "invalid DDS magic number",
+            DdsError::UnexpectedEnd => "unexpected end of DDS file",
+            DdsError::InvalidHeader(_) => "invalid DDS file header",
+            DdsError::UnsupportedTexture(_) => "unsupported DDS texture type",
+            DdsError::UnsupportedFourCc(_)
+            | DdsError::UnsupportedDxgiFormat(_)
+            | DdsError::UnsupportedPixelFormat { .. } => {
+                "unsupported DDS pixel format"
+            }
+        }
+    }
+}
+
+// The bit masks of an uncompressed DDS pixel format, in the order red
+// (or luminance), green, blue, alpha
+#[derive(Debug, Clone, Copy)]
+struct TexelMasks {
+    bit_count: u32,
+    masks: [u32; 4]
+}
+
+// How the texels of a DDS file are stored
+#[derive(Debug, Clone, Copy)]
+enum DdsFormat {
+    Compressed(CompressedTexelFormat),
+    Uncompressed(ImageFormat, TexelMasks)
+}
+
+impl TextureFile {
+    /// Read a 2D or cubemap texture from the contents of a DDS file,
+    /// with or without the DX10 header extension.
+    ///
+    /// Files may contain BC1 through BC5 compressed data (which are read
+    /// as the [`S3tcFormat`](enum.S3tcFormat.html) and [`RgtcFormat`]
+    /// (enum.RgtcFormat.html) formats), or uncompressed RGB, RGBA,
+    /// luminance, or alpha data. Uncompressed texels are converted to the
+    /// component order of the matching [`ImageFormat`]
+    /// (struct.ImageFormat.html), so BGRA files are read as RGBA.
+    ///
+    /// # Failures
+    /// An error will be returned if the file is malformed, if it contains
+    /// a volume texture, a texture array, or a cubemap without all six
+    /// faces, or if its pixel format can't be uploaded by glitter (such
+    /// as sRGB or floating-point formats). Note that the file's format is
+    /// not checked against what the current context supports until the
+    /// file is uploaded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use glitter::{TextureFile, TextureFileFormat, ImageFormat};
+    ///
+    /// # fn set_u32(bytes: &mut [u8], offset: usize, value: u32) {
+    /// #     for i in 0..4 {
+    /// #         bytes[offset + i] = (value >> (i * 8)) as u8;
+    /// #     }
+    /// # }
+    /// // A DDS file containing a single 2x1 image, with BGRA texels
+    /// let mut dds = vec![0; 128];
+    /// set_u32(&mut dds, 0, 0x20534444); // "DDS "
+    /// set_u32(&mut dds, 4, 124);
+    /// set_u32(&mut dds, 8, 0x100F);
+    /// set_u32(&mut dds, 12, 1); // Height
+    /// set_u32(&mut dds, 16, 2); // Width
+    /// set_u32(&mut dds, 76, 32);
+    /// set_u32(&mut dds, 80, 0x41); // DDPF_RGB | DDPF_ALPHAPIXELS
+    /// set_u32(&mut dds, 88, 32);
+    /// set_u32(&mut dds, 92, 0x00FF0000); // Red mask
+    /// set_u32(&mut dds, 96, 0x0000FF00); // Green mask
+    /// set_u32(&mut dds, 100, 0x000000FF); // Blue mask
+    /// set_u32(&mut dds, 104, 0xFF000000); // Alpha mask
+    /// dds.extend_from_slice(&[0xFF, 0, 0, 0xFF, 0, 0, 0xFF, 0x80]);
+    ///
+    /// let file = TextureFile::from_dds(&dds).unwrap();
+    /// assert_eq!(file.format(),
+    ///            TextureFileFormat::Uncompressed(ImageFormat::rgba8()));
+    /// assert_eq!(file.level_size(0), (2, 1));
+    /// assert_eq!(file.level_data(0, 0), &[0, 0, 0xFF, 0xFF, 0xFF, 0, 0, 0x80]);
+    ///
+    /// assert!(TextureFile::from_dds(&dds[..130]).is_err());
+    /// ```
+    pub fn from_dds(bytes: &[u8]) -> Result<TextureFile, DdsError> {
+        if try!(_read_u32(bytes, 0)) != DDS_MAGIC {
+            return Err(DdsError::InvalidMagic);
+        }
+        if try!(_read_u32(bytes, 4)) != DDS_HEADER_SIZE {
+            return Err(DdsError::InvalidHeader("invalid header size"));
+        }
+        if try!(_read_u32(bytes, 76)) != DDS_PIXEL_FORMAT_SIZE {
+            return Err(DdsError::InvalidHeader("invalid pixel format size"));
+        }
+
+        let flags = try!(_read_u32(bytes, 8));
+        let height = try!(_read_u32(bytes, 12)) as usize;
+        let width = try!(_read_u32(bytes, 16)) as usize;
+        let depth = try!(_read_u32(bytes, 24));
+        let levels = try!(_read_u32(bytes, 28)) as usize;
+        let pixel_flags = try!(_read_u32(bytes, 80));
+        let four_cc = try!(_read_u32(bytes, 84));
+        let caps2 = try!(_read_u32(bytes, 112));
+
+        if caps2 & DDSCAPS2_VOLUME != 0 || (flags & DDSD_DEPTH != 0 && depth > 1) {
+            return Err(DdsError::UnsupportedTexture("volume textures are not supported"));
+        }
+
+        let mut pos = 4 + DDS_HEADER_SIZE as usize;
+        let (format, faces) = if pixel_flags & DDPF_FOURCC != 0
+            && &_four_cc_bytes(four_cc) == b"DX10"
+        {
+            let dxgi_format = try!(_read_u32(bytes, pos));
+            let dimension = try!(_read_u32(bytes, pos + 4));
+            let misc_flags = try!(_read_u32(bytes, pos + 8));
+            let array_size = try!(_read_u32(bytes, pos + 12));
+            pos += DX10_HEADER_SIZE;
+
+            if dimension != DX10_DIMENSION_TEXTURE2D {
+                return Err(DdsError::UnsupportedTexture("only 2D textures and cubemaps are supported"));
+            }
+            if array_size != 1 {
+                return Err(DdsError::UnsupportedTexture("texture arrays are not supported"));
+            }
+
+            let format = match _dxgi_format(dxgi_format) {
+                Some(format) => format,
+                None => {
+                    return Err(DdsError::UnsupportedDxgiFormat(dxgi_format));
+                }
+            };
+            let faces = if misc_flags & DX10_MISC_TEXTURECUBE != 0 { 6 } else { 1 };
+            (format, faces)
+        }
+        else {
+            let faces = if caps2 & DDSCAPS2_CUBEMAP == 0 {
+                1
+            }
+            else if caps2 & DDSCAPS2_CUBEMAP_ALL_FACES == DDSCAPS2_CUBEMAP_ALL_FACES {
+                6
+            }
+            else {
+                return Err(DdsError::UnsupportedTexture("cubemaps must have all six faces"));
+            };
+            (try!(_legacy_format(bytes, pixel_flags, four_cc)), faces)
+        };
+
+        if width == 0 || height == 0 {
+            return Err(DdsError::InvalidHeader("the texture has a width or height of 0"));
+        }
+        if width > super::MAX_DIMENSION || height > super::MAX_DIMENSION {
+            return Err(DdsError::InvalidHeader("the texture is too large"));
+        }
+        if faces == 6 && width != height {
+            return Err(DdsError::InvalidHeader("cubemap faces are not square"));
+        }
+
+        // The mipmap count is only used if its flag is set, and some
+        // writers set a count of 0 for textures without mipmaps
+        let levels = if flags & DDSD_MIPMAPCOUNT != 0 && levels > 0 {
+            levels
+        }
+        else {
+            1
+        };
+        if levels > super::_max_levels(width, height) {
+            return Err(DdsError::InvalidHeader("too many mipmap levels"));
+        }
+
+        // DDS files store every mipmap level of a face before the next
+        // face, but texture files are indexed by level first
+        let mut file_levels: Vec<Vec<Vec<u8>>> = (0..levels).map(|_| {
+            Vec::with_capacity(faces)
+        }).collect();
+        for _ in 0..faces {
+            for (level, file_faces) in file_levels.iter_mut().enumerate() {
+                let level_width = super::_mip_dimension(width, level);
+                let level_height = super::_mip_dimension(height, level);
+
+                let data = match format {
+                    DdsFormat::Compressed(format) => {
+                        let size = format.image_size(level_width, level_height);
+                        let data = try!(_slice(bytes, pos, size));
+                        pos += size;
+                        data.to_vec()
+                    },
+                    DdsFormat::Uncompressed(format, masks) => {
+                        let texels = level_width * level_height;
+                        let size = texels * (masks.bit_count / 8) as usize;
+                        let data = try!(_slice(bytes, pos, size));
+                        pos += size;
+                        _convert_texels(data, masks, format)
+                    }
+                };
+                file_faces.push(data);
+            }
+        }
+
+        let format = match format {
+            DdsFormat::Compressed(format) => {
+                TextureFileFormat::Compressed(format)
+            },
+            DdsFormat::Uncompressed(format, _) => {
+                TextureFileFormat::Uncompressed(format)
+            }
+        };
+
+        Ok(TextureFile {
+            width: width,
+            height: height,
+            format: format,
+            levels: file_levels
+        })
+    }
+}
+
+// Get the format of a DDS file without a DX10 header
+fn _legacy_format(bytes: &[u8], pixel_flags: u32, four_cc: u32)
+    -> Result<DdsFormat, DdsError>
+{
+    if pixel_flags & DDPF_FOURCC != 0 {
+        let four_cc = _four_cc_bytes(four_cc);
+        let format: CompressedTexelFormat = match &four_cc {
+            b"DXT1" if pixel_flags & DDPF_ALPHAPIXELS != 0 => {
+                S3tcFormat::Dxt1Rgba.into()
+            },
+            b"DXT1" => S3tcFormat::Dxt1Rgb.into(),
+            b"DXT2" | b"DXT3" => S3tcFormat::Dxt3Rgba.into(),
+            b"DXT4" | b"DXT5" => S3tcFormat::Dxt5Rgba.into(),
+            b"ATI1" | b"BC4U" => RgtcFormat::RedRgtc1.into(),
+            b"BC4S" => RgtcFormat::SignedRedRgtc1.into(),
+            b"ATI2" | b"BC5U" => RgtcFormat::RgRgtc2.into(),
+            b"BC5S" => RgtcFormat::SignedRgRgtc2.into(),
+            _ => {
+                return Err(DdsError::UnsupportedFourCc(four_cc));
+            }
+        };
+        return Ok(DdsFormat::Compressed(format));
+    }
+
+    let bit_count = try!(_read_u32(bytes, 88));
+    let mut masks = [0; 4];
+    for (i, mask) in masks.iter_mut().enumerate() {
+        *mask = try!(_read_u32(bytes, 92 + i * 4));
+    }
+
+    // The alpha mask is only valid if one of the alpha flags is set, and
+    // the color masks are only valid if one of the color flags is set
+    if pixel_flags & (DDPF_ALPHAPIXELS | DDPF_ALPHA) == 0 {
+        masks[3] = 0;
+    }
+    if pixel_flags & (DDPF_RGB | DDPF_LUMINANCE) == 0 {
+        masks[0] = 0;
+        masks[1] = 0;
+        masks[2] = 0;
+    }
+    else if pixel_flags & DDPF_LUMINANCE != 0 {
+        masks[1] = 0;
+        masks[2] = 0;
+    }
+
+    let masks = TexelMasks { bit_count: bit_count, masks: masks };
+    match _uncompressed_format(masks, pixel_flags & DDPF_LUMINANCE != 0) {
+        Some(format) => Ok(DdsFormat::Uncompressed(format, masks)),
+        None => {
+            Err(DdsError::UnsupportedPixelFormat {
+                bit_count: bit_count,
+                red_mask: masks.masks[0],
+                green_mask: masks.masks[1],
+                blue_mask: masks.masks[2],
+                alpha_mask: masks.masks[3]
+            })
+        }
+    }
+}
+
+// Get the format of a DDS file from the `DXGI_FORMAT` in its DX10 header
+fn _dxgi_format(dxgi_format: u32) -> Option<DdsFormat> {
+    let uncompressed = |bit_count, masks| {
+        let masks = TexelMasks { bit_count: bit_count, masks: masks };
+        _uncompressed_format(masks, false).map(|format| {
+            DdsFormat::Uncompressed(format, masks)
+        })
+    };
+
+    let format: CompressedTexelFormat = match dxgi_format {
+        // DXGI_FORMAT_R8G8B8A8_UNORM
+        28 => return uncompressed(32, [0xFF, 0xFF00, 0xFF0000, 0xFF000000]),
+        // DXGI_FORMAT_A8_UNORM
+        65 => return uncompressed(8, [0, 0, 0, 0xFF]),
+        // DXGI_FORMAT_B5G6R5_UNORM
+        85 => return uncompressed(16, [0xF800, 0x7E0, 0x1F, 0]),
+        // DXGI_FORMAT_B5G5R5A1_UNORM
+        86 => return uncompressed(16, [0x7C00, 0x3E0, 0x1F, 0x8000]),
+        // DXGI_FORMAT_B8G8R8A8_UNORM
+        87 => return uncompressed(32, [0xFF0000, 0xFF00, 0xFF, 0xFF000000]),
+        // DXGI_FORMAT_B8G8R8X8_UNORM
+        88 => return uncompressed(32, [0xFF0000, 0xFF00, 0xFF, 0]),
+        // DXGI_FORMAT_B4G4R4A4_UNORM
+        115 => return uncompressed(16, [0xF00, 0xF0, 0xF, 0xF000]),
+
+        // DXGI_FORMAT_BC1_UNORM
+        71 => S3tcFormat::Dxt1Rgba.into(),
+        // DXGI_FORMAT_BC2_UNORM
+        74 => S3tcFormat::Dxt3Rgba.into(),
+        // DXGI_FORMAT_BC3_UNORM
+        77 => S3tcFormat::Dxt5Rgba.into(),
+        // DXGI_FORMAT_BC4_UNORM
+        80 => RgtcFormat::RedRgtc1.into(),
+        // DXGI_FORMAT_BC4_SNORM
+        81 => RgtcFormat::SignedRedRgtc1.into(),
+        // DXGI_FORMAT_BC5_UNORM
+        83 => RgtcFormat::RgRgtc2.into(),
+        // DXGI_FORMAT_BC5_SNORM
+        84 => RgtcFormat::SignedRgRgtc2.into(),
+
+        _ => return None
+    };
+    Some(DdsFormat::Compressed(format))
+}
+
+// Find an image format that can hold every component of an uncompressed
+// DDS pixel format without losing precision
+fn _uncompressed_format(masks: TexelMasks, luminance: bool)
+    -> Option<ImageFormat>
+{
+    let mut bits = [0; 4];
+    for (bits, &mask) in bits.iter_mut().zip(masks.masks.iter()) {
+        *bits = match _mask_bits(mask) {
+            Some(mask_bits) => mask_bits,
+            None => {
+                return None;
+            }
+        };
+    }
+
+    let bits = (bits[0], bits[1], bits[2], bits[3]);
+    let format = match (masks.bit_count, luminance, bits) {
+        (8, true, (8, 0, 0, 0)) => (TexelType::UnsignedByte, TexelFormat::Luminance),
+        (16, true, (8, 0, 0, 8)) => (TexelType::UnsignedByte, TexelFormat::LuminanceAlpha),
+        (8, false, (0, 0, 0, 8)) => (TexelType::UnsignedByte, TexelFormat::Alpha),
+        (24, false, (8, 8, 8, 0))
+        | (32, false, (8, 8, 8, 0)) => (TexelType::UnsignedByte, TexelFormat::RGB),
+        (32, false, (8, 8, 8, 8)) => (TexelType::UnsignedByte, TexelFormat::RGBA),
+        (16, false, (5, 6, 5, 0)) => (TexelType::UnsignedShort565, TexelFormat::RGB),
+        (16, false, (4, 4, 4, 4)) => (TexelType::UnsignedShort4444, TexelFormat::RGBA),
+        (16, false, (5, 5, 5, 1)) => (TexelType::UnsignedShort5551, TexelFormat::RGBA),
+        _ => {
+            return None;
+        }
+    };
+
+    Some(ImageFormat { texel_type: format.0, texel_format: format.1 })
+}
+
+// Get the number of bits in a mask, or `None` if the bits aren't
+// contiguous
+fn _mask_bits(mask: u32) -> Option<u32> {
+    if mask == 0 {
+        return Some(0);
+    }
+
+    let shifted = mask >> mask.trailing_zeros();
+    if shifted & (shifted.wrapping_add(1)) == 0 {
+        Some(shifted.count_ones())
+    }
+    else {
+        None
+    }
+}
+
+// Convert uncompressed DDS texels to the layout of an image format, whose
+// components have the same sizes as the masks
+fn _convert_texels(data: &[u8], masks: TexelMasks, format: ImageFormat)
+    -> Vec<u8>
+{
+    let texel_bytes = (masks.bit_count / 8) as usize;
+    let mut converted = Vec::with_capacity(data.len() / texel_bytes
+                                           * format.texel_size());
+    for texel in data.chunks(texel_bytes) {
+        let texel = texel.iter().rev().fold(0, |value, &byte| {
+            value << 8 | byte as u32
+        });
+        let mut c = [0; 4];
+        for (component, &mask) in c.iter_mut().zip(masks.masks.iter()) {
+            if mask != 0 {
+                *component = (texel & mask) >> mask.trailing_zeros();
+            }
+        }
+        let (r, g, b, a) = (c[0], c[1], c[2], c[3]);
+
+        let packed = match (format.texel_type, format.texel_format) {
+            (TexelType::UnsignedByte, TexelFormat::Luminance) => {
+                converted.push(r as u8);
+                continue;
+            },
+            (TexelType::UnsignedByte, TexelFormat::LuminanceAlpha) => {
+                converted.extend_from_slice(&[r as u8, a as u8]);
+                continue;
+            },
+            (TexelType::UnsignedByte, TexelFormat::Alpha) => {
+                converted.push(a as u8);
+                continue;
+            },
+            (TexelType::UnsignedByte, TexelFormat::RGB) => {
+                converted.extend_from_slice(&[r as u8, g as u8, b as u8]);
+                continue;
+            },
+            (TexelType::UnsignedByte, TexelFormat::RGBA) => {
+                converted.extend_from_slice(&[r as u8, g as u8, b as u8,
+                                              a as u8]);
+                continue;
+            },
+            (TexelType::UnsignedShort565, _) => r << 11 | g << 5 | b,
+            (TexelType::UnsignedShort4444, _) => r << 12 | g << 8 | b << 4 | a,
+            (TexelType::UnsignedShort5551, _) => r << 11 | g << 6 | b << 1 | a,
+            (TexelType::Float, _) | (TexelType::HalfFloatOes, _) => {
+                unreachable!("DDS pixel formats are never converted to floats")
+            }
+        };
+
+        // Packed texels are uploaded in the platform's byte order
+        let packed = packed as u16;
+        let bytes = if cfg!(target_endian = "big") {
+            [(packed >> 8) as u8, packed as u8]
+        }
+        else {
+            [packed as u8, (packed >> 8) as u8]
+        };
+        converted.extend_from_slice(&bytes);
+    }
+    converted
+}
+
+fn _four_cc_bytes(four_cc: u32) -> [u8; 4] {
+    [four_cc as u8, (four_cc >> 8) as u8, (four_cc >> 16) as u8,
+     (four_cc >> 24) as u8]
+}
+
+fn _slice(bytes: &[u8], pos: usize, len: usize) -> Result<&[u8], DdsError> {
+    match pos.checked_add(len) {
+        Some(end) if end <= bytes.len() => Ok(&bytes[pos..end]),
+        _ => Err(DdsError::UnexpectedEnd)
+    }
+}
+
+fn _read_u32(bytes: &[u8], pos: usize) -> Result<u32, DdsError> {
+    let field = try!(_slice(bytes, pos, 4));
+    Ok(field.iter().rev().fold(0, |value, &byte| value << 8 | byte as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use image_data::{ImageFormat, TexelType, TexelFormat};
+    use compressed_image::{S3tcFormat, RgtcFormat};
+    use texture_file::{TextureFile, TextureFileFormat};
+    use super::*;
+
+    const DDSD_REQUIRED: u32 = 0x100F;
+
+    fn _set_u32(bytes: &mut [u8], offset: usize, value: u32) {
+        for i in 0..4 {
+            bytes[offset + i] = (value >> (i * 8)) as u8;
+        }
+    }
+
+    // A DDS header without a DX10 header, for a texture with the given
+    // size and an uncompressed pixel format
+    fn _legacy(width: u32, height: u32, pixel_flags: u32, bit_count: u32,
+               masks: [u32; 4])
+        -> Vec<u8>
+    {
+        let mut bytes = vec![0; 128];
+        _set_u32(&mut bytes, 0, DDS_MAGIC);
+        _set_u32(&mut bytes, 4, DDS_HEADER_SIZE);
+        _set_u32(&mut bytes, 8, DDSD_REQUIRED);
+        _set_u32(&mut bytes, 12, height);
+        _set_u32(&mut bytes, 16, width);
+        _set_u32(&mut bytes, 76, DDS_PIXEL_FORMAT_SIZE);
+        _set_u32(&mut bytes, 80, pixel_flags);
+        _set_u32(&mut bytes, 88, bit_count);
+        for (i, &mask) in masks.iter().enumerate() {
+            _set_u32(&mut bytes, 92 + i * 4, mask);
+        }
+        bytes
+    }
+
+    // A DDS header without a DX10 header, for a texture with the given
+    // size and a FourCC code
+    fn _four_cc(width: u32, height: u32, four_cc: &[u8; 4]) -> Vec<u8> {
+        let mut bytes = _legacy(width, height, DDPF_FOURCC, 0, [0; 4]);
+        bytes[84..88].copy_from_slice(four_cc);
+        bytes
+    }
+
+    // A DDS header followed by a DX10 header with the given DXGI format
+    fn _dx10(width: u32, height: u32, dxgi_format: u32, misc_flags: u32)
+        -> Vec<u8>
+    {
+        let mut bytes = _four_cc(width, height, b"DX10");
+        bytes.extend_from_slice(&[0; DX10_HEADER_SIZE]);
+        _set_u32(&mut bytes, 128, dxgi_format);
+        _set_u32(&mut bytes, 132, DX10_DIMENSION_TEXTURE2D);
+        _set_u32(&mut bytes, 136, misc_flags);
+        _set_u32(&mut bytes, 140, 1);
+        bytes
+    }
+
+    fn _set_levels(bytes: &mut [u8], levels: u32) {
+        _set_u32(bytes, 8, DDSD_REQUIRED | DDSD_MIPMAPCOUNT);
+        _set_u32(bytes, 28, levels);
+    }
+
+    fn _error(bytes: &[u8]) -> String {
+        TextureFile::from_dds(bytes).unwrap_err().to_string()
+    }
+
+    fn _native_u16(value: u16) -> [u8; 2] {
+        if cfg!(target_endian = "big") {
+            [(value >> 8) as u8, value as u8]
+        }
+        else {
+            [value as u8, (value >> 8) as u8]
+        }
+    }
+
+    #[test]
+    fn read_legacy_mipmaps() {
+        // A 2x2 BGRA image and its 1x1 mipmap
+        let mut bytes = _legacy(2, 2, DDPF_RGB | DDPF_ALPHAPIXELS, 32,
+                                [0xFF0000, 0xFF00, 0xFF, 0xFF000000]);
+        _set_levels(&mut bytes, 2);
+        bytes.extend((0..16).map(|i| i as u8));
+        bytes.extend_from_slice(&[0x10, 0x20, 0x30, 0x40]);
+
+        let file = TextureFile::from_dds(&bytes).unwrap();
+        assert_eq!(file.format(),
+                   TextureFileFormat::Uncompressed(ImageFormat::rgba8()));
+        assert_eq!(file.levels(), 2);
+        assert_eq!(file.level_data(0, 0), &[2, 1, 0, 3, 6, 5, 4, 7,
+                                            10, 9, 8, 11, 14, 13, 12, 15]);
+        assert_eq!(file.level_size(1), (1, 1));
+        assert_eq!(file.level_data(1, 0), &[0x30, 0x20, 0x10, 0x40]);
+
+        // A mipmap count without its flag is ignored
+        _set_u32(&mut bytes, 8, DDSD_REQUIRED);
+        assert_eq!(TextureFile::from_dds(&bytes).unwrap().levels(), 1);
+    }
+
+    #[test]
+    fn read_legacy_pixel_formats() {
+        let read = |pixel_flags, bit_count, masks, texel: &[u8]| {
+            let mut bytes = _legacy(1, 1, pixel_flags, bit_count, masks);
+            bytes.extend_from_slice(texel);
+            let file = TextureFile::from_dds(&bytes).unwrap();
+            let format = match file.format() {
+                TextureFileFormat::Uncompressed(format) => format,
+                format => panic!("unexpected format {:?}", format)
+            };
+            (format.texel_type, format.texel_format, file.level_data(0, 0).to_vec())
+        };
+
+        assert_eq!(read(DDPF_RGB, 24, [0xFF0000, 0xFF00, 0xFF, 0], &[1, 2, 3]),
+                   (TexelType::UnsignedByte, TexelFormat::RGB, vec![3, 2, 1]));
+        assert_eq!(read(DDPF_RGB, 32, [0xFF0000, 0xFF00, 0xFF, 0xFF000000], &[1, 2, 3, 4]),
+                   (TexelType::UnsignedByte, TexelFormat::RGB, vec![3, 2, 1]));
+        assert_eq!(read(DDPF_LUMINANCE, 8, [0xFF, 0, 0, 0], &[7]),
+                   (TexelType::UnsignedByte, TexelFormat::Luminance, vec![7]));
+        assert_eq!(read(DDPF_LUMINANCE | DDPF_ALPHAPIXELS, 16, [0xFF, 0, 0, 0xFF00], &[7, 8]),
+                   (TexelType::UnsignedByte, TexelFormat::LuminanceAlpha, vec![7, 8]));
+        assert_eq!(read(DDPF_ALPHA, 8, [0, 0, 0, 0xFF], &[9]),
+                   (TexelType::UnsignedByte, TexelFormat::Alpha, vec![9]));
+
+        assert_eq!(read(DDPF_RGB, 16, [0xF800, 0x7E0, 0x1F, 0], &[0x34, 0x12]),
+                   (TexelType::UnsignedShort565, TexelFormat::RGB, _native_u16(0x1234).to_vec()));
+        assert_eq!(read(DDPF_RGB | DDPF_ALPHAPIXELS, 16, [0xF00, 0xF0, 0xF, 0xF000], &[0x34, 0x12]),
+                   (TexelType::UnsignedShort4444, TexelFormat::RGBA, _native_u16(0x2341).to_vec()));
+
+        // A1R5G5B5 texels are reordered to R5G5B5A1
+        assert_eq!(read(DDPF_RGB | DDPF_ALPHAPIXELS, 16, [0x7C00, 0x3E0, 0x1F, 0x8000], &[0x34, 0x92]),
+                   (TexelType::UnsignedShort5551, TexelFormat::RGBA, _native_u16(0x2469).to_vec()));
+    }
+
+    #[test]
+    fn read_legacy_compressed() {
+        let read = |four_cc, pixel_flags| {
+            let mut bytes = _four_cc(4, 4, four_cc);
+            _set_u32(&mut bytes, 80, DDPF_FOURCC | pixel_flags);
+            bytes.extend_from_slice(&[0xAB; 16]);
+            TextureFile::from_dds(&bytes).unwrap().format()
+        };
+
+        assert_eq!(read(b"DXT1", 0), TextureFileFormat::Compressed(S3tcFormat::Dxt1Rgb.into()));
+        assert_eq!(read(b"DXT1", DDPF_ALPHAPIXELS),
+                   TextureFileFormat::Compressed(S3tcFormat::Dxt1Rgba.into()));
+        assert_eq!(read(b"DXT3", 0), TextureFileFormat::Compressed(S3tcFormat::Dxt3Rgba.into()));
+        assert_eq!(read(b"DXT5", 0), TextureFileFormat::Compressed(S3tcFormat::Dxt5Rgba.into()));
+        assert_eq!(read(b"ATI1", 0), TextureFileFormat::Compressed(RgtcFormat::RedRgtc1.into()));
+        assert_eq!(read(b"BC4S", 0), TextureFileFormat::Compressed(RgtcFormat::SignedRedRgtc1.into()));
+        assert_eq!(read(b"ATI2", 0), TextureFileFormat::Compressed(RgtcFormat::RgRgtc2.into()));
+        assert_eq!(read(b"BC5S", 0), TextureFileFormat::Compressed(RgtcFormat::SignedRgRgtc2.into()));
+    }
+
+    #[test]
+    fn read_dx10() {
+        let mut bytes = _dx10(8, 4, 71, 0);
+        bytes.extend((0..16).map(|i| i as u8));
+        let file = TextureFile::from_dds(&bytes).unwrap();
+        assert_eq!(file.format(),
+                   TextureFileFormat::Compressed(S3tcFormat::Dxt1Rgba.into()));
+        assert_eq!(file.level_size(0), (8, 4));
+        assert_eq!(file.level_data(0, 0), &(0..16).collect::<Vec<u8>>()[..]);
+
+        let mut bytes = _dx10(1, 1, 87, 0);
+        bytes.extend_from_slice(&[1, 2, 3, 4]);
+        let file = TextureFile::from_dds(&bytes).unwrap();
+        assert_eq!(file.format(),
+                   TextureFileFormat::Uncompressed(ImageFormat::rgba8()));
+        assert_eq!(file.level_data(0, 0), &[3, 2, 1, 4]);
+    }
+
+    #[test]
+    fn read_cube_maps() {
+        // DDS files store each face with all of its mipmaps, before the
+        // next face
+        let face_data = |face: u8| {
+            let mut data = vec![face; 8];
+            data.extend_from_slice(&[face | 0x80; 8]);
+            data
+        };
+
+        let mut legacy = _four_cc(4, 4, b"DXT1");
+        _set_u32(&mut legacy, 112, DDSCAPS2_CUBEMAP | DDSCAPS2_CUBEMAP_ALL_FACES);
+        let mut dx10 = _dx10(4, 4, 71, DX10_MISC_TEXTURECUBE);
+        for bytes in &mut [&mut legacy, &mut dx10] {
+            _set_levels(bytes, 2);
+            for face in 0..6 {
+                bytes.extend_from_slice(&face_data(face));
+            }
+
+            let file = TextureFile::from_dds(bytes).unwrap();
+            assert!(file.is_cube_map());
+            assert_eq!(file.levels(), 2);
+            for face in 0..6 {
+                assert_eq!(file.level_data(0, face as usize), &[face; 8]);
+                assert_eq!(file.level_data(1, face as usize), &[face | 0x80; 8]);
+            }
+        }
+    }
+
+    #[test]
+    fn reject_truncated() {
+        let mut bytes = _dx10(4, 4, 71, 0);
+        _set_levels(&mut bytes, 3);
+        bytes.extend_from_slice(&[0; 24]);
+        assert!(TextureFile::from_dds(&bytes).is_ok());
+        for len in 0..bytes.len() {
+            assert_eq!(_error(&bytes[..len]), "the DDS file is truncated");
+        }
+    }
+
+    #[test]
+    fn reject_invalid_headers() {
+        let rgba = |width, height| {
+            _legacy(width, height, DDPF_RGB | DDPF_ALPHAPIXELS, 32,
+                    [0xFF0000, 0xFF00, 0xFF, 0xFF000000])
+        };
+
+        let mut bytes = rgba(1, 1);
+        bytes[0] = b'X';
+        assert_eq!(_error(&bytes), "the data is not a DDS file");
+
+        let mut bytes = rgba(1, 1);
+        _set_u32(&mut bytes, 4, 128);
+        assert_eq!(_error(&bytes), "the DDS file has an invalid header: invalid header size");
+
+        let mut bytes = rgba(1, 1);
+        _set_u32(&mut bytes, 76, 24);
+        assert_eq!(_error(&bytes), "the DDS file has an invalid header: invalid pixel format size");
+
+        assert_eq!(_error(&rgba(0, 1)),
+                   "the DDS file has an invalid header: the texture has a width or height of 0");
+        assert_eq!(_error(&rgba(1, 0)),
+                   "the DDS file has an invalid header: the texture has a width or height of 0");
+        assert_eq!(_error(&rgba(1 << 17, 1)),
+                   "the DDS file has an invalid header: the texture is too large");
+
+        let mut bytes = rgba(2, 2);
+        _set_levels(&mut bytes, 3);
+        assert_eq!(_error(&bytes), "the DDS file has an invalid header: too many mipmap levels");
+
+        let mut bytes = rgba(2, 1);
+        _set_u32(&mut bytes, 112, DDSCAPS2_CUBEMAP | DDSCAPS2_CUBEMAP_ALL_FACES);
+        assert_eq!(_error(&bytes), "the DDS file has an invalid header: cubemap faces are not square");
+    }
+
+    #[test]
+    fn reject_unsupported_textures() {
+        let mut bytes = _four_cc(4, 4, b"DXT1");
+        _set_u32(&mut bytes, 112, DDSCAPS2_CUBEMAP | 0x400);
+        assert_eq!(_error(&bytes),
+                   "the DDS file contains an unsupported texture: cubemaps must have all six faces");
+
+        let mut bytes = _four_cc(4, 4, b"DXT1");
+        _set_u32(&mut bytes, 112, DDSCAPS2_VOLUME);
+        assert_eq!(_error(&bytes),
+                   "the DDS file contains an unsupported texture: volume textures are not supported");
+
+        let mut bytes = _dx10(4, 4, 71, 0);
+        _set_u32(&mut bytes, 132, 4);
+        assert_eq!(_error(&bytes),
+                   "the DDS file contains an unsupported texture: only 2D textures and cubemaps are supported");
+
+        let mut bytes = _dx10(4, 4, 71, 0);
+        _set_u32(&mut bytes, 140, 2);
+        assert_eq!(_error(&bytes),
+                   "the DDS file contains an unsupported texture: texture arrays are not supported");
+    }
+
+    #[test]
+    fn reject_unsupported_pixel_formats() {
+        assert_eq!(_error(&_four_cc(4, 4, b"ETC1")), "unsupported DDS FourCC code \"ETC1\"");
+
+        // DXGI_FORMAT_R32G32B32A32_FLOAT
+        assert_eq!(_error(&_dx10(4, 4, 2, 0)), "unsupported DDS DXGI_FORMAT 2");
+
+        // A2B10G10R10
+        let bytes = _legacy(1, 1, DDPF_RGB | DDPF_ALPHAPIXELS, 32,
+                            [0x3FF, 0xFFC00, 0x3FF00000, 0xC0000000]);
+        assert_eq!(_error(&bytes),
+                   "unsupported DDS pixel format (32 bits per texel, masks R 0x3FF, G 0xFFC00, B 0x3FF00000, A 0xC0000000)");
+
+        // A mask whose bits aren't contiguous
+        let bytes = _legacy(1, 1, DDPF_RGB, 24, [0xFF0000, 0xF0F0, 0xFF, 0]);
+        assert_eq!(_error(&bytes),
+                   "unsupported DDS pixel format (24 bits per texel, masks R 0xFF0000, G 0xF0F0, B 0xFF, A 0x0)");
+    }
+}
@@ -0,0 +1,859 @@
+//! Contains the parser for [KTX 1.1]
+//! (https://registry.khronos.org/KTX/specs/1.0/ktxspec.v1.html) and [KTX2]
+//! (https://registry.khronos.org/KTX/specs/2.0/ktxspec.v2.html) files.
+
+use std::fmt;
+use std::error;
+use gl::types::*;
+use image_data::{ImageFormat, TexelType, TexelFormat};
+use compressed_image::CompressedTexelFormat;
+use super::{TextureFile, TextureFileFormat};
+use super::zstd;
+
+const KTX1_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x31, 0x31, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A
+];
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A
+];
+
+const KTX1_ENDIANNESS: u32 = 0x04030201;
+const KTX1_HEADER_SIZE: usize = 64;
+const KTX2_HEADER_SIZE: usize = 80;
+const KTX2_LEVEL_INDEX_SIZE: usize = 24;
+
+const GL_HALF_FLOAT: GLenum = 0x140B;
+
+const SUPERCOMPRESSION_NONE: u32 = 0;
+const SUPERCOMPRESSION_ZSTD: u32 = 2;
+
+/// An error that occurred while reading a KTX or KTX2 file.
+#[derive(Debug)]
+pub enum KtxError {
+    /// The data doesn't start with the KTX 1.1 or KTX2 file identifier.
+    InvalidIdentifier,
+
+    /// The data ended before the end of the file's header or image data.
+    UnexpectedEnd,
+
+    /// The file's header contains an invalid value.
+    InvalidHeader(&'static str),
+
+    /// The file contains a kind of texture that can't be loaded, such as
+    /// a 3D texture or a texture array.
+    UnsupportedTexture(&'static str),
+
+    /// The `glType`, `glFormat`, and `glInternalFormat` of a KTX 1.1 file
+    /// don't describe a format that glitter can upload.
+    UnsupportedGlFormat {
+        /// The file's `glType` field.
+        gl_type: GLenum,
+
+        /// The file's `glFormat` field.
+        gl_format: GLenum,
+
+        /// The file's `glInternalFormat` field.
+        gl_internal_format: GLenum
+    },
+
+    /// The `vkFormat` of a KTX2 file doesn't have an equivalent format
+    /// that glitter can upload.
+    UnsupportedVkFormat(u32),
+
+    /// A KTX2 file uses a supercompression scheme other than Zstandard.
+    UnsupportedSupercompression(u32),
+
+    /// A supercompressed mipmap level of a KTX2 file could not
+    /// be decompressed.
+    InvalidSupercompressedData(&'static str)
+}
+
+impl fmt::Display for KtxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            KtxError::InvalidIdentifier => {
+                write!(f, "the data is not a KTX or KTX2 file")
+            },
+            KtxError::UnexpectedEnd => {
+                write!(f, "the KTX file is truncated")
+            },
+            KtxError::InvalidHeader(msg) => {
+                write!(f, "the KTX file has an invalid header: {}", msg)
+            },
+            KtxError::UnsupportedTexture(msg) => {
+                write!(f, "the KTX file contains an unsupported texture: {}",
+                       msg)
+            },
+            KtxError::UnsupportedGlFormat { gl_type,
+                                            gl_format,
+                                            gl_internal_format } => {
+                write!(f, "unsupported KTX format (glType 0x{:X}, glFormat 0x{:X}, glInternalFormat 0x{:X})",
+                       gl_type, gl_format, gl_internal_format)
+            },
+            KtxError::UnsupportedVkFormat(vk_format) => {
+                write!(f, "unsupported KTX2 vkFormat {}", vk_format)
+            },
+            KtxError::UnsupportedSupercompression(scheme) => {
+                write!(f, "unsupported KTX2 supercompression scheme {}",
+                       scheme)
+            },
+            KtxError::InvalidSupercompressedData(msg) => {
+                write!(f, "invalid supercompressed KTX2 data: {}", msg)
+            }
+        }
+    }
+}
+
+impl error::Error for KtxError {
+    fn description(&self) -> &str {
+        match *self {
+            KtxError::InvalidIdentifier => "invalid KTX file identifier",
+            KtxError::UnexpectedEnd => "unexpected end of KTX file",
+            KtxError::InvalidHeader(_) => "invalid KTX file header",
+            KtxError::UnsupportedTexture(_) => "unsupported KTX texture type",
+            KtxError::UnsupportedGlFormat { .. }
+            | KtxError::UnsupportedVkFormat(_) => "unsupported KTX format",
+            KtxError::UnsupportedSupercompression(_) => {
+                "unsupported KTX2 supercompression scheme"
+            },
+            KtxError::InvalidSupercompressedData(_) => {
+                "invalid supercompressed KTX2 data"
+            }
+        }
+    }
+}
+
+impl TextureFile {
+    /// Read a 2D or cubemap texture from the contents of a KTX 1.1 or
+    /// KTX2 file. The version of the file is detected from its
+    /// identifier. KTX2 files may either be uncompressed or
+    /// supercompressed with Zstandard.
+    ///
+    /// Each mipmap level stored in the file is read. A file that asks
+    /// for its mipmaps to be generated at load time is read with only
+    /// its base level.
+    ///
+    /// # Failures
+    /// An error will be returned if the file is malformed, if it contains
+    /// a 1D texture, a 3D texture, or a texture array, or if its format
+    /// can't be uploaded by glitter. Note that the file's format is not
+    /// checked against what the current context supports until the file
+    /// is uploaded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use glitter::{TextureFile, TextureFileFormat, ImageFormat};
+    /// use glitter::{TexelType, TexelFormat};
+    ///
+    /// # fn push_u32(bytes: &mut Vec<u8>, value: u32) {
+    /// #     for i in 0..4 {
+    /// #         bytes.push((value >> (i * 8)) as u8);
+    /// #     }
+    /// # }
+    /// // A KTX 1.1 file containing a single 2x1 RGB image
+    /// let mut ktx = b"\xABKTX 11\xBB\r\n\x1A\n".to_vec();
+    /// for &field in &[0x04030201, 0x1401, 1, 0x1907, 0x8051, 0x1907,
+    ///                 2, 1, 0, 0, 1, 1, 0] {
+    ///     push_u32(&mut ktx, field);
+    /// }
+    /// // Each row of texels is padded to a multiple of 4 bytes
+    /// push_u32(&mut ktx, 8);
+    /// ktx.extend_from_slice(&[0xFF, 0, 0, 0, 0xFF, 0, 0, 0]);
+    ///
+    /// let file = TextureFile::from_ktx(&ktx).unwrap();
+    /// let rgb8 = ImageFormat {
+    ///     texel_type: TexelType::UnsignedByte,
+    ///     texel_format: TexelFormat::RGB
+    /// };
+    /// assert_eq!(file.format(), TextureFileFormat::Uncompressed(rgb8));
+    /// assert_eq!(file.level_size(0), (2, 1));
+    /// assert_eq!(file.level_data(0, 0), &[0xFF, 0, 0, 0, 0xFF, 0]);
+    ///
+    /// assert!(TextureFile::from_ktx(&ktx[..70]).is_err());
+    /// ```
+    pub fn from_ktx(bytes: &[u8]) -> Result<TextureFile, KtxError> {
+        let identifier = try!(_slice(bytes, 0, 12));
+        if identifier == &KTX1_IDENTIFIER[..] {
+            _read_ktx1(bytes)
+        }
+        else if identifier == &KTX2_IDENTIFIER[..] {
+            _read_ktx2(bytes)
+        }
+        else {
+            Err(KtxError::InvalidIdentifier)
+        }
+    }
+}
+
+fn _read_ktx1(bytes: &[u8]) -> Result<TextureFile, KtxError> {
+    let big_endian = match try!(_read_u32(bytes, 12, false)) {
+        KTX1_ENDIANNESS => false,
+        endianness if endianness.swap_bytes() == KTX1_ENDIANNESS => true,
+        _ => {
+            return Err(KtxError::InvalidHeader("invalid endianness"));
+        }
+    };
+
+    let mut header = [0; 12];
+    for (i, field) in header.iter_mut().enumerate() {
+        *field = try!(_read_u32(bytes, 16 + i * 4, big_endian));
+    }
+    let (gl_type, gl_type_size, gl_format, gl_internal_format) =
+        (header[0], header[1], header[2], header[3]);
+    let (width, height, depth) = (header[5], header[6], header[7]);
+    let (array_elements, faces, levels) = (header[8], header[9], header[10]);
+    let key_value_bytes = header[11];
+
+    if depth != 0 {
+        return Err(KtxError::UnsupportedTexture("3D textures are not supported"));
+    }
+    if array_elements != 0 {
+        return Err(KtxError::UnsupportedTexture("texture arrays are not supported"));
+    }
+
+    let format = try!(_ktx1_format(gl_type, gl_format, gl_internal_format));
+    let (width, height, faces, levels) =
+        try!(_check_dimensions(width, height, faces, levels));
+
+    // Multi-byte texels are stored in the file's byte order, which
+    // must be swapped if it doesn't match the platform's
+    let swap_size = match gl_type_size {
+        1 | 2 | 4 if big_endian != cfg!(target_endian = "big") => {
+            gl_type_size as usize
+        },
+        1 | 2 | 4 => 1,
+        _ => {
+            return Err(KtxError::InvalidHeader("invalid glTypeSize"));
+        }
+    };
+
+    let mut pos = KTX1_HEADER_SIZE + key_value_bytes as usize;
+    let mut file_levels = Vec::with_capacity(levels);
+    for level in 0..levels {
+        let (level_width, level_height) = _level_size(width, height, level);
+        let image_size = try!(_read_u32(bytes, pos, big_endian)) as usize;
+        pos += 4;
+
+        // Uncompressed rows are padded to a multiple of 4 bytes
+        let (row_size, stride) = match format {
+            TextureFileFormat::Uncompressed(format) => {
+                let row_size = level_width * format.texel_size();
+                (row_size, _pad4(row_size))
+            },
+            TextureFileFormat::Compressed(_) => {
+                let size = format.image_size(level_width, level_height);
+                (size, size)
+            }
+        };
+        let rows = match format {
+            TextureFileFormat::Uncompressed(_) => level_height,
+            TextureFileFormat::Compressed(_) => 1
+        };
+
+        // The image size is the size of a single face for cubemaps,
+        // or the size of the whole level otherwise
+        if image_size != stride * rows {
+            return Err(KtxError::InvalidHeader("a mipmap level has the wrong imageSize"));
+        }
+
+        let mut file_faces = Vec::with_capacity(faces);
+        for _ in 0..faces {
+            let face = try!(_slice(bytes, pos as u64, image_size as u64));
+            let mut data = Vec::with_capacity(row_size * rows);
+            for row in face.chunks(stride) {
+                data.extend_from_slice(&row[..row_size]);
+            }
+            _swap_bytes(&mut data, swap_size);
+            file_faces.push(data);
+
+            pos += _pad4(image_size);
+        }
+        file_levels.push(file_faces);
+    }
+
+    Ok(TextureFile {
+        width: width,
+        height: height,
+        format: format,
+        levels: file_levels
+    })
+}
+
+fn _read_ktx2(bytes: &[u8]) -> Result<TextureFile, KtxError> {
+    let mut header = [0; 9];
+    for (i, field) in header.iter_mut().enumerate() {
+        *field = try!(_read_u32(bytes, 12 + i * 4, false));
+    }
+    let (vk_format, type_size) = (header[0], header[1]);
+    let (width, height, depth) = (header[2], header[3], header[4]);
+    let (layers, faces, levels) = (header[5], header[6], header[7]);
+    let supercompression = header[8];
+
+    if depth != 0 {
+        return Err(KtxError::UnsupportedTexture("3D textures are not supported"));
+    }
+    if layers != 0 {
+        return Err(KtxError::UnsupportedTexture("texture arrays are not supported"));
+    }
+    match supercompression {
+        SUPERCOMPRESSION_NONE | SUPERCOMPRESSION_ZSTD => { },
+        scheme => {
+            return Err(KtxError::UnsupportedSupercompression(scheme));
+        }
+    }
+
+    let format = match _vk_format(vk_format) {
+        Some(format) => format,
+        None => {
+            return Err(KtxError::UnsupportedVkFormat(vk_format));
+        }
+    };
+    let (width, height, faces, levels) =
+        try!(_check_dimensions(width, height, faces, levels));
+
+    // Multi-byte texels are always stored in little-endian order
+    let swap_size = match type_size {
+        1 | 2 | 4 if cfg!(target_endian = "big") => type_size as usize,
+        1 | 2 | 4 => 1,
+        _ => {
+            return Err(KtxError::InvalidHeader("invalid typeSize"));
+        }
+    };
+
+    let mut file_levels = Vec::with_capacity(levels);
+    for level in 0..levels {
+        let index = KTX2_HEADER_SIZE + level * KTX2_LEVEL_INDEX_SIZE;
+        let offset = try!(_read_u64(bytes, index));
+        let length = try!(_read_u64(bytes, index + 8));
+        let uncompressed_length = try!(_read_u64(bytes, index + 16));
+
+        let data = try!(_slice(bytes, offset, length));
+        let data = if supercompression == SUPERCOMPRESSION_ZSTD {
+            let data = try!(zstd::decompress(data)
+                .map_err(KtxError::InvalidSupercompressedData));
+            if data.len() as u64 != uncompressed_length {
+                let msg = "a mipmap level has the wrong uncompressed length";
+                return Err(KtxError::InvalidSupercompressedData(msg));
+            }
+            data
+        }
+        else {
+            data.to_vec()
+        };
+
+        let (level_width, level_height) = _level_size(width, height, level);
+        let image_size = format.image_size(level_width, level_height);
+        if data.len() != image_size * faces {
+            return Err(KtxError::InvalidHeader("a mipmap level has the wrong length"));
+        }
+
+        let file_faces = data.chunks(image_size).map(|face| {
+            let mut face = face.to_vec();
+            _swap_bytes(&mut face, swap_size);
+            face
+        }).collect();
+        file_levels.push(file_faces);
+    }
+
+    Ok(TextureFile {
+        width: width,
+        height: height,
+        format: format,
+        levels: file_levels
+    })
+}
+
+// Convert the format fields of a KTX 1.1 file to a texture file format
+fn _ktx1_format(gl_type: GLenum, gl_format: GLenum, gl_internal_format: GLenum)
+    -> Result<TextureFileFormat, KtxError>
+{
+    let unsupported = KtxError::UnsupportedGlFormat {
+        gl_type: gl_type,
+        gl_format: gl_format,
+        gl_internal_format: gl_internal_format
+    };
+
+    // Compressed formats have a glType and glFormat of 0
+    if gl_type == 0 && gl_format == 0 {
+        return CompressedTexelFormat::from_gl(gl_internal_format)
+            .map(TextureFileFormat::Compressed)
+            .map_err(|_| unsupported);
+    }
+
+    // Desktop OpenGL's GL_HALF_FLOAT has the same layout as
+    // GL_HALF_FLOAT_OES, which is what OpenGL ES 2 accepts
+    let gl_type = if gl_type == GL_HALF_FLOAT {
+        TexelType::HalfFloatOes.gl_enum()
+    }
+    else {
+        gl_type
+    };
+
+    let (texel_type, texel_format) = match (TexelType::from_gl(gl_type),
+                                            TexelFormat::from_gl(gl_format))
+    {
+        (Ok(texel_type), Ok(texel_format)) => (texel_type, texel_format),
+        _ => {
+            return Err(unsupported);
+        }
+    };
+
+    // Packed texel types can only be used with a matching format
+    match (texel_type, texel_format) {
+        (TexelType::UnsignedByte, _)
+        | (TexelType::Float, _)
+        | (TexelType::HalfFloatOes, _)
+        | (TexelType::UnsignedShort565, TexelFormat::RGB)
+        | (TexelType::UnsignedShort4444, TexelFormat::RGBA)
+        | (TexelType::UnsignedShort5551, TexelFormat::RGBA) => {
+            Ok(TextureFileFormat::Uncompressed(ImageFormat {
+                texel_type: texel_type,
+                texel_format: texel_format
+            }))
+        },
+        _ => Err(unsupported)
+    }
+}
+
+// Convert the `vkFormat` of a KTX2 file to a texture file format
+fn _vk_format(vk_format: u32) -> Option<TextureFileFormat> {
+    let uncompressed = |texel_type, texel_format| {
+        Some(TextureFileFormat::Uncompressed(ImageFormat {
+            texel_type: texel_type,
+            texel_format: texel_format
+        }))
+    };
+
+    let gl_enum = match vk_format {
+        // VK_FORMAT_R4G4B4A4_UNORM_PACK16
+        2 => return uncompressed(TexelType::UnsignedShort4444, TexelFormat::RGBA),
+        // VK_FORMAT_R5G6B5_UNORM_PACK16
+        4 => return uncompressed(TexelType::UnsignedShort565, TexelFormat::RGB),
+        // VK_FORMAT_R5G5B5A1_UNORM_PACK16
+        6 => return uncompressed(TexelType::UnsignedShort5551, TexelFormat::RGBA),
+        // VK_FORMAT_R8G8B8_UNORM
+        23 => return uncompressed(TexelType::UnsignedByte, TexelFormat::RGB),
+        // VK_FORMAT_R8G8B8A8_UNORM
+        37 => return uncompressed(TexelType::UnsignedByte, TexelFormat::RGBA),
+
+        // VK_FORMAT_BC1_RGB_UNORM_BLOCK
+        131 => 0x83F0,
+        // VK_FORMAT_BC1_RGBA_UNORM_BLOCK
+        133 => 0x83F1,
+        // VK_FORMAT_BC2_UNORM_BLOCK
+        135 => 0x83F2,
+        // VK_FORMAT_BC3_UNORM_BLOCK
+        137 => 0x83F3,
+        // VK_FORMAT_BC4_UNORM_BLOCK
+        139 => 0x8DBB,
+        // VK_FORMAT_BC4_SNORM_BLOCK
+        140 => 0x8DBC,
+        // VK_FORMAT_BC5_UNORM_BLOCK
+        141 => 0x8DBD,
+        // VK_FORMAT_BC5_SNORM_BLOCK
+        142 => 0x8DBE,
+
+        // VK_FORMAT_ETC2_R8G8B8_UNORM_BLOCK
+        147 => 0x9274,
+        // VK_FORMAT_ETC2_R8G8B8_SRGB_BLOCK
+        148 => 0x9275,
+        // VK_FORMAT_ETC2_R8G8B8A1_UNORM_BLOCK
+        149 => 0x9276,
+        // VK_FORMAT_ETC2_R8G8B8A1_SRGB_BLOCK
+        150 => 0x9277,
+        // VK_FORMAT_ETC2_R8G8B8A8_UNORM_BLOCK
+        151 => 0x9278,
+        // VK_FORMAT_ETC2_R8G8B8A8_SRGB_BLOCK
+        152 => 0x9279,
+        // VK_FORMAT_EAC_R11_UNORM_BLOCK
+        153 => 0x9270,
+        // VK_FORMAT_EAC_R11_SNORM_BLOCK
+        154 => 0x9271,
+        // VK_FORMAT_EAC_R11G11_UNORM_BLOCK
+        155 => 0x9272,
+        // VK_FORMAT_EAC_R11G11_SNORM_BLOCK
+        156 => 0x9273,
+
+        // VK_FORMAT_ASTC_4x4_UNORM_BLOCK through
+        // VK_FORMAT_ASTC_12x12_SRGB_BLOCK, which alternate between
+        // UNORM and SRGB for each block size
+        vk_format if vk_format >= 157 && vk_format <= 184 => {
+            let block = vk_format - 157;
+            if block % 2 == 0 { 0x93B0 + block / 2 } else { 0x93D0 + block / 2 }
+        },
+
+        // VK_FORMAT_PVRTC1_2BPP_UNORM_BLOCK_IMG
+        1000054000 => 0x8C03,
+        // VK_FORMAT_PVRTC1_4BPP_UNORM_BLOCK_IMG
+        1000054001 => 0x8C02,
+
+        _ => return None
+    };
+
+    CompressedTexelFormat::from_gl(gl_enum).ok()
+        .map(TextureFileFormat::Compressed)
+}
+
+// Validate the dimensions, face count, and mipmap level count of a
+// texture, treating a level count of 0 as a single level
+fn _check_dimensions(width: u32, height: u32, faces: u32, levels: u32)
+    -> Result<(usize, usize, usize, usize), KtxError>
+{
+    let (width, height) = (width as usize, height as usize);
+    if width == 0 {
+        return Err(KtxError::InvalidHeader("the texture has a width of 0"));
+    }
+    if height == 0 {
+        return Err(KtxError::UnsupportedTexture("1D textures are not supported"));
+    }
+    if width > super::MAX_DIMENSION || height > super::MAX_DIMENSION {
+        return Err(KtxError::InvalidHeader("the texture is too large"));
+    }
+
+    let faces = match faces {
+        1 => 1,
+        6 if width == height => 6,
+        6 => {
+            return Err(KtxError::InvalidHeader("cubemap faces are not square"));
+        },
+        _ => {
+            return Err(KtxError::InvalidHeader("the face count must be 1 or 6"));
+        }
+    };
+
+    let levels = match levels as usize {
+        0 => 1,
+        levels if levels <= super::_max_levels(width, height) => levels,
+        _ => {
+            return Err(KtxError::InvalidHeader("too many mipmap levels"));
+        }
+    };
+
+    Ok((width, height, faces, levels))
+}
+
+fn _level_size(width: usize, height: usize, level: usize) -> (usize, usize) {
+    (super::_mip_dimension(width, level), super::_mip_dimension(height, level))
+}
+
+fn _pad4(size: usize) -> usize {
+    (size + 3) & !3
+}
+
+fn _swap_bytes(data: &mut [u8], size: usize) {
+    if size > 1 {
+        for texel in data.chunks_mut(size) {
+            texel.reverse();
+        }
+    }
+}
+
+fn _slice(bytes: &[u8], pos: u64, len: u64) -> Result<&[u8], KtxError> {
+    match pos.checked_add(len) {
+        Some(end) if end <= bytes.len() as u64 => {
+            Ok(&bytes[pos as usize..end as usize])
+        },
+        _ => Err(KtxError::UnexpectedEnd)
+    }
+}
+
+fn _read_u32(bytes: &[u8], pos: usize, big_endian: bool)
+    -> Result<u32, KtxError>
+{
+    let field = try!(_slice(bytes, pos as u64, 4));
+    Ok(field.iter().enumerate().fold(0, |value, (i, &byte)| {
+        let shift = if big_endian { 24 - i * 8 } else { i * 8 };
+        value | (byte as u32) << shift
+    }))
+}
+
+fn _read_u64(bytes: &[u8], pos: usize) -> Result<u64, KtxError> {
+    let low = try!(_read_u32(bytes, pos, false)) as u64;
+    let high = try!(_read_u32(bytes, pos + 4, false)) as u64;
+    Ok(low | high << 32)
+}
+
+#[cfg(test)]
+mod tests {
+    use image_data::{ImageFormat, TexelType, TexelFormat};
+    use compressed_image::{CompressedTexelFormat, Etc1Format, S3tcFormat};
+    use texture_file::{TextureFile, TextureFileFormat};
+    use super::*;
+
+    const RGBA8: TextureFileFormat = TextureFileFormat::Uncompressed(ImageFormat {
+        texel_type: TexelType::UnsignedByte,
+        texel_format: TexelFormat::RGBA
+    });
+
+    fn _push_u32(bytes: &mut Vec<u8>, value: u32, big_endian: bool) {
+        for i in 0..4 {
+            let shift = if big_endian { 24 - i * 8 } else { i * 8 };
+            bytes.push((value >> shift) as u8);
+        }
+    }
+
+    fn _push_u64(bytes: &mut Vec<u8>, value: u64) {
+        _push_u32(bytes, value as u32, false);
+        _push_u32(bytes, (value >> 32) as u32, false);
+    }
+
+    // The identifier and header of a KTX 1.1 file, from `glType` through
+    // `bytesOfKeyValueData`
+    fn _ktx1_header(big_endian: bool, fields: [u32; 12]) -> Vec<u8> {
+        let mut bytes = KTX1_IDENTIFIER.to_vec();
+        _push_u32(&mut bytes, KTX1_ENDIANNESS, big_endian);
+        for &field in &fields {
+            _push_u32(&mut bytes, field, big_endian);
+        }
+        bytes
+    }
+
+    // A KTX 1.1 file with a 2x2 RGBA8 image and its 1x1 mipmap
+    fn _ktx1_rgba8() -> Vec<u8> {
+        let mut bytes = _ktx1_header(false, [0x1401, 1, 0x1908, 0x8058, 0x1908,
+                                             2, 2, 0, 0, 1, 2, 8]);
+        bytes.extend_from_slice(b"keyvalue");
+        _push_u32(&mut bytes, 16, false);
+        bytes.extend((0..16).map(|i| i as u8));
+        _push_u32(&mut bytes, 4, false);
+        bytes.extend_from_slice(&[0xFF, 0x80, 0x40, 0x20]);
+        bytes
+    }
+
+    // The identifier, header, and level index of a KTX2 file, with the
+    // given data for each mipmap level stored after the level index
+    fn _ktx2(fields: [u32; 9], levels: &[(&[u8], u64)]) -> Vec<u8> {
+        let mut bytes = KTX2_IDENTIFIER.to_vec();
+        for &field in &fields {
+            _push_u32(&mut bytes, field, false);
+        }
+        // The data format descriptor, key/value data, and
+        // supercompression global data are all empty
+        bytes.extend_from_slice(&[0; 32]);
+
+        let mut offset = KTX2_HEADER_SIZE + levels.len() * KTX2_LEVEL_INDEX_SIZE;
+        for &(data, uncompressed_length) in levels {
+            _push_u64(&mut bytes, offset as u64);
+            _push_u64(&mut bytes, data.len() as u64);
+            _push_u64(&mut bytes, uncompressed_length);
+            offset += data.len();
+        }
+        for &(data, _) in levels {
+            bytes.extend_from_slice(data);
+        }
+        bytes
+    }
+
+    fn _error(bytes: &[u8]) -> String {
+        TextureFile::from_ktx(bytes).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn read_ktx1_mipmaps() {
+        let file = TextureFile::from_ktx(&_ktx1_rgba8()).unwrap();
+        assert_eq!(file.format(), RGBA8);
+        assert!(!file.is_cube_map());
+        assert_eq!(file.levels(), 2);
+        assert_eq!(file.level_size(0), (2, 2));
+        assert_eq!(file.level_data(0, 0), &(0..16).collect::<Vec<u8>>()[..]);
+        assert_eq!(file.level_size(1), (1, 1));
+        assert_eq!(file.level_data(1, 0), &[0xFF, 0x80, 0x40, 0x20]);
+    }
+
+    #[test]
+    fn read_ktx1_endianness() {
+        // A 1x1 RGB565 image, with its row padded to 4 bytes
+        for &big_endian in &[false, true] {
+            let mut bytes = _ktx1_header(big_endian,
+                                         [0x8363, 2, 0x1907, 0x8D62, 0x1907,
+                                          1, 1, 0, 0, 1, 1, 0]);
+            _push_u32(&mut bytes, 4, big_endian);
+            if big_endian {
+                bytes.extend_from_slice(&[0x12, 0x34, 0, 0]);
+            }
+            else {
+                bytes.extend_from_slice(&[0x34, 0x12, 0, 0]);
+            }
+
+            let file = TextureFile::from_ktx(&bytes).unwrap();
+            let rgb565 = ImageFormat {
+                texel_type: TexelType::UnsignedShort565,
+                texel_format: TexelFormat::RGB
+            };
+            assert_eq!(file.format(), TextureFileFormat::Uncompressed(rgb565));
+
+            // Texels are converted to the platform's byte order
+            let texel = [0x1234u16];
+            let native: &[u8] = unsafe {
+                ::std::slice::from_raw_parts(texel.as_ptr() as *const u8, 2)
+            };
+            assert_eq!(file.level_data(0, 0), native);
+        }
+    }
+
+    #[test]
+    fn read_ktx1_cube_map() {
+        // A 1x1 ETC1 cubemap, where each face is a single 8-byte block
+        let mut bytes = _ktx1_header(false, [0, 1, 0, 0x8D64, 0x1907,
+                                             1, 1, 0, 0, 6, 1, 0]);
+        _push_u32(&mut bytes, 8, false);
+        for face in 0..6 {
+            bytes.extend_from_slice(&[face; 8]);
+        }
+
+        let file = TextureFile::from_ktx(&bytes).unwrap();
+        assert_eq!(file.format(),
+                   TextureFileFormat::Compressed(CompressedTexelFormat::Etc1(Etc1Format::Etc1Rgb8)));
+        assert!(file.is_cube_map());
+        for face in 0..6 {
+            assert_eq!(file.level_data(0, face as usize), &[face; 8]);
+        }
+    }
+
+    #[test]
+    fn read_ktx1_without_mipmaps() {
+        // A level count of 0 asks for mipmaps to be generated
+        let mut bytes = _ktx1_header(false, [0x1401, 1, 0x1908, 0x8058, 0x1908,
+                                             1, 1, 0, 0, 1, 0, 0]);
+        _push_u32(&mut bytes, 4, false);
+        bytes.extend_from_slice(&[1, 2, 3, 4]);
+
+        let file = TextureFile::from_ktx(&bytes).unwrap();
+        assert_eq!(file.levels(), 1);
+        assert_eq!(file.level_data(0, 0), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn reject_truncated_ktx1() {
+        let bytes = _ktx1_rgba8();
+        for len in 0..bytes.len() {
+            assert_eq!(_error(&bytes[..len]), "the KTX file is truncated");
+        }
+    }
+
+    #[test]
+    fn reject_invalid_ktx1() {
+        assert_eq!(_error(b"\xABKTX 12\xBB\r\n\x1A\n"),
+                   "the data is not a KTX or KTX2 file");
+
+        let mut bytes = _ktx1_rgba8();
+        bytes[12] = 0x05;
+        assert_eq!(_error(&bytes),
+                   "the KTX file has an invalid header: invalid endianness");
+
+        let header = |fields| {
+            let mut bytes = _ktx1_header(false, fields);
+            bytes.extend_from_slice(&[0; 64]);
+            _error(&bytes)
+        };
+        assert_eq!(header([0x1401, 1, 0x1908, 0x8058, 0x1908, 2, 2, 0, 0, 1, 1, 0]),
+                   "the KTX file has an invalid header: a mipmap level has the wrong imageSize");
+        assert_eq!(header([0x1401, 1, 0x1908, 0x8058, 0x1908, 2, 2, 0, 0, 1, 3, 0]),
+                   "the KTX file has an invalid header: too many mipmap levels");
+        assert_eq!(header([0x1401, 1, 0x1908, 0x8058, 0x1908, 0, 2, 0, 0, 1, 1, 0]),
+                   "the KTX file has an invalid header: the texture has a width of 0");
+        assert_eq!(header([0x1401, 1, 0x1908, 0x8058, 0x1908, 2, 1, 0, 0, 6, 1, 0]),
+                   "the KTX file has an invalid header: cubemap faces are not square");
+        assert_eq!(header([0x1401, 1, 0x1908, 0x8058, 0x1908, 2, 2, 0, 0, 2, 1, 0]),
+                   "the KTX file has an invalid header: the face count must be 1 or 6");
+        assert_eq!(header([0x1401, 3, 0x1908, 0x8058, 0x1908, 2, 2, 0, 0, 1, 1, 0]),
+                   "the KTX file has an invalid header: invalid glTypeSize");
+        assert_eq!(header([0x1401, 1, 0x1908, 0x8058, 0x1908, 2, 0, 0, 0, 1, 1, 0]),
+                   "the KTX file contains an unsupported texture: 1D textures are not supported");
+        assert_eq!(header([0x1401, 1, 0x1908, 0x8058, 0x1908, 2, 2, 2, 0, 1, 1, 0]),
+                   "the KTX file contains an unsupported texture: 3D textures are not supported");
+        assert_eq!(header([0x1401, 1, 0x1908, 0x8058, 0x1908, 2, 2, 0, 4, 1, 1, 0]),
+                   "the KTX file contains an unsupported texture: texture arrays are not supported");
+        assert_eq!(header([0x8363, 2, 0x1908, 0x8058, 0x1908, 2, 2, 0, 0, 1, 1, 0]),
+                   "unsupported KTX format (glType 0x8363, glFormat 0x1908, glInternalFormat 0x8058)");
+    }
+
+    #[test]
+    fn read_ktx2_mipmaps() {
+        let level_0: Vec<u8> = (0..16).collect();
+        let level_1 = [0xFF, 0x80, 0x40, 0x20];
+        let bytes = _ktx2([37, 1, 2, 2, 0, 0, 1, 2, 0],
+                          &[(&level_0, 16), (&level_1, 4)]);
+
+        let file = TextureFile::from_ktx(&bytes).unwrap();
+        assert_eq!(file.format(), RGBA8);
+        assert_eq!(file.levels(), 2);
+        assert_eq!(file.level_data(0, 0), &level_0[..]);
+        assert_eq!(file.level_data(1, 0), &level_1);
+    }
+
+    #[test]
+    fn read_ktx2_zstd() {
+        // The same levels as `read_ktx2_mipmaps`, compressed with
+        // `zstd -19 --no-check`
+        let level_0 = [
+            0x28, 0xB5, 0x2F, 0xFD, 0x00, 0x68, 0x81, 0x00, 0x00, 0x00, 0x01, 0x02,
+            0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
+            0x0F
+        ];
+        let level_1 = [
+            0x28, 0xB5, 0x2F, 0xFD, 0x00, 0x68, 0x21, 0x00, 0x00, 0xFF, 0x80, 0x40,
+            0x20
+        ];
+        let bytes = _ktx2([37, 1, 2, 2, 0, 0, 1, 2, 2],
+                          &[(&level_0, 16), (&level_1, 4)]);
+
+        let file = TextureFile::from_ktx(&bytes).unwrap();
+        assert_eq!(file.level_data(0, 0), &(0..16).collect::<Vec<u8>>()[..]);
+        assert_eq!(file.level_data(1, 0), &[0xFF, 0x80, 0x40, 0x20]);
+
+        let bytes = _ktx2([37, 1, 2, 2, 0, 0, 1, 2, 2],
+                          &[(&level_0, 16), (&level_1, 8)]);
+        assert_eq!(_error(&bytes),
+                   "invalid supercompressed KTX2 data: a mipmap level has the wrong uncompressed length");
+
+        let bytes = _ktx2([37, 1, 2, 2, 0, 0, 1, 1, 2],
+                          &[(&level_0[..20], 16)]);
+        assert!(_error(&bytes).starts_with("invalid supercompressed KTX2 data: "));
+    }
+
+    #[test]
+    fn read_ktx2_cube_map() {
+        // A 4x4 BC1 cubemap, where each face is a single 8-byte block
+        let data: Vec<u8> = (0..48).collect();
+        let bytes = _ktx2([131, 1, 4, 4, 0, 0, 6, 1, 0], &[(&data, 48)]);
+
+        let file = TextureFile::from_ktx(&bytes).unwrap();
+        assert_eq!(file.format(),
+                   TextureFileFormat::Compressed(CompressedTexelFormat::S3tc(S3tcFormat::Dxt1Rgb)));
+        assert!(file.is_cube_map());
+        for face in 0..6 {
+            assert_eq!(file.level_data(0, face), &data[face * 8..(face + 1) * 8]);
+        }
+    }
+
+    #[test]
+    fn reject_truncated_ktx2() {
+        let level_0: Vec<u8> = (0..16).collect();
+        let bytes = _ktx2([37, 1, 2, 2, 0, 0, 1, 2, 0],
+                          &[(&level_0, 16), (&[1, 2, 3, 4], 4)]);
+        for len in 0..bytes.len() {
+            assert_eq!(_error(&bytes[..len]), "the KTX file is truncated");
+        }
+    }
+
+    #[test]
+    fn reject_invalid_ktx2() {
+        let data = [0; 16];
+        let header = |fields| _error(&_ktx2(fields, &[(&data, 16)]));
+        assert_eq!(header([37, 1, 2, 2, 0, 0, 1, 1, 1]),
+                   "unsupported KTX2 supercompression scheme 1");
+        assert_eq!(header([1, 1, 2, 2, 0, 0, 1, 1, 0]),
+                   "unsupported KTX2 vkFormat 1");
+        assert_eq!(header([37, 1, 4, 4, 0, 0, 1, 1, 0]),
+                   "the KTX file has an invalid header: a mipmap level has the wrong length");
+        assert_eq!(header([37, 3, 2, 2, 0, 0, 1, 1, 0]),
+                   "the KTX file has an invalid header: invalid typeSize");
+        assert_eq!(header([37, 1, 2, 2, 1, 0, 1, 1, 0]),
+                   "the KTX file contains an unsupported texture: 3D textures are not supported");
+        assert_eq!(header([37, 1, 2, 2, 0, 2, 1, 1, 0]),
+                   "the KTX file contains an unsupported texture: texture arrays are not supported");
+    }
+}
@@ -0,0 +1,379 @@
+//! Contains [`TextureFile`](struct.TextureFile.html), which holds the
+//! image data of a 2D or cubemap texture (including every mipmap level)
+//! that was read from a texture container file, such as a KTX or
+//! DDS file.
+//!
+//! Texture files are parsed without an OpenGL context, so they can also
+//! be used by tools and tests. The parsed data can then be uploaded with
+//! [`TextureFile::texture_2d`](struct.TextureFile.html#method.texture_2d)
+//! or [`TextureFile::texture_cube_map`]
+//! (struct.TextureFile.html#method.texture_cube_map). Compressed data
+//! is uploaded as-is when the current context supports its format, and
+//! is otherwise decoded on the CPU when it's uploaded (see
+//! [`gl.compressed_format_or_decoded`]
+//! (../context/texture_context/trait.ContextTextureExt.html#method.compressed_format_or_decoded)).
+
+use std::cmp;
+use image_data::{ImageFormat, ImageView};
+use compressed_image::{CompressedImage2d, CompressedFormat,
+                       CompressedTexelFormat};
+use context::{ContextTextureExt, ContextTextureBuilderExt};
+use texture::{Texture2d, TextureCubeMap, TxCubeMapImageTarget,
+              SamplerParams};
+use types::GLError;
+
+mod zstd;
+pub mod ktx;
+pub mod dds;
+
+pub use self::ktx::*;
+pub use self::dds::*;
+
+// The largest width or height of a texture that will be read from a file
+const MAX_DIMENSION: usize = 1 << 16;
+
+/// The format of the image data in a [`TextureFile`]
+/// (struct.TextureFile.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFileFormat {
+    /// Uncompressed image data, which is uploaded with [`gl.tex_image_2d`]
+    /// (../context/texture_context/trait.ContextTextureExt.html#method.tex_image_2d).
+    Uncompressed(ImageFormat),
+
+    /// Compressed image data, which is uploaded with
+    /// [`gl.compressed_tex_image_2d`]
+    /// (../context/texture_context/trait.ContextTextureExt.html#method.compressed_tex_image_2d).
+    Compressed(CompressedTexelFormat)
+}
+
+impl TextureFileFormat {
+    /// Get the number of bytes used to store an image with this format
+    /// and the given size, without any padding between rows.
+    pub fn image_size(&self, width: usize, height: usize) -> usize {
+        match *self {
+            TextureFileFormat::Uncompressed(format) => {
+                width * height * format.texel_size()
+            },
+            TextureFileFormat::Compressed(format) => {
+                format.image_size(width, height)
+            }
+        }
+    }
+}
+
+/// The image data of a 2D or cubemap texture, including each of its
+/// mipmap levels, that was read from a texture container file.
+///
+/// # Example
+///
+/// ```no_run
+/// # #![allow(unused_variables)]
+/// # extern crate glitter;
+/// # use glitter::prelude::*;
+/// # use glitter::{TextureFile, SamplerParams};
+/// # fn main() {
+/// # let mut gl = unsafe { glitter::Context::current_context() };
+/// # let bytes: Vec<u8> = vec![];
+/// let file = TextureFile::from_ktx(&bytes).unwrap();
+///
+/// let texture = file.texture_2d(&mut gl, SamplerParams::default())
+///     .unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextureFile {
+    width: usize,
+    height: usize,
+    format: TextureFileFormat,
+    // The image data for each face of each mipmap level,
+    // indexed as `levels[level][face]`
+    levels: Vec<Vec<Vec<u8>>>
+}
+
+impl TextureFile {
+    /// Get the width of the base mipmap level, in texels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the height of the base mipmap level, in texels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get the format of the image data.
+    pub fn format(&self) -> TextureFileFormat {
+        self.format
+    }
+
+    /// Returns `true` if the file contains the six faces of a cubemap.
+    pub fn is_cube_map(&self) -> bool {
+        self.levels[0].len() == 6
+    }
+
+    /// Get the number of mipmap levels in the file, including the
+    /// base level.
+    pub fn levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Get the width and height of a mipmap level, in texels.
+    pub fn level_size(&self, level: usize) -> (usize, usize) {
+        (_mip_dimension(self.width, level), _mip_dimension(self.height, level))
+    }
+
+    /// Get the image data of a single face of a mipmap level, without
+    /// any padding between rows. For 2D textures, `face` must be 0. For
+    /// cubemaps, the faces are in the order returned by
+    /// [`TxCubeMapImageTarget::faces`]
+    /// (../texture/enum.TxCubeMapImageTarget.html#method.faces).
+    ///
+    /// # Panics
+    /// This function will panic if the level or face is out of range.
+    pub fn level_data(&self, level: usize, face: usize) -> &[u8] {
+        &self.levels[level][face]
+    }
+
+    /// Create a 2D texture from the file's image data, using the given
+    /// filtering and wrapping parameters. Every mipmap level in the file
+    /// is uploaded.
+    ///
+    /// # Failures
+    /// An error will be returned if the file contains a cubemap, if the
+    /// file's compressed format isn't supported by the context (see
+    /// [`gl.compressed_format`]
+    /// (../context/texture_context/trait.ContextTextureExt.html#method.compressed_format))
+    /// and can't be decoded (see [`CompressedTexelFormat::can_decode`]
+    /// (../enum.CompressedTexelFormat.html#method.can_decode)), or for
+    /// any of the reasons described in [`Texture2dBuilder::try_unwrap`]
+    /// (../context/texture_context/struct.Texture2dBuilder.html#method.try_unwrap).
+    /// In particular, the default `SamplerParams` use a mipmap filter,
+    /// which requires the file to have a complete mipmap chain.
+    pub fn texture_2d<C>(&self, gl: C, sampler: SamplerParams)
+        -> Result<Texture2d, GLError>
+        where C: ContextTextureBuilderExt
+    {
+        if self.is_cube_map() {
+            let msg = "Error building texture: the texture file contains a cubemap";
+            return Err(GLError::Message(msg.to_owned()));
+        }
+
+        // The images must be created before the builder that borrows them
+        let images = try!(self.face_images(&gl, 0));
+
+        let builder = gl.build_texture_2d()
+            .min_filter(sampler.min_filter)
+            .mag_filter(sampler.mag_filter)
+            .wrap_s(sampler.wrap_s)
+            .wrap_t(sampler.wrap_t);
+
+        let builder = match images {
+            FaceImages::Uncompressed(ref images) => {
+                builder.image_2d(&images[0])
+                    .mipmaps(&images[1..])
+            },
+            FaceImages::Compressed(ref images) => {
+                builder.compressed_image_2d(&images[0])
+                    .compressed_mipmaps(&images[1..])
+            }
+        };
+        builder.try_unwrap()
+    }
+
+    /// Create a cubemap texture from the file's image data, using the
+    /// given filtering and wrapping parameters. Every mipmap level in
+    /// the file is uploaded.
+    ///
+    /// # Failures
+    /// An error will be returned if the file doesn't contain a cubemap,
+    /// if the file's compressed format isn't supported by the context and
+    /// can't be decoded, or for any of the reasons described in
+    /// [`TextureCubeMapBuilder::try_unwrap`]
+    /// (../context/texture_context/struct.TextureCubeMapBuilder.html#method.try_unwrap).
+    pub fn texture_cube_map<C>(&self, gl: C, sampler: SamplerParams)
+        -> Result<TextureCubeMap, GLError>
+        where C: ContextTextureBuilderExt
+    {
+        if !self.is_cube_map() {
+            let msg = "Error building cubemap: the texture file does not contain a cubemap";
+            return Err(GLError::Message(msg.to_owned()));
+        }
+
+        let mut faces = Vec::with_capacity(6);
+        for face in 0..6 {
+            faces.push(try!(self.face_images(&gl, face)));
+        }
+
+        let mut builder = gl.build_texture_cube_map()
+            .min_filter(sampler.min_filter)
+            .mag_filter(sampler.mag_filter)
+            .wrap_s(sampler.wrap_s)
+            .wrap_t(sampler.wrap_t);
+
+        let targets = TxCubeMapImageTarget::faces();
+        for (target, images) in targets.iter().zip(faces.iter()) {
+            builder = match *images {
+                FaceImages::Uncompressed(ref images) => {
+                    builder.face(*target, &images[0])
+                        .face_mipmaps(*target, &images[1..])
+                },
+                FaceImages::Compressed(ref images) => {
+                    builder.compressed_face(*target, &images[0])
+                        .compressed_face_mipmaps(*target, &images[1..])
+                }
+            };
+        }
+        builder.try_unwrap()
+    }
+
+    // Get every mipmap level of a face in a form that the context can
+    // upload. Compressed data is decoded when it's uploaded if the
+    // context doesn't support its format
+    fn face_images<C>(&self, gl: &C, face: usize)
+        -> Result<FaceImages, GLError>
+        where C: ContextTextureExt
+    {
+        match self.format {
+            TextureFileFormat::Uncompressed(format) => {
+                let images = try!(self.images(face, format));
+                Ok(FaceImages::Uncompressed(images))
+            },
+            TextureFileFormat::Compressed(format) => {
+                match gl.compressed_format_or_decoded(format) {
+                    Some(format) => {
+                        let images = self.compressed_images(face, format);
+                        Ok(FaceImages::Compressed(images))
+                    },
+                    None => {
+                        let msg = format!("Error building texture: the texture file has format {:?}, which is not supported by the current context",
+                                          format);
+                        Err(GLError::Message(msg))
+                    }
+                }
+            }
+        }
+    }
+
+    // Get an image view of every mipmap level of a face
+    fn images(&self, face: usize, format: ImageFormat)
+        -> Result<Vec<ImageView>, GLError>
+    {
+        (0..self.levels()).map(|level| {
+            let (width, height) = self.level_size(level);
+            let bytes = &self.levels[level][face];
+            let view = ImageView::new(bytes,
+                                      width,
+                                      height,
+                                      format,
+                                      width * format.texel_size());
+            view.ok_or_else(|| {
+                let msg = format!("Error building texture: mipmap level {} of the texture file has {} bytes, which is too few for a {}x{} image with format {:?}",
+                                  level, bytes.len(), width, height, format);
+                GLError::Message(msg)
+            })
+        }).collect()
+    }
+
+    // Get every compressed mipmap level of a face
+    fn compressed_images(&self, face: usize, format: CompressedFormat)
+        -> Vec<CompressedLevel>
+    {
+        (0..self.levels()).map(|level| {
+            let (width, height) = self.level_size(level);
+            CompressedLevel {
+                width: width,
+                height: height,
+                format: format,
+                bytes: &self.levels[level][face]
+            }
+        }).collect()
+    }
+}
+
+// The mipmap levels of a single face of a texture file, ready to upload
+enum FaceImages<'a> {
+    Uncompressed(Vec<ImageView<'a>>),
+    Compressed(Vec<CompressedLevel<'a>>)
+}
+
+// A borrowed compressed mipmap level of a texture file
+struct CompressedLevel<'a> {
+    width: usize,
+    height: usize,
+    format: CompressedFormat,
+    bytes: &'a [u8]
+}
+
+impl<'a> CompressedImage2d for CompressedLevel<'a> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn format(&self) -> CompressedFormat {
+        self.format
+    }
+
+    fn compressed_bytes(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
+fn _mip_dimension(base: usize, level: usize) -> usize {
+    let dimension = if level >= 64 { 0 } else { base >> level };
+    if dimension > 0 { dimension } else { 1 }
+}
+
+// Get the number of levels in a complete mipmap chain
+fn _max_levels(width: usize, height: usize) -> usize {
+    let mut size = cmp::max(width, height);
+    let mut levels = 1;
+    while size > 1 {
+        size /= 2;
+        levels += 1;
+    }
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use image_data::{Image2d, ImageFormat};
+    use super::*;
+
+    fn _file(levels: Vec<Vec<Vec<u8>>>) -> TextureFile {
+        TextureFile {
+            width: 2,
+            height: 2,
+            format: TextureFileFormat::Uncompressed(ImageFormat::rgba8()),
+            levels: levels
+        }
+    }
+
+    #[test]
+    fn images() {
+        let file = _file(vec![vec![vec![0; 16]], vec![vec![0; 4]]]);
+        let images = file.images(0, ImageFormat::rgba8()).unwrap();
+        assert_eq!(images.len(), 2);
+        assert_eq!((images[0].width(), images[0].height()), (2, 2));
+        assert_eq!((images[1].width(), images[1].height()), (1, 1));
+    }
+
+    #[test]
+    fn images_with_short_level() {
+        let file = _file(vec![vec![vec![0; 16]], vec![vec![0; 2]]]);
+        match file.images(0, ImageFormat::rgba8()) {
+            Err(GLError::Message(msg)) => {
+                assert!(msg.starts_with("Error building texture: mipmap \
+                                         level 1 of the texture file has 2 \
+                                         bytes, which is too few for a 1x1 \
+                                         image"));
+            },
+            result => {
+                panic!("Unexpected result: {:?}", result.map(|_| ()));
+            }
+        }
+    }
+}
@@ -0,0 +1,998 @@
+// A decoder for Zstandard-compressed data (RFC 8878), which is used to
+// read supercompressed KTX2 files. Only the features that are needed to
+// decompress complete frames are supported: dictionaries are not, and
+// content checksums are skipped rather than verified.
+
+use std::cmp;
+
+const FRAME_MAGIC: u32 = 0xFD2FB528;
+const SKIPPABLE_MAGIC_MASK: u32 = 0xFFFFFFF0;
+const SKIPPABLE_MAGIC: u32 = 0x184D2A50;
+
+const MAX_LITERALS_LOG: u32 = 11;
+const MAX_WEIGHTS_LOG: u32 = 6;
+const MAX_LITERAL_LENGTH_LOG: u32 = 9;
+const MAX_MATCH_LENGTH_LOG: u32 = 9;
+const MAX_OFFSET_LOG: u32 = 8;
+
+const LITERAL_LENGTH_DEFAULT: [i16; 36] = [
+    4, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2,
+    2, 3, 2, 1, 1, 1, 1, 1, -1, -1, -1, -1
+];
+
+const MATCH_LENGTH_DEFAULT: [i16; 53] = [
+    1, 4, 3, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, -1, -1,
+    -1, -1, -1, -1, -1
+];
+
+const OFFSET_DEFAULT: [i16; 29] = [
+    1, 1, 1, 1, 1, 1, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    -1, -1, -1, -1, -1
+];
+
+const LITERAL_LENGTH_BASELINES: [(u32, u32); 36] = [
+    (0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0), (6, 0), (7, 0),
+    (8, 0), (9, 0), (10, 0), (11, 0), (12, 0), (13, 0), (14, 0), (15, 0),
+    (16, 1), (18, 1), (20, 1), (22, 1), (24, 2), (28, 2), (32, 3), (40, 3),
+    (48, 4), (64, 6), (128, 7), (256, 8), (512, 9), (1024, 10), (2048, 11),
+    (4096, 12), (8192, 13), (16384, 14), (32768, 15), (65536, 16)
+];
+
+const MATCH_LENGTH_BASELINES: [(u32, u32); 53] = [
+    (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0), (9, 0), (10, 0),
+    (11, 0), (12, 0), (13, 0), (14, 0), (15, 0), (16, 0), (17, 0), (18, 0),
+    (19, 0), (20, 0), (21, 0), (22, 0), (23, 0), (24, 0), (25, 0), (26, 0),
+    (27, 0), (28, 0), (29, 0), (30, 0), (31, 0), (32, 0), (33, 0), (34, 0),
+    (35, 1), (37, 1), (39, 1), (41, 1), (43, 2), (47, 2), (51, 3), (59, 3),
+    (67, 4), (83, 4), (99, 5), (131, 7), (259, 8), (515, 9), (1027, 10),
+    (2051, 11), (4099, 12), (8195, 13), (16387, 14), (32771, 15),
+    (65539, 16)
+];
+
+/// Decompress every Zstandard frame in `data`, skipping any skippable
+/// frames, and return the concatenated output.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut output = vec![];
+    let mut pos = 0;
+    while pos < data.len() {
+        let magic = try!(_read_u32(data, pos));
+        if magic & SKIPPABLE_MAGIC_MASK == SKIPPABLE_MAGIC {
+            let size = try!(_read_u32(data, pos + 4)) as usize;
+            pos += 8 + size;
+            if pos > data.len() {
+                return Err("skippable frame extends past the end of the data");
+            }
+        }
+        else if magic == FRAME_MAGIC {
+            pos = try!(_decompress_frame(data, pos + 4, &mut output));
+        }
+        else {
+            return Err("invalid frame magic number");
+        }
+    }
+    Ok(output)
+}
+
+// Decompress the frame whose header starts at `pos`, and return the
+// position after the end of the frame
+fn _decompress_frame(data: &[u8], mut pos: usize, output: &mut Vec<u8>)
+    -> Result<usize, &'static str>
+{
+    let descriptor = try!(_read_u8(data, pos));
+    pos += 1;
+
+    let content_size_flag = descriptor >> 6;
+    let single_segment = descriptor & 0x20 != 0;
+    let has_checksum = descriptor & 0x04 != 0;
+    let dictionary_id_flag = descriptor & 0x03;
+    if descriptor & 0x08 != 0 {
+        return Err("reserved frame header bit is set");
+    }
+
+    if !single_segment {
+        pos += 1; // Window descriptor
+    }
+
+    let dictionary_id_size = [0, 1, 2, 4][dictionary_id_flag as usize];
+    let dictionary_id = try!(_read_le(data, pos, dictionary_id_size));
+    if dictionary_id != 0 {
+        return Err("frames that use a dictionary are not supported");
+    }
+    pos += dictionary_id_size;
+
+    let content_size_size = match content_size_flag {
+        0 => if single_segment { 1 } else { 0 },
+        1 => 2,
+        2 => 4,
+        _ => 8
+    };
+    let content_size = try!(_read_le(data, pos, content_size_size));
+    pos += content_size_size;
+    if content_size_size > 0 {
+        let content_size = if content_size_size == 2 {
+            content_size + 256
+        }
+        else {
+            content_size
+        };
+        output.reserve(cmp::min(content_size, 1 << 28) as usize);
+    }
+
+    let frame_start = output.len();
+    let mut state = FrameState::new();
+    loop {
+        let header = try!(_read_le(data, pos, 3)) as usize;
+        pos += 3;
+
+        let last_block = header & 1 != 0;
+        let block_type = (header >> 1) & 3;
+        let block_size = header >> 3;
+        match block_type {
+            0 => {
+                let block = try!(_slice(data, pos, block_size));
+                output.extend_from_slice(block);
+                pos += block_size;
+            },
+            1 => {
+                let byte = try!(_read_u8(data, pos));
+                output.extend(::std::iter::repeat(byte).take(block_size));
+                pos += 1;
+            },
+            2 => {
+                let block = try!(_slice(data, pos, block_size));
+                try!(state.decompress_block(block, output, frame_start));
+                pos += block_size;
+            },
+            _ => {
+                return Err("reserved block type");
+            }
+        }
+
+        if last_block {
+            break;
+        }
+    }
+
+    if has_checksum {
+        try!(_slice(data, pos, 4));
+        pos += 4;
+    }
+
+    Ok(pos)
+}
+
+// The state that is carried between the compressed blocks of a frame
+struct FrameState {
+    huffman: Option<HuffmanTable>,
+    literal_lengths: Option<FseTable>,
+    offsets: Option<FseTable>,
+    match_lengths: Option<FseTable>,
+    repeat_offsets: [usize; 3]
+}
+
+impl FrameState {
+    fn new() -> Self {
+        FrameState {
+            huffman: None,
+            literal_lengths: None,
+            offsets: None,
+            match_lengths: None,
+            repeat_offsets: [1, 4, 8]
+        }
+    }
+
+    fn decompress_block(&mut self,
+                        block: &[u8],
+                        output: &mut Vec<u8>,
+                        frame_start: usize)
+        -> Result<(), &'static str>
+    {
+        let (literals, pos) = try!(self.decode_literals(block));
+        let sequences = try!(self.decode_sequences(&block[pos..]));
+
+        let mut literal_pos = 0;
+        for &(literal_length, offset, match_length) in sequences.iter() {
+            let literal_end = literal_pos + literal_length;
+            if literal_end > literals.len() {
+                return Err("sequence uses more literals than were decoded");
+            }
+            output.extend_from_slice(&literals[literal_pos..literal_end]);
+            literal_pos = literal_end;
+
+            if offset == 0 || offset > output.len() - frame_start {
+                return Err("match offset is outside of the frame");
+            }
+            let start = output.len() - offset;
+            for i in 0..match_length {
+                let byte = output[start + i];
+                output.push(byte);
+            }
+        }
+        output.extend_from_slice(&literals[literal_pos..]);
+
+        Ok(())
+    }
+
+    // Decode the literals section of a block, returning the literals and
+    // the position of the sequences section
+    fn decode_literals(&mut self, block: &[u8])
+        -> Result<(Vec<u8>, usize), &'static str>
+    {
+        let first = try!(_read_u8(block, 0)) as usize;
+        let literals_type = first & 3;
+        let size_format = (first >> 2) & 3;
+
+        if literals_type < 2 {
+            let (header_size, size) = match size_format {
+                0 | 2 => (1, first >> 3),
+                1 => (2, try!(_read_le(block, 0, 2)) as usize >> 4),
+                _ => (3, try!(_read_le(block, 0, 3)) as usize >> 4)
+            };
+
+            return if literals_type == 0 {
+                let literals = try!(_slice(block, header_size, size));
+                Ok((literals.to_vec(), header_size + size))
+            }
+            else {
+                let byte = try!(_read_u8(block, header_size));
+                Ok((vec![byte; size], header_size + 1))
+            };
+        }
+
+        let (header_size, streams, size_bits) = match size_format {
+            0 => (3, 1, 10),
+            1 => (3, 4, 10),
+            2 => (4, 4, 14),
+            _ => (5, 4, 18)
+        };
+        let header = try!(_read_le(block, 0, header_size));
+        let mask = (1 << size_bits) - 1;
+        let regenerated_size = ((header >> 4) & mask) as usize;
+        let compressed_size = ((header >> (4 + size_bits)) & mask) as usize;
+
+        let compressed = try!(_slice(block, header_size, compressed_size));
+        let streams_start = if literals_type == 2 {
+            let (table, table_size) = try!(HuffmanTable::read(compressed));
+            self.huffman = Some(table);
+            table_size
+        }
+        else {
+            0
+        };
+        let table = match self.huffman {
+            Some(ref table) => table,
+            None => { return Err("treeless literals without a previous Huffman table"); }
+        };
+
+        let data = &compressed[streams_start..];
+        let mut literals = Vec::with_capacity(regenerated_size);
+        if streams == 1 {
+            try!(table.decode_stream(data, regenerated_size, &mut literals));
+        }
+        else {
+            let sizes = [try!(_read_le(data, 0, 2)) as usize,
+                         try!(_read_le(data, 2, 2)) as usize,
+                         try!(_read_le(data, 4, 2)) as usize];
+            let stream_regenerated = (regenerated_size + 3) / 4;
+            let mut start = 6;
+            for i in 0..4 {
+                let (size, count) = if i < 3 {
+                    (sizes[i], stream_regenerated)
+                }
+                else {
+                    if start > data.len() {
+                        return Err("literal streams extend past the end of the block");
+                    }
+                    (data.len() - start,
+                     regenerated_size - stream_regenerated * 3)
+                };
+                let stream = try!(_slice(data, start, size));
+                try!(table.decode_stream(stream, count, &mut literals));
+                start += size;
+            }
+        }
+
+        Ok((literals, header_size + compressed_size))
+    }
+
+    // Decode the sequences section of a block into a list of
+    // (literal length, offset, match length) triples
+    fn decode_sequences(&mut self, section: &[u8])
+        -> Result<Vec<(usize, usize, usize)>, &'static str>
+    {
+        let first = try!(_read_u8(section, 0)) as usize;
+        let (count, mut pos) = if first == 0 {
+            return Ok(vec![]);
+        }
+        else if first < 128 {
+            (first, 1)
+        }
+        else if first < 255 {
+            (((first - 128) << 8) + try!(_read_u8(section, 1)) as usize, 2)
+        }
+        else {
+            (try!(_read_le(section, 1, 2)) as usize + 0x7F00, 3)
+        };
+
+        let modes = try!(_read_u8(section, pos));
+        pos += 1;
+        if modes & 3 != 0 {
+            return Err("reserved sequence compression mode bits are set");
+        }
+
+        pos += try!(_read_sequence_table(&mut self.literal_lengths,
+                                         (modes >> 6) & 3,
+                                         &section[pos..],
+                                         &LITERAL_LENGTH_DEFAULT, 6,
+                                         MAX_LITERAL_LENGTH_LOG));
+        pos += try!(_read_sequence_table(&mut self.offsets,
+                                         (modes >> 4) & 3,
+                                         &section[pos..],
+                                         &OFFSET_DEFAULT, 5,
+                                         MAX_OFFSET_LOG));
+        pos += try!(_read_sequence_table(&mut self.match_lengths,
+                                         (modes >> 2) & 3,
+                                         &section[pos..],
+                                         &MATCH_LENGTH_DEFAULT, 6,
+                                         MAX_MATCH_LENGTH_LOG));
+
+        let literal_lengths = self.literal_lengths.as_ref().unwrap();
+        let offsets = self.offsets.as_ref().unwrap();
+        let match_lengths = self.match_lengths.as_ref().unwrap();
+
+        let mut bits = try!(BackwardBits::new(&section[pos..]));
+        let mut literal_length_state = bits.read(literal_lengths.log);
+        let mut offset_state = bits.read(offsets.log);
+        let mut match_length_state = bits.read(match_lengths.log);
+
+        let mut sequences = Vec::with_capacity(count);
+        for i in 0..count {
+            let literal_length_code = literal_lengths.symbol(literal_length_state);
+            let offset_code = offsets.symbol(offset_state);
+            let match_length_code = match_lengths.symbol(match_length_state);
+            if literal_length_code as usize >= LITERAL_LENGTH_BASELINES.len()
+                || match_length_code as usize >= MATCH_LENGTH_BASELINES.len()
+                || offset_code > 31
+            {
+                return Err("invalid sequence code");
+            }
+
+            let offset_value = (1 << offset_code) + bits.read(offset_code as u32);
+            let (base, extra) = MATCH_LENGTH_BASELINES[match_length_code as usize];
+            let match_length = base as usize + bits.read(extra);
+            let (base, extra) = LITERAL_LENGTH_BASELINES[literal_length_code as usize];
+            let literal_length = base as usize + bits.read(extra);
+
+            let offset = _resolve_offset(&mut self.repeat_offsets,
+                                         offset_value,
+                                         literal_length);
+            sequences.push((literal_length, offset, match_length));
+
+            if i + 1 < count {
+                literal_length_state = literal_lengths.update(literal_length_state, &mut bits);
+                match_length_state = match_lengths.update(match_length_state, &mut bits);
+                offset_state = offsets.update(offset_state, &mut bits);
+            }
+        }
+
+        Ok(sequences)
+    }
+}
+
+// Convert an offset value from a sequence to an actual offset, updating
+// the repeat offsets
+fn _resolve_offset(reps: &mut [usize; 3],
+                   offset_value: usize,
+                   literal_length: usize)
+    -> usize
+{
+    if offset_value > 3 {
+        let offset = offset_value - 3;
+        reps[2] = reps[1];
+        reps[1] = reps[0];
+        reps[0] = offset;
+        return offset;
+    }
+
+    let index = if literal_length == 0 {
+        offset_value
+    }
+    else {
+        offset_value - 1
+    };
+    if index == 0 {
+        return reps[0];
+    }
+
+    let offset = if index == 3 {
+        if reps[0] > 1 { reps[0] - 1 } else { 1 }
+    }
+    else {
+        reps[index]
+    };
+    if index > 1 {
+        reps[2] = reps[1];
+    }
+    reps[1] = reps[0];
+    reps[0] = offset;
+    offset
+}
+
+// Read (or reuse) the FSE table for one of the sequence codes, returning
+// the number of bytes read
+fn _read_sequence_table(table: &mut Option<FseTable>,
+                        mode: u8,
+                        data: &[u8],
+                        default: &[i16],
+                        default_log: u32,
+                        max_log: u32)
+    -> Result<usize, &'static str>
+{
+    match mode {
+        0 => {
+            *table = Some(try!(FseTable::new(default, default_log)));
+            Ok(0)
+        },
+        1 => {
+            let symbol = try!(_read_u8(data, 0));
+            *table = Some(FseTable::rle(symbol));
+            Ok(1)
+        },
+        2 => {
+            let (new_table, size) = try!(FseTable::read(data, max_log));
+            *table = Some(new_table);
+            Ok(size)
+        },
+        _ => {
+            if table.is_none() {
+                return Err("repeated sequence table without a previous table");
+            }
+            Ok(0)
+        }
+    }
+}
+
+// A decoding table for finite state entropy (tANS) coded data
+struct FseTable {
+    log: u32,
+    // (symbol, number of bits, baseline) for each state
+    states: Vec<(u8, u32, usize)>
+}
+
+impl FseTable {
+    // Read an FSE table description, returning the table and the number
+    // of bytes read
+    fn read(data: &[u8], max_log: u32) -> Result<(Self, usize), &'static str> {
+        let mut bits = ForwardBits::new(data);
+        let log = bits.read(4) as u32 + 5;
+        if log > max_log {
+            return Err("FSE table accuracy log is too large");
+        }
+
+        let mut counts: Vec<i16> = vec![];
+        let mut remaining = (1i32 << log) + 1;
+        let mut threshold = 1i32 << log;
+        let mut nb_bits = log + 1;
+        while remaining > 1 {
+            if counts.len() > 255 {
+                return Err("FSE table has too many symbols");
+            }
+
+            let max = (2 * threshold - 1) - remaining;
+            let value = bits.peek(nb_bits) as i32;
+            let value = if value & (threshold - 1) < max {
+                bits.skip(nb_bits - 1);
+                value & (threshold - 1)
+            }
+            else {
+                bits.skip(nb_bits);
+                let value = value & (2 * threshold - 1);
+                if value >= threshold { value - max } else { value }
+            };
+            let count = value - 1;
+
+            remaining -= count.abs();
+            counts.push(count as i16);
+
+            if count == 0 {
+                loop {
+                    let repeat = bits.read(2);
+                    for _ in 0..repeat {
+                        counts.push(0);
+                    }
+                    if repeat != 3 {
+                        break;
+                    }
+                }
+            }
+
+            while remaining < threshold && nb_bits > 1 {
+                nb_bits -= 1;
+                threshold >>= 1;
+            }
+        }
+        if remaining != 1 || bits.overflowed() {
+            return Err("invalid FSE table description");
+        }
+
+        let table = try!(FseTable::new(&counts, log));
+        Ok((table, bits.bytes_read()))
+    }
+
+    fn new(counts: &[i16], log: u32) -> Result<Self, &'static str> {
+        let size = 1 << log;
+        let mut symbols = vec![0u8; size];
+        let mut high = size;
+        for (symbol, &count) in counts.iter().enumerate() {
+            if count == -1 {
+                if high == 0 {
+                    return Err("invalid FSE table description");
+                }
+                high -= 1;
+                symbols[high] = symbol as u8;
+            }
+        }
+
+        let step = (size >> 1) + (size >> 3) + 3;
+        let mask = size - 1;
+        let mut pos = 0;
+        for (symbol, &count) in counts.iter().enumerate() {
+            for _ in 0..cmp::max(count, 0) {
+                symbols[pos] = symbol as u8;
+                pos = (pos + step) & mask;
+                while pos >= high {
+                    pos = (pos + step) & mask;
+                }
+            }
+        }
+        if pos != 0 {
+            return Err("invalid FSE table description");
+        }
+
+        let mut next: Vec<usize> = counts.iter().map(|&count| {
+            if count == -1 { 1 } else { count as usize }
+        }).collect();
+        let states = symbols.iter().map(|&symbol| {
+            let state = next[symbol as usize];
+            next[symbol as usize] += 1;
+            let nb_bits = log - _highest_bit(state as u32);
+            let baseline = (state << nb_bits) - size;
+            (symbol, nb_bits, baseline)
+        }).collect();
+
+        Ok(FseTable { log: log, states: states })
+    }
+
+    fn rle(symbol: u8) -> Self {
+        FseTable { log: 0, states: vec![(symbol, 0, 0)] }
+    }
+
+    fn symbol(&self, state: usize) -> u8 {
+        self.states[state].0
+    }
+
+    fn update(&self, state: usize, bits: &mut BackwardBits) -> usize {
+        let (_, nb_bits, baseline) = self.states[state];
+        baseline + bits.read(nb_bits)
+    }
+}
+
+// A decoding table for Huffman-coded literals
+struct HuffmanTable {
+    max_bits: u32,
+    // (symbol, number of bits) for each possible `max_bits` prefix
+    entries: Vec<(u8, u32)>
+}
+
+impl HuffmanTable {
+    // Read a Huffman tree description, returning the table and the
+    // number of bytes read
+    fn read(data: &[u8]) -> Result<(Self, usize), &'static str> {
+        let header = try!(_read_u8(data, 0)) as usize;
+        let (mut weights, size) = if header < 128 {
+            let compressed = try!(_slice(data, 1, header));
+            (try!(_read_fse_weights(compressed)), 1 + header)
+        }
+        else {
+            let count = header - 127;
+            let bytes = try!(_slice(data, 1, (count + 1) / 2));
+            let weights = (0..count).map(|i| {
+                let byte = bytes[i / 2];
+                if i % 2 == 0 { byte >> 4 } else { byte & 0xF }
+            }).collect();
+            (weights, 1 + bytes.len())
+        };
+
+        let mut total: u32 = 0;
+        for &weight in weights.iter() {
+            if weight as u32 > MAX_LITERALS_LOG {
+                return Err("invalid Huffman weight");
+            }
+            if weight > 0 {
+                total += 1 << (weight - 1);
+            }
+        }
+        if total == 0 {
+            return Err("invalid Huffman weights");
+        }
+        let max_bits = _highest_bit(total) + 1;
+        let left_over = (1 << max_bits) - total;
+        if !left_over.is_power_of_two() || max_bits > MAX_LITERALS_LOG {
+            return Err("invalid Huffman weights");
+        }
+        weights.push(_highest_bit(left_over) as u8 + 1);
+
+        let mut rank_start = vec![0usize; max_bits as usize + 2];
+        let mut next = 0;
+        for weight in 1..(max_bits + 1) {
+            rank_start[weight as usize] = next;
+            let count = weights.iter().filter(|&&w| w as u32 == weight).count();
+            next += count << (weight - 1);
+        }
+
+        let mut entries = vec![(0u8, 0u32); 1 << max_bits];
+        for (symbol, &weight) in weights.iter().enumerate() {
+            if weight == 0 {
+                continue;
+            }
+            let length = 1 << (weight - 1);
+            let start = rank_start[weight as usize];
+            for entry in &mut entries[start..(start + length)] {
+                *entry = (symbol as u8, max_bits + 1 - weight as u32);
+            }
+            rank_start[weight as usize] += length;
+        }
+
+        Ok((HuffmanTable { max_bits: max_bits, entries: entries }, size))
+    }
+
+    fn decode_stream(&self, data: &[u8], count: usize, output: &mut Vec<u8>)
+        -> Result<(), &'static str>
+    {
+        let mut bits = try!(BackwardBits::new(data));
+        for _ in 0..count {
+            let (symbol, nb_bits) = self.entries[bits.peek(self.max_bits)];
+            bits.skip(nb_bits);
+            output.push(symbol);
+        }
+        if bits.remaining() != 0 {
+            return Err("Huffman stream was not fully consumed");
+        }
+        Ok(())
+    }
+}
+
+// Decode Huffman weights that were compressed with FSE, using two
+// interleaved states
+fn _read_fse_weights(data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let (table, table_size) = try!(FseTable::read(data, MAX_WEIGHTS_LOG));
+    let mut bits = try!(BackwardBits::new(&data[table_size..]));
+    let mut states = [bits.read(table.log), bits.read(table.log)];
+
+    let mut weights = vec![];
+    let mut current = 0;
+    loop {
+        if weights.len() > 255 {
+            return Err("too many Huffman weights");
+        }
+        weights.push(table.symbol(states[current]));
+        states[current] = table.update(states[current], &mut bits);
+        current = 1 - current;
+        if bits.overflowed() {
+            weights.push(table.symbol(states[current]));
+            break;
+        }
+    }
+    Ok(weights)
+}
+
+// Reads bits from the start of a slice, least-significant bit first
+struct ForwardBits<'a> {
+    data: &'a [u8],
+    pos: usize
+}
+
+impl<'a> ForwardBits<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        ForwardBits { data: data, pos: 0 }
+    }
+
+    fn peek(&self, count: u32) -> usize {
+        _bits_at(self.data, self.pos as isize, count)
+    }
+
+    fn skip(&mut self, count: u32) {
+        self.pos += count as usize;
+    }
+
+    fn read(&mut self, count: u32) -> usize {
+        let value = self.peek(count);
+        self.skip(count);
+        value
+    }
+
+    fn overflowed(&self) -> bool {
+        self.pos > self.data.len() * 8
+    }
+
+    fn bytes_read(&self) -> usize {
+        (self.pos + 7) / 8
+    }
+}
+
+// Reads bits from the end of a slice towards the start, starting below
+// the highest set bit of the last byte. Reading past the start of the
+// slice returns zeros.
+struct BackwardBits<'a> {
+    data: &'a [u8],
+    pos: isize
+}
+
+impl<'a> BackwardBits<'a> {
+    fn new(data: &'a [u8]) -> Result<Self, &'static str> {
+        let last = match data.last() {
+            Some(&last) if last != 0 => last,
+            _ => { return Err("bitstream is missing its end marker"); }
+        };
+        let pos = (data.len() - 1) * 8 + _highest_bit(last as u32) as usize;
+        Ok(BackwardBits { data: data, pos: pos as isize })
+    }
+
+    fn peek(&self, count: u32) -> usize {
+        _bits_at(self.data, self.pos - count as isize, count)
+    }
+
+    fn skip(&mut self, count: u32) {
+        self.pos -= count as isize;
+    }
+
+    fn read(&mut self, count: u32) -> usize {
+        let value = self.peek(count);
+        self.skip(count);
+        value
+    }
+
+    fn remaining(&self) -> isize {
+        self.pos
+    }
+
+    fn overflowed(&self) -> bool {
+        self.pos < 0
+    }
+}
+
+// Returns `count` bits starting at bit `start` of `data` (where bit 0 is
+// the least-significant bit of the first byte). Bits outside of `data`
+// are read as zeros.
+fn _bits_at(data: &[u8], start: isize, count: u32) -> usize {
+    if count == 0 {
+        return 0;
+    }
+    if start < 0 {
+        let available = count as isize + start;
+        if available <= 0 {
+            return 0;
+        }
+        return _bits_at(data, 0, available as u32) << (-start) as u32;
+    }
+
+    let byte = start as usize / 8;
+    let shift = start as usize % 8;
+    let mut value: u64 = 0;
+    for i in 0..cmp::min(8, data.len().saturating_sub(byte)) {
+        value |= (data[byte + i] as u64) << (8 * i);
+    }
+    ((value >> shift) & ((1 << count) - 1)) as usize
+}
+
+fn _highest_bit(value: u32) -> u32 {
+    31 - value.leading_zeros()
+}
+
+fn _slice(data: &[u8], pos: usize, len: usize)
+    -> Result<&[u8], &'static str>
+{
+    if pos + len > data.len() {
+        return Err("unexpected end of data");
+    }
+    Ok(&data[pos..(pos + len)])
+}
+
+fn _read_u8(data: &[u8], pos: usize) -> Result<u8, &'static str> {
+    _slice(data, pos, 1).map(|bytes| bytes[0])
+}
+
+fn _read_u32(data: &[u8], pos: usize) -> Result<u32, &'static str> {
+    _read_le(data, pos, 4).map(|value| value as u32)
+}
+
+// Read a little-endian integer of `size` bytes
+fn _read_le(data: &[u8], pos: usize, size: usize)
+    -> Result<u64, &'static str>
+{
+    let bytes = try!(_slice(data, pos, size));
+    Ok(bytes.iter().rev().fold(0, |value, &byte| (value << 8) | byte as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The test frames were produced by the reference `zstd` command line
+    // tool (version 1.5), so that each frame exercises the parts of the
+    // format that the reference encoder actually uses.
+
+    // `zstd -19` of "glitter": a single raw block, with a checksum
+    const RAW_FRAME: &'static [u8] = &[
+        0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x68, 0x39, 0x00, 0x00, 0x67, 0x6c, 0x69,
+        0x74, 0x74, 0x65, 0x72, 0x6d, 0x1b, 0xa1, 0x84
+    ];
+
+    // `zstd -1` of 131072 bytes of 0x2A followed by 10 bytes of 0x07: a
+    // compressed block with a single literal and predefined sequence
+    // tables, followed by an RLE block
+    const RLE_FRAME: &'static [u8] = &[
+        0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x48, 0x54, 0x00, 0x00, 0x10, 0x2a, 0x2a,
+        0x01, 0x00, 0xfb, 0xff, 0x39, 0xc0, 0x02, 0x53, 0x00, 0x00, 0x07, 0x78,
+        0xdc, 0x3b, 0x82
+    ];
+
+    // `zstd -19 --no-check` of `_sample(300)`: a single compressed block
+    // with Huffman-coded literals and FSE-coded sequences
+    const HUFFMAN_FRAME: &'static [u8] = &[
+        0x28, 0xb5, 0x2f, 0xfd, 0x00, 0x68, 0x65, 0x02, 0x00, 0xc2, 0x82, 0x09,
+        0x10, 0xc0, 0xa7, 0x03, 0xd4, 0x05, 0xaf, 0x8a, 0xa3, 0x26, 0x64, 0xef,
+        0xbd, 0x59, 0x51, 0x71, 0x6e, 0x17, 0xbf, 0xed, 0xfa, 0x7b, 0x95, 0x49,
+        0x87, 0x10, 0x88, 0x77, 0x66, 0x55, 0x0f, 0x52, 0x9e, 0xd0, 0x60, 0x12,
+        0xa5, 0x09, 0x1c, 0xa8, 0x10, 0xc8, 0xde, 0x37, 0x80, 0x95, 0x14, 0x1e,
+        0x10, 0x10, 0x01, 0x71, 0xf3, 0x3c, 0x12, 0xe7, 0x25, 0x6e, 0xbb, 0x17,
+        0x36, 0x55, 0x97, 0xb4, 0x40, 0x9f, 0x8b, 0x43, 0x19, 0x74, 0x63, 0x10,
+        0x53
+    ];
+
+    // `zstd -19` of `_sample(200000)`: two compressed blocks, where the
+    // second reuses the Huffman table (treeless literals) and the literal
+    // length table of the first
+    const MULTI_BLOCK_FRAME: &'static [u8] = &[
+        0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x68, 0xa4, 0x0e, 0x00, 0xc2, 0x0b, 0x1c,
+        0x12, 0xb0, 0xa7, 0x0d, 0x8d, 0xa6, 0xda, 0x22, 0xdf, 0x90, 0x69, 0x66,
+        0xbb, 0x77, 0x4a, 0xa9, 0x1c, 0xd1, 0x95, 0x77, 0x05, 0x33, 0x11, 0x0b,
+        0x62, 0x62, 0xb9, 0x43, 0xc4, 0x2b, 0xe5, 0x4a, 0x29, 0x97, 0x88, 0x97,
+        0x78, 0x99, 0x87, 0x25, 0x11, 0x13, 0x2f, 0x01, 0x03, 0x56, 0x74, 0x77,
+        0xac, 0xe8, 0xee, 0x58, 0xd1, 0xdd, 0xb1, 0xa2, 0xbb, 0x63, 0x45, 0x77,
+        0xc7, 0x8a, 0xee, 0x8e, 0x15, 0xdd, 0x1d, 0x2b, 0xba, 0x3b, 0x56, 0x74,
+        0x77, 0xac, 0xe8, 0xee, 0x58, 0xd1, 0x11, 0xe8, 0x58, 0xd1, 0xdd, 0xbb,
+        0xed, 0x94, 0x18, 0x04, 0x2b, 0xba, 0x3b, 0x45, 0x7d, 0xdd, 0x76, 0x4a,
+        0x0c, 0x82, 0x59, 0x15, 0xd1, 0x6f, 0xb7, 0x40, 0x52, 0x1c, 0xc1, 0x04,
+        0x46, 0x81, 0x14, 0x46, 0x80, 0xb2, 0xa8, 0x30, 0xf6, 0xec, 0x19, 0x21,
+        0x92, 0x48, 0x04, 0x45, 0x44, 0x44, 0x44, 0xca, 0x1e, 0x12, 0x40, 0x10,
+        0x40, 0xa0, 0x08, 0x56, 0x98, 0x69, 0xe1, 0xff, 0x3f, 0x11, 0x1a, 0x23,
+        0x49, 0xf5, 0xd2, 0x28, 0x94, 0x9f, 0x4b, 0x51, 0x5b, 0x8f, 0x94, 0x81,
+        0x22, 0xff, 0x51, 0xfe, 0x8b, 0x7c, 0x97, 0xc2, 0x8d, 0x3c, 0xfd, 0x5f,
+        0x82, 0x9d, 0x62, 0xdd, 0x3e, 0xbb, 0x1f, 0xa6, 0x46, 0xb8, 0xca, 0xf9,
+        0x36, 0xe2, 0x79, 0x76, 0xe8, 0x8c, 0xcd, 0xb9, 0xd3, 0xc2, 0xaa, 0x40,
+        0x19, 0xdd, 0x3a, 0x2c, 0xfb, 0xec, 0x9a, 0x50, 0xc5, 0x72, 0x5c, 0x16,
+        0x9c, 0xd7, 0x0a, 0xee, 0xc5, 0x20, 0x9b, 0xcb, 0xfc, 0x8d, 0x15, 0x5f,
+        0xfc, 0x35, 0x6f, 0x9e, 0x1d, 0xb5, 0xb5, 0x78, 0x9a, 0xbc, 0xb5, 0x3f,
+        0xd3, 0x48, 0x44, 0xab, 0x1e, 0xa7, 0x8d, 0xcd, 0xe1, 0x19, 0xe0, 0xe1,
+        0x01, 0x5b, 0x1e, 0xea, 0xa3, 0x77, 0x08, 0x10, 0x09, 0x92, 0xf5, 0x87,
+        0x21, 0x24, 0xb9, 0xcf, 0x71, 0x57, 0x3c, 0xeb, 0x0c, 0x76, 0x18, 0x25,
+        0xd6, 0x53, 0xe4, 0x42, 0x11, 0xed, 0xf2, 0xb1, 0x0c, 0xfc, 0x9f, 0xaa,
+        0x08, 0x94, 0x0d, 0x5a, 0x15, 0x7f, 0xfb, 0x5a, 0x95, 0x43, 0x4c, 0xa5,
+        0x53, 0xf8, 0x66, 0xbd, 0xa3, 0x28, 0x99, 0x2f, 0xa0, 0xa0, 0x86, 0x37,
+        0xa0, 0x18, 0x0c, 0xa9, 0xc8, 0x44, 0xc4, 0xeb, 0xc8, 0xa4, 0x7d, 0x53,
+        0xf6, 0x23, 0x49, 0x80, 0x73, 0x8e, 0xff, 0x7b, 0x63, 0x2b, 0xdd, 0x9b,
+        0x2d, 0x1d, 0x68, 0x7b, 0xf1, 0x24, 0xb7, 0xd8, 0x01, 0x71, 0xd5, 0xf0,
+        0x5d, 0xc7, 0xa3, 0xa1, 0x53, 0xeb, 0xd1, 0xd6, 0xa2, 0x5d, 0xce, 0x10,
+        0x1f, 0x4b, 0x3e, 0x16, 0xd8, 0x0a, 0x72, 0x71, 0x73, 0xff, 0x6b, 0xc7,
+        0xf2, 0x5e, 0x28, 0x60, 0xf7, 0xb3, 0x2d, 0xb6, 0x95, 0x88, 0x72, 0xc1,
+        0xc5, 0x17, 0x28, 0x3e, 0xb0, 0x96, 0xb5, 0x88, 0x0b, 0xc8, 0xe2, 0x31,
+        0x04, 0xa4, 0x24, 0x6c, 0x03, 0xa8, 0x64, 0x7f, 0x96, 0x06, 0x2f, 0x02,
+        0x78, 0xfa, 0x07, 0x28, 0x9a, 0x82, 0x30, 0xb2, 0x3f, 0xa5, 0x35, 0x00,
+        0xa1, 0xc4, 0x19, 0x50, 0xfe, 0x6e, 0x0d, 0x94, 0xfe, 0x0c, 0xba, 0x26,
+        0x01, 0x32, 0xaa, 0xf3, 0x90, 0x6e, 0x32, 0xa3, 0xcf, 0x8d, 0x6e, 0xea,
+        0xce, 0x6e, 0x77, 0x1b, 0xbf, 0xe6, 0x4d, 0x08, 0x3d, 0xf7, 0xbe, 0x6b,
+        0xa3, 0x5b, 0xe3, 0xa5, 0x09, 0x3e, 0xaf, 0xab, 0x37, 0x1f, 0xc2, 0x10,
+        0x23, 0x18, 0x3f, 0x8c, 0xe1, 0x69, 0x08, 0x08, 0x53, 0xf5, 0x05, 0x00,
+        0xf3, 0x41, 0x04, 0x97, 0x58, 0xb0, 0x14, 0xbc, 0xbb, 0x2c, 0x87, 0x77,
+        0x05, 0x13, 0x33, 0x8d, 0xcc, 0x82, 0xa5, 0x94, 0x28, 0xe8, 0x41, 0x24,
+        0xe1, 0x33, 0x24, 0x30, 0xc8, 0x50, 0x1f, 0x11, 0xfc, 0xff, 0xdf, 0x10,
+        0x3c, 0x43, 0x68, 0x18, 0x81, 0xa1, 0xb8, 0xc8, 0x1d, 0x8c, 0xdb, 0x02,
+        0xe4, 0x1b, 0x75, 0xfc, 0x2c, 0x8e, 0x55, 0x7d, 0x89, 0xd7, 0xf2, 0xa9,
+        0x8e, 0xff, 0x4e, 0x66, 0x63, 0x85, 0x99, 0xf3, 0x0e, 0x46, 0xa8, 0x5b,
+        0xfb, 0xd0, 0xe3, 0xb6, 0x67, 0x47, 0xd6, 0x8c, 0xed, 0x96, 0x8e, 0x57,
+        0x47, 0x01, 0x64, 0xc3, 0xac, 0x4d, 0xca, 0xf2, 0xb2, 0x41, 0xc8, 0x24,
+        0xa9, 0x90, 0x3f, 0x3a, 0xb3, 0x11, 0x57, 0x8d, 0x64, 0x90, 0x43, 0xe9,
+        0x09, 0xeb, 0xe4, 0x34, 0xbd, 0xdd, 0x49, 0xbe, 0xcd, 0x5a, 0x43, 0x44,
+        0x62, 0x95, 0xfb, 0xb0, 0x4a, 0x8f, 0x84, 0xf5, 0x2d, 0x7e, 0xc5, 0x1f,
+        0xd1, 0xc1, 0xfa, 0x61, 0x58, 0x51, 0x29, 0x20, 0x24, 0xc9, 0x1a, 0xe0,
+        0x62, 0x7c, 0x8a, 0x77, 0xc0, 0x7b, 0x9c, 0xe4, 0x8f, 0x63, 0xed, 0x3e,
+        0x0f, 0xe4, 0x9a, 0x57, 0xbc, 0x39, 0xec, 0x8f, 0x70, 0x22, 0x51, 0xd4,
+        0x18, 0x07, 0xfe, 0xee, 0x40, 0x34, 0xac, 0x3e, 0xa0, 0xf0, 0x68, 0x58,
+        0x73, 0xcd, 0x63, 0x0e, 0x8c, 0x5f, 0xf3, 0x4b, 0x1b, 0x04, 0x01, 0x8f,
+        0xac, 0x25
+    ];
+
+    // Text with a repeating pattern and occasional random bytes
+    fn _sample(len: usize) -> Vec<u8> {
+        let mut sample = vec![];
+        let mut seed: u32 = 7;
+        let mut line = 0;
+        while sample.len() < len {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            if (seed >> 16) % 200 == 0 {
+                sample.push(b'a' + ((seed >> 20) % 4) as u8);
+            }
+            else {
+                let text = format!("level {} texel {}\n", line % 16, line % 7);
+                sample.extend_from_slice(text.as_bytes());
+            }
+            line += 1;
+        }
+        sample.truncate(len);
+        sample
+    }
+
+    #[test]
+    fn decompress_raw_block() {
+        assert_eq!(decompress(RAW_FRAME).unwrap(), b"glitter".to_vec());
+    }
+
+    #[test]
+    fn decompress_rle_block() {
+        let mut expected = vec![0x2A; 131072];
+        expected.extend_from_slice(&[0x07; 10]);
+        assert_eq!(decompress(RLE_FRAME).unwrap(), expected);
+    }
+
+    #[test]
+    fn decompress_huffman_literals() {
+        assert_eq!(decompress(HUFFMAN_FRAME).unwrap(), _sample(300));
+    }
+
+    #[test]
+    fn decompress_multiple_blocks() {
+        assert!(decompress(MULTI_BLOCK_FRAME).unwrap() == _sample(200000));
+    }
+
+    #[test]
+    fn decompress_concatenated_frames() {
+        let mut data = vec![];
+        data.extend_from_slice(RAW_FRAME);
+        data.extend_from_slice(&[0x53, 0x2A, 0x4D, 0x18, 3, 0, 0, 0, 1, 2, 3]);
+        data.extend_from_slice(HUFFMAN_FRAME);
+
+        let mut expected = b"glitter".to_vec();
+        expected.extend_from_slice(&_sample(300));
+        assert_eq!(decompress(&data).unwrap(), expected);
+        assert_eq!(decompress(&[]).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn reject_truncated_frames() {
+        for frame in &[RAW_FRAME, RLE_FRAME, HUFFMAN_FRAME, MULTI_BLOCK_FRAME] {
+            for len in 1..frame.len() {
+                assert!(decompress(&frame[..len]).is_err());
+            }
+        }
+
+        let skippable = [0x50, 0x2A, 0x4D, 0x18, 4, 0, 0, 0, 1, 2, 3];
+        assert_eq!(decompress(&skippable),
+                   Err("skippable frame extends past the end of the data"));
+    }
+
+    #[test]
+    fn reject_invalid_frames() {
+        assert_eq!(decompress(b"glitter!"), Err("invalid frame magic number"));
+
+        // A frame header with a 1-byte dictionary ID
+        let dictionary = [0x28, 0xB5, 0x2F, 0xFD, 0x01, 0x00, 0x05];
+        assert_eq!(decompress(&dictionary),
+                   Err("frames that use a dictionary are not supported"));
+
+        // A single-segment frame header, followed by a reserved block
+        let reserved = [0x28, 0xB5, 0x2F, 0xFD, 0x20, 0x00, 0x07, 0x00, 0x00];
+        assert_eq!(decompress(&reserved), Err("reserved block type"));
+    }
+}
@@ -165,6 +165,35 @@ gl_enum! {
 }
 
 
+gl_enum! {
+    /// The strings that describe the current OpenGL implementation, which
+    /// can be retrieved using [`gl.get_string`]
+    /// (../context/trait.ContextExt.html#method.get_string).
+    pub gl_enum StringName {
+        /// The company responsible for the OpenGL implementation.
+        pub const Vendor as VENDOR = gl::VENDOR,
+
+        /// The name of the renderer, which is typically specific to
+        /// a particular hardware configuration.
+        pub const Renderer as RENDERER = gl::RENDERER,
+
+        /// The version of OpenGL that is supported by the implementation.
+        pub const Version as VERSION = gl::VERSION,
+
+        /// The version of the shading language that is supported by
+        /// the implementation.
+        pub const ShadingLanguageVersion as SHADING_LANGUAGE_VERSION =
+            gl::SHADING_LANGUAGE_VERSION,
+
+        /// A space-separated list of the supported OpenGL extensions.
+        /// Note that this value is not available with core profile
+        /// contexts; use [`gl.extensions`]
+        /// (../context/trait.ContextExt.html#method.extensions) instead.
+        pub const Extensions as EXTENSIONS = gl::EXTENSIONS
+    }
+}
+
+
 
 /// The various possible OpenGL errors.
 #[derive(Debug)]
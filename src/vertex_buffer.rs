@@ -6,7 +6,8 @@ use std::collections::{HashMap, HashSet};
 use context::{ContextOf, AContext, ContextBufferExt,
               ArrayBufferBinding, ArrayBufferContext,
               ElementArrayBufferBinding, ElementArrayBufferContext};
-use program::ProgramAttrib;
+use context::ContextProgramExt;
+use program::{Program, ProgramAttrib};
 use vertex_data::{VertexData, VertexBytes, VertexAttribute};
 use index_data::{IndexData, IndexDatum};
 use buffer::Buffer;
@@ -68,6 +69,39 @@ impl AttribBinder {
         }
     }
 
+    /// Look up each attribute in the `AttribBinder` again by name within
+    /// the provided program. This should be used after a program has been
+    /// rebuilt, such as by a [`ShaderWatcher`]
+    /// (../shader_watcher/struct.ShaderWatcher.html), since the attribute
+    /// indices of the old program may no longer be valid.
+    ///
+    /// # Failures
+    /// An error will be returned if any of the attributes could not be
+    /// found in the program. Attributes that could not be found are left
+    /// unchanged.
+    pub fn refresh<C>(&mut self, gl: &C, program: &Program)
+        -> Result<(), AttribError>
+        where C: ContextProgramExt
+    {
+        let mut missing = Vec::<String>::new();
+        for (name, attrib) in self.attribs.iter_mut() {
+            match gl.get_attrib_location(program, name) {
+                Ok(new_attrib) => { *attrib = new_attrib; },
+                Err(_) => { missing.push(name.clone()); }
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(())
+        }
+        else {
+            Err(AttribError {
+                missing_attribs: missing,
+                unknown_attribs: vec![]
+            })
+        }
+    }
+
     fn for_each<T, F>(&self, mut f: F) -> Result<(), AttribError>
         where T: VertexData, F: FnMut(VertexAttribute, ProgramAttrib)
     {